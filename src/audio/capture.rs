@@ -1,15 +1,19 @@
-// WASAPI microphone capture module.
+// WASAPI microphone and system loopback capture module.
 //
 // This entire module is Windows-only since it depends on the `wasapi` crate.
 
 #![cfg(target_os = "windows")]
 
+use std::cell::Cell;
 use std::collections::VecDeque;
 
 use anyhow::Result;
 use thiserror::Error;
 use wasapi::*;
 
+pub use crate::audio::devices::CaptureDevice;
+use crate::audio::devices::find_device;
+
 /// Errors that can occur during audio capture.
 ///
 /// `DeviceInvalidated` signals that the audio device was lost (e.g. sleep/wake,
@@ -22,7 +26,114 @@ pub enum CaptureError {
     Other(#[from] anyhow::Error),
 }
 
-/// Captures audio from the default microphone via WASAPI in shared event-driven mode.
+/// How long a microphone or app stream may go without a buffer event before
+/// `read_frames` fails and the pipeline reopens the device.
+pub const STALL_TIMEOUT_MS: u32 = 1000;
+
+/// Count a timed-out event wait of `timeout_ms` towards `stalled_ms`. Returns
+/// `true` (and starts counting again) once the stream has waited
+/// `STALL_TIMEOUT_MS` in total without an event.
+pub fn event_stalled(stalled_ms: &Cell<u32>, timeout_ms: u32) -> bool {
+    let stalled = stalled_ms.get() + timeout_ms;
+    if stalled >= STALL_TIMEOUT_MS {
+        stalled_ms.set(0);
+        return true;
+    }
+    stalled_ms.set(stalled);
+    false
+}
+
+/// A WASAPI capture stream that a capture pipeline can drive: microphones
+/// and the system loopback.
+pub trait CaptureStream {
+    /// Start the capture stream. Must be called before `read_frames`.
+    fn start(&self) -> Result<()>;
+    /// Wait for and read the next captured 16-bit mono samples, if any.
+    fn read_frames(&self) -> std::result::Result<Option<Vec<i16>>, CaptureError>;
+    /// Stop the capture stream.
+    fn stop(&self) -> Result<()>;
+}
+
+/// List all active capture endpoints.
+pub fn list_capture_devices() -> Result<Vec<CaptureDevice>> {
+    initialize_mta()
+        .ok()
+        .map_err(|e| anyhow::anyhow!("COM MTA initialization failed: {:?}", e))?;
+
+    let enumerator = DeviceEnumerator::new()
+        .map_err(|e| anyhow::anyhow!("Failed to create device enumerator: {:?}", e))?;
+    Ok(enumerate_capture_devices(&enumerator)?
+        .into_iter()
+        .map(|(_, info)| info)
+        .collect())
+}
+
+/// Every active capture endpoint paired with its description.
+fn enumerate_capture_devices(
+    enumerator: &DeviceEnumerator,
+) -> Result<Vec<(Device, CaptureDevice)>> {
+    // No default device (e.g. every mic unplugged) isn't an error here.
+    let default_id = enumerator
+        .get_default_device(&Direction::Capture)
+        .and_then(|d| d.get_id())
+        .ok();
+    let collection = enumerator
+        .get_device_collection(&Direction::Capture)
+        .map_err(|e| anyhow::anyhow!("Failed to enumerate capture devices: {:?}", e))?;
+    let count = collection
+        .get_nbr_devices()
+        .map_err(|e| anyhow::anyhow!("Failed to count capture devices: {:?}", e))?;
+
+    let mut devices = Vec::new();
+    for index in 0..count {
+        let device = collection
+            .get_device_at_index(index)
+            .map_err(|e| anyhow::anyhow!("Failed to get capture device {}: {:?}", index, e))?;
+        let id = device
+            .get_id()
+            .map_err(|e| anyhow::anyhow!("Failed to get device ID: {:?}", e))?;
+        let name = device.get_friendlyname().unwrap_or_else(|_| "(unknown)".to_string());
+        let info = CaptureDevice {
+            is_default: default_id.as_deref() == Some(id.as_str()),
+            name,
+            id,
+        };
+        devices.push((device, info));
+    }
+    Ok(devices)
+}
+
+/// The capture device matching `wanted` (see `find_device`), or the default
+/// device if `wanted` is `None` or (unless `exact`) not currently connected.
+fn select_capture_device(
+    enumerator: &DeviceEnumerator,
+    wanted: Option<&str>,
+    exact: bool,
+) -> Result<Device> {
+    if let Some(wanted) = wanted {
+        let mut devices = enumerate_capture_devices(enumerator)?;
+        let infos: Vec<CaptureDevice> = devices.iter().map(|(_, info)| info.clone()).collect();
+        match find_device(&infos, wanted) {
+            Some(index) => {
+                let (device, info) = devices.swap_remove(index);
+                tracing::info!("Using capture device '{}'", info.name);
+                return Ok(device);
+            }
+            None if exact => anyhow::bail!("Capture device '{}' not found", wanted),
+            None => tracing::warn!(
+                "Capture device '{}' not found, falling back to the default device",
+                wanted
+            ),
+        }
+    }
+
+    enumerator
+        .get_default_device(&Direction::Capture)
+        .map_err(|e| anyhow::anyhow!("Failed to get default capture device: {:?}", e))
+}
+
+/// Captures audio from a microphone (the default one unless configured) via
+/// WASAPI in shared event-driven mode.
 ///
 /// The captured format is 16-bit mono PCM at the requested sample rate.
 /// WASAPI's autoconvert feature handles any necessary resampling from the
@@ -31,22 +142,49 @@ pub struct MicCapture {
     audio_client: AudioClient,
     capture_client: AudioCaptureClient,
     event_handle: Handle,
+    event_timeout_ms: u32,
+    stalled_ms: Cell<u32>,
     sample_rate: u32,
     blockalign: u32,
 }
 
 impl MicCapture {
-    /// Create a new `MicCapture` that will capture from the default recording device.
+    /// Create a new `MicCapture` on the device named by `device` (friendly-name
+    /// substring or endpoint ID), or the default recording device if `None`.
+    /// An unknown device falls back to the default with a warning.
     ///
     /// `desired_sample_rate` should be 16000 (for VAD compatibility) or 8000.
-    pub fn new(desired_sample_rate: u32) -> Result<Self> {
+    /// `event_timeout_ms` is the longest single wait in `read_frames`
+    /// (`capture.event_timeout_ms`).
+    pub fn new(
+        desired_sample_rate: u32,
+        device: Option<&str>,
+        event_timeout_ms: u32,
+    ) -> Result<Self> {
+        Self::open(desired_sample_rate, device, false, event_timeout_ms)
+    }
+
+    /// Like `new`, but fails instead of falling back to the default device
+    /// when `device` isn't connected (used when recording several mics).
+    pub fn new_exact(
+        desired_sample_rate: u32,
+        device: &str,
+        event_timeout_ms: u32,
+    ) -> Result<Self> {
+        Self::open(desired_sample_rate, Some(device), true, event_timeout_ms)
+    }
+
+    fn open(
+        desired_sample_rate: u32,
+        device: Option<&str>,
+        exact: bool,
+        event_timeout_ms: u32,
+    ) -> Result<Self> {
         initialize_mta().ok().map_err(|e| anyhow::anyhow!("COM MTA initialization failed: {:?}", e))?;
 
         let enumerator = DeviceEnumerator::new()
             .map_err(|e| anyhow::anyhow!("Failed to create device enumerator: {:?}", e))?;
-        let device = enumerator
-            .get_default_device(&Direction::Capture)
-            .map_err(|e| anyhow::anyhow!("Failed to get default capture device: {:?}", e))?;
+        let device = select_capture_device(&enumerator, device, exact)?;
 
         let mut audio_client = device
             .get_iaudioclient()
@@ -89,6 +227,8 @@ impl MicCapture {
             audio_client,
             capture_client,
             event_handle,
+            event_timeout_ms,
+            stalled_ms: Cell::new(0),
             sample_rate: desired_sample_rate,
             blockalign,
         })
@@ -105,22 +245,30 @@ impl MicCapture {
     /// Wait for the next event and read captured frames as 16-bit PCM samples.
     ///
     /// Returns `Ok(Some(samples))` when audio data is available, or `Ok(None)`
-    /// if no data was captured in this cycle (e.g. silence flags set).
+    /// if no data was captured in this cycle (e.g. silence flags set, or the
+    /// wait timed out). A device with no event for `STALL_TIMEOUT_MS` fails.
     /// Returns `Err(CaptureError::DeviceInvalidated)` when the device is lost
     /// (sleep/wake, USB unplug, default device change), or `Err(CaptureError::Other)`
     /// for other failures.
     pub fn read_frames(&self) -> std::result::Result<Option<Vec<i16>>, CaptureError> {
-        // Wait for WASAPI to signal that a buffer is ready.
-        if let Err(e) = self.event_handle.wait_for_event(1000) {
+        // Wait for WASAPI to signal that a buffer is ready. Waits are short so
+        // the pipeline gets to check for shutdown in between.
+        if let Err(e) = self.event_handle.wait_for_event(self.event_timeout_ms) {
             let msg = format!("{:?}", e);
             if Self::is_device_invalidated_error(&msg) {
                 return Err(CaptureError::DeviceInvalidated);
             }
+            if msg.to_uppercase().contains("TIMEOUT")
+                && !event_stalled(&self.stalled_ms, self.event_timeout_ms)
+            {
+                return Ok(None);
+            }
             return Err(CaptureError::Other(anyhow::anyhow!(
                 "Event wait timeout/error: {}",
                 msg
             )));
         }
+        self.stalled_ms.set(0);
 
         // Read captured bytes into a VecDeque, matching the pattern from the
         // wasapi crate's record example.
@@ -184,3 +332,155 @@ impl MicCapture {
         self.sample_rate
     }
 }
+
+impl CaptureStream for MicCapture {
+    fn start(&self) -> Result<()> {
+        MicCapture::start(self)
+    }
+
+    fn read_frames(&self) -> std::result::Result<Option<Vec<i16>>, CaptureError> {
+        MicCapture::read_frames(self)
+    }
+
+    fn stop(&self) -> Result<()> {
+        MicCapture::stop(self)
+    }
+}
+
+/// Captures everything playing on the default playback device (WASAPI
+/// loopback), e.g. a browser-based meeting. Same format and event-driven
+/// shared mode as `MicCapture`.
+///
+/// Windows only delivers loopback packets while something is playing, so a
+/// silent gap shows up as `Ok(None)` reads rather than an event timeout error.
+pub struct SystemLoopbackCapture {
+    audio_client: AudioClient,
+    capture_client: AudioCaptureClient,
+    event_handle: Handle,
+    event_timeout_ms: u32,
+    sample_rate: u32,
+}
+
+impl SystemLoopbackCapture {
+    /// Open a loopback stream on the default render device.
+    ///
+    /// `desired_sample_rate` should be 16000 (for VAD compatibility) or 8000.
+    /// `event_timeout_ms` is the longest single wait in `read_frames`.
+    pub fn new(desired_sample_rate: u32, event_timeout_ms: u32) -> Result<Self> {
+        initialize_mta().ok().map_err(|e| anyhow::anyhow!("COM MTA initialization failed: {:?}", e))?;
+
+        let enumerator = DeviceEnumerator::new()
+            .map_err(|e| anyhow::anyhow!("Failed to create device enumerator: {:?}", e))?;
+        let device = enumerator
+            .get_default_device(&Direction::Render)
+            .map_err(|e| anyhow::anyhow!("Failed to get default playback device: {:?}", e))?;
+
+        let mut audio_client = device
+            .get_iaudioclient()
+            .map_err(|e| anyhow::anyhow!("Failed to get IAudioClient: {:?}", e))?;
+
+        // Request 16-bit mono PCM at the desired sample rate.
+        let desired_format = WaveFormat::new(
+            16,                           // bits per sample
+            16,                           // valid bits per sample
+            &SampleType::Int,             // integer samples
+            desired_sample_rate as usize, // sample rate
+            1,                            // mono
+            None,                         // no specific channel mask
+        );
+
+        let (_, min_time) = audio_client
+            .get_device_period()
+            .map_err(|e| anyhow::anyhow!("Failed to get device period: {:?}", e))?;
+
+        // Capturing from a render device sets up a loopback stream.
+        let mode = StreamMode::EventsShared {
+            autoconvert: true,
+            buffer_duration_hns: min_time,
+        };
+        audio_client
+            .initialize_client(&desired_format, &Direction::Capture, &mode)
+            .map_err(|e| anyhow::anyhow!("Failed to initialize loopback client: {:?}", e))?;
+
+        let event_handle = audio_client
+            .set_get_eventhandle()
+            .map_err(|e| anyhow::anyhow!("Failed to set/get event handle: {:?}", e))?;
+
+        let capture_client = audio_client
+            .get_audiocaptureclient()
+            .map_err(|e| anyhow::anyhow!("Failed to get AudioCaptureClient: {:?}", e))?;
+
+        Ok(Self {
+            audio_client,
+            capture_client,
+            event_handle,
+            event_timeout_ms,
+            sample_rate: desired_sample_rate,
+        })
+    }
+
+    /// The sample rate this capture was configured with.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+impl CaptureStream for SystemLoopbackCapture {
+    fn start(&self) -> Result<()> {
+        self.audio_client
+            .start_stream()
+            .map_err(|e| anyhow::anyhow!("Failed to start loopback stream: {:?}", e))?;
+        Ok(())
+    }
+
+    fn read_frames(&self) -> std::result::Result<Option<Vec<i16>>, CaptureError> {
+        if let Err(e) = self.event_handle.wait_for_event(self.event_timeout_ms) {
+            let msg = format!("{:?}", e);
+            if MicCapture::is_device_invalidated_error(&msg) {
+                return Err(CaptureError::DeviceInvalidated);
+            }
+            // Nothing is playing.
+            if msg.to_uppercase().contains("TIMEOUT") {
+                return Ok(None);
+            }
+            return Err(CaptureError::Other(anyhow::anyhow!(
+                "Event wait error: {}",
+                msg
+            )));
+        }
+
+        let mut sample_queue: VecDeque<u8> = VecDeque::new();
+        if let Err(e) = self
+            .capture_client
+            .read_from_device_to_deque(&mut sample_queue)
+        {
+            let msg = format!("{:?}", e);
+            if MicCapture::is_device_invalidated_error(&msg) {
+                return Err(CaptureError::DeviceInvalidated);
+            }
+            return Err(CaptureError::Other(anyhow::anyhow!(
+                "Failed to read from loopback device: {}",
+                msg
+            )));
+        }
+
+        if sample_queue.is_empty() {
+            return Ok(None);
+        }
+
+        let bytes: Vec<u8> = sample_queue.into_iter().collect();
+        let samples: Vec<i16> = bytes
+            .chunks_exact(2)
+            .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]))
+            .collect();
+
+        Ok(Some(samples))
+    }
+
+    fn stop(&self) -> Result<()> {
+        self.audio_client
+            .stop_stream()
+            .map_err(|e| anyhow::anyhow!("Failed to stop loopback stream: {:?}", e))?;
+        Ok(())
+    }
+}
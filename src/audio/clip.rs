@@ -0,0 +1,214 @@
+// Manual clip capture: records a fixed-duration note from the mic, bypassing VAD.
+//
+// `record_clip` is cross-platform — it drives the file writer's `AudioMessage`
+// path directly from any capture function, so it can be tested without a device.
+// `run_clip` wires it to `MicCapture` and is only functional on Windows.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::audio::pipeline::AudioMessage;
+use crate::config::Config;
+
+/// Source name used for manually recorded clips (file prefix `note_`).
+pub const CLIP_SOURCE: &str = "note";
+
+/// Record `duration_secs` of audio from `capture_fn` as a single segment.
+///
+/// Every captured sample is forwarded to the file writer (no VAD), starting
+/// with `SpeechStart` and ending with `SpeechEnd`. The final chunk is trimmed
+/// so the clip is exactly `duration_secs` long unless `shutdown` is set first.
+/// Returns the number of samples sent.
+pub fn record_clip(
+    source_name: &str,
+    capture_fn: impl Fn() -> Result<Option<Vec<i16>>>,
+    start_fn: impl Fn() -> Result<()>,
+    sample_rate: u32,
+    duration_secs: u32,
+    sender: &Sender<AudioMessage>,
+    shutdown: &Arc<AtomicBool>,
+) -> Result<usize> {
+    let target_samples = sample_rate as usize * duration_secs as usize;
+    let mut written: usize = 0;
+
+    start_fn()?;
+
+    while written < target_samples && !shutdown.load(Ordering::Relaxed) {
+        let mut samples = match capture_fn()? {
+            Some(s) => s,
+            None => continue,
+        };
+        samples.truncate(target_samples - written);
+        let len = samples.len();
+
+        if written == 0 {
+            sender.send(AudioMessage::SpeechStart {
+                source: source_name.to_string(),
+                samples,
+                sample_rate,
+            })?;
+        } else {
+            sender.send(AudioMessage::SpeechContinue {
+                source: source_name.to_string(),
+                samples,
+            })?;
+        }
+        written += len;
+    }
+
+    if written > 0 {
+        sender.send(AudioMessage::SpeechEnd {
+            source: source_name.to_string(),
+        })?;
+    }
+
+    Ok(written)
+}
+
+/// Record a fixed-duration note from the default microphone into the
+/// recordings directory. The resulting WAV is picked up by the transcriber
+/// like any other recording.
+#[cfg(target_os = "windows")]
+pub fn run_clip(config: &Config, duration_secs: u32) -> Result<()> {
+    use crate::audio::capture::MicCapture;
+    use crate::audio::file_writer::run_file_writer;
+
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let shutdown_ctrlc = shutdown.clone();
+    ctrlc::set_handler(move || {
+        shutdown_ctrlc.store(true, Ordering::Relaxed);
+    })?;
+
+    let (sender, receiver) = std::sync::mpsc::channel::<AudioMessage>();
+    let output_config = config.output.clone();
+    // Notes aren't paused; the flag only receives the writer's disk-full pause.
+    let paused = Arc::new(AtomicBool::new(false));
+    let sample_rate = config.capture.sample_rate;
+    let writer_handle = std::thread::Builder::new()
+        .name("file-writer".into())
+        .spawn(move || {
+            run_file_writer(
+                receiver,
+                &output_config,
+                sample_rate,
+                std::time::Duration::ZERO,
+                paused,
+                None,
+                None,
+            )
+        })?;
+
+    let device = config.capture.device.as_deref();
+    let capture = MicCapture::new(sample_rate, device, config.capture.event_timeout_ms)?;
+    let capture_fn = || -> Result<Option<Vec<i16>>> { Ok(capture.read_frames()?) };
+    let start_fn = || -> Result<()> { capture.start() };
+
+    println!("Recording {}s note (Ctrl+C to stop early)...", duration_secs);
+    let result = record_clip(
+        CLIP_SOURCE,
+        capture_fn,
+        start_fn,
+        sample_rate,
+        duration_secs,
+        &sender,
+        &shutdown,
+    );
+
+    if let Err(e) = capture.stop() {
+        tracing::warn!("Error stopping mic capture: {:?}", e);
+    }
+    drop(sender);
+    writer_handle
+        .join()
+        .map_err(|_| anyhow::anyhow!("File writer thread panicked"))??;
+
+    let written = result?;
+    println!(
+        "Saved {:.1}s note to {}",
+        written as f64 / sample_rate as f64,
+        config.output.directory.display()
+    );
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn run_clip(_config: &Config, _duration_secs: u32) -> Result<()> {
+    anyhow::bail!("Manual clip capture is only supported on Windows")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_record_clip_writes_exact_duration() {
+        let (tx, rx) = mpsc::channel();
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        // Silence is recorded too — there is no VAD gate.
+        let capture_fn = || -> Result<Option<Vec<i16>>> { Ok(Some(vec![0i16; 3])) };
+        let start_fn = || -> Result<()> { Ok(()) };
+
+        // 8 Hz * 2s = 16 samples; chunks of 3 means the last one is trimmed.
+        let written = record_clip("note", capture_fn, start_fn, 8, 2, &tx, &shutdown).unwrap();
+        assert_eq!(written, 16);
+        drop(tx);
+
+        let messages: Vec<AudioMessage> = rx.try_iter().collect();
+        let mut total = 0;
+        for msg in &messages {
+            match msg {
+                AudioMessage::SpeechStart {
+                    source, samples, ..
+                } => {
+                    assert_eq!(source, "note");
+                    total += samples.len();
+                }
+                AudioMessage::SpeechContinue { samples, .. } => total += samples.len(),
+                AudioMessage::SpeechEnd { .. }
+                | AudioMessage::SaveClip { .. }
+                | AudioMessage::Heartbeat { .. }
+                | AudioMessage::SourceStopped { .. }
+                | AudioMessage::Shutdown => {}
+            }
+        }
+        assert_eq!(total, 16);
+        assert!(matches!(messages[0], AudioMessage::SpeechStart { .. }));
+        assert!(matches!(messages.last(), Some(AudioMessage::SpeechEnd { .. })));
+    }
+
+    #[test]
+    fn test_record_clip_skips_empty_reads() {
+        let (tx, rx) = mpsc::channel();
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let reads = std::cell::RefCell::new(vec![Some(vec![1i16; 8]), None, None]);
+        let capture_fn = || -> Result<Option<Vec<i16>>> { Ok(reads.borrow_mut().pop().flatten()) };
+        let start_fn = || -> Result<()> { Ok(()) };
+
+        let written = record_clip("note", capture_fn, start_fn, 8, 1, &tx, &shutdown).unwrap();
+        assert_eq!(written, 8);
+        drop(tx);
+        assert_eq!(rx.try_iter().count(), 2); // SpeechStart + SpeechEnd
+    }
+
+    #[test]
+    fn test_record_clip_stops_on_shutdown() {
+        let (tx, rx) = mpsc::channel();
+        let shutdown = Arc::new(AtomicBool::new(true));
+
+        let capture_fn = || -> Result<Option<Vec<i16>>> {
+            panic!("capture_fn should not be called when shutdown is true");
+        };
+        let start_fn = || -> Result<()> { Ok(()) };
+
+        let written = record_clip("note", capture_fn, start_fn, 16000, 60, &tx, &shutdown).unwrap();
+        assert_eq!(written, 0);
+        drop(tx);
+        assert_eq!(rx.try_iter().count(), 0);
+    }
+}
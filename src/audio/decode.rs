@@ -0,0 +1,138 @@
+// Reading recordings back for transcription, whatever format they were
+// written in (see `output.format`).
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+/// File extensions deskmic writes recordings with.
+pub const RECORDING_EXTENSIONS: &[&str] = &["wav", "flac", "ogg"];
+
+/// Whether `path` looks like a deskmic recording (by extension).
+pub fn is_recording(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| {
+            RECORDING_EXTENSIONS
+                .iter()
+                .any(|r| e.eq_ignore_ascii_case(r))
+        })
+        .unwrap_or(false)
+}
+
+fn has_extension(path: &Path, ext: &str) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case(ext))
+}
+
+fn is_flac(path: &Path) -> bool {
+    has_extension(path, "flac")
+}
+
+/// Ogg Opus recordings (`output.format = "opus"`).
+pub fn is_opus(path: &Path) -> bool {
+    has_extension(path, "ogg")
+}
+
+/// Opus decoding needs libopus, which only the Windows build links.
+#[cfg(not(target_os = "windows"))]
+fn opus_unsupported(path: &Path) -> anyhow::Error {
+    anyhow::anyhow!(
+        "{}: Opus recordings can only be decoded on Windows",
+        path.display()
+    )
+}
+
+/// Decode a mono 16-bit recording into samples and its sample rate.
+pub fn read_samples(path: &Path) -> Result<(Vec<i16>, u32)> {
+    if is_opus(path) {
+        #[cfg(target_os = "windows")]
+        return crate::audio::opus::read_samples(path);
+        #[cfg(not(target_os = "windows"))]
+        return Err(opus_unsupported(path));
+    }
+    if is_flac(path) {
+        let mut reader = claxon::FlacReader::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let sample_rate = reader.streaminfo().sample_rate;
+        let samples = reader
+            .samples()
+            .map(|s| s.map(|s| s as i16))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok((samples, sample_rate))
+    } else {
+        let mut reader = hound::WavReader::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let sample_rate = reader.spec().sample_rate;
+        let samples = reader
+            .samples::<i16>()
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok((samples, sample_rate))
+    }
+}
+
+/// Length of a recording in seconds, read from its header.
+pub fn duration_secs(path: &Path) -> Result<f64> {
+    if is_opus(path) {
+        #[cfg(target_os = "windows")]
+        return crate::audio::opus::duration_secs(path);
+        #[cfg(not(target_os = "windows"))]
+        return Err(opus_unsupported(path));
+    }
+    if is_flac(path) {
+        let reader = claxon::FlacReader::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let info = reader.streaminfo();
+        let frames = info.samples.unwrap_or(0) / info.channels.max(1) as u64;
+        Ok(frames as f64 / info.sample_rate as f64)
+    } else {
+        let reader = hound::WavReader::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        Ok(reader.duration() as f64 / reader.spec().sample_rate as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::flac::FlacWriter;
+
+    #[test]
+    fn test_is_recording() {
+        assert!(is_recording(Path::new("mic_09-00-00.wav")));
+        assert!(is_recording(Path::new("mic_09-00-00.FLAC")));
+        assert!(is_recording(Path::new("mic_09-00-00.ogg")));
+        assert!(!is_recording(Path::new("transcripts.jsonl")));
+        assert!(!is_recording(Path::new("mic_09-00-00")));
+    }
+
+    #[test]
+    fn test_reads_wav_and_flac_alike() {
+        let dir = tempfile::tempdir().unwrap();
+        let samples: Vec<i16> = (0..8000).map(|i| ((i * 37) % 2000 - 1000) as i16).collect();
+
+        let wav = dir.path().join("mic_09-00-00.wav");
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 16000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&wav, spec).unwrap();
+        for &s in &samples {
+            writer.write_sample(s).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let flac = dir.path().join("mic_09-00-00.flac");
+        let mut writer = FlacWriter::create(&flac, 16000, &[]).unwrap();
+        writer.write_samples(&samples).unwrap();
+        writer.finalize().unwrap();
+
+        for path in [&wav, &flac] {
+            assert_eq!(read_samples(path).unwrap(), (samples.clone(), 16000));
+            assert!((duration_secs(path).unwrap() - 0.5).abs() < 1e-9);
+        }
+    }
+}
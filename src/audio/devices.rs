@@ -0,0 +1,68 @@
+// Capture device descriptions and selection.
+//
+// Enumeration itself lives in `capture` (Windows-only); matching a configured
+// device name or ID is plain string work, so it's cross-platform and tested here.
+
+/// A capture endpoint (microphone or line-in) known to Windows.
+#[derive(Debug, Clone)]
+pub struct CaptureDevice {
+    /// Friendly name shown in Sound settings, e.g. "Microphone (USB Audio)".
+    pub name: String,
+    /// WASAPI endpoint ID, stable across reboots.
+    pub id: String,
+    /// Whether this is the current default recording device.
+    pub is_default: bool,
+}
+
+/// Index of the device matching `wanted`: an exact endpoint ID, or else the
+/// first device whose friendly name contains `wanted` (case-insensitive).
+pub fn find_device(devices: &[CaptureDevice], wanted: &str) -> Option<usize> {
+    let wanted = wanted.trim();
+    if wanted.is_empty() {
+        return None;
+    }
+    if let Some(index) = devices.iter().position(|d| d.id == wanted) {
+        return Some(index);
+    }
+    let wanted = wanted.to_lowercase();
+    devices
+        .iter()
+        .position(|d| d.name.to_lowercase().contains(&wanted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn devices() -> Vec<CaptureDevice> {
+        vec![
+            CaptureDevice {
+                name: "Microphone Array (Realtek Audio)".to_string(),
+                id: "{0.0.1.00000000}.{aaaa}".to_string(),
+                is_default: true,
+            },
+            CaptureDevice {
+                name: "Headset Microphone (Jabra Evolve2)".to_string(),
+                id: "{0.0.1.00000000}.{bbbb}".to_string(),
+                is_default: false,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_find_device_by_name_substring() {
+        assert_eq!(find_device(&devices(), "jabra"), Some(1));
+        assert_eq!(find_device(&devices(), "MICROPHONE"), Some(0));
+    }
+
+    #[test]
+    fn test_find_device_by_id() {
+        assert_eq!(find_device(&devices(), "{0.0.1.00000000}.{bbbb}"), Some(1));
+    }
+
+    #[test]
+    fn test_find_device_missing() {
+        assert_eq!(find_device(&devices(), "Yeti"), None);
+        assert_eq!(find_device(&devices(), "  "), None);
+    }
+}
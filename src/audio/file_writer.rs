@@ -1,148 +1,807 @@
-// File writer thread: receives AudioMessages from capture pipelines and writes WAV files.
+// File writer thread: receives AudioMessages from capture pipelines and writes WAV
+// (or FLAC / Ogg Opus, per `output.format`) files.
 //
-// Each speech segment becomes one WAV file. Files are rotated if they exceed
-// `max_file_duration_mins`. Optionally organized into date-based subdirectories.
+// Each speech segment becomes one file. Files are rotated if they exceed
+// `max_file_duration_mins`; the segment then continues in a fresh file so no
+// audio is lost at the boundary. Optionally organized into date-based subdirectories.
+// With `vad.merge_gap_secs`, a finished segment's file is held open that long,
+// and speech starting again within it is appended instead of starting a new file.
+// A write error only drops the affected source's file; the thread keeps running.
+// A full disk pauses recording (with a toast) until space is freed.
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::Receiver;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use chrono::Local;
+use chrono::{DateTime, Local};
 use hound::{SampleFormat, WavSpec, WavWriter};
 
+use crate::audio::flac::FlacWriter;
+#[cfg(target_os = "windows")]
+use crate::audio::opus::OpusWriter;
 use crate::audio::pipeline::AudioMessage;
-use crate::config::OutputConfig;
+use crate::audio::wav_metadata;
+use crate::config::{fill_template, OutputConfig};
+use crate::monitoring::Heartbeats;
+use crate::storage::LowDiskTrigger;
+
+/// The encoder behind an open recording.
+enum Encoder {
+    Wav(WavWriter<std::io::BufWriter<std::fs::File>>),
+    Flac(FlacWriter),
+    #[cfg(target_os = "windows")]
+    Opus(OpusWriter),
+}
 
 struct ActiveFile {
-    writer: WavWriter<std::io::BufWriter<std::fs::File>>,
+    encoder: Encoder,
     path: PathBuf,
     sample_count: usize,
     max_samples: usize,
+    sample_rate: u32,
+    source: String,
+    started: DateTime<Local>,
+    embed_metadata: bool,
+}
+
+impl ActiveFile {
+    /// Create a new recording for `source` in the output directory, named
+    /// after `started`.
+    fn create(
+        output_config: &OutputConfig,
+        source: &str,
+        sample_rate: u32,
+        max_samples: usize,
+        started: DateTime<Local>,
+    ) -> Result<Self> {
+        let path = make_file_path(
+            &output_config.directory,
+            &output_config.filename_template,
+            source,
+            output_config.machine_id().as_deref(),
+            output_config.organize_by_date,
+            output_config.extension(),
+            started,
+        )?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // FLAC and Opus carry their tags in the stream header, so they're
+        // written up front.
+        let tags = if output_config.embed_metadata {
+            vorbis_tags(source, started)
+        } else {
+            Vec::new()
+        };
+        let encoder = match output_config.format.as_str() {
+            "flac" => Encoder::Flac(FlacWriter::create(&path, sample_rate, &tags)?),
+            #[cfg(target_os = "windows")]
+            "opus" => Encoder::Opus(OpusWriter::create(&path, sample_rate, &tags)?),
+            #[cfg(not(target_os = "windows"))]
+            "opus" => anyhow::bail!("Opus recording is only supported on Windows"),
+            _ => {
+                let spec = WavSpec {
+                    channels: 1,
+                    sample_rate,
+                    bits_per_sample: 16,
+                    sample_format: SampleFormat::Int,
+                };
+                Encoder::Wav(WavWriter::create(&path, spec)?)
+            }
+        };
+
+        Ok(Self {
+            encoder,
+            path,
+            sample_count: 0,
+            max_samples,
+            sample_rate,
+            source: source.to_string(),
+            started,
+            embed_metadata: output_config.embed_metadata,
+        })
+    }
+
+    fn write(&mut self, samples: &[i16]) -> Result<()> {
+        match &mut self.encoder {
+            Encoder::Wav(writer) => {
+                for &sample in samples {
+                    writer.write_sample(sample)?;
+                }
+            }
+            Encoder::Flac(writer) => writer.write_samples(samples)?,
+            #[cfg(target_os = "windows")]
+            Encoder::Opus(writer) => writer.write_samples(samples)?,
+        }
+        self.sample_count += samples.len();
+        Ok(())
+    }
+
+    /// Samples actually encoded so far, used for rotation. Opus only encodes
+    /// whole 20 ms frames, so a partial frame isn't counted until it's complete.
+    fn encoded_samples(&self) -> usize {
+        match &self.encoder {
+            #[cfg(target_os = "windows")]
+            Encoder::Opus(writer) => writer.encoded_samples() as usize,
+            _ => self.sample_count,
+        }
+    }
+
+    /// Finalize the file (flushing any buffered tail) and, for WAV with metadata
+    /// enabled, embed INFO metadata. Returns the path of the completed file.
+    fn finish(self) -> Result<PathBuf> {
+        let is_wav = match self.encoder {
+            Encoder::Wav(writer) => {
+                writer.finalize()?;
+                true
+            }
+            Encoder::Flac(writer) => {
+                writer.finalize()?;
+                false
+            }
+            #[cfg(target_os = "windows")]
+            Encoder::Opus(writer) => {
+                writer.finalize()?;
+                false
+            }
+        };
+        if is_wav && self.embed_metadata {
+            let tags = wav_metadata::deskmic_tags(&self.source, self.started);
+            if let Err(e) = wav_metadata::append_info_chunk(&self.path, &tags) {
+                tracing::warn!("Failed to embed metadata in {}: {:?}", self.path.display(), e);
+            }
+        }
+        Ok(self.path)
+    }
+
+    /// Close the file without finalizing it, and delete it.
+    fn discard(self) {
+        drop(self.encoder);
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            tracing::warn!("Failed to remove {}: {:?}", self.path.display(), e);
+        }
+    }
 }
 
+/// Vorbis comments (FLAC and Opus) equivalent to `wav_metadata::deskmic_tags`.
+fn vorbis_tags(source: &str, started: DateTime<Local>) -> Vec<(&'static str, String)> {
+    vec![
+        ("SOURCE", source.to_string()),
+        ("DATE", started.format("%Y-%m-%d %H:%M:%S").to_string()),
+        ("ENCODER", format!("deskmic {}", env!("CARGO_PKG_VERSION"))),
+    ]
+}
+
+/// How long after a failed write a still-running segment may open a new file.
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often free space is checked while recording is paused for a full disk.
+const DISK_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Free space needed on the output volume to resume after a full disk.
+const DISK_RESUME_BYTES: u64 = 256 * 1024 * 1024;
+
+/// How the writer measures free space and tells the user about a full disk;
+/// tests swap these out.
+#[derive(Clone, Copy)]
+struct DiskHooks {
+    free_space: fn(&Path) -> Option<u64>,
+    notify: fn(&str, &str),
+    /// How often free space is checked while recording is paused.
+    check_interval: Duration,
+}
+
+const SYSTEM_DISK_HOOKS: DiskHooks = DiskHooks {
+    free_space: crate::storage::free_space,
+    notify: crate::monitoring::send_toast,
+    check_interval: DISK_CHECK_INTERVAL,
+};
+
 /// Runs the file writer loop. Call on a dedicated thread.
 ///
 /// Blocks until the channel is closed (all senders dropped), then finalizes
 /// any remaining open files before returning.
+///
+/// A failure writing one source's file (disk full, permissions) is logged and
+/// that file dropped; other sources keep recording. If the failed segment is
+/// still going, a new file is tried every `RETRY_INTERVAL`.
+///
+/// When the output disk is full, recording is paused through `paused` and a
+/// toast shown; once `DISK_RESUME_BYTES` are free again, recording resumes.
+/// With `low_disk`, free space is also checked as recordings start and while
+/// any is open, and cleanup runs early when it's low or the disk fills up.
+///
+/// Pipeline heartbeats are recorded in `heartbeats`, if given, for the gap
+/// timer to spot a stalled capture.
+///
+/// A non-zero `merge_gap` holds each finished segment's file open for that
+/// long; if the same source starts speaking again in time, the new segment is
+/// appended to it, so brief pauses don't split a conversation into many files.
 pub fn run_file_writer(
     receiver: Receiver<AudioMessage>,
     output_config: &OutputConfig,
     sample_rate: u32,
+    merge_gap: Duration,
+    paused: Arc<AtomicBool>,
+    low_disk: Option<LowDiskTrigger>,
+    heartbeats: Option<Arc<Heartbeats>>,
 ) -> Result<()> {
-    let mut active_files: HashMap<String, ActiveFile> = HashMap::new();
-    let max_samples = (output_config.max_file_duration_mins as usize) * 60 * sample_rate as usize;
+    let writer = FileWriter {
+        output_config,
+        sample_rate,
+        max_samples: (output_config.max_file_duration_mins as usize) * 60 * sample_rate as usize,
+        active_files: HashMap::new(),
+        ended: HashMap::new(),
+        merge_gap,
+        failed: HashMap::new(),
+        segment_rates: HashMap::new(),
+        paused,
+        disk_full: None,
+        last_disk_check: Instant::now(),
+        disk_hooks: SYSTEM_DISK_HOOKS,
+        low_disk,
+        heartbeats,
+    };
 
-    for msg in receiver {
+    writer.run(receiver);
+    Ok(())
+}
+
+/// Open recordings, one per source, and the sources whose file failed.
+struct FileWriter<'a> {
+    output_config: &'a OutputConfig,
+    sample_rate: u32,
+    max_samples: usize,
+    active_files: HashMap<String, ActiveFile>,
+    /// Files whose segment has ended, held open (with when it ended) for
+    /// `merge_gap` in case the source starts speaking again.
+    ended: HashMap<String, (ActiveFile, Instant)>,
+    merge_gap: Duration,
+    /// When each source's last file failed, so retries are spaced out.
+    failed: HashMap<String, Instant>,
+    /// Sample rate of each source's latest segment, for a file resumed after
+    /// a failure.
+    segment_rates: HashMap<String, u32>,
+    /// The shared pause flag, set while the disk is full.
+    paused: Arc<AtomicBool>,
+    /// Set while the disk is full: whether the writer itself paused recording
+    /// (rather than the user), and so should resume it.
+    disk_full: Option<bool>,
+    /// When `check_disk_freed` last ran.
+    last_disk_check: Instant,
+    disk_hooks: DiskHooks,
+    low_disk: Option<LowDiskTrigger>,
+    heartbeats: Option<Arc<Heartbeats>>,
+}
+
+impl FileWriter<'_> {
+    /// Write messages from `receiver` until shutdown or until it closes, then
+    /// finalize every open file.
+    fn run(mut self, receiver: Receiver<AudioMessage>) {
+        loop {
+            self.close_ended();
+            // Checked on every pass: heartbeats keep the receive below from
+            // ever timing out while capture is running.
+            if self.last_disk_check.elapsed() >= self.disk_hooks.check_interval {
+                self.last_disk_check = Instant::now();
+                self.check_disk_freed();
+                // Continuous recording only rotates files, never starts one.
+                if !self.active_files.is_empty() {
+                    self.check_low_disk();
+                }
+            }
+            let msg = match receiver.recv_timeout(self.next_wake()) {
+                Ok(msg) => msg,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            let source = match &msg {
+                AudioMessage::SpeechStart { source, .. }
+                | AudioMessage::SpeechContinue { source, .. }
+                | AudioMessage::SpeechEnd { source }
+                | AudioMessage::Heartbeat { source }
+                | AudioMessage::SourceStopped { source } => source.clone(),
+                AudioMessage::SaveClip { source, .. } => saved_clip_source(source),
+                AudioMessage::Shutdown => break,
+            };
+            if let Err(e) = self.handle(msg) {
+                self.fail(source, e);
+            }
+        }
+
+        // Shutdown (or channel closed) -- finalize all open files.
+        let ended = self.ended.into_values().map(|(active, _)| active);
+        for active in self.active_files.into_values().chain(ended) {
+            match active.finish() {
+                Ok(path) => tracing::info!("Finalized on shutdown: {}", path.display()),
+                Err(e) => tracing::error!("Failed to finalize recording on shutdown: {:#}", e),
+            }
+        }
+    }
+
+    fn handle(&mut self, msg: AudioMessage) -> Result<()> {
         match msg {
             AudioMessage::SpeechStart {
                 source,
                 samples,
                 sample_rate: sr,
             } => {
+                self.failed.remove(&source);
+                self.segment_rates.insert(source.clone(), sr);
                 // Close any existing file for this source.
-                if let Some(active) = active_files.remove(&source) {
-                    active.writer.finalize()?;
-                    tracing::info!("Closed {}", active.path.display());
+                if let Some(active) = self.active_files.remove(&source) {
+                    let path = active.finish()?;
+                    tracing::info!("Closed {}", path.display());
+                }
+                if let Some((active, ended)) = self.ended.remove(&source) {
+                    if ended.elapsed() <= self.merge_gap && active.sample_rate == sr {
+                        // The pre-roll covers the gap when it's no longer than
+                        // the pre-speech buffer; pad whatever it doesn't.
+                        let gap = (ended.elapsed().as_secs_f64() * sr as f64) as usize;
+                        let padding = vec![0i16; gap.saturating_sub(samples.len())];
+                        tracing::info!("Continued recording: {}", active.path.display());
+                        let active = self.active_files.entry(source).or_insert(active);
+                        active.write(&padding)?;
+                        active.write(&samples)?;
+                        return Ok(());
+                    }
+                    let path = active.finish()?;
+                    tracing::info!("Finished recording: {}", path.display());
                 }
 
                 // Create new file.
-                let path = make_file_path(
-                    &output_config.directory,
+                let active = ActiveFile::create(
+                    self.output_config,
                     &source,
-                    output_config.organize_by_date,
-                );
-                if let Some(parent) = path.parent() {
-                    std::fs::create_dir_all(parent)?;
-                }
+                    sr,
+                    self.max_samples,
+                    Local::now(),
+                )?;
+                tracing::info!("Started recording: {}", active.path.display());
+                self.check_low_disk();
+                // Insert before writing so a failed write still finalizes it.
+                let active = self.active_files.entry(source).or_insert(active);
+                active.write(&samples)?;
+            }
 
-                let spec = WavSpec {
-                    channels: 1,
-                    sample_rate: sr,
-                    bits_per_sample: 16,
-                    sample_format: SampleFormat::Int,
+            AudioMessage::SpeechContinue { source, samples } => {
+                if !self.active_files.contains_key(&source) {
+                    // Keep a segment whose file failed going in a new file.
+                    match self.failed.get(&source) {
+                        Some(at) if at.elapsed() >= RETRY_INTERVAL => {}
+                        _ => return Ok(()),
+                    }
+                    let sample_rate = self.segment_rates.get(&source).copied();
+                    let active = ActiveFile::create(
+                        self.output_config,
+                        &source,
+                        sample_rate.unwrap_or(self.sample_rate),
+                        self.max_samples,
+                        Local::now(),
+                    )?;
+                    tracing::info!("Resumed recording: {}", active.path.display());
+                    self.failed.remove(&source);
+                    self.active_files.insert(source.clone(), active);
+                }
+                let Some(active) = self.active_files.get_mut(&source) else {
+                    return Ok(());
                 };
-                let mut writer = WavWriter::create(&path, spec)?;
-                for &sample in &samples {
-                    writer.write_sample(sample)?;
+                active.write(&samples)?;
+
+                if active.encoded_samples() >= active.max_samples {
+                    // Speech is still ongoing: open a continuation file so
+                    // subsequent SpeechContinue data keeps being recorded.
+                    let next = ActiveFile::create(
+                        self.output_config,
+                        &source,
+                        active.sample_rate,
+                        self.max_samples,
+                        Local::now(),
+                    )?;
+                    let finished = match std::mem::replace(active, next).finish() {
+                        Ok(path) => path,
+                        Err(e) => {
+                            // Don't leave the continuation file behind empty.
+                            if let Some(next) = self.active_files.remove(&source) {
+                                next.discard();
+                            }
+                            return Err(e);
+                        }
+                    };
+                    tracing::info!(
+                        "Rotated (max duration): {} -> {}",
+                        finished.display(),
+                        active.path.display()
+                    );
                 }
-                tracing::info!("Started recording: {}", path.display());
-
-                active_files.insert(
-                    source.clone(),
-                    ActiveFile {
-                        writer,
-                        path,
-                        sample_count: samples.len(),
-                        max_samples,
-                    },
-                );
             }
 
-            AudioMessage::SpeechContinue { source, samples } => {
-                if let Some(active) = active_files.get_mut(&source) {
-                    for &sample in &samples {
-                        active.writer.write_sample(sample)?;
+            AudioMessage::SpeechEnd { source } => {
+                self.failed.remove(&source);
+                if let Some(active) = self.active_files.remove(&source) {
+                    if !self.merge_gap.is_zero() {
+                        self.ended.insert(source, (active, Instant::now()));
+                        return Ok(());
                     }
-                    active.sample_count += samples.len();
+                    let path = active.finish()?;
+                    tracing::info!("Finished recording: {}", path.display());
+                }
+            }
 
-                    if active.sample_count >= active.max_samples {
-                        let active = active_files.remove(&source).unwrap();
-                        active.writer.finalize()?;
-                        tracing::info!("Rotated (max duration): {}", active.path.display());
-                    }
+            AudioMessage::SaveClip { source, samples } => {
+                // A one-shot file beside (not replacing) any open recording,
+                // named after when its audio starts rather than when it was saved.
+                let source = saved_clip_source(&source);
+                let length_ms = samples.len() as u64 * 1000 / self.sample_rate as u64;
+                let started = Local::now() - chrono::Duration::milliseconds(length_ms as i64);
+                let mut clip = ActiveFile::create(
+                    self.output_config,
+                    &source,
+                    self.sample_rate,
+                    usize::MAX,
+                    started,
+                )?;
+                clip.write(&samples)?;
+                let path = clip.finish()?;
+                tracing::info!("Saved clip: {}", path.display());
+            }
+
+            AudioMessage::Heartbeat { source } => {
+                if let Some(heartbeats) = &self.heartbeats {
+                    heartbeats.beat(&source);
                 }
             }
 
-            AudioMessage::SpeechEnd { source } => {
-                if let Some(active) = active_files.remove(&source) {
-                    active.writer.finalize()?;
-                    tracing::info!("Finished recording: {}", active.path.display());
+            AudioMessage::SourceStopped { source } => {
+                if let Some(heartbeats) = &self.heartbeats {
+                    heartbeats.stopped(&source);
                 }
             }
+
+            AudioMessage::Shutdown => {}
         }
+        Ok(())
     }
 
-    // Channel closed -- finalize all open files.
-    for (_, active) in active_files {
-        active.writer.finalize()?;
-        tracing::info!("Finalized on shutdown: {}", active.path.display());
+    /// Finalize held files whose `merge_gap` has passed.
+    fn close_ended(&mut self) {
+        let expired: Vec<String> = self
+            .ended
+            .iter()
+            .filter(|(_, (_, ended))| ended.elapsed() > self.merge_gap)
+            .map(|(source, _)| source.clone())
+            .collect();
+        for source in expired {
+            let Some((active, _)) = self.ended.remove(&source) else {
+                continue;
+            };
+            match active.finish() {
+                Ok(path) => tracing::info!("Finished recording: {}", path.display()),
+                Err(e) => self.fail(source, e),
+            }
+        }
     }
 
-    Ok(())
+    /// How long to wait for a message: until the next held file is due to be
+    /// finalized, or the next free space check.
+    fn next_wake(&self) -> Duration {
+        let disk_check = self
+            .disk_hooks
+            .check_interval
+            .saturating_sub(self.last_disk_check.elapsed());
+        self.ended
+            .values()
+            .map(|(_, ended)| {
+                (self.merge_gap + Duration::from_millis(1)).saturating_sub(ended.elapsed())
+            })
+            .fold(disk_check, Duration::min)
+    }
+
+    /// Run cleanup early if free space is below `storage.min_free_gb`
+    /// (rate-limited by `LowDiskTrigger`).
+    fn check_low_disk(&mut self) {
+        if let Some(low_disk) = &mut self.low_disk {
+            low_disk.check(&self.output_config.directory);
+        }
+    }
+
+    /// Log `error` for `source`, and drop its file after trying to finalize
+    /// what was written so far.
+    fn fail(&mut self, source: String, error: anyhow::Error) {
+        tracing::error!("Recording {} failed: {:#}", source, error);
+        if let Some(active) = self.active_files.remove(&source) {
+            match active.finish() {
+                Ok(path) => tracing::warn!("Closed {} early", path.display()),
+                Err(e) => tracing::warn!("Failed to finalize {} recording: {:#}", source, e),
+            }
+        }
+        self.failed.insert(source, Instant::now());
+        if is_disk_full(&error) {
+            self.pause_for_full_disk();
+        }
+    }
+
+    /// Pause recording until `check_disk_freed` sees enough free space.
+    fn pause_for_full_disk(&mut self) {
+        let we_paused = !self.paused.swap(true, Ordering::Relaxed);
+        if let Some(ours) = &mut self.disk_full {
+            // Already paused for this; the user may have resumed by hand.
+            *ours |= we_paused;
+            return;
+        }
+        self.disk_full = Some(we_paused);
+        let dir = self.output_config.directory.display();
+        tracing::error!("Disk full writing to {}, recording paused", dir);
+        if let Some(low_disk) = &self.low_disk {
+            low_disk.run_now();
+        }
+        (self.disk_hooks.notify)(
+            "Recording paused: disk full",
+            &format!(
+                "deskmic can't write to {}. Recording resumes once space is freed.",
+                dir
+            ),
+        );
+    }
+
+    /// While paused for a full disk, resume once enough space is free. If
+    /// free space can't be measured (no mounted disk holds the directory),
+    /// recording resumes anyway: the next write pauses it again if the disk
+    /// is still full.
+    fn check_disk_freed(&mut self) {
+        let Some(we_paused) = self.disk_full else {
+            return;
+        };
+        let free = (self.disk_hooks.free_space)(&self.output_config.directory);
+        if free.is_some_and(|bytes| bytes < DISK_RESUME_BYTES) {
+            return;
+        }
+        self.disk_full = None;
+        self.failed.clear();
+        tracing::info!("Disk space freed, resuming recording");
+        if we_paused {
+            self.paused.store(false, Ordering::Relaxed);
+            (self.disk_hooks.notify)(
+                "Recording resumed",
+                "Disk space was freed, so deskmic is recording again.",
+            );
+        }
+    }
+}
+
+/// Whether `error` was caused by the disk (or the user's quota) being full.
+pub fn is_disk_full(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        let io = match cause.downcast_ref::<hound::Error>() {
+            Some(hound::Error::IoError(e)) => Some(e),
+            _ => cause.downcast_ref::<std::io::Error>(),
+        };
+        io.is_some_and(|e| {
+            matches!(
+                e.kind(),
+                std::io::ErrorKind::StorageFull | std::io::ErrorKind::QuotaExceeded
+            )
+        })
+    })
 }
 
-fn make_file_path(base_dir: &Path, source: &str, organize_by_date: bool) -> PathBuf {
-    let now = Local::now();
-    let filename = format!("{}_{}.wav", source, now.format("%H-%M-%S"));
+/// Source name for a saved clip of `source`, e.g. `mic-clip`, so clips are
+/// told apart from VAD recordings.
+fn saved_clip_source(source: &str) -> String {
+    format!("{}-clip", source)
+}
 
-    if organize_by_date {
+/// Build a path from `template` (by default `{source}_{HH-MM-SS}.{ext}`, or
+/// `{source}_{HH-MM-SS}_{machine}.{ext}` with a machine id) for a file
+/// starting at `at` that doesn't already exist.
+///
+/// Filenames only have one-second resolution, so a segment (or rotation)
+/// starting in the same second as an existing file gets a counter before the
+/// extension (`mic_14-30-05.2.wav`) rather than overwriting it. The time in
+/// the name stays the real start time.
+fn make_file_path(
+    base_dir: &Path,
+    template: &str,
+    source: &str,
+    machine_id: Option<&str>,
+    organize_by_date: bool,
+    extension: &str,
+    at: DateTime<Local>,
+) -> Result<PathBuf> {
+    let path = make_file_path_at(
+        base_dir,
+        template,
+        source,
+        machine_id,
+        organize_by_date,
+        extension,
+        at,
+    )?;
+    Ok(first_free_path(path))
+}
+
+/// `path` if nothing is there yet, otherwise the first of `stem.2.ext`,
+/// `stem.3.ext`, ... that is free.
+fn first_free_path(path: PathBuf) -> PathBuf {
+    if !path.exists() {
+        return path;
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = path.extension().map(|e| e.to_string_lossy());
+    let mut counter = 2;
+    loop {
+        let name = match &ext {
+            Some(ext) => format!("{}.{}.{}", stem, counter, ext),
+            None => format!("{}.{}", stem, counter),
+        };
+        let candidate = path.with_file_name(name);
+        if !candidate.exists() {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+
+fn make_file_path_at(
+    base_dir: &Path,
+    template: &str,
+    source: &str,
+    machine_id: Option<&str>,
+    organize_by_date: bool,
+    extension: &str,
+    now: DateTime<Local>,
+) -> Result<PathBuf> {
+    let filename = fill_template(template, |name| match name {
+        "source" => Some(source.to_string()),
+        "date" => Some(now.format("%Y-%m-%d").to_string()),
+        "time" => Some(now.format("%H-%M-%S").to_string()),
+        "ext" => Some(extension.to_string()),
+        _ => None,
+    })
+    .map_err(|e| anyhow::anyhow!("Bad output.filename_template: {}", e))?;
+    let filename = match (machine_id, filename.rsplit_once('.')) {
+        (Some(id), Some((stem, ext))) => format!("{}_{}.{}", stem, id, ext),
+        (Some(id), None) => format!("{}_{}", filename, id),
+        (None, _) => filename,
+    };
+
+    Ok(if organize_by_date {
         base_dir
             .join(now.format("%Y-%m-%d").to_string())
             .join(filename)
     } else {
         base_dir.join(filename)
-    }
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{parse_recording_name, DEFAULT_FILENAME_TEMPLATE as TEMPLATE};
+    use chrono::{TimeZone, Timelike};
+    use std::cell::Cell;
     use std::sync::mpsc;
 
+    fn unpaused() -> Arc<AtomicBool> {
+        Arc::new(AtomicBool::new(false))
+    }
+
+    thread_local! {
+        /// What `fake_free_space` reports on this test's thread.
+        static FREE_SPACE: Cell<Option<u64>> = const { Cell::new(None) };
+    }
+
+    fn fake_free_space(_: &Path) -> Option<u64> {
+        FREE_SPACE.get()
+    }
+
+    /// Disk hooks that report `FREE_SPACE`, show no toasts and check often.
+    const TEST_DISK_HOOKS: DiskHooks = DiskHooks {
+        free_space: fake_free_space,
+        notify: |_, _| {},
+        check_interval: Duration::from_millis(50),
+    };
+
+    /// A writer recording at 16 kHz, for driving `FileWriter` directly.
+    fn test_writer(output_config: &OutputConfig, paused: Arc<AtomicBool>) -> FileWriter<'_> {
+        FileWriter {
+            output_config,
+            sample_rate: 16000,
+            max_samples: 16000,
+            active_files: HashMap::new(),
+            ended: HashMap::new(),
+            merge_gap: Duration::ZERO,
+            failed: HashMap::new(),
+            segment_rates: HashMap::new(),
+            paused,
+            disk_full: None,
+            last_disk_check: Instant::now(),
+            disk_hooks: TEST_DISK_HOOKS,
+            low_disk: None,
+            heartbeats: None,
+        }
+    }
+
     #[test]
     fn test_make_file_path_with_date() {
-        let path = make_file_path(Path::new("/tmp/recordings"), "mic", true);
+        let path = make_file_path(
+            Path::new("/tmp/recordings"),
+            TEMPLATE,
+            "mic",
+            None,
+            true,
+            "wav",
+            Local::now(),
+        )
+        .unwrap();
         let path_str = path.to_str().unwrap();
         assert!(path_str.contains("mic_"));
         assert!(path_str.ends_with(".wav"));
         assert!(path_str.contains(&Local::now().format("%Y-%m-%d").to_string()));
     }
 
+    #[test]
+    fn test_make_file_path_with_machine_id() {
+        let at = Local.with_ymd_and_hms(2026, 2, 17, 14, 30, 5).unwrap();
+        let path = make_file_path_at(
+            Path::new("/tmp/recordings"),
+            TEMPLATE,
+            "mic",
+            Some("desk"),
+            false,
+            "wav",
+            at,
+        )
+        .unwrap();
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        assert_eq!(name, "mic_14-30-05_desk.wav");
+        assert_eq!(
+            crate::summarize::prompt::extract_hour(TEMPLATE, &name),
+            Some(14)
+        );
+    }
+
+    #[test]
+    fn test_make_file_path_custom_template() {
+        let at = Local.with_ymd_and_hms(2026, 2, 17, 14, 30, 5).unwrap();
+        let template = "{date}-{time}-{source}.{ext}";
+        let path =
+            make_file_path_at(Path::new("/r"), template, "mic", None, true, "flac", at).unwrap();
+        assert_eq!(
+            path,
+            Path::new("/r/2026-02-17/2026-02-17-14-30-05-mic.flac")
+        );
+
+        let path = make_file_path_at(
+            Path::new("/r"),
+            template,
+            "mic",
+            Some("desk"),
+            false,
+            "wav",
+            at,
+        )
+        .unwrap();
+        assert_eq!(path, Path::new("/r/2026-02-17-14-30-05-mic_desk.wav"));
+
+        // The name reads back through the same template.
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let parsed = crate::config::parse_recording_name(template, &name).unwrap();
+        assert_eq!(parsed.source, "mic");
+        assert_eq!(parsed.time, (14, 30, 5));
+        assert_eq!(parsed.machine.as_deref(), Some("desk"));
+    }
+
     #[test]
     fn test_make_file_path_without_date() {
         let base = Path::new("/tmp/recordings");
-        let path = make_file_path(base, "teams", false);
+        let path =
+            make_file_path(base, TEMPLATE, "teams", None, false, "wav", Local::now()).unwrap();
         let path_str = path.to_string_lossy();
         let expected_prefix = format!("{}{}", base.display(), std::path::MAIN_SEPARATOR);
         assert!(
@@ -163,6 +822,10 @@ mod tests {
             directory: tmp_dir.path().to_path_buf(),
             max_file_duration_mins: 30,
             organize_by_date: false,
+            embed_metadata: false,
+            machine_id: String::new(),
+            format: "wav".to_string(),
+            filename_template: TEMPLATE.to_string(),
         };
 
         let (tx, rx) = mpsc::channel();
@@ -193,7 +856,15 @@ mod tests {
         // Drop sender so the receiver loop exits.
         drop(tx);
 
-        let result = run_file_writer(rx, &output_config, sample_rate);
+        let result = run_file_writer(
+            rx,
+            &output_config,
+            sample_rate,
+            Duration::ZERO,
+            unpaused(),
+            None,
+            None,
+        );
         assert!(result.is_ok(), "file writer failed: {:?}", result);
 
         // Find the WAV file in the temp directory.
@@ -229,6 +900,380 @@ mod tests {
         assert_eq!(&samples[start_samples.len()..], &continue_samples[..]);
     }
 
+    #[test]
+    fn test_file_writer_survives_failing_source() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let output_config = OutputConfig {
+            directory: tmp_dir.path().to_path_buf(),
+            max_file_duration_mins: 30,
+            organize_by_date: false,
+            embed_metadata: false,
+            machine_id: String::new(),
+            format: "wav".to_string(),
+            filename_template: "{source}/{source}_{time}.{ext}".to_string(),
+        };
+        // A file where the "bad" source's directory should be: it can't be
+        // created (even for root, unlike a permissions change).
+        std::fs::write(tmp_dir.path().join("bad"), b"").unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        for source in ["bad", "good"] {
+            tx.send(AudioMessage::SpeechStart {
+                source: source.to_string(),
+                samples: vec![1; 160],
+                sample_rate: 16000,
+            })
+            .unwrap();
+            tx.send(AudioMessage::SpeechContinue {
+                source: source.to_string(),
+                samples: vec![2; 160],
+            })
+            .unwrap();
+        }
+        tx.send(AudioMessage::SpeechEnd {
+            source: "good".to_string(),
+        })
+        .unwrap();
+        drop(tx);
+
+        run_file_writer(
+            rx,
+            &output_config,
+            16000,
+            Duration::ZERO,
+            unpaused(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let good: Vec<_> = std::fs::read_dir(tmp_dir.path().join("good"))
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        assert_eq!(good.len(), 1);
+        let reader = hound::WavReader::open(&good[0]).unwrap();
+        assert_eq!(reader.len(), 320);
+    }
+
+    #[test]
+    fn test_resumed_file_keeps_segment_sample_rate() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let output_config = OutputConfig {
+            directory: tmp_dir.path().to_path_buf(),
+            organize_by_date: false,
+            ..OutputConfig::default()
+        };
+        let mut writer = test_writer(&output_config, unpaused());
+
+        writer
+            .handle(AudioMessage::SpeechStart {
+                source: "mic".to_string(),
+                samples: vec![1; 480],
+                sample_rate: 48000,
+            })
+            .unwrap();
+        writer.fail("mic".to_string(), anyhow::anyhow!("write failed"));
+        let retry_due = Instant::now().checked_sub(RETRY_INTERVAL).unwrap();
+        writer.failed.insert("mic".to_string(), retry_due);
+        writer
+            .handle(AudioMessage::SpeechContinue {
+                source: "mic".to_string(),
+                samples: vec![2; 480],
+            })
+            .unwrap();
+
+        let resumed = writer.active_files.remove("mic").unwrap().finish().unwrap();
+        let reader = hound::WavReader::open(&resumed).unwrap();
+        assert_eq!(reader.spec().sample_rate, 48000);
+        assert_eq!(reader.len(), 480);
+    }
+
+    #[test]
+    fn test_is_disk_full() {
+        let full = std::io::Error::from(std::io::ErrorKind::StorageFull);
+        let error = anyhow::Error::from(hound::Error::IoError(full)).context("writing mic");
+        assert!(is_disk_full(&error));
+
+        let quota = std::io::Error::from(std::io::ErrorKind::QuotaExceeded);
+        assert!(is_disk_full(&anyhow::Error::from(quota)));
+
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert!(!is_disk_full(&anyhow::Error::from(denied)));
+        assert!(!is_disk_full(&anyhow::anyhow!("no space left")));
+    }
+
+    #[test]
+    fn test_disk_full_pauses_until_space_is_free() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let output_config = OutputConfig {
+            directory: tmp_dir.path().to_path_buf(),
+            ..OutputConfig::default()
+        };
+        let paused = unpaused();
+        let mut writer = test_writer(&output_config, paused.clone());
+
+        let full = anyhow::Error::from(std::io::Error::from(std::io::ErrorKind::StorageFull));
+        writer.fail("mic".to_string(), full);
+        assert!(paused.load(Ordering::Relaxed));
+        assert_eq!(writer.disk_full, Some(true));
+
+        FREE_SPACE.set(Some(DISK_RESUME_BYTES - 1));
+        writer.check_disk_freed();
+        assert!(paused.load(Ordering::Relaxed));
+        assert_eq!(writer.disk_full, Some(true));
+
+        FREE_SPACE.set(Some(DISK_RESUME_BYTES));
+        writer.check_disk_freed();
+        assert!(!paused.load(Ordering::Relaxed));
+        assert_eq!(writer.disk_full, None);
+        assert!(writer.failed.is_empty());
+
+        // A pause the user made is left alone when space frees up.
+        paused.store(true, Ordering::Relaxed);
+        writer.pause_for_full_disk();
+        assert_eq!(writer.disk_full, Some(false));
+        writer.check_disk_freed();
+        assert!(paused.load(Ordering::Relaxed));
+        assert_eq!(writer.disk_full, None);
+
+        // Unmeasurable free space doesn't keep recording paused for good.
+        paused.store(false, Ordering::Relaxed);
+        writer.pause_for_full_disk();
+        FREE_SPACE.set(None);
+        writer.check_disk_freed();
+        assert!(!paused.load(Ordering::Relaxed));
+        assert_eq!(writer.disk_full, None);
+    }
+
+    #[test]
+    fn test_disk_full_resumes_while_heartbeats_arrive() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let output_config = OutputConfig {
+            directory: tmp_dir.path().to_path_buf(),
+            ..OutputConfig::default()
+        };
+        let paused = unpaused();
+        let mut writer = test_writer(&output_config, paused.clone());
+        let full = anyhow::Error::from(std::io::Error::from(std::io::ErrorKind::StorageFull));
+        writer.fail("mic".to_string(), full);
+        assert!(paused.load(Ordering::Relaxed));
+        FREE_SPACE.set(Some(DISK_RESUME_BYTES));
+
+        // Heartbeats far more often than the disk check, as from a running
+        // capture, so the writer never waits out a receive timeout.
+        let (tx, rx) = mpsc::channel();
+        let watched = paused.clone();
+        let sender = std::thread::spawn(move || {
+            let start = Instant::now();
+            while watched.load(Ordering::Relaxed) && start.elapsed() < Duration::from_secs(5) {
+                let source = "mic".to_string();
+                tx.send(AudioMessage::Heartbeat { source }).unwrap();
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            tx.send(AudioMessage::Shutdown).unwrap();
+        });
+        writer.run(rx);
+        sender.join().unwrap();
+
+        assert!(!paused.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_file_writer_finalizes_on_shutdown_message() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let output_config = OutputConfig {
+            directory: tmp_dir.path().to_path_buf(),
+            max_file_duration_mins: 30,
+            organize_by_date: false,
+            embed_metadata: false,
+            machine_id: String::new(),
+            format: "wav".to_string(),
+            filename_template: TEMPLATE.to_string(),
+        };
+
+        let (tx, rx) = mpsc::channel();
+        let sample_rate = 16000u32;
+
+        // Speech starts but never ends, and the sender stays alive.
+        tx.send(AudioMessage::SpeechStart {
+            source: "mic".to_string(),
+            samples: vec![100i16; 160],
+            sample_rate,
+        })
+        .unwrap();
+        tx.send(AudioMessage::Shutdown).unwrap();
+        tx.send(AudioMessage::SpeechContinue {
+            source: "mic".to_string(),
+            samples: vec![100i16; 160],
+        })
+        .unwrap();
+
+        run_file_writer(
+            rx,
+            &output_config,
+            sample_rate,
+            Duration::ZERO,
+            unpaused(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let entries: Vec<_> = std::fs::read_dir(tmp_dir.path())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .collect();
+        assert_eq!(entries.len(), 1);
+        let reader = hound::WavReader::open(entries[0].path()).unwrap();
+        assert_eq!(reader.len(), 160, "Messages after Shutdown are ignored");
+        drop(tx);
+    }
+
+    #[test]
+    fn test_file_writer_saves_clip_beside_open_recording() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let output_config = OutputConfig {
+            directory: tmp_dir.path().to_path_buf(),
+            max_file_duration_mins: 30,
+            organize_by_date: false,
+            embed_metadata: false,
+            machine_id: String::new(),
+            format: "wav".to_string(),
+            filename_template: TEMPLATE.to_string(),
+        };
+
+        let (tx, rx) = mpsc::channel();
+        let sample_rate = 16000u32;
+        tx.send(AudioMessage::SpeechStart {
+            source: "mic".to_string(),
+            samples: vec![100i16; 160],
+            sample_rate,
+        })
+        .unwrap();
+        tx.send(AudioMessage::SaveClip {
+            source: "mic".to_string(),
+            samples: vec![7i16; 480],
+        })
+        .unwrap();
+        tx.send(AudioMessage::SpeechContinue {
+            source: "mic".to_string(),
+            samples: vec![100i16; 160],
+        })
+        .unwrap();
+        drop(tx);
+
+        run_file_writer(
+            rx,
+            &output_config,
+            sample_rate,
+            Duration::ZERO,
+            unpaused(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let mut lengths: Vec<(String, u32)> = std::fs::read_dir(tmp_dir.path())
+            .unwrap()
+            .map(|e| {
+                let path = e.unwrap().path();
+                let name = path.file_name().unwrap().to_string_lossy().to_string();
+                let source = crate::transcribe::backend::source_from_filename(TEMPLATE, &name);
+                (source, hound::WavReader::open(&path).unwrap().len())
+            })
+            .collect();
+        lengths.sort();
+        assert_eq!(
+            lengths,
+            vec![("mic".to_string(), 320), ("mic-clip".to_string(), 480)]
+        );
+    }
+
+    #[test]
+    fn test_saved_clip_is_named_after_its_start() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let output_config = OutputConfig {
+            directory: tmp_dir.path().to_path_buf(),
+            organize_by_date: false,
+            ..OutputConfig::default()
+        };
+
+        // 90 seconds of audio at 8 Hz.
+        let (tx, rx) = mpsc::channel();
+        tx.send(AudioMessage::SaveClip {
+            source: "mic".to_string(),
+            samples: vec![7i16; 8 * 90],
+        })
+        .unwrap();
+        drop(tx);
+        let saved_at = Local::now();
+        run_file_writer(
+            rx,
+            &output_config,
+            8,
+            Duration::ZERO,
+            unpaused(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let entry = std::fs::read_dir(tmp_dir.path()).unwrap().next().unwrap();
+        let name = entry.unwrap().file_name().to_string_lossy().to_string();
+        let (h, m, s) = parse_recording_name(TEMPLATE, &name).unwrap().time;
+        let named = (h * 3600 + m * 60 + s) as i64;
+        let saved = saved_at.num_seconds_from_midnight() as i64;
+        let before = (saved - named).rem_euclid(86_400);
+        assert!(
+            (89..=91).contains(&before),
+            "{} is {}s before save",
+            name,
+            before
+        );
+    }
+
+    #[test]
+    fn test_file_writer_records_heartbeats() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let output_config = OutputConfig {
+            directory: tmp_dir.path().to_path_buf(),
+            ..OutputConfig::default()
+        };
+        let heartbeats = Arc::new(Heartbeats::new());
+
+        let (tx, rx) = mpsc::channel();
+        for source in ["mic", "teams"] {
+            tx.send(AudioMessage::Heartbeat {
+                source: source.to_string(),
+            })
+            .unwrap();
+        }
+        tx.send(AudioMessage::SourceStopped {
+            source: "teams".to_string(),
+        })
+        .unwrap();
+        drop(tx);
+
+        let beats = Some(heartbeats.clone());
+        run_file_writer(
+            rx,
+            &output_config,
+            16000,
+            Duration::ZERO,
+            unpaused(),
+            None,
+            beats,
+        )
+        .unwrap();
+
+        // Heartbeats alone write nothing.
+        assert_eq!(std::fs::read_dir(tmp_dir.path()).unwrap().count(), 0);
+        let later = Instant::now() + crate::monitoring::CAPTURE_STALL_TIMEOUT * 2;
+        let stalled = heartbeats.stalled(later, crate::monitoring::CAPTURE_STALL_TIMEOUT);
+        assert_eq!(stalled, vec!["mic"]);
+    }
+
     #[test]
     fn test_file_writer_organizes_by_date() {
         let tmp_dir = tempfile::tempdir().unwrap();
@@ -236,6 +1281,10 @@ mod tests {
             directory: tmp_dir.path().to_path_buf(),
             max_file_duration_mins: 30,
             organize_by_date: true,
+            embed_metadata: false,
+            machine_id: String::new(),
+            format: "wav".to_string(),
+            filename_template: TEMPLATE.to_string(),
         };
 
         let (tx, rx) = mpsc::channel();
@@ -255,7 +1304,16 @@ mod tests {
 
         drop(tx);
 
-        run_file_writer(rx, &output_config, sample_rate).unwrap();
+        run_file_writer(
+            rx,
+            &output_config,
+            sample_rate,
+            Duration::ZERO,
+            unpaused(),
+            None,
+            None,
+        )
+        .unwrap();
 
         // Should have created a date subdirectory.
         let date_dir = tmp_dir
@@ -270,4 +1328,229 @@ mod tests {
         assert_eq!(entries.len(), 1);
         assert!(entries[0].path().to_str().unwrap().contains("mic_"));
     }
+
+    #[test]
+    fn test_file_writer_rotation_continues_segment() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let output_config = OutputConfig {
+            directory: tmp_dir.path().to_path_buf(),
+            max_file_duration_mins: 1,
+            organize_by_date: false,
+            embed_metadata: false,
+            machine_id: String::new(),
+            format: "wav".to_string(),
+            filename_template: TEMPLATE.to_string(),
+        };
+
+        // A low sample rate keeps one minute (the rotation limit) small: 6000 samples.
+        let (tx, rx) = mpsc::channel();
+        let sample_rate = 100u32;
+        let chunk = vec![7i16; 1000];
+
+        tx.send(AudioMessage::SpeechStart {
+            source: "mic".to_string(),
+            samples: chunk.clone(),
+            sample_rate,
+        })
+        .unwrap();
+        let mut total_sent = chunk.len();
+
+        // Enough to cross the limit twice (15000 samples > 2 * 6000).
+        for _ in 0..14 {
+            tx.send(AudioMessage::SpeechContinue {
+                source: "mic".to_string(),
+                samples: chunk.clone(),
+            })
+            .unwrap();
+            total_sent += chunk.len();
+        }
+
+        tx.send(AudioMessage::SpeechEnd {
+            source: "mic".to_string(),
+        })
+        .unwrap();
+        drop(tx);
+
+        run_file_writer(
+            rx,
+            &output_config,
+            sample_rate,
+            Duration::ZERO,
+            unpaused(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let wavs: Vec<_> = std::fs::read_dir(tmp_dir.path())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .collect();
+        assert_eq!(wavs.len(), 3, "Expected two rotations (three files)");
+
+        let total_written: usize = wavs
+            .iter()
+            .map(|p| hound::WavReader::open(p).unwrap().duration() as usize)
+            .sum();
+        assert_eq!(total_written, total_sent);
+    }
+
+    #[test]
+    fn test_file_writer_writes_flac() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let output_config = OutputConfig {
+            directory: tmp_dir.path().to_path_buf(),
+            max_file_duration_mins: 30,
+            organize_by_date: false,
+            embed_metadata: true,
+            machine_id: String::new(),
+            format: "flac".to_string(),
+            filename_template: TEMPLATE.to_string(),
+        };
+
+        let (tx, rx) = mpsc::channel();
+        let samples: Vec<i16> = (0..5000).map(|i| ((i * 13) % 4000 - 2000) as i16).collect();
+        tx.send(AudioMessage::SpeechStart {
+            source: "mic".to_string(),
+            samples: samples.clone(),
+            sample_rate: 16000,
+        })
+        .unwrap();
+        tx.send(AudioMessage::SpeechEnd {
+            source: "mic".to_string(),
+        })
+        .unwrap();
+        drop(tx);
+
+        run_file_writer(
+            rx,
+            &output_config,
+            16000,
+            Duration::ZERO,
+            unpaused(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let entries: Vec<_> = std::fs::read_dir(tmp_dir.path())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .collect();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].to_str().unwrap().ends_with(".flac"));
+
+        let reader = claxon::FlacReader::open(&entries[0]).unwrap();
+        assert_eq!(reader.get_tag("SOURCE").next(), Some("mic"));
+        assert_eq!(
+            crate::audio::decode::read_samples(&entries[0]).unwrap(),
+            (samples, 16000)
+        );
+    }
+
+    /// Run the writer with `merge_gap` while sending two 1000-sample segments
+    /// of `mic` `pause` apart; returns the lengths of the files written.
+    fn write_two_segments(merge_gap: Duration, pause: Duration) -> Vec<u32> {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let output_config = OutputConfig {
+            directory: tmp_dir.path().to_path_buf(),
+            max_file_duration_mins: 30,
+            organize_by_date: false,
+            embed_metadata: false,
+            machine_id: String::new(),
+            format: "wav".to_string(),
+            filename_template: TEMPLATE.to_string(),
+        };
+
+        let (tx, rx) = mpsc::channel();
+        let writer_config = output_config.clone();
+        let writer = std::thread::spawn(move || {
+            run_file_writer(rx, &writer_config, 16000, merge_gap, unpaused(), None, None)
+        });
+        for i in 0..2 {
+            if i > 0 {
+                std::thread::sleep(pause);
+            }
+            tx.send(AudioMessage::SpeechStart {
+                source: "mic".to_string(),
+                samples: vec![1000; 1000],
+                sample_rate: 16000,
+            })
+            .unwrap();
+            tx.send(AudioMessage::SpeechEnd {
+                source: "mic".to_string(),
+            })
+            .unwrap();
+        }
+        drop(tx);
+        writer.join().unwrap().unwrap();
+
+        let mut lengths: Vec<u32> = std::fs::read_dir(tmp_dir.path())
+            .unwrap()
+            .map(|e| {
+                hound::WavReader::open(e.unwrap().path())
+                    .unwrap()
+                    .duration()
+            })
+            .collect();
+        lengths.sort();
+        lengths
+    }
+
+    #[test]
+    fn test_file_writer_merges_segments_within_gap() {
+        let lengths = write_two_segments(Duration::from_secs(5), Duration::from_millis(300));
+        assert_eq!(lengths.len(), 1, "Expected one merged file");
+        // The 300 ms gap is up to 4800 samples, 1000 of them covered by the
+        // second segment's pre-roll; the rest is silence.
+        assert!(lengths[0] > 1000 + 1000, "Gap not padded: {:?}", lengths);
+        assert!(lengths[0] < 1000 + 16000, "Too much padding: {:?}", lengths);
+    }
+
+    #[test]
+    fn test_file_writer_splits_segments_after_gap() {
+        let lengths = write_two_segments(Duration::from_millis(50), Duration::from_millis(300));
+        assert_eq!(lengths, vec![1000, 1000]);
+
+        let lengths = write_two_segments(Duration::ZERO, Duration::ZERO);
+        assert_eq!(lengths, vec![1000, 1000]);
+    }
+
+    #[test]
+    fn test_make_file_path_skips_existing() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let at = Local::now();
+        let first =
+            make_file_path(tmp_dir.path(), TEMPLATE, "mic", None, false, "wav", at).unwrap();
+        std::fs::write(&first, b"").unwrap();
+
+        let second =
+            make_file_path(tmp_dir.path(), TEMPLATE, "mic", None, false, "wav", at).unwrap();
+        assert_ne!(first, second);
+        assert!(!second.exists());
+    }
+
+    #[test]
+    fn test_first_free_path_adds_counter() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let path = tmp_dir.path().join("mic_14-30-05_desk.wav");
+        assert_eq!(first_free_path(path.clone()), path);
+
+        std::fs::write(&path, b"").unwrap();
+        let second = first_free_path(path.clone());
+        assert_eq!(second, tmp_dir.path().join("mic_14-30-05_desk.2.wav"));
+
+        std::fs::write(&second, b"").unwrap();
+        let third = first_free_path(path);
+        assert_eq!(third, tmp_dir.path().join("mic_14-30-05_desk.3.wav"));
+
+        // The counter doesn't change what the name parses to.
+        let name = third.file_name().unwrap().to_str().unwrap();
+        let parsed = parse_recording_name(TEMPLATE, name).unwrap();
+        assert_eq!(parsed.source, "mic");
+        assert_eq!(parsed.time, (14, 30, 5));
+        assert_eq!(parsed.machine.as_deref(), Some("desk"));
+    }
 }
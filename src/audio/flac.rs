@@ -0,0 +1,427 @@
+// Minimal FLAC encoder for deskmic recordings (16-bit mono PCM).
+//
+// Each block uses the best of FLAC's fixed predictors (orders 0-4) with a
+// single Rice partition, which gets most of FLAC's size savings on speech
+// without an LPC search. Output decodes with any FLAC reader. STREAMINFO is
+// rewritten on `finalize` with the real sample count and frame sizes; the MD5
+// field is left zero ("not computed"), which the format allows.
+
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use anyhow::Result;
+
+/// Samples per FLAC frame (the format's common default).
+const BLOCK_SIZE: usize = 4096;
+
+const BITS_PER_SAMPLE: u32 = 16;
+
+/// Offset of the STREAMINFO body: "fLaC" marker + 4-byte block header.
+const STREAMINFO_OFFSET: u64 = 8;
+
+/// Largest Rice parameter tried (4-bit parameter field, 15 is the escape code).
+const MAX_RICE_PARAM: u32 = 14;
+
+/// Streams 16-bit mono samples into a `.flac` file.
+pub struct FlacWriter {
+    file: BufWriter<File>,
+    sample_rate: u32,
+    pending: Vec<i16>,
+    frame_number: u32,
+    total_samples: u64,
+    min_frame_bytes: u32,
+    max_frame_bytes: u32,
+}
+
+impl FlacWriter {
+    /// Create `path` and write the stream header. `tags` are stored as Vorbis
+    /// comments (`KEY=value`).
+    pub fn create(path: &Path, sample_rate: u32, tags: &[(&str, String)]) -> Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(b"fLaC")?;
+
+        // STREAMINFO placeholder, rewritten by `finalize`.
+        file.write_all(&[0x00, 0x00, 0x00, 34])?;
+        file.write_all(&[0u8; 34])?;
+
+        let comments = vorbis_comment(tags);
+        // Last metadata block (0x80) of type VORBIS_COMMENT (4).
+        let len = comments.len() as u32;
+        file.write_all(&[0x84, (len >> 16) as u8, (len >> 8) as u8, len as u8])?;
+        file.write_all(&comments)?;
+
+        Ok(Self {
+            file,
+            sample_rate,
+            pending: Vec::with_capacity(BLOCK_SIZE),
+            frame_number: 0,
+            total_samples: 0,
+            min_frame_bytes: u32::MAX,
+            max_frame_bytes: 0,
+        })
+    }
+
+    pub fn write_samples(&mut self, samples: &[i16]) -> Result<()> {
+        for &sample in samples {
+            self.pending.push(sample);
+            if self.pending.len() == BLOCK_SIZE {
+                self.flush_block()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Encode any buffered samples and patch STREAMINFO.
+    pub fn finalize(mut self) -> Result<()> {
+        if !self.pending.is_empty() {
+            self.flush_block()?;
+        }
+        let streaminfo = self.streaminfo();
+        self.file.seek(SeekFrom::Start(STREAMINFO_OFFSET))?;
+        self.file.write_all(&streaminfo)?;
+        self.file.flush()?;
+        Ok(())
+    }
+
+    fn flush_block(&mut self) -> Result<()> {
+        let samples: Vec<i32> = self.pending.drain(..).map(i32::from).collect();
+        let frame = encode_frame(&samples, self.frame_number);
+        self.file.write_all(&frame)?;
+
+        self.frame_number += 1;
+        self.total_samples += samples.len() as u64;
+        self.min_frame_bytes = self.min_frame_bytes.min(frame.len() as u32);
+        self.max_frame_bytes = self.max_frame_bytes.max(frame.len() as u32);
+        Ok(())
+    }
+
+    fn streaminfo(&self) -> Vec<u8> {
+        let mut bits = BitWriter::default();
+        bits.write(BLOCK_SIZE as u64, 16); // min block size
+        bits.write(BLOCK_SIZE as u64, 16); // max block size
+        let min_frame = if self.max_frame_bytes == 0 {
+            0
+        } else {
+            self.min_frame_bytes
+        };
+        bits.write(min_frame as u64, 24);
+        bits.write(self.max_frame_bytes as u64, 24);
+        bits.write(self.sample_rate as u64, 20);
+        bits.write(0, 3); // channels - 1
+        bits.write((BITS_PER_SAMPLE - 1) as u64, 5);
+        bits.write(self.total_samples, 36);
+        bits.write(0, 64); // MD5 (unknown)
+        bits.write(0, 64);
+        bits.into_bytes()
+    }
+}
+
+/// Vorbis comment list (vendor string plus `KEY=value` tags) as used by FLAC's
+/// VORBIS_COMMENT block and `OpusTags`. Lengths are little-endian, unlike the
+/// rest of FLAC.
+pub fn vorbis_comment(tags: &[(&str, String)]) -> Vec<u8> {
+    let vendor = format!("deskmic {}", env!("CARGO_PKG_VERSION"));
+    let mut out = Vec::new();
+    out.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    out.extend_from_slice(vendor.as_bytes());
+    out.extend_from_slice(&(tags.len() as u32).to_le_bytes());
+    for (key, value) in tags {
+        let comment = format!("{}={}", key, value);
+        out.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+        out.extend_from_slice(comment.as_bytes());
+    }
+    out
+}
+
+/// Encode one frame (header, single subframe, CRC-16 footer).
+fn encode_frame(samples: &[i32], frame_number: u32) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+    bits.write(0b11_1111_1111_1110, 14); // sync code
+    bits.write(0, 1); // reserved
+    bits.write(0, 1); // fixed block size stream
+    bits.write(0b0111, 4); // block size: 16-bit (n - 1) after the header
+    bits.write(0b0000, 4); // sample rate: from STREAMINFO
+    bits.write(0b0000, 4); // one channel
+    bits.write(0b100, 3); // 16 bits per sample
+    bits.write(0, 1); // reserved
+    write_utf8_number(&mut bits, frame_number);
+    bits.write((samples.len() - 1) as u64, 16);
+    let header_crc = crc8(bits.bytes());
+    bits.write(header_crc as u64, 8);
+
+    write_subframe(&mut bits, samples);
+    bits.align();
+    let crc = crc16(bits.bytes());
+    bits.write(crc as u64, 16);
+    bits.into_bytes()
+}
+
+fn write_subframe(bits: &mut BitWriter, samples: &[i32]) {
+    if samples.iter().all(|&s| s == samples[0]) {
+        bits.write(0b0000_0000, 8); // CONSTANT
+        bits.write_signed(samples[0], BITS_PER_SAMPLE);
+        return;
+    }
+
+    let verbatim_bits = samples.len() as u64 * BITS_PER_SAMPLE as u64;
+    let best = (0..=4usize)
+        .filter(|&order| samples.len() > order)
+        .map(|order| {
+            let residuals = fixed_residuals(samples, order);
+            let (param, cost) = best_rice_param(&residuals);
+            let total = order as u64 * BITS_PER_SAMPLE as u64 + 6 + cost;
+            (order, residuals, param, total)
+        })
+        .min_by_key(|(_, _, _, total)| *total);
+
+    match best {
+        Some((order, residuals, param, total)) if total < verbatim_bits => {
+            bits.write(0b0001_0000 | (order as u64) << 1, 8); // FIXED, order
+            for &warmup in &samples[..order] {
+                bits.write_signed(warmup, BITS_PER_SAMPLE);
+            }
+            bits.write(0b00, 2); // Rice coding, 4-bit parameters
+            bits.write(0, 4); // partition order 0
+            bits.write(param as u64, 4);
+            for &r in &residuals {
+                let folded = fold(r);
+                bits.write_unary(folded >> param);
+                bits.write(folded as u64 & ((1u64 << param) - 1), param);
+            }
+        }
+        _ => {
+            bits.write(0b0000_0010, 8); // VERBATIM
+            for &s in samples {
+                bits.write_signed(s, BITS_PER_SAMPLE);
+            }
+        }
+    }
+}
+
+/// Residuals of FLAC's fixed polynomial predictor of the given order.
+fn fixed_residuals(samples: &[i32], order: usize) -> Vec<i32> {
+    (order..samples.len())
+        .map(|i| {
+            let s = |k: usize| samples[i - k];
+            match order {
+                0 => s(0),
+                1 => s(0) - s(1),
+                2 => s(0) - 2 * s(1) + s(2),
+                3 => s(0) - 3 * s(1) + 3 * s(2) - s(3),
+                _ => s(0) - 4 * s(1) + 6 * s(2) - 4 * s(3) + s(4),
+            }
+        })
+        .collect()
+}
+
+/// Map a signed residual to unsigned (0, -1, 1, -2, ... -> 0, 1, 2, 3, ...).
+fn fold(r: i32) -> u32 {
+    ((r << 1) ^ (r >> 31)) as u32
+}
+
+/// Rice parameter with the fewest encoded bits, and that bit count.
+fn best_rice_param(residuals: &[i32]) -> (u32, u64) {
+    let folded: Vec<u32> = residuals.iter().map(|&r| fold(r)).collect();
+    (0..=MAX_RICE_PARAM)
+        .map(|k| {
+            let cost: u64 = folded.iter().map(|&u| (u >> k) as u64 + 1 + k as u64).sum();
+            (k, cost)
+        })
+        .min_by_key(|&(_, cost)| cost)
+        .unwrap_or((0, 0))
+}
+
+/// Frame numbers use UTF-8-style variable-length coding.
+fn write_utf8_number(bits: &mut BitWriter, value: u32) {
+    if value < 0x80 {
+        bits.write(value as u64, 8);
+        return;
+    }
+    let extra = match value {
+        0..=0x7FF => 1,
+        0x800..=0xFFFF => 2,
+        0x1_0000..=0x1F_FFFF => 3,
+        0x20_0000..=0x3FF_FFFF => 4,
+        _ => 5,
+    };
+    let lead_marker = (0xFF00u32 >> (extra + 1)) & 0xFF;
+    bits.write((lead_marker | (value >> (6 * extra))) as u64, 8);
+    for i in (0..extra).rev() {
+        bits.write((0x80 | ((value >> (6 * i)) & 0x3F)) as u64, 8);
+    }
+}
+
+fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for &byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// MSB-first bit writer.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u64,
+    acc_bits: u32,
+}
+
+impl BitWriter {
+    /// Write the low `n` bits of `value` (up to 64).
+    fn write(&mut self, value: u64, n: u32) {
+        if n > 32 {
+            self.write(value >> 32, n - 32);
+            self.write(value & 0xFFFF_FFFF, 32);
+            return;
+        }
+        if n == 0 {
+            return;
+        }
+        self.acc = (self.acc << n) | (value & ((1u64 << n) - 1));
+        self.acc_bits += n;
+        while self.acc_bits >= 8 {
+            self.acc_bits -= 8;
+            self.bytes.push((self.acc >> self.acc_bits) as u8);
+        }
+        self.acc &= (1u64 << self.acc_bits) - 1;
+    }
+
+    fn write_signed(&mut self, value: i32, n: u32) {
+        self.write(value as u32 as u64, n);
+    }
+
+    /// `q` zero bits followed by a one.
+    fn write_unary(&mut self, mut q: u32) {
+        while q >= 32 {
+            self.write(0, 32);
+            q -= 32;
+        }
+        self.write(1, q + 1);
+    }
+
+    /// Pad with zero bits to the next byte boundary.
+    fn align(&mut self) {
+        if self.acc_bits > 0 {
+            self.write(0, 8 - self.acc_bits);
+        }
+    }
+
+    /// Completed bytes so far (call after `align` for the full stream).
+    fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        self.align();
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(samples: &[i16]) -> (Vec<i16>, claxon::metadata::StreamInfo) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mic_09-00-00.flac");
+        let mut writer =
+            FlacWriter::create(&path, 16000, &[("SOURCE", "mic".to_string())]).unwrap();
+        writer.write_samples(samples).unwrap();
+        writer.finalize().unwrap();
+
+        let mut reader = claxon::FlacReader::open(&path).unwrap();
+        assert_eq!(reader.get_tag("SOURCE").next(), Some("mic"));
+        let info = reader.streaminfo();
+        let decoded = reader.samples().map(|s| s.unwrap() as i16).collect();
+        (decoded, info)
+    }
+
+    #[test]
+    fn test_round_trip_speech_like_signal() {
+        // Several blocks plus a partial one, with a loud, varying signal.
+        let samples: Vec<i16> = (0..10_000)
+            .map(|i| {
+                let t = i as f64 / 16000.0;
+                ((t * 440.0 * std::f64::consts::TAU).sin() * 12000.0
+                    + (t * 97.0 * std::f64::consts::TAU).sin() * 8000.0) as i16
+            })
+            .collect();
+        let (decoded, info) = round_trip(&samples);
+
+        assert_eq!(decoded, samples);
+        assert_eq!(info.samples, Some(10_000));
+        assert_eq!(info.sample_rate, 16000);
+        assert_eq!(info.channels, 1);
+        assert_eq!(info.bits_per_sample, 16);
+    }
+
+    #[test]
+    fn test_round_trip_extremes_and_silence() {
+        let mut samples = vec![0i16; 5000];
+        samples.extend([i16::MIN, i16::MAX, i16::MIN, i16::MAX, -1, 1, 0]);
+        samples.extend((0..3000).map(|i| if i % 2 == 0 { i16::MAX } else { i16::MIN }));
+        let (decoded, _) = round_trip(&samples);
+        assert_eq!(decoded, samples);
+    }
+
+    #[test]
+    fn test_round_trip_tiny_and_empty() {
+        assert_eq!(round_trip(&[5, -3, 7]).0, vec![5, -3, 7]);
+        let (decoded, info) = round_trip(&[]);
+        assert!(decoded.is_empty());
+        // A zero total reads back as "unknown".
+        assert_eq!(info.samples.unwrap_or(0), 0);
+    }
+
+    #[test]
+    fn test_compresses_speech_like_signal() {
+        let samples: Vec<i16> = (0..16000)
+            .map(|i| ((i as f64 / 16000.0 * 300.0 * std::f64::consts::TAU).sin() * 3000.0) as i16)
+            .collect();
+        let frame = encode_frame(
+            &samples[..BLOCK_SIZE]
+                .iter()
+                .map(|&s| s as i32)
+                .collect::<Vec<_>>(),
+            0,
+        );
+        // Less than half the size of the raw 16-bit samples.
+        assert!(frame.len() < BLOCK_SIZE);
+    }
+
+    #[test]
+    fn test_utf8_frame_numbers() {
+        let encode = |n| {
+            let mut bits = BitWriter::default();
+            write_utf8_number(&mut bits, n);
+            bits.into_bytes()
+        };
+        assert_eq!(encode(0x7F), vec![0x7F]);
+        assert_eq!(encode(0x80), vec![0xC2, 0x80]);
+        assert_eq!(encode(0x1234), vec![0xE1, 0x88, 0xB4]);
+    }
+}
@@ -0,0 +1,238 @@
+// Input level telemetry: per-source peak/RMS published for live monitoring.
+//
+// Capture pipelines feed every chunk into a shared `LevelMeter`; a writer
+// thread periodically folds the accumulated levels into `LevelStatus` and
+// writes it next to the recordings, where the tray (or `deskmic status`) can
+// read it.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::config::machine_scoped;
+
+/// How often the level file is rewritten.
+const WRITE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Level reported for digital silence (log of zero).
+pub const SILENCE_DBFS: f32 = -96.0;
+
+const LEVELS_FILE_NAME: &str = ".audio-levels.json";
+
+/// Smallest peak (about -60 dBFS) counted as audible rather than silence or
+/// dither, for `LevelMeter::last_heard`.
+const AUDIBLE_PEAK: i32 = 32;
+
+/// Convert a linear amplitude (0.0..=1.0 of full scale) to dBFS.
+pub fn to_dbfs(amplitude: f64) -> f32 {
+    if amplitude <= 0.0 {
+        return SILENCE_DBFS;
+    }
+    ((20.0 * amplitude.log10()) as f32).max(SILENCE_DBFS)
+}
+
+/// Peak and RMS accumulated since the last snapshot.
+#[derive(Debug, Default)]
+struct Window {
+    peak: i32,
+    sum_squares: f64,
+    samples: u64,
+}
+
+/// Shared, thread-safe accumulator of input levels keyed by source.
+#[derive(Debug, Default)]
+pub struct LevelMeter {
+    windows: Mutex<BTreeMap<String, Window>>,
+    heard: Mutex<BTreeMap<String, Instant>>,
+}
+
+impl LevelMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fold one chunk of captured samples into `source`'s current window.
+    pub fn record(&self, source: &str, samples: &[i16]) {
+        if samples.is_empty() {
+            return;
+        }
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        let window = windows.entry(source.to_string()).or_default();
+        let mut peak = 0;
+        for &s in samples {
+            let s = s as i32;
+            peak = peak.max(s.abs());
+            window.sum_squares += (s * s) as f64;
+        }
+        window.peak = window.peak.max(peak);
+        window.samples += samples.len() as u64;
+        drop(windows);
+
+        if peak >= AUDIBLE_PEAK {
+            let mut heard = self.heard.lock().unwrap_or_else(|e| e.into_inner());
+            heard.insert(source.to_string(), Instant::now());
+        }
+    }
+
+    /// When `source` last captured audible (non-silent) audio. Unlike the
+    /// levels, this isn't reset by `take`.
+    pub fn last_heard(&self, source: &str) -> Option<Instant> {
+        let heard = self.heard.lock().unwrap_or_else(|e| e.into_inner());
+        heard.get(source).copied()
+    }
+
+    /// When any source last captured audible audio.
+    pub fn last_heard_any(&self) -> Option<Instant> {
+        let heard = self.heard.lock().unwrap_or_else(|e| e.into_inner());
+        heard.values().max().copied()
+    }
+
+    /// Levels for every source that captured audio since the last call, and
+    /// reset their windows.
+    pub fn take(&self) -> BTreeMap<String, SourceLevel> {
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        let now = chrono::Local::now().to_rfc3339();
+        std::mem::take(&mut *windows)
+            .into_iter()
+            .map(|(source, w)| {
+                let full_scale = i16::MAX as f64 + 1.0;
+                let rms = (w.sum_squares / w.samples as f64).sqrt() / full_scale;
+                let level = SourceLevel {
+                    peak_dbfs: to_dbfs(w.peak as f64 / full_scale),
+                    rms_dbfs: to_dbfs(rms),
+                    updated_at: now.clone(),
+                };
+                (source, level)
+            })
+            .collect()
+    }
+}
+
+/// Most recent input level for one source.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceLevel {
+    pub peak_dbfs: f32,
+    pub rms_dbfs: f32,
+    /// ISO-8601 timestamp of the window this level covers.
+    pub updated_at: String,
+}
+
+/// Level snapshot written to disk by the recorder and read by the tray.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LevelStatus {
+    /// Last level per source. Sources that stop capturing (paused, Teams
+    /// closed) keep their last entry, with an older `updated_at`.
+    pub sources: BTreeMap<String, SourceLevel>,
+}
+
+impl LevelStatus {
+    /// Write the levels to the level file in the recordings directory.
+    /// With a `machine_id`, each machine has its own level file.
+    pub fn write(&self, recordings_dir: &Path, machine_id: Option<&str>) -> Result<()> {
+        let path = recordings_dir.join(machine_scoped(LEVELS_FILE_NAME, machine_id));
+        let content = serde_json::to_string_pretty(self)?;
+        // Write atomically, like `TranscriptionStatus::write`.
+        let mut tmp_path = path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        std::fs::write(&tmp_path, &content)?;
+        std::fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    /// Read the level file from the recordings directory. Returns `None` if
+    /// the file doesn't exist or can't be parsed.
+    pub fn read(recordings_dir: &Path, machine_id: Option<&str>) -> Option<Self> {
+        let path = recordings_dir.join(machine_scoped(LEVELS_FILE_NAME, machine_id));
+        let content = std::fs::read_to_string(&path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+}
+
+/// Periodically write `meter`'s levels to the level file until shutdown.
+pub fn run_level_writer(
+    recordings_dir: PathBuf,
+    machine_id: Option<String>,
+    meter: Arc<LevelMeter>,
+    shutdown: Arc<AtomicBool>,
+) {
+    let mut status = LevelStatus::default();
+
+    while !shutdown.load(Ordering::Relaxed) {
+        let start = Instant::now();
+        while start.elapsed() < WRITE_INTERVAL && !shutdown.load(Ordering::Relaxed) {
+            std::thread::sleep(Duration::from_millis(250));
+        }
+
+        let latest = meter.take();
+        if latest.is_empty() {
+            continue;
+        }
+        status.sources.extend(latest);
+        if let Err(e) = status.write(&recordings_dir, machine_id.as_deref()) {
+            tracing::debug!("Failed to write audio levels: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meter_peak_and_rms() {
+        let meter = LevelMeter::new();
+        // Half-scale square wave: peak and RMS both at -6 dBFS.
+        meter.record("mic", &[16384, -16384, 16384, -16384]);
+        meter.record("teams", &[0; 4]);
+
+        let levels = meter.take();
+        let mic = &levels["mic"];
+        assert!(
+            (mic.peak_dbfs + 6.02).abs() < 0.01,
+            "peak {}",
+            mic.peak_dbfs
+        );
+        assert!((mic.rms_dbfs + 6.02).abs() < 0.01, "rms {}", mic.rms_dbfs);
+        assert_eq!(levels["teams"].peak_dbfs, SILENCE_DBFS);
+
+        // Windows reset after each take.
+        assert!(meter.take().is_empty());
+    }
+
+    #[test]
+    fn test_meter_last_heard() {
+        let meter = LevelMeter::new();
+        let before = Instant::now();
+        meter.record("teams", &[0, 3, -5, 0]);
+        assert!(meter.last_heard("teams").is_none());
+
+        meter.record("mic", &[0, 1000, -1000]);
+        assert!(meter.last_heard("mic").unwrap() >= before);
+        meter.take();
+        assert!(meter.last_heard("mic").is_some());
+        assert!(meter.last_heard("teams").is_none());
+        assert_eq!(meter.last_heard_any(), meter.last_heard("mic"));
+        assert!(LevelMeter::new().last_heard_any().is_none());
+    }
+
+    #[test]
+    fn test_level_status_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let meter = LevelMeter::new();
+        meter.record("mic", &[1000, -2000, 3000]);
+        let status = LevelStatus {
+            sources: meter.take(),
+        };
+
+        status.write(dir.path(), Some("laptop")).unwrap();
+        assert!(dir.path().join(".audio-levels.laptop.json").exists());
+        let read = LevelStatus::read(dir.path(), Some("laptop")).unwrap();
+        assert_eq!(read.sources, status.sources);
+        assert!(LevelStatus::read(dir.path(), None).is_none());
+    }
+}
@@ -1,9 +1,18 @@
 #[cfg(target_os = "windows")]
 pub mod capture;
+pub mod clip;
+pub mod decode;
+pub mod devices;
 pub mod file_writer;
+pub mod flac;
+pub mod levels;
+#[cfg(target_os = "windows")]
+pub mod opus;
 pub mod pipeline;
 pub mod ring_buffer;
 #[cfg(target_os = "windows")]
 pub mod teams_capture;
 pub mod teams_monitor;
+pub mod test_capture;
 pub mod vad;
+pub mod wav_metadata;
@@ -0,0 +1,269 @@
+// Ogg Opus recordings for long-term archival (`output.format = "opus"`).
+//
+// Windows-only: `audiopus` links libopus, which is only part of the Windows
+// build (alongside whisper-rs). Files follow RFC 7845: an `OpusHead` and an
+// `OpusTags` page, then one 20 ms Opus packet per audio packet. Granule
+// positions are in 48 kHz units regardless of the capture rate.
+
+#![cfg(target_os = "windows")]
+
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use audiopus::coder::{Decoder, Encoder};
+use audiopus::packet::Packet;
+use audiopus::{Application, Bitrate, Channels, MutSignals, SampleRate};
+use ogg::{PacketReader, PacketWriteEndInfo, PacketWriter};
+
+use crate::audio::flac::vorbis_comment;
+
+/// Target bitrate: plenty for intelligible speech at a fraction of WAV's size.
+const BITRATE_BPS: i32 = 24_000;
+
+/// Opus frame length (packets are 20 ms each).
+const FRAME_MS: u32 = 20;
+
+/// Largest Opus packet we need room for (RFC 6716 recommends 4000 bytes).
+const MAX_PACKET_BYTES: usize = 4000;
+
+/// Granule positions count samples at 48 kHz.
+const GRANULE_RATE: u64 = 48_000;
+
+/// Ogg logical stream serial; recordings hold a single stream.
+const SERIAL: u32 = 0x6465_736b; // "desk"
+
+/// Streams 16-bit mono samples into an Ogg Opus file.
+pub struct OpusWriter {
+    packets: PacketWriter<BufWriter<File>>,
+    encoder: Encoder,
+    /// Samples per 20 ms frame at the input rate.
+    frame_len: usize,
+    /// 48 kHz granules per input sample.
+    granule_scale: u64,
+    pre_skip: u64,
+    /// Samples waiting for a full frame.
+    pending: Vec<i16>,
+    /// Last encoded packet, held back so the final one can end the stream.
+    held: Option<Vec<u8>>,
+    /// Input samples covered by the packets encoded so far.
+    encoded_samples: u64,
+    /// Real (unpadded) input samples written.
+    total_samples: u64,
+}
+
+impl OpusWriter {
+    /// Create `path` and write the Opus headers. `tags` are stored as Vorbis
+    /// comments in `OpusTags`. `sample_rate` must be one Opus accepts (8000
+    /// or 16000 for deskmic).
+    pub fn create(path: &Path, sample_rate: u32, tags: &[(&str, String)]) -> Result<Self> {
+        let rate = SampleRate::try_from(sample_rate as i32)
+            .map_err(|e| anyhow::anyhow!("Opus can't encode at {} Hz: {}", sample_rate, e))?;
+        let mut encoder = Encoder::new(rate, Channels::Mono, Application::Voip)
+            .map_err(|e| anyhow::anyhow!("Failed to create Opus encoder: {}", e))?;
+        encoder
+            .set_bitrate(Bitrate::BitsPerSecond(BITRATE_BPS))
+            .map_err(|e| anyhow::anyhow!("Failed to set Opus bitrate: {}", e))?;
+
+        let granule_scale = GRANULE_RATE / sample_rate as u64;
+        let lookahead = encoder
+            .lookahead()
+            .map_err(|e| anyhow::anyhow!("Failed to query Opus lookahead: {}", e))?;
+        let pre_skip = lookahead as u64 * granule_scale;
+
+        let mut packets = PacketWriter::new(BufWriter::new(File::create(path)?));
+        packets.write_packet(
+            opus_head(sample_rate, pre_skip as u16).into(),
+            SERIAL,
+            PacketWriteEndInfo::EndPage,
+            0,
+        )?;
+        packets.write_packet(
+            opus_tags(tags).into(),
+            SERIAL,
+            PacketWriteEndInfo::EndPage,
+            0,
+        )?;
+
+        Ok(Self {
+            packets,
+            encoder,
+            frame_len: (sample_rate * FRAME_MS / 1000) as usize,
+            granule_scale,
+            pre_skip,
+            pending: Vec::new(),
+            held: None,
+            encoded_samples: 0,
+            total_samples: 0,
+        })
+    }
+
+    pub fn write_samples(&mut self, samples: &[i16]) -> Result<()> {
+        self.total_samples += samples.len() as u64;
+        self.pending.extend_from_slice(samples);
+        while self.pending.len() >= self.frame_len {
+            let frame: Vec<i16> = self.pending.drain(..self.frame_len).collect();
+            self.encode_frame(&frame)?;
+        }
+        Ok(())
+    }
+
+    /// Input samples already encoded into whole Opus frames. Samples still
+    /// waiting for a full 20 ms frame aren't counted.
+    pub fn encoded_samples(&self) -> u64 {
+        self.encoded_samples
+    }
+
+    /// Pad and encode the partial tail frame, then end the stream. The final
+    /// granule position trims the padding on playback.
+    pub fn finalize(mut self) -> Result<()> {
+        if !self.pending.is_empty() || self.held.is_none() {
+            let mut frame = std::mem::take(&mut self.pending);
+            frame.resize(self.frame_len, 0);
+            self.encode_frame(&frame)?;
+        }
+        let last = self.held.take().unwrap_or_default();
+        let end = self.pre_skip + self.total_samples * self.granule_scale;
+        self.packets
+            .write_packet(last.into(), SERIAL, PacketWriteEndInfo::EndStream, end)?;
+        self.packets.inner_mut().flush()?;
+        Ok(())
+    }
+
+    fn encode_frame(&mut self, frame: &[i16]) -> Result<()> {
+        let mut out = vec![0u8; MAX_PACKET_BYTES];
+        let len = self
+            .encoder
+            .encode(frame, &mut out)
+            .map_err(|e| anyhow::anyhow!("Opus encoding failed: {}", e))?;
+        out.truncate(len);
+
+        // The previous packet now isn't the last one, so it can be written
+        // with its (untrimmed) granule position.
+        if let Some(prev) = self.held.replace(out) {
+            let granule = self.pre_skip + self.encoded_samples * self.granule_scale;
+            self.packets.write_packet(
+                prev.into(),
+                SERIAL,
+                PacketWriteEndInfo::NormalPacket,
+                granule,
+            )?;
+        }
+        self.encoded_samples += frame.len() as u64;
+        Ok(())
+    }
+}
+
+/// `OpusHead` identification header (RFC 7845 §5.1), mono, mapping family 0.
+fn opus_head(input_sample_rate: u32, pre_skip: u16) -> Vec<u8> {
+    let mut head = Vec::with_capacity(19);
+    head.extend_from_slice(b"OpusHead");
+    head.push(1); // version
+    head.push(1); // channels
+    head.extend_from_slice(&pre_skip.to_le_bytes());
+    head.extend_from_slice(&input_sample_rate.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+    head.push(0); // channel mapping family
+    head
+}
+
+/// `OpusTags` comment header (RFC 7845 §5.2).
+fn opus_tags(tags: &[(&str, String)]) -> Vec<u8> {
+    let mut out = b"OpusTags".to_vec();
+    out.extend(vorbis_comment(tags));
+    out
+}
+
+/// Decode an Ogg Opus recording into samples at its original input rate
+/// (16 kHz if that rate isn't one Opus decodes to).
+pub fn read_samples(path: &Path) -> Result<(Vec<i16>, u32)> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut reader = PacketReader::new(BufReader::new(file));
+
+    let head = reader
+        .read_packet()?
+        .filter(|p| p.data.starts_with(b"OpusHead") && p.data.len() >= 19)
+        .ok_or_else(|| anyhow::anyhow!("{} is not an Ogg Opus file", path.display()))?;
+    let pre_skip_48k = u16::from_le_bytes([head.data[10], head.data[11]]) as u64;
+    let input_rate =
+        u32::from_le_bytes([head.data[12], head.data[13], head.data[14], head.data[15]]);
+    let (rate, sample_rate) = match SampleRate::try_from(input_rate as i32) {
+        Ok(rate) => (rate, input_rate),
+        Err(_) => (SampleRate::Hz16000, 16000),
+    };
+    let granule_scale = GRANULE_RATE / sample_rate as u64;
+
+    // OpusTags
+    reader.read_packet()?;
+
+    let mut decoder = Decoder::new(rate, Channels::Mono)
+        .map_err(|e| anyhow::anyhow!("Failed to create Opus decoder: {}", e))?;
+    // 120 ms is the longest Opus packet.
+    let mut buf = vec![0i16; sample_rate as usize * 120 / 1000];
+    let mut samples = Vec::new();
+    let mut last_granule = 0;
+    while let Some(packet) = reader.read_packet()? {
+        let input = Packet::try_from(packet.data.as_slice())
+            .map_err(|e| anyhow::anyhow!("Bad Opus packet: {}", e))?;
+        let output = MutSignals::try_from(&mut buf[..])
+            .map_err(|e| anyhow::anyhow!("Bad Opus buffer: {}", e))?;
+        let n = decoder
+            .decode(Some(input), output, false)
+            .map_err(|e| anyhow::anyhow!("Opus decoding failed: {}", e))?;
+        samples.extend_from_slice(&buf[..n]);
+        last_granule = packet.absgp_page();
+    }
+
+    // Drop the encoder delay from the front and padding from the end.
+    let skip = (pre_skip_48k / granule_scale) as usize;
+    let total = (last_granule.saturating_sub(pre_skip_48k) / granule_scale) as usize;
+    let samples: Vec<i16> = samples.into_iter().skip(skip).take(total).collect();
+    Ok((samples, sample_rate))
+}
+
+/// Length of an Ogg Opus recording in seconds, from its granule positions.
+pub fn duration_secs(path: &Path) -> Result<f64> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut reader = PacketReader::new(BufReader::new(file));
+
+    let head = reader
+        .read_packet()?
+        .filter(|p| p.data.starts_with(b"OpusHead") && p.data.len() >= 19)
+        .ok_or_else(|| anyhow::anyhow!("{} is not an Ogg Opus file", path.display()))?;
+    let pre_skip = u16::from_le_bytes([head.data[10], head.data[11]]) as u64;
+
+    let mut last_granule = 0;
+    while let Some(packet) = reader.read_packet()? {
+        last_granule = packet.absgp_page();
+    }
+    Ok(last_granule.saturating_sub(pre_skip) as f64 / GRANULE_RATE as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_keeps_length() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mic_09-00-00.ogg");
+        // 1.25 s: not a whole number of 20 ms frames per write.
+        let samples: Vec<i16> = (0..20_000)
+            .map(|i| ((i as f64 / 16000.0 * 220.0 * std::f64::consts::TAU).sin() * 8000.0) as i16)
+            .collect();
+
+        let mut writer = OpusWriter::create(&path, 16000, &[("SOURCE", "mic".into())]).unwrap();
+        for chunk in samples.chunks(1234) {
+            writer.write_samples(chunk).unwrap();
+        }
+        assert_eq!(writer.encoded_samples(), 19_840);
+        writer.finalize().unwrap();
+
+        let (decoded, rate) = read_samples(&path).unwrap();
+        assert_eq!(rate, 16000);
+        assert_eq!(decoded.len(), samples.len());
+        assert!((duration_secs(&path).unwrap() - 1.25).abs() < 1e-9);
+    }
+}
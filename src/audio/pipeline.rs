@@ -5,14 +5,21 @@
 // `&mut dyn VadProcessor` trait object, so the caller provides the platform-specific
 // VAD implementation.
 
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::mpsc::Sender;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 
+use crate::audio::levels::LevelMeter;
 use crate::audio::ring_buffer::RingBuffer;
-use crate::audio::vad::VadProcessor;
+use crate::audio::vad::{AdaptiveGate, VadProcessor};
+use crate::config::VadConfig;
+use crate::config_watch::LiveSettings;
+
+/// How often a running pipeline sends `AudioMessage::Heartbeat`.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
 
 /// Messages sent from the capture pipeline to the file writer.
 #[derive(Debug)]
@@ -27,6 +34,97 @@ pub enum AudioMessage {
     SpeechContinue { source: String, samples: Vec<i16> },
     /// Speech has ended (silence threshold exceeded).
     SpeechEnd { source: String },
+    /// The last few seconds of a source, saved on request as a file of its own
+    /// regardless of speech.
+    SaveClip { source: String, samples: Vec<i16> },
+    /// The source's capture loop is still turning, speech or not. Sent every
+    /// `HEARTBEAT_INTERVAL`, so a wedged capture thread goes quiet.
+    Heartbeat { source: String },
+    /// The source's pipeline stopped on purpose (shutdown, or its app closed),
+    /// so missing heartbeats aren't a stall.
+    SourceStopped { source: String },
+    /// The recorder is shutting down: finalize every open file and stop, even
+    /// if some sender is never dropped.
+    Shutdown,
+}
+
+/// "Save the last N seconds" requests, shared by the tray and every capture
+/// pipeline. Each pipeline answers a request with one `SaveClip` message.
+pub struct ClipRequests {
+    secs: u32,
+    requested: AtomicU64,
+}
+
+impl ClipRequests {
+    /// `secs` of audio are kept per source; 0 turns clips off.
+    pub fn new(secs: u32) -> Self {
+        Self {
+            secs,
+            requested: AtomicU64::new(0),
+        }
+    }
+
+    pub fn secs(&self) -> u32 {
+        self.secs
+    }
+
+    /// Ask every running pipeline to save its recent audio.
+    pub fn request(&self) {
+        self.requested.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Whether a request arrived since `seen`, which is then brought up to date.
+    fn take(&self, seen: &mut u64) -> bool {
+        let requested = self.requested.load(Ordering::Relaxed);
+        std::mem::replace(seen, requested) != requested
+    }
+}
+
+/// Scale `samples` by `gain_db`, saturating at the i16 range instead of wrapping.
+pub fn apply_gain(samples: &mut [i16], gain_db: f32) {
+    if gain_db == 0.0 {
+        return;
+    }
+    let factor = 10f32.powf(gain_db / 20.0);
+    for sample in samples.iter_mut() {
+        *sample = (*sample as f32 * factor)
+            .round()
+            .clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+    }
+}
+
+/// State every capture pipeline shares with the rest of the recorder. Cheap to
+/// clone: each field is a shared handle.
+#[derive(Clone)]
+pub struct PipelineContext {
+    /// Live VAD settings and input gain; threshold, pre-speech buffer,
+    /// silence length and gain changes from a config reload are applied
+    /// between chunks. The gain is applied before VAD, so it's also in the
+    /// recordings.
+    pub settings: Arc<LiveSettings>,
+    /// Meter that every captured (post-gain) chunk is recorded into under the
+    /// source's name, for the live level file.
+    pub levels: Arc<LevelMeter>,
+    /// The last `clips.secs()` of captured (post-gain) audio is kept whether
+    /// or not it's speech, and sent as `SaveClip` when a clip is requested.
+    pub clips: Arc<ClipRequests>,
+    /// Channel for `AudioMessage`s consumed by the file writer, including a
+    /// `Heartbeat` every `HEARTBEAT_INTERVAL` (paused or not) and
+    /// `SourceStopped` when the loop exits cleanly.
+    pub sender: Sender<AudioMessage>,
+    /// When set to `true`, the loop exits.
+    pub shutdown: Arc<AtomicBool>,
+    /// When `true`, audio is still drained from the capture device (to
+    /// prevent WASAPI buffer overflow) but VAD processing is skipped, buffers
+    /// are cleared, and any in-progress speech segment is closed out.
+    pub paused: Arc<AtomicBool>,
+}
+
+/// The `AdaptiveGate` for `config`, if adaptive mode is on.
+fn adaptive_gate(sample_rate: u32, config: &VadConfig) -> Option<AdaptiveGate> {
+    config
+        .adaptive
+        .then(|| AdaptiveGate::new(sample_rate, config.min_segment_secs))
 }
 
 /// Runs the capture -> VAD -> file-writer pipeline on the calling thread.
@@ -35,12 +133,12 @@ pub enum AudioMessage {
 /// - `capture_fn`: called repeatedly to obtain the next chunk of i16 samples.
 ///   Returns `Ok(None)` if the device was invalidated (triggers graceful shutdown).
 /// - `start_fn`: called once before the capture loop begins (e.g. to start WASAPI stream).
-/// - `vad`: any implementation of `VadProcessor`.
-/// - `sender`: channel for `AudioMessage`s consumed by the file writer.
-/// - `shutdown`: atomic flag; when set to `true`, the loop exits.
-/// - `paused`: atomic flag; when `true`, audio is still drained from the capture
-///   device (to prevent WASAPI buffer overflow) but VAD processing is skipped,
-///   buffers are cleared, and any in-progress speech segment is closed out.
+/// - `vad`: any implementation of `VadProcessor`. With `vad.enabled = false` it
+///   isn't consulted: every chunk counts as speech, so one segment runs until
+///   pause or shutdown and the file writer splits it by duration. With
+///   `vad.adaptive` its verdicts go through an `AdaptiveGate`.
+/// - `ctx`: settings, level meter, clip requests, file writer channel and the
+///   shutdown/pause flags; see `PipelineContext`.
 ///
 /// The pipeline buffers non-speech audio in a ring buffer so that the first
 /// `pre_speech_buffer_secs` of audio before speech onset is included in the
@@ -50,25 +148,45 @@ pub fn run_capture_pipeline(
     capture_fn: impl Fn() -> Result<Option<Vec<i16>>>,
     start_fn: impl Fn() -> Result<()>,
     sample_rate: u32,
-    pre_speech_buffer_secs: f32,
-    silence_threshold_secs: f32,
     vad: &mut dyn VadProcessor,
     chunk_size: usize,
-    sender: Sender<AudioMessage>,
-    shutdown: Arc<AtomicBool>,
-    paused: Arc<AtomicBool>,
+    ctx: &PipelineContext,
 ) -> Result<()> {
-    let mut ring_buffer = RingBuffer::new(sample_rate, pre_speech_buffer_secs);
-    let silence_samples = (sample_rate as f32 * silence_threshold_secs) as usize;
+    let PipelineContext {
+        settings,
+        levels,
+        clips,
+        sender,
+        shutdown,
+        paused,
+    } = ctx;
+    let mut vad_config = settings.vad();
+    let mut ring_buffer = RingBuffer::new(sample_rate, vad_config.pre_speech_buffer_secs);
+    let clip_secs = clips.secs() as f32;
+    let mut recent = RingBuffer::new(sample_rate, clip_secs);
+    let mut clips_seen = 0;
+    clips.take(&mut clips_seen);
+    let mut silence_samples = (sample_rate as f32 * vad_config.silence_threshold_secs) as usize;
+    let mut adaptive = adaptive_gate(sample_rate, &vad_config);
 
     let mut is_speaking = false;
     let mut silence_count: usize = 0;
     let mut pending_samples: Vec<i16> = Vec::new();
+    let mut last_heartbeat = Instant::now();
+    let mut heartbeat_sent = false;
 
     start_fn()?;
 
     while !shutdown.load(Ordering::Relaxed) {
-        let samples = match capture_fn()? {
+        let captured = capture_fn()?;
+        if last_heartbeat.elapsed() >= HEARTBEAT_INTERVAL {
+            last_heartbeat = Instant::now();
+            heartbeat_sent = true;
+            sender.send(AudioMessage::Heartbeat {
+                source: source_name.clone(),
+            })?;
+        }
+        let mut samples = match captured {
             Some(s) => s,
             None => {
                 // Empty buffer from WASAPI — normal, not fatal. Just try again.
@@ -78,6 +196,8 @@ pub fn run_capture_pipeline(
 
         // When paused, drain audio (already read above) but skip all processing.
         // Close out any in-progress speech segment so the WAV file is finalized.
+        // A clip requested while paused has nothing to save.
+        let clip_requested = clips.take(&mut clips_seen);
         if paused.load(Ordering::Relaxed) {
             if is_speaking {
                 let _ = sender.send(AudioMessage::SpeechEnd {
@@ -88,15 +208,46 @@ pub fn run_capture_pipeline(
             }
             pending_samples.clear();
             ring_buffer.clear();
+            recent.clear();
             continue;
         }
 
+        // Pick up VAD settings changed by a config reload.
+        let latest = settings.vad();
+        if latest != vad_config {
+            vad.set_threshold(latest.speech_threshold);
+            ring_buffer.resize(sample_rate, latest.pre_speech_buffer_secs);
+            silence_samples = (sample_rate as f32 * latest.silence_threshold_secs) as usize;
+            if (latest.adaptive, latest.min_segment_secs)
+                != (vad_config.adaptive, vad_config.min_segment_secs)
+            {
+                adaptive = adaptive_gate(sample_rate, &latest);
+            }
+            vad_config = latest;
+        }
+
+        apply_gain(&mut samples, settings.gain_db());
+        levels.record(&source_name, &samples);
+        recent.push(&samples);
+        if clip_requested && clip_secs > 0.0 {
+            sender.send(AudioMessage::SaveClip {
+                source: source_name.clone(),
+                samples: recent.tail(clip_secs),
+            })?;
+        }
         pending_samples.extend_from_slice(&samples);
 
         // Process complete chunks through VAD.
         while pending_samples.len() >= chunk_size {
             let chunk: Vec<i16> = pending_samples.drain(..chunk_size).collect();
-            let speech = vad.is_speech(&chunk);
+            // Continuous mode: skip detection and treat everything as speech.
+            let speech = !vad_config.enabled || {
+                let verdict = vad.is_speech(&chunk);
+                match &mut adaptive {
+                    Some(gate) => gate.apply(&chunk, verdict),
+                    None => verdict,
+                }
+            };
 
             if speech {
                 silence_count = 0;
@@ -104,7 +255,9 @@ pub fn run_capture_pipeline(
                 if !is_speaking {
                     // Transition: silence -> speech.
                     is_speaking = true;
-                    let mut initial = ring_buffer.drain();
+                    // Never more pre-roll than configured, however long the
+                    // silence before it.
+                    let mut initial = ring_buffer.drain_tail(vad_config.pre_speech_buffer_secs);
                     initial.extend_from_slice(&chunk);
                     sender.send(AudioMessage::SpeechStart {
                         source: source_name.clone(),
@@ -145,6 +298,11 @@ pub fn run_capture_pipeline(
     // If we exit the loop while still in a speech segment, close it out.
     if is_speaking {
         let _ = sender.send(AudioMessage::SpeechEnd {
+            source: source_name.clone(),
+        });
+    }
+    if heartbeat_sent {
+        let _ = sender.send(AudioMessage::SourceStopped {
             source: source_name,
         });
     }
@@ -152,6 +310,27 @@ pub fn run_capture_pipeline(
     Ok(())
 }
 
+/// Meters `source_name`'s audio from another capture on the calling thread,
+/// without VAD and without sending anything to the file writer, until
+/// `shutdown` is set. Used to probe which of an app's processes plays its
+/// audio while another pipeline records the app, so the writer only ever
+/// gets one producer per source.
+pub fn run_level_probe(
+    source_name: &str,
+    capture_fn: impl Fn() -> Result<Option<Vec<i16>>>,
+    start_fn: impl Fn() -> Result<()>,
+    levels: &LevelMeter,
+    shutdown: &AtomicBool,
+) -> Result<()> {
+    start_fn()?;
+    while !shutdown.load(Ordering::Relaxed) {
+        if let Some(samples) = capture_fn()? {
+            levels.record(source_name, &samples);
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -159,6 +338,29 @@ mod tests {
     use std::sync::mpsc;
     use std::sync::Arc;
 
+    fn settings(pre_speech_buffer_secs: f32, silence_threshold_secs: f32) -> LiveSettings {
+        let mut config = crate::config::Config::default();
+        config.vad.pre_speech_buffer_secs = pre_speech_buffer_secs;
+        config.vad.silence_threshold_secs = silence_threshold_secs;
+        LiveSettings::new(&config)
+    }
+
+    /// A context with a fresh level meter, clips off and no pause.
+    fn context(
+        settings: LiveSettings,
+        sender: Sender<AudioMessage>,
+        shutdown: Arc<AtomicBool>,
+    ) -> PipelineContext {
+        PipelineContext {
+            settings: Arc::new(settings),
+            levels: Arc::new(LevelMeter::new()),
+            clips: Arc::new(ClipRequests::new(0)),
+            sender,
+            shutdown,
+            paused: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
     /// A test VAD that considers any chunk where the first sample is non-zero as speech.
     struct TestVad;
 
@@ -211,19 +413,23 @@ mod tests {
 
         let start_fn = || -> Result<()> { Ok(()) };
         let mut vad = TestVad;
+        let levels = Arc::new(LevelMeter::new());
 
         let result = run_capture_pipeline(
             "test-mic".to_string(),
             capture_fn,
             start_fn,
             sample_rate,
-            pre_speech_buffer_secs,
-            silence_threshold_secs,
             &mut vad,
             chunk_size,
-            tx,
-            shutdown,
-            Arc::new(AtomicBool::new(false)),
+            &PipelineContext {
+                levels: levels.clone(),
+                ..context(
+                    settings(pre_speech_buffer_secs, silence_threshold_secs),
+                    tx,
+                    shutdown,
+                )
+            },
         );
 
         assert!(result.is_ok());
@@ -260,6 +466,86 @@ mod tests {
             }
             other => panic!("Expected SpeechEnd, got {:?}", other),
         }
+
+        // Captured chunks are metered under the source name.
+        let level = &levels.take()["test-mic"];
+        assert!(level.peak_dbfs > crate::audio::levels::SILENCE_DBFS);
+    }
+
+    /// A VAD that must not be consulted.
+    struct UnusedVad;
+
+    impl VadProcessor for UnusedVad {
+        fn is_speech(&mut self, _samples: &[i16]) -> bool {
+            panic!("is_speech should not be called with vad.enabled = false");
+        }
+    }
+
+    #[test]
+    fn test_pipeline_continuous_mode_records_one_segment() {
+        let (tx, rx) = mpsc::channel();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let shutdown_clone = shutdown.clone();
+        let paused = Arc::new(AtomicBool::new(false));
+        let paused_clone = paused.clone();
+
+        // Silence only: with VAD disabled it's recorded anyway, and the
+        // segment stays open (no SpeechEnd after silence) until the pause.
+        let chunk_size = 4;
+        let silence_chunk = vec![0i16; chunk_size];
+        let mut chunks: Vec<Vec<i16>> = vec![silence_chunk.clone(); 4];
+        chunks.reverse();
+
+        let chunks = std::cell::RefCell::new(chunks);
+
+        let capture_fn = move || -> Result<Option<Vec<i16>>> {
+            let mut c = chunks.borrow_mut();
+            if c.len() == 1 {
+                // Pause before the last chunk so the segment is closed out.
+                paused_clone.store(true, Ordering::Relaxed);
+            }
+            match c.pop() {
+                Some(val) => Ok(Some(val)),
+                None => {
+                    shutdown_clone.store(true, Ordering::Relaxed);
+                    Ok(None)
+                }
+            }
+        };
+
+        let start_fn = || -> Result<()> { Ok(()) };
+        let mut vad = UnusedVad;
+        // silence_threshold_secs = 0.5 would end a VAD segment after one chunk.
+        let mut config = crate::config::Config::default();
+        config.vad.enabled = false;
+        config.vad.pre_speech_buffer_secs = 0.5;
+        config.vad.silence_threshold_secs = 0.5;
+
+        let result = run_capture_pipeline(
+            "test-mic".to_string(),
+            capture_fn,
+            start_fn,
+            8,
+            &mut vad,
+            chunk_size,
+            &PipelineContext {
+                paused,
+                ..context(LiveSettings::new(&config), tx, shutdown)
+            },
+        );
+
+        assert!(result.is_ok());
+
+        let messages: Vec<AudioMessage> = rx.try_iter().collect();
+        assert_eq!(messages.len(), 4, "got {:?}", messages);
+        match &messages[0] {
+            AudioMessage::SpeechStart { samples, .. } => assert_eq!(samples.len(), chunk_size),
+            other => panic!("Expected SpeechStart, got {:?}", other),
+        }
+        for message in &messages[1..3] {
+            assert!(matches!(message, AudioMessage::SpeechContinue { .. }));
+        }
+        assert!(matches!(messages[3], AudioMessage::SpeechEnd { .. }));
     }
 
     #[test]
@@ -280,13 +566,9 @@ mod tests {
             capture_fn,
             start_fn,
             16000,
-            5.0,
-            3.0,
             &mut vad,
             512,
-            tx,
-            shutdown,
-            Arc::new(AtomicBool::new(false)),
+            &context(settings(5.0, 3.0), tx, shutdown),
         );
 
         assert!(result.is_ok());
@@ -335,13 +617,12 @@ mod tests {
             capture_fn,
             start_fn,
             8,
-            0.5,
-            0.5,
             &mut vad,
             chunk_size,
-            tx,
-            shutdown,
-            paused,
+            &PipelineContext {
+                paused,
+                ..context(settings(0.5, 0.5), tx, shutdown)
+            },
         );
 
         assert!(result.is_ok());
@@ -355,6 +636,186 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pipeline_applies_reloaded_settings() {
+        let (tx, rx) = mpsc::channel();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let shutdown_clone = shutdown.clone();
+
+        // 8 samples of silence end a segment until the reload halves it to 4,
+        // so the single silent chunk between the two speech chunks splits them.
+        let live = Arc::new(settings(0.5, 1.0));
+        let live_clone = live.clone();
+
+        let chunk_size = 4;
+        let mut chunks: Vec<Vec<i16>> = vec![vec![100; 4], vec![0; 4], vec![100; 4]];
+        chunks.reverse();
+        let chunks = std::cell::RefCell::new(chunks);
+
+        let capture_fn = move || -> Result<Option<Vec<i16>>> {
+            let mut c = chunks.borrow_mut();
+            if c.len() == 2 {
+                let mut config = crate::config::Config::default();
+                config.vad.pre_speech_buffer_secs = 0.5;
+                config.vad.silence_threshold_secs = 0.5;
+                live_clone.update(&config);
+            }
+            match c.pop() {
+                Some(val) => Ok(Some(val)),
+                None => {
+                    shutdown_clone.store(true, Ordering::Relaxed);
+                    Ok(None)
+                }
+            }
+        };
+
+        let mut vad = TestVad;
+        run_capture_pipeline(
+            "test-mic".to_string(),
+            capture_fn,
+            || Ok(()),
+            8,
+            &mut vad,
+            chunk_size,
+            &PipelineContext {
+                settings: live.clone(),
+                ..context(settings(0.5, 0.5), tx, shutdown)
+            },
+        )
+        .unwrap();
+
+        let starts = rx
+            .try_iter()
+            .filter(|m| matches!(m, AudioMessage::SpeechStart { .. }))
+            .count();
+        assert_eq!(starts, 2);
+    }
+
+    /// Run the pipeline over `reads` (samples, and whether to be paused while
+    /// reading them) and return the length of each `SpeechStart` payload.
+    fn speech_start_lengths(reads: Vec<(Vec<i16>, bool)>, pre_speech_secs: f32) -> Vec<usize> {
+        let (tx, rx) = mpsc::channel();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let shutdown_clone = shutdown.clone();
+        let paused = Arc::new(AtomicBool::new(false));
+        let paused_clone = paused.clone();
+
+        let mut reads = reads;
+        reads.reverse();
+        let reads = std::cell::RefCell::new(reads);
+        let capture_fn = move || -> Result<Option<Vec<i16>>> {
+            match reads.borrow_mut().pop() {
+                Some((samples, pause)) => {
+                    paused_clone.store(pause, Ordering::Relaxed);
+                    Ok(Some(samples))
+                }
+                None => {
+                    shutdown_clone.store(true, Ordering::Relaxed);
+                    Ok(None)
+                }
+            }
+        };
+
+        let mut vad = TestVad;
+        run_capture_pipeline(
+            "test-mic".to_string(),
+            capture_fn,
+            || Ok(()),
+            8,
+            &mut vad,
+            4,
+            &PipelineContext {
+                paused,
+                ..context(settings(pre_speech_secs, 0.5), tx, shutdown)
+            },
+        )
+        .unwrap();
+
+        rx.try_iter()
+            .filter_map(|m| match m {
+                AudioMessage::SpeechStart { samples, .. } => Some(samples.len()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pipeline_pre_speech_is_bounded_after_long_silence() {
+        // Reads of 3 samples don't line up with 4-sample chunks, so a
+        // remainder is always pending. 1s at 8 Hz = 8 samples of pre-roll.
+        let mut reads = vec![(vec![0i16; 3], false); 1000];
+        reads.push((vec![100; 4], false));
+        reads.push((vec![100; 4], false));
+        // The full pre-roll plus the first speech chunk, and no more.
+        assert_eq!(speech_start_lengths(reads, 1.0), vec![8 + 4]);
+    }
+
+    #[test]
+    fn test_pipeline_pre_speech_skips_audio_from_before_pause() {
+        let mut reads = vec![(vec![0i16; 3], false); 100];
+        reads.extend(vec![(vec![0i16; 3], true); 2]);
+        // After resuming: 3 silent samples, then speech.
+        reads.push((vec![0; 3], false));
+        reads.extend(vec![(vec![100; 3], false); 3]);
+        // The chunk [0, 0, 0, 100] is the only pre-roll; nothing from before
+        // the pause is prepended.
+        assert_eq!(speech_start_lengths(reads, 1.0), vec![4 + 4]);
+    }
+
+    #[test]
+    fn test_pipeline_adaptive_mode_skips_blips() {
+        // Chunks are 0.5 s at 8 Hz; a single loud chunk is a blip, two make
+        // the 1 s minimum segment.
+        let quiet = vec![0i16; 4];
+        let loud = vec![3000i16; 4];
+        let mut chunks = vec![quiet.clone(); 4];
+        chunks.push(loud.clone());
+        chunks.extend(vec![quiet.clone(); 4]);
+        chunks.extend(vec![loud.clone(); 3]);
+        chunks.extend(vec![quiet.clone(); 4]);
+
+        let count_starts = |adaptive: bool| {
+            let (tx, rx) = mpsc::channel();
+            let shutdown = Arc::new(AtomicBool::new(false));
+            let shutdown_clone = shutdown.clone();
+            let mut remaining = chunks.clone();
+            remaining.reverse();
+            let remaining = std::cell::RefCell::new(remaining);
+            let capture_fn = move || -> Result<Option<Vec<i16>>> {
+                match remaining.borrow_mut().pop() {
+                    Some(chunk) => Ok(Some(chunk)),
+                    None => {
+                        shutdown_clone.store(true, Ordering::Relaxed);
+                        Ok(None)
+                    }
+                }
+            };
+            let mut config = crate::config::Config::default();
+            config.vad.pre_speech_buffer_secs = 1.0;
+            config.vad.silence_threshold_secs = 0.5;
+            config.vad.adaptive = adaptive;
+            config.vad.min_segment_secs = 1.0;
+
+            let mut vad = TestVad;
+            run_capture_pipeline(
+                "test-mic".to_string(),
+                capture_fn,
+                || Ok(()),
+                8,
+                &mut vad,
+                4,
+                &context(LiveSettings::new(&config), tx, shutdown),
+            )
+            .unwrap();
+            rx.try_iter()
+                .filter(|m| matches!(m, AudioMessage::SpeechStart { .. }))
+                .count()
+        };
+
+        assert_eq!(count_starts(false), 2);
+        assert_eq!(count_starts(true), 1);
+    }
+
     #[test]
     fn test_pipeline_none_does_not_kill_capture() {
         // Regression test for #14: Ok(None) from WASAPI (empty buffer) should NOT
@@ -402,13 +863,13 @@ mod tests {
             capture_fn,
             start_fn,
             sample_rate,
-            pre_speech_buffer_secs,
-            silence_threshold_secs,
             &mut vad,
             chunk_size,
-            tx,
-            shutdown,
-            Arc::new(AtomicBool::new(false)),
+            &context(
+                settings(pre_speech_buffer_secs, silence_threshold_secs),
+                tx,
+                shutdown,
+            ),
         );
 
         assert!(result.is_ok());
@@ -428,4 +889,190 @@ mod tests {
             other => panic!("Expected SpeechStart, got {:?}", other),
         }
     }
+
+    #[test]
+    fn test_pipeline_saves_clip_on_request() {
+        let (tx, rx) = mpsc::channel();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let shutdown_clone = shutdown.clone();
+        // 2 seconds at 4 Hz: the clip is the last 8 samples, speech or not.
+        let clips = Arc::new(ClipRequests::new(2));
+        let clips_clone = clips.clone();
+
+        let mut chunks: Vec<Vec<i16>> = vec![
+            vec![0, 0, 0, 0],
+            vec![1, 2, 3, 4],
+            vec![0, 0, 0, 5],
+            vec![0, 0, 0, 6],
+        ];
+        chunks.reverse();
+        let chunks = std::cell::RefCell::new(chunks);
+
+        let capture_fn = move || -> Result<Option<Vec<i16>>> {
+            let mut c = chunks.borrow_mut();
+            if c.len() == 1 {
+                clips_clone.request();
+            }
+            match c.pop() {
+                Some(val) => Ok(Some(val)),
+                None => {
+                    shutdown_clone.store(true, Ordering::Relaxed);
+                    Ok(None)
+                }
+            }
+        };
+
+        let mut vad = TestVad;
+        run_capture_pipeline(
+            "test-mic".to_string(),
+            capture_fn,
+            || Ok(()),
+            4,
+            &mut vad,
+            4,
+            &PipelineContext {
+                clips: clips.clone(),
+                ..context(settings(1.0, 10.0), tx, shutdown)
+            },
+        )
+        .unwrap();
+
+        let saved: Vec<_> = rx
+            .try_iter()
+            .filter_map(|m| match m {
+                AudioMessage::SaveClip { source, samples } => Some((source, samples)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            saved,
+            vec![("test-mic".to_string(), vec![0, 0, 0, 5, 0, 0, 0, 6])]
+        );
+    }
+
+    /// A capture that returns `chunks` in order, then sets `shutdown`.
+    fn scripted_capture(
+        chunks: Vec<Vec<i16>>,
+        shutdown: Arc<AtomicBool>,
+    ) -> impl Fn() -> Result<Option<Vec<i16>>> {
+        let chunks = std::cell::RefCell::new(chunks.into_iter());
+        move || match chunks.borrow_mut().next() {
+            Some(chunk) => Ok(Some(chunk)),
+            None => {
+                shutdown.store(true, Ordering::Relaxed);
+                Ok(None)
+            }
+        }
+    }
+
+    #[test]
+    fn test_level_probe_does_not_mix_into_recording() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let output_config = crate::config::OutputConfig {
+            directory: tmp_dir.path().to_path_buf(),
+            organize_by_date: false,
+            ..Default::default()
+        };
+        let (tx, rx) = mpsc::channel();
+        let sample_rate = 8;
+        let probe_levels = LevelMeter::new();
+
+        // The app's recording pipeline and a probe of its child process run
+        // at once, under the same source.
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                let shutdown = Arc::new(AtomicBool::new(false));
+                let capture_fn = scripted_capture(vec![vec![100i16; 4]; 3], shutdown.clone());
+                let ctx = context(settings(0.0, 10.0), tx, shutdown);
+                run_capture_pipeline(
+                    "teams".to_string(),
+                    capture_fn,
+                    || Ok(()),
+                    sample_rate,
+                    &mut TestVad,
+                    4,
+                    &ctx,
+                )
+                .unwrap();
+            });
+            s.spawn(|| {
+                let shutdown = Arc::new(AtomicBool::new(false));
+                let capture_fn = scripted_capture(vec![vec![2000i16; 4]; 3], shutdown.clone());
+                run_level_probe("teams", capture_fn, || Ok(()), &probe_levels, &shutdown).unwrap();
+            });
+        });
+
+        crate::audio::file_writer::run_file_writer(
+            rx,
+            &output_config,
+            sample_rate,
+            Duration::ZERO,
+            Arc::new(AtomicBool::new(false)),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let files: Vec<_> = std::fs::read_dir(tmp_dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1, "one app, one recording: {:?}", files);
+        let samples: Vec<i16> = hound::WavReader::open(&files[0])
+            .unwrap()
+            .into_samples()
+            .map(|s| s.unwrap())
+            .collect();
+        assert_eq!(
+            samples,
+            vec![100i16; 12],
+            "only the recording pipeline's audio"
+        );
+        assert!(probe_levels.last_heard("teams").is_some());
+    }
+
+    #[test]
+    fn test_clip_requests_are_taken_once() {
+        let clips = ClipRequests::new(30);
+        clips.request();
+        let mut seen = 0;
+        assert!(clips.take(&mut seen));
+        assert!(!clips.take(&mut seen));
+        clips.request();
+        clips.request();
+        assert!(clips.take(&mut seen));
+        assert!(!clips.take(&mut seen));
+    }
+
+    fn rms(samples: &[i16]) -> f64 {
+        let sum: f64 = samples.iter().map(|&s| (s as f64).powi(2)).sum();
+        (sum / samples.len() as f64).sqrt()
+    }
+
+    #[test]
+    fn test_apply_gain_scales_rms() {
+        // 1000-amplitude sine: RMS is 1000 / sqrt(2).
+        let sine: Vec<i16> = (0..1600)
+            .map(|i| ((i as f64 / 16.0 * std::f64::consts::TAU).sin() * 1000.0) as i16)
+            .collect();
+        let before = rms(&sine);
+        assert!((before - 707.1).abs() < 2.0);
+
+        let mut boosted = sine.clone();
+        apply_gain(&mut boosted, 20.0);
+        assert!((rms(&boosted) / before - 10.0).abs() < 0.01);
+
+        let mut unchanged = sine.clone();
+        apply_gain(&mut unchanged, 0.0);
+        assert_eq!(unchanged, sine);
+    }
+
+    #[test]
+    fn test_apply_gain_saturates() {
+        let mut samples = vec![20000i16, -20000, 100];
+        apply_gain(&mut samples, 12.0);
+        assert_eq!(samples[0], i16::MAX);
+        assert_eq!(samples[1], i16::MIN);
+        assert_eq!(samples[2], 398);
+    }
 }
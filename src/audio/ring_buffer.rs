@@ -3,6 +3,7 @@ use std::collections::VecDeque;
 pub struct RingBuffer {
     buffer: VecDeque<i16>,
     capacity: usize,
+    sample_rate: u32,
 }
 
 impl RingBuffer {
@@ -11,6 +12,7 @@ impl RingBuffer {
         Self {
             buffer: VecDeque::with_capacity(capacity),
             capacity,
+            sample_rate,
         }
     }
 
@@ -27,6 +29,27 @@ impl RingBuffer {
         self.buffer.drain(..).collect()
     }
 
+    /// Copy the most recent `duration_secs` of audio (or everything held, if
+    /// less) without removing it.
+    pub fn tail(&self, duration_secs: f32) -> Vec<i16> {
+        let start = self.tail_start(duration_secs);
+        self.buffer.range(start..).copied().collect()
+    }
+
+    /// Take the most recent `duration_secs` of audio and empty the buffer,
+    /// discarding anything older.
+    pub fn drain_tail(&mut self, duration_secs: f32) -> Vec<i16> {
+        let start = self.tail_start(duration_secs);
+        let tail = self.buffer.drain(start..).collect();
+        self.buffer.clear();
+        tail
+    }
+
+    fn tail_start(&self, duration_secs: f32) -> usize {
+        let wanted = (self.sample_rate as f32 * duration_secs.max(0.0)) as usize;
+        self.buffer.len().saturating_sub(wanted)
+    }
+
     pub fn len(&self) -> usize {
         self.buffer.len()
     }
@@ -34,6 +57,15 @@ impl RingBuffer {
     pub fn clear(&mut self) {
         self.buffer.clear();
     }
+
+    /// Change the buffer length, dropping the oldest samples if it shrinks.
+    pub fn resize(&mut self, sample_rate: u32, duration_secs: f32) {
+        self.capacity = (sample_rate as f32 * duration_secs) as usize;
+        self.sample_rate = sample_rate;
+        while self.buffer.len() > self.capacity {
+            self.buffer.pop_front();
+        }
+    }
 }
 
 #[cfg(test)]
@@ -68,4 +100,51 @@ mod tests {
         // Should contain the newest 16000 samples (4000..20000)
         assert_eq!(drained[0], 4000);
     }
+
+    #[test]
+    fn test_resize_keeps_newest_samples() {
+        let mut buf = RingBuffer::new(10, 1.0);
+        buf.push(&(0..10).collect::<Vec<i16>>());
+        buf.resize(10, 0.5);
+        assert_eq!(buf.drain(), vec![5, 6, 7, 8, 9]);
+
+        buf.resize(10, 2.0);
+        buf.push(&(0..20).collect::<Vec<i16>>());
+        assert_eq!(buf.len(), 20);
+    }
+
+    #[test]
+    fn test_tail_partially_filled() {
+        let mut buf = RingBuffer::new(10, 3.0);
+        buf.push(&(0..15).collect::<Vec<i16>>());
+        assert_eq!(buf.tail(1.0), (5..15).collect::<Vec<i16>>());
+        assert_eq!(buf.tail(0.5), vec![10, 11, 12, 13, 14]);
+        // Asking for more than is held returns everything.
+        assert_eq!(buf.tail(2.0), (0..15).collect::<Vec<i16>>());
+        assert!(buf.tail(0.0).is_empty());
+        // Copying leaves the buffer intact.
+        assert_eq!(buf.len(), 15);
+    }
+
+    #[test]
+    fn test_tail_fully_filled() {
+        let mut buf = RingBuffer::new(10, 3.0);
+        buf.push(&(0..45).collect::<Vec<i16>>());
+        assert_eq!(buf.len(), 30);
+        assert_eq!(buf.tail(1.0), (35..45).collect::<Vec<i16>>());
+        assert_eq!(buf.tail(5.0), (15..45).collect::<Vec<i16>>());
+        assert_eq!(buf.len(), 30);
+    }
+
+    #[test]
+    fn test_drain_tail_empties_buffer() {
+        let mut buf = RingBuffer::new(10, 3.0);
+        buf.push(&(0..30).collect::<Vec<i16>>());
+        assert_eq!(buf.drain_tail(0.5), vec![25, 26, 27, 28, 29]);
+        assert_eq!(buf.len(), 0);
+
+        buf.push(&[1, 2, 3]);
+        assert_eq!(buf.drain_tail(1.0), vec![1, 2, 3]);
+        assert_eq!(buf.len(), 0);
+    }
 }
@@ -5,12 +5,15 @@
 
 #![cfg(target_os = "windows")]
 
+use std::cell::Cell;
 use std::collections::VecDeque;
 
 use anyhow::Result;
 use thiserror::Error;
 use wasapi::*;
 
+use crate::audio::capture::event_stalled;
+
 /// Errors that can occur during Teams audio capture.
 ///
 /// `DeviceInvalidated` signals that the audio device or process was lost
@@ -34,6 +37,8 @@ pub struct TeamsCapture {
     audio_client: AudioClient,
     capture_client: AudioCaptureClient,
     event_handle: Handle,
+    event_timeout_ms: u32,
+    stalled_ms: Cell<u32>,
     sample_rate: u32,
     process_id: u32,
 }
@@ -43,7 +48,12 @@ impl TeamsCapture {
     ///
     /// `process_id` must be a valid PID of the target process.
     /// `desired_sample_rate` should be 16000 (for VAD compatibility) or 8000.
-    pub fn new(process_id: u32, desired_sample_rate: u32) -> Result<Self> {
+    /// `event_timeout_ms` is the longest single wait in `read_frames`.
+    pub fn new(
+        process_id: u32,
+        desired_sample_rate: u32,
+        event_timeout_ms: u32,
+    ) -> Result<Self> {
         initialize_mta().ok().map_err(|e| anyhow::anyhow!("COM MTA initialization failed: {:?}", e))?;
 
         // Request 16-bit mono PCM at the desired sample rate.
@@ -94,6 +104,8 @@ impl TeamsCapture {
             audio_client,
             capture_client,
             event_handle,
+            event_timeout_ms,
+            stalled_ms: Cell::new(0),
             sample_rate: desired_sample_rate,
             process_id,
         })
@@ -110,21 +122,29 @@ impl TeamsCapture {
     /// Wait for the next event and read captured frames as 16-bit PCM samples.
     ///
     /// Returns `Ok(Some(samples))` when audio data is available, or `Ok(None)`
-    /// if no data was captured in this cycle.
+    /// if no data was captured in this cycle or the wait timed out. A stream
+    /// with no event for `STALL_TIMEOUT_MS` fails.
     /// Returns `Err(CaptureError::DeviceInvalidated)` when the device or process
     /// is lost, or `Err(CaptureError::Other)` for other failures.
     pub fn read_frames(&self) -> std::result::Result<Option<Vec<i16>>, CaptureError> {
-        // Wait for WASAPI to signal that a buffer is ready.
-        if let Err(e) = self.event_handle.wait_for_event(1000) {
+        // Wait for WASAPI to signal that a buffer is ready, briefly, so the
+        // pipeline can check for shutdown between waits.
+        if let Err(e) = self.event_handle.wait_for_event(self.event_timeout_ms) {
             let msg = format!("{:?}", e);
             if Self::is_device_invalidated_error(&msg) {
                 return Err(CaptureError::DeviceInvalidated);
             }
+            if msg.to_uppercase().contains("TIMEOUT")
+                && !event_stalled(&self.stalled_ms, self.event_timeout_ms)
+            {
+                return Ok(None);
+            }
             return Err(CaptureError::Other(anyhow::anyhow!(
                 "Event wait timeout/error: {}",
                 msg
             )));
         }
+        self.stalled_ms.set(0);
 
         // Read captured bytes into a VecDeque, matching the pattern from capture.rs.
         let mut sample_queue: VecDeque<u8> = VecDeque::new();
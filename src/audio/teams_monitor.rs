@@ -1,26 +1,181 @@
-// Teams process monitor: detects when Teams starts/stops and manages capture pipeline.
+// App process monitor: detects when target apps (Teams, Zoom, ...) start/stop
+// and manages one capture pipeline per app.
 //
-// `find_teams_pid` is cross-platform (uses sysinfo which works on all platforms).
-// `run_teams_monitor` is Windows-only because it uses `TeamsCapture`.
+// `find_pids`, `app_sources`, `target_started`, `process_name_matches` and
+// `audio_candidates` are cross-platform (sysinfo works on all platforms).
+// `run_teams_monitor` is Windows-only because it uses `TeamsCapture` and
+// Toolhelp process snapshots to notice app launches quickly.
 
+use std::collections::HashSet;
 use std::ffi::OsStr;
 use sysinfo::{ProcessRefreshKind, RefreshKind, System};
 
-/// Finds the PID of the first matching process from the given list of process names.
+use crate::config::{sanitize_label, TargetsConfig};
+
+/// Finds a running process for each of the given process names, as
+/// `(process name, PID)` pairs in `process_names` order. Names with no running
+/// process are left out. A process with exactly the configured name is looked
+/// up first; otherwise every process is compared under `match_mode` (see
+/// `process_name_matches`).
 ///
 /// This function is cross-platform — it uses the `sysinfo` crate which works
-/// on Windows, Linux, and macOS. Returns `None` if no matching process is found.
-pub fn find_teams_pid(process_names: &[String]) -> Option<u32> {
+/// on Windows, Linux, and macOS.
+pub fn find_pids(process_names: &[String], match_mode: &str) -> Vec<(String, u32)> {
+    let refreshes = RefreshKind::nothing().with_processes(ProcessRefreshKind::nothing());
+    let system = System::new_with_specifics(refreshes);
+    process_names
+        .iter()
+        .filter_map(|name| {
+            let proc_ = system
+                .processes_by_exact_name(OsStr::new(name))
+                .next()
+                .or_else(|| {
+                    system.processes().values().find(|p| {
+                        process_name_matches(match_mode, name, &p.name().to_string_lossy())
+                    })
+                })?;
+            Some((name.clone(), proc_.pid().as_u32()))
+        })
+        .collect()
+}
+
+/// PIDs to try capturing an app's audio from: `root_pid` first, then its
+/// descendants (breadth-first, by PID within each level). Apps like new Teams
+/// play call audio from a helper process, which the main process's loopback
+/// doesn't always include.
+pub fn audio_candidates(root_pid: u32) -> Vec<u32> {
     let refreshes = RefreshKind::nothing().with_processes(ProcessRefreshKind::nothing());
     let system = System::new_with_specifics(refreshes);
-    for name in process_names {
-        let os_name = OsStr::new(name);
-        let mut procs = system.processes_by_name(os_name);
-        if let Some(proc_) = procs.next() {
-            return Some(proc_.pid().as_u32());
+    let processes: Vec<(u32, Option<u32>)> = system
+        .processes()
+        .values()
+        .map(|p| (p.pid().as_u32(), p.parent().map(|parent| parent.as_u32())))
+        .collect();
+    let mut candidates = vec![root_pid];
+    candidates.extend(descendants(&processes, root_pid));
+    candidates
+}
+
+/// Descendants of `root` in `processes` (`(PID, parent PID)` pairs),
+/// breadth-first and by PID within each level.
+fn descendants(processes: &[(u32, Option<u32>)], root: u32) -> Vec<u32> {
+    let mut found: Vec<u32> = Vec::new();
+    let mut level = vec![root];
+    while !level.is_empty() {
+        let mut children: Vec<u32> = processes
+            .iter()
+            .filter(|(pid, parent)| {
+                parent.is_some_and(|p| level.contains(&p)) && *pid != root && !found.contains(pid)
+            })
+            .map(|(pid, _)| *pid)
+            .collect();
+        children.sort_unstable();
+        children.dedup();
+        found.extend(&children);
+        level = children;
+    }
+    found
+}
+
+/// The candidate after `current` in `candidates`, wrapping around; the first
+/// candidate if `current` is no longer among them.
+pub fn next_candidate(candidates: &[u32], current: u32) -> Option<u32> {
+    let next = match candidates.iter().position(|&pid| pid == current) {
+        Some(i) => (i + 1) % candidates.len(),
+        None => 0,
+    };
+    candidates.get(next).copied()
+}
+
+/// Whether the running process `name` matches the configured `pattern` under
+/// `targets.match_mode`: "exact" (the whole name), "contains" (a substring)
+/// or "glob" (`*` matches any run of characters, `?` any one). Case is
+/// ignored, as Windows does for executable names. On Linux, where process
+/// names are cut to 15 bytes, a cut name also matches the start of `pattern`.
+pub fn process_name_matches(match_mode: &str, pattern: &str, name: &str) -> bool {
+    let (pattern, name) = (pattern.to_lowercase(), name.to_lowercase());
+    let matched = match match_mode {
+        "contains" => name.contains(&pattern),
+        "glob" => {
+            let pattern: Vec<char> = pattern.chars().collect();
+            let name: Vec<char> = name.chars().collect();
+            glob_matches(&pattern, &name)
+        }
+        _ => name == pattern,
+    };
+    matched || (cfg!(target_os = "linux") && name.len() == 15 && pattern.starts_with(&name))
+}
+
+fn glob_matches(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|i| glob_matches(rest, &name[i..])),
+        Some(('?', rest)) => !name.is_empty() && glob_matches(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && glob_matches(rest, &name[1..]),
+    }
+}
+
+/// Default source name for audio captured from `process_name`: "teams" for the
+/// classic and new Teams executables, otherwise the lowercased executable stem
+/// ("zoom.exe" -> "zoom").
+pub fn app_source(process_name: &str) -> String {
+    let lower = process_name.to_ascii_lowercase();
+    let stem = lower.strip_suffix(".exe").unwrap_or(&lower);
+    match stem {
+        "ms-teams" | "teams" => "teams".to_string(),
+        _ => match sanitize_label(stem) {
+            s if s.is_empty() => "app".to_string(),
+            s => s,
+        },
+    }
+}
+
+/// Groups `targets.processes` by the source they record as (after
+/// `targets.labels`), in config order. Processes sharing a source, like the
+/// two Teams executables, are one app: only one of them is captured at a time.
+pub fn app_sources(targets: &TargetsConfig) -> Vec<(String, Vec<String>)> {
+    let mut apps: Vec<(String, Vec<String>)> = Vec::new();
+    for name in &targets.processes {
+        let source = targets.source_label(&app_source(name), std::slice::from_ref(name));
+        match apps.iter_mut().find(|(s, _)| *s == source) {
+            Some((_, names)) => names.push(name.clone()),
+            None => apps.push((source, vec![name.clone()])),
         }
     }
-    None
+    apps
+}
+
+/// Whether the process snapshot `current` (`(PID, executable name)` pairs) has
+/// a process matching `process_names` (see `process_name_matches`) that isn't
+/// among the `previous` snapshot's PIDs.
+pub fn target_started(
+    previous: &HashSet<u32>,
+    current: &[(u32, String)],
+    process_names: &[String],
+    match_mode: &str,
+) -> bool {
+    current.iter().any(|(pid, exe)| {
+        !previous.contains(pid)
+            && process_names
+                .iter()
+                .any(|n| process_name_matches(match_mode, n, exe))
+    })
+}
+
+/// First Windows build with the process loopback API `TeamsCapture` uses
+/// (Windows 11 / Server 2022).
+pub const MIN_APP_LOOPBACK_BUILD: u32 = 20348;
+
+/// Whether the Windows build `build` (sysinfo's kernel version: "22631" or
+/// "10.0.22631") supports application loopback capture. An unknown or
+/// unparsable build is assumed to.
+pub fn app_loopback_supported(build: Option<&str>) -> bool {
+    let number = build.and_then(|b| {
+        let parts: Vec<&str> = b.trim().split('.').collect();
+        let build = if parts.len() >= 3 { parts[2] } else { parts[0] };
+        build.parse::<u32>().ok()
+    });
+    !matches!(number, Some(n) if n < MIN_APP_LOOPBACK_BUILD)
 }
 
 /// Checks whether a process with the given PID is still alive.
@@ -70,168 +225,536 @@ pub fn decide_pid_action(
     }
 }
 
-// --- Windows-only monitor that spawns the Teams capture pipeline ---
+// --- Windows-only monitor that spawns the app capture pipelines ---
 
 #[cfg(target_os = "windows")]
 mod monitor {
+    use std::collections::HashSet;
     use std::sync::atomic::{AtomicBool, Ordering};
-    use std::sync::mpsc::Sender;
+    use std::sync::mpsc::{self, RecvTimeoutError, Sender};
     use std::sync::Arc;
+    use std::time::{Duration, Instant};
 
     use anyhow::Result;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+        TH32CS_SNAPPROCESS,
+    };
 
-    use crate::audio::pipeline::{run_capture_pipeline, AudioMessage};
+    use crate::audio::levels::LevelMeter;
+    use crate::audio::pipeline::{run_capture_pipeline, run_level_probe, PipelineContext};
     use crate::audio::teams_capture::TeamsCapture;
-    use crate::audio::vad::Vad;
-    use crate::config::Config;
+    use crate::audio::vad::{chunk_size_for, new_vad};
+    use crate::config::{CaptureConfig, Config};
+
+    use super::{
+        app_loopback_supported, app_sources, audio_candidates, decide_pid_action, find_pids,
+        is_process_alive, next_candidate, target_started, PidAction, MIN_APP_LOOPBACK_BUILD,
+    };
 
-    use super::{decide_pid_action, find_teams_pid, is_process_alive, PidAction};
+    /// How often the launch watcher snapshots the process list.
+    const WATCH_INTERVAL: Duration = Duration::from_secs(1);
 
-    /// Monitors for Teams process and spawns/stops capture pipeline accordingly.
+    /// A capture of one process, running on its own thread.
+    struct Pipeline {
+        /// Process being captured.
+        pid: u32,
+        /// Meter the capture's levels go to: the shared one for the recording
+        /// pipeline, one of its own for a probe, so each process's audio can
+        /// be told apart.
+        levels: Arc<LevelMeter>,
+        shutdown: Arc<AtomicBool>,
+        handle: std::thread::JoinHandle<()>,
+    }
+
+    impl Pipeline {
+        fn stop(self) {
+            self.shutdown.store(true, Ordering::Relaxed);
+            let _ = self.handle.join();
+        }
+    }
+
+    /// The captures for one app.
+    struct AppCapture {
+        /// The app's main process, as found by `find_pids`.
+        root_pid: u32,
+        /// The app's only recording pipeline: on its main process, or on the
+        /// child process its audio was last heard from. The file writer keeps
+        /// one file per source, so no other pipeline may record the app.
+        recording: Pipeline,
+        /// A metering-only capture of another of the app's processes, while
+        /// the app is silent, to find the one that plays its audio.
+        probe: Option<Pipeline>,
+        /// When the current wait for audio began.
+        waiting_since: Instant,
+        /// Whether audio has been heard since the last silence of
+        /// `targets.audio_probe_secs` (a call is going on).
+        heard: bool,
+    }
+
+    impl AppCapture {
+        fn new(recording: Pipeline) -> Self {
+            Self {
+                root_pid: recording.pid,
+                recording,
+                probe: None,
+                waiting_since: Instant::now(),
+                heard: false,
+            }
+        }
+
+        fn stop(self) {
+            if let Some(probe) = self.probe {
+                probe.stop();
+            }
+            self.recording.stop();
+        }
+    }
+
+    /// What `probe_audio` wants done next for an app.
+    enum ProbeStep {
+        Wait,
+        /// Probe this process instead of the one being probed, if any.
+        Probe(u32),
+        /// Record this process in place of the one being recorded.
+        Record(u32),
+    }
+
+    /// All running processes as `(PID, executable name)`.
+    fn process_snapshot() -> Result<Vec<(u32, String)>> {
+        let mut processes = Vec::new();
+        unsafe {
+            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0)?;
+            let mut entry = PROCESSENTRY32W {
+                dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+                ..Default::default()
+            };
+            let mut more = Process32FirstW(snapshot, &mut entry).is_ok();
+            while more {
+                let name = &entry.szExeFile;
+                let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+                processes.push((entry.th32ProcessID, String::from_utf16_lossy(&name[..len])));
+                more = Process32NextW(snapshot, &mut entry).is_ok();
+            }
+            let _ = CloseHandle(snapshot);
+        }
+        Ok(processes)
+    }
+
+    /// Snapshot processes every `WATCH_INTERVAL` and send on `wake` when a
+    /// target app launches, so the monitor reacts without waiting for its next
+    /// poll. Fails up front if process snapshots don't work.
+    fn spawn_launch_watcher(
+        process_names: Vec<String>,
+        match_mode: String,
+        wake: Sender<()>,
+        shutdown: Arc<AtomicBool>,
+    ) -> Result<std::thread::JoinHandle<()>> {
+        let pids = |snapshot: Vec<(u32, String)>| -> HashSet<u32> {
+            snapshot.into_iter().map(|(pid, _)| pid).collect()
+        };
+        let mut previous = pids(process_snapshot()?);
+
+        let handle = std::thread::Builder::new()
+            .name("app-launch-watch".into())
+            .spawn(move || {
+                while !shutdown.load(Ordering::Relaxed) {
+                    std::thread::sleep(WATCH_INTERVAL);
+                    let current = match process_snapshot() {
+                        Ok(current) => current,
+                        Err(e) => {
+                            tracing::warn!("App launch watcher stopped, polling only: {:?}", e);
+                            return;
+                        }
+                    };
+                    let started = target_started(&previous, &current, &process_names, &match_mode);
+                    if started && wake.send(()).is_err() {
+                        return;
+                    }
+                    previous = pids(current);
+                }
+            })?;
+        Ok(handle)
+    }
+
+    /// Monitors the target apps and spawns/stops a capture pipeline per app.
     ///
-    /// Polls every 5 seconds for the Teams process. When detected, creates a
-    /// `TeamsCapture` and runs the audio pipeline. When the process disappears,
-    /// shuts down the pipeline and waits for the next appearance.
+    /// Polls every `targets.poll_interval_secs` for the configured processes,
+    /// and right away when the launch watcher sees one start (so short calls
+    /// aren't missed). If the watcher can't run, it falls back to polling only.
+    /// Each app (see `app_sources`) that is running gets its own `TeamsCapture`
+    /// and pipeline, recording under its own source name (`teams`, `zoom`, ...).
+    /// When an app's process disappears, its pipeline is shut down until it
+    /// appears again.
     ///
-    /// Uses `decide_pid_action` to handle PID changes correctly: if the old PID
-    /// is still alive but a different PID is found (Teams runs multiple processes),
-    /// we keep the current capture instead of tearing down and restarting.
-    pub fn run_teams_monitor(
-        config: Config,
-        sender: Sender<AudioMessage>,
-        shutdown: Arc<AtomicBool>,
-        paused: Arc<AtomicBool>,
-    ) -> Result<()> {
-        let mut active_pid: Option<u32> = None;
-        let mut pipeline_shutdown: Option<Arc<AtomicBool>> = None;
-        let mut pipeline_handle: Option<std::thread::JoinHandle<()>> = None;
+    /// Each app has one recording pipeline, on its main process at first.
+    /// While it hasn't heard audio for `targets.audio_probe_secs`, the app's
+    /// other processes (see `audio_candidates`) are probed in turn, metering
+    /// only. Once a probe hears audio, recording moves to that process, so
+    /// the app is never recorded twice. Which PID the audio comes from is
+    /// logged once per call; a call ends after that long a silence.
+    ///
+    /// On Windows builds without application loopback (Windows 10), app capture
+    /// is disabled with one warning and toast instead of failing every poll;
+    /// the thread then just waits for shutdown.
+    ///
+    /// Uses `decide_pid_action` per app to handle PID changes correctly: if the
+    /// old PID is still alive but a different PID is found (Teams runs multiple
+    /// processes), we keep the current capture instead of tearing down and restarting.
+    pub fn run_teams_monitor(config: Config, ctx: PipelineContext) -> Result<()> {
+        let PipelineContext {
+            shutdown, paused, ..
+        } = &ctx;
+        let chunk_size = chunk_size_for(config.capture.sample_rate)?;
+        let apps = app_sources(&config.targets);
+
+        let build = sysinfo::System::kernel_version();
+        if !apps.is_empty() && !app_loopback_supported(build.as_deref()) {
+            let message = format!(
+                "Recording app audio needs Windows 11 (build {} or later); this is build {}. \
+                 App capture is disabled; set targets.system_loopback = true to record \
+                 all desktop audio instead.",
+                MIN_APP_LOOPBACK_BUILD,
+                build.unwrap_or_default()
+            );
+            tracing::warn!("{}", message);
+            crate::monitoring::send_toast("deskmic: app audio unavailable", &message);
+            // Keep running so the watchdog doesn't treat this as a crash.
+            while !shutdown.load(Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_millis(500));
+            }
+            return Ok(());
+        }
+
+        let mut active: Vec<(String, AppCapture)> = Vec::new();
+        let poll_interval = Duration::from_secs(config.targets.poll_interval_secs);
+        let probe = Duration::from_secs(config.targets.audio_probe_secs);
+
+        let (wake_tx, wake_rx) = mpsc::channel();
+        let watcher = spawn_launch_watcher(
+            config.targets.processes.clone(),
+            config.targets.match_mode.clone(),
+            wake_tx,
+            shutdown.clone(),
+        );
+        let (mut wake_rx, watcher) = match watcher {
+            Ok(handle) => (Some(wake_rx), Some(handle)),
+            Err(e) => {
+                tracing::warn!(
+                    "Can't watch for app launches ({:?}), polling every {}s",
+                    e,
+                    poll_interval.as_secs()
+                );
+                (None, None)
+            }
+        };
 
         while !shutdown.load(Ordering::Relaxed) {
-            std::thread::sleep(std::time::Duration::from_secs(5));
-            let current_pid = find_teams_pid(&config.targets.processes);
-
-            let action = decide_pid_action(active_pid, current_pid, is_process_alive);
-
-            match action {
-                PidAction::StartCapture(pid) => {
-                    // Teams just started — spawn capture pipeline.
-                    tracing::info!("Teams detected (PID {}), starting capture", pid);
-                    let pipe_shutdown = Arc::new(AtomicBool::new(false));
-                    let pipe_shutdown_clone = pipe_shutdown.clone();
-                    let sender_clone = sender.clone();
-                    let paused_clone = paused.clone();
-                    let sample_rate = config.capture.sample_rate;
-                    let pre_speech_buffer_secs = config.vad.pre_speech_buffer_secs;
-                    let silence_threshold_secs = config.vad.silence_threshold_secs;
-                    let speech_threshold = config.vad.speech_threshold;
-
-                    let handle = std::thread::Builder::new()
-                        .name("teams-capture".into())
-                        .spawn(move || {
-                            match TeamsCapture::new(pid, sample_rate) {
-                                Ok(capture) => {
-                                    let capture_fn = || -> Result<Option<Vec<i16>>> {
-                                        Ok(capture.read_frames()?)
-                                    };
-                                    let start_fn = || -> Result<()> { capture.start() };
-
-                                    // Determine VAD chunk size based on sample rate.
-                                    let chunk_size: usize = match sample_rate {
-                                        8000 => 256,
-                                        16000 => 512,
-                                        _ => 512,
-                                    };
-
-                                    match Vad::new(sample_rate, speech_threshold) {
-                                        Ok(mut vad) => {
-                                            if let Err(e) = run_capture_pipeline(
-                                                "teams".to_string(),
-                                                capture_fn,
-                                                start_fn,
-                                                sample_rate,
-                                                pre_speech_buffer_secs,
-                                                silence_threshold_secs,
-                                                &mut vad,
-                                                chunk_size,
-                                                sender_clone,
-                                                pipe_shutdown_clone,
-                                                paused_clone,
-                                            ) {
-                                                tracing::error!("Teams pipeline error: {:?}", e);
-                                            }
-                                        }
-                                        Err(e) => {
-                                            tracing::error!("Failed to create VAD: {:?}", e);
-                                        }
-                                    }
-
-                                    if let Err(e) = capture.stop() {
-                                        tracing::warn!("Error stopping Teams capture: {:?}", e);
-                                    }
-                                }
-                                Err(e) => {
-                                    tracing::error!(
-                                        "Failed to start Teams capture for PID {}: {:?}",
-                                        pid,
-                                        e
-                                    );
-                                }
-                            }
-                        })?;
-
-                    active_pid = Some(pid);
-                    pipeline_shutdown = Some(pipe_shutdown);
-                    pipeline_handle = Some(handle);
+            let woke = match &wake_rx {
+                Some(rx) => rx.recv_timeout(poll_interval),
+                None => {
+                    std::thread::sleep(poll_interval);
+                    Err(RecvTimeoutError::Timeout)
                 }
-                PidAction::StopCapture => {
-                    // Teams process gone — stop capture.
-                    tracing::info!("Teams process gone, stopping capture");
-                    if let Some(ps) = pipeline_shutdown.take() {
-                        ps.store(true, Ordering::Relaxed);
+            };
+            match woke {
+                Ok(()) => tracing::debug!("Target app launched, checking now"),
+                Err(RecvTimeoutError::Timeout) => {}
+                // The watcher gave up; keep polling.
+                Err(RecvTimeoutError::Disconnected) => wake_rx = None,
+            }
+            let found = find_pids(&config.targets.processes, &config.targets.match_mode);
+
+            for (source, processes) in &apps {
+                let current_pid = found
+                    .iter()
+                    .find(|(name, _)| processes.contains(name))
+                    .map(|(_, pid)| *pid);
+                let slot = active.iter().position(|(s, _)| s == source);
+                let active_pid = slot.map(|i| active[i].1.root_pid);
+
+                match decide_pid_action(active_pid, current_pid, is_process_alive) {
+                    PidAction::StartCapture(pid) => {
+                        // App just started — spawn its capture pipeline.
+                        tracing::info!("{} detected (PID {}), starting capture", source, pid);
+                        let recording = spawn_app_capture(
+                            pid,
+                            source.clone(),
+                            &config.capture,
+                            chunk_size,
+                            &ctx,
+                        )?;
+                        active.push((source.clone(), AppCapture::new(recording)));
                     }
-                    if let Some(handle) = pipeline_handle.take() {
-                        let _ = handle.join();
+                    PidAction::StopCapture => {
+                        // App process gone — stop capture.
+                        tracing::info!("{} process gone, stopping capture", source);
+                        if let Some(i) = slot {
+                            active.remove(i).1.stop();
+                        }
                     }
-                    active_pid = None;
-                }
-                PidAction::RestartCapture(new_pid) => {
-                    // Old PID is dead, new PID found — tear down and restart.
-                    tracing::info!(
-                        "Teams PID changed (old process dead), restarting capture on PID {}",
-                        new_pid,
-                    );
-                    if let Some(ps) = pipeline_shutdown.take() {
-                        ps.store(true, Ordering::Relaxed);
+                    PidAction::RestartCapture(new_pid) => {
+                        // Old PID is dead, new PID found — tear down and restart.
+                        tracing::info!(
+                            "{} PID changed (old process dead), restarting capture on PID {}",
+                            source,
+                            new_pid,
+                        );
+                        // Dropping the entry lets the next poll pick up the new PID.
+                        if let Some(i) = slot {
+                            active.remove(i).1.stop();
+                        }
+                    }
+                    PidAction::KeepCurrent => {
+                        // Different PID found but old PID is still alive — ignore.
+                        // This fixes #13: Teams runs multiple processes, non-deterministic
+                        // iteration order causes different PIDs each poll.
+                        tracing::debug!(
+                            "{} returned different PID but active process still alive, keeping current capture",
+                            source
+                        );
                     }
-                    if let Some(handle) = pipeline_handle.take() {
-                        let _ = handle.join();
+                    PidAction::NoChange => {
+                        // No change — continue polling.
                     }
-                    // Set active_pid to None so next iteration picks up the new PID.
-                    active_pid = None;
                 }
-                PidAction::KeepCurrent => {
-                    // Different PID found but old PID is still alive — ignore.
-                    // This fixes #13: Teams runs multiple processes, non-deterministic
-                    // iteration order causes different PIDs each poll.
-                    tracing::debug!(
-                        "Teams returned different PID but active process still alive, keeping current capture"
-                    );
+            }
+
+            if probe.is_zero() {
+                continue;
+            }
+            for i in 0..active.len() {
+                let (source, capture) = &mut active[i];
+                if paused.load(Ordering::Relaxed) {
+                    // Paused pipelines don't meter audio; restart the wait after.
+                    capture.waiting_since = Instant::now();
+                    continue;
                 }
-                PidAction::NoChange => {
-                    // No change — continue polling.
+                match probe_audio(source, capture, probe) {
+                    ProbeStep::Wait => {}
+                    ProbeStep::Probe(pid) => {
+                        if let Some(old) = capture.probe.take() {
+                            old.stop();
+                        }
+                        capture.probe =
+                            Some(spawn_level_probe(pid, source.clone(), &config.capture)?);
+                    }
+                    ProbeStep::Record(pid) => {
+                        // Stop the old pipeline before starting the new one,
+                        // so their messages never mix in the writer.
+                        let (source, mut capture) = active.remove(i);
+                        capture.recording.stop();
+                        capture.recording = spawn_app_capture(
+                            pid,
+                            source.clone(),
+                            &config.capture,
+                            chunk_size,
+                            &ctx,
+                        )?;
+                        active.insert(i, (source, capture));
+                    }
                 }
             }
         }
 
-        // Shutdown: clean up any active pipeline.
-        if let Some(ps) = pipeline_shutdown.take() {
-            ps.store(true, Ordering::Relaxed);
+        // Shutdown: clean up any active pipelines.
+        for (_, capture) in active {
+            capture.stop();
         }
-        if let Some(handle) = pipeline_handle.take() {
+        if let Some(handle) = watcher {
             let _ = handle.join();
         }
         Ok(())
     }
+
+    /// Check whether `capture`'s app is playing audio, and decide what to
+    /// record and probe next.
+    fn probe_audio(source: &str, capture: &mut AppCapture, probe: Duration) -> ProbeStep {
+        let root_pid = capture.root_pid;
+        let recording_pid = capture.recording.pid;
+        if recording_pid != root_pid && capture.recording.handle.is_finished() {
+            // The child process it was recording exited.
+            if let Some(old) = capture.probe.take() {
+                old.stop();
+            }
+            capture.heard = false;
+            capture.waiting_since = Instant::now();
+            return ProbeStep::Record(root_pid);
+        }
+
+        let heard_at = capture.recording.levels.last_heard(source);
+        if heard_at.is_some_and(|at| at.elapsed() < probe) {
+            if let Some(old) = capture.probe.take() {
+                old.stop();
+            }
+            if !capture.heard {
+                capture.heard = true;
+                log_audio_source(source, recording_pid, root_pid);
+            }
+            return ProbeStep::Wait;
+        }
+        // A probe's meter is its own, so anything it heard is since it began.
+        if let Some(probe) = capture
+            .probe
+            .take_if(|p| p.levels.last_heard(source).is_some())
+        {
+            let pid = probe.pid;
+            probe.stop();
+            capture.heard = true;
+            capture.waiting_since = Instant::now();
+            log_audio_source(source, pid, root_pid);
+            return ProbeStep::Record(pid);
+        }
+        if capture.heard {
+            // The call ended; the next one may play from another process.
+            capture.heard = false;
+            capture.waiting_since = Instant::now();
+            return ProbeStep::Wait;
+        }
+        if capture.waiting_since.elapsed() < probe {
+            return ProbeStep::Wait;
+        }
+
+        capture.waiting_since = Instant::now();
+        let candidates: Vec<u32> = audio_candidates(root_pid)
+            .into_iter()
+            .filter(|&pid| pid != recording_pid)
+            .collect();
+        let current = capture.probe.as_ref().map_or(recording_pid, |p| p.pid);
+        match next_candidate(&candidates, current) {
+            Some(next) if next != current => {
+                tracing::debug!(
+                    "No {} audio for {}s, probing PID {}",
+                    source,
+                    probe.as_secs(),
+                    next
+                );
+                ProbeStep::Probe(next)
+            }
+            // Nothing else to try; keep listening here.
+            _ => ProbeStep::Wait,
+        }
+    }
+
+    /// Log that `source`'s audio comes from `pid`.
+    fn log_audio_source(source: &str, pid: u32, root_pid: u32) {
+        if pid == root_pid {
+            tracing::info!("{} audio is coming from PID {}", source, pid);
+        } else {
+            tracing::info!(
+                "{} audio is coming from PID {} (a child of PID {}), recording it",
+                source,
+                pid,
+                root_pid
+            );
+        }
+    }
+
+    /// Start capturing `pid`'s audio into a pipeline for `source` on a new
+    /// thread. The pipeline shares `ctx` except for its own shutdown flag, so
+    /// it can be stopped on its own.
+    fn spawn_app_capture(
+        pid: u32,
+        source: String,
+        capture_config: &CaptureConfig,
+        chunk_size: usize,
+        ctx: &PipelineContext,
+    ) -> Result<Pipeline> {
+        let pipe_shutdown = Arc::new(AtomicBool::new(false));
+        let ctx = PipelineContext {
+            shutdown: pipe_shutdown.clone(),
+            ..ctx.clone()
+        };
+        let levels = ctx.levels.clone();
+        let sample_rate = capture_config.sample_rate;
+        let wait_ms = capture_config.event_timeout_ms;
+
+        let handle = std::thread::Builder::new()
+            .name(format!("{}-capture", source))
+            .spawn(move || match TeamsCapture::new(pid, sample_rate, wait_ms) {
+                Ok(capture) => {
+                    let capture_fn = || -> Result<Option<Vec<i16>>> { Ok(capture.read_frames()?) };
+                    let start_fn = || -> Result<()> { capture.start() };
+
+                    let speech_threshold = ctx.settings.vad().speech_threshold;
+                    let mut vad = new_vad(sample_rate, speech_threshold);
+                    if let Err(e) = run_capture_pipeline(
+                        source.clone(),
+                        capture_fn,
+                        start_fn,
+                        sample_rate,
+                        vad.as_mut(),
+                        chunk_size,
+                        &ctx,
+                    ) {
+                        tracing::error!("{} pipeline error: {:?}", source, e);
+                    }
+
+                    if let Err(e) = capture.stop() {
+                        tracing::warn!("Error stopping {} capture: {:?}", source, e);
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to start {} capture for PID {}: {:?}",
+                        source,
+                        pid,
+                        e
+                    );
+                }
+            })?;
+
+        Ok(Pipeline {
+            pid,
+            levels,
+            shutdown: pipe_shutdown,
+            handle,
+        })
+    }
+
+    /// Start metering `pid`'s audio on a new thread, into a level meter of its
+    /// own; nothing is sent to the file writer (see `run_level_probe`).
+    fn spawn_level_probe(
+        pid: u32,
+        source: String,
+        capture_config: &CaptureConfig,
+    ) -> Result<Pipeline> {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let levels = Arc::new(LevelMeter::new());
+        let (probe_shutdown, probe_levels) = (shutdown.clone(), levels.clone());
+        let sample_rate = capture_config.sample_rate;
+        let wait_ms = capture_config.event_timeout_ms;
+
+        let handle = std::thread::Builder::new()
+            .name(format!("{}-probe", source))
+            .spawn(move || match TeamsCapture::new(pid, sample_rate, wait_ms) {
+                Ok(capture) => {
+                    let capture_fn = || -> Result<Option<Vec<i16>>> { Ok(capture.read_frames()?) };
+                    let start_fn = || -> Result<()> { capture.start() };
+                    if let Err(e) = run_level_probe(
+                        &source,
+                        capture_fn,
+                        start_fn,
+                        &probe_levels,
+                        &probe_shutdown,
+                    ) {
+                        tracing::debug!("{} probe of PID {} stopped: {:?}", source, pid, e);
+                    }
+                    if let Err(e) = capture.stop() {
+                        tracing::warn!("Error stopping {} probe: {:?}", source, e);
+                    }
+                }
+                Err(e) => tracing::debug!("Can't probe {} PID {}: {:?}", source, pid, e),
+            })?;
+
+        Ok(Pipeline {
+            pid,
+            levels,
+            shutdown,
+            handle,
+        })
+    }
 }
 
 #[cfg(target_os = "windows")]
@@ -243,14 +766,157 @@ mod tests {
 
     #[test]
     fn test_find_nonexistent_process() {
-        let pid = find_teams_pid(&["definitely-not-a-real-process-12345.exe".to_string()]);
-        assert!(pid.is_none());
+        let name = "definitely-not-a-real-process-12345.exe".to_string();
+        let pids = find_pids(&[name], "exact");
+        assert!(pids.is_empty());
     }
 
     #[test]
     fn test_find_empty_process_list() {
-        let pid = find_teams_pid(&[]);
-        assert!(pid.is_none());
+        let pids = find_pids(&[], "glob");
+        assert!(pids.is_empty());
+    }
+
+    #[test]
+    fn test_find_own_process_by_mode() {
+        let refreshes = RefreshKind::nothing().with_processes(ProcessRefreshKind::nothing());
+        let system = System::new_with_specifics(refreshes);
+        let own = system.process(sysinfo::get_current_pid().unwrap()).unwrap();
+        let name = own.name().to_string_lossy().to_uppercase();
+
+        assert_eq!(find_pids(std::slice::from_ref(&name), "exact").len(), 1);
+        let prefix: String = name.chars().take(3).collect();
+        assert!(find_pids(&[format!("{}*", prefix)], "exact").is_empty());
+        assert_eq!(find_pids(&[format!("{}*", prefix)], "glob").len(), 1);
+        assert_eq!(find_pids(&[prefix], "contains").len(), 1);
+    }
+
+    #[test]
+    fn test_descendants_breadth_first() {
+        let processes = [
+            (1, None),
+            (10, Some(1)),
+            (30, Some(10)),
+            (20, Some(10)),
+            (5, Some(1)),
+            (99, Some(2)),
+        ];
+        assert_eq!(descendants(&processes, 1), vec![5, 10, 20, 30]);
+        assert_eq!(descendants(&processes, 10), vec![20, 30]);
+        assert!(descendants(&processes, 99).is_empty());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_audio_candidates_include_children() {
+        use std::process::Command;
+
+        let mut child = Command::new("sleep").arg("5").spawn().unwrap();
+        let own = std::process::id();
+        let candidates = audio_candidates(own);
+        let _ = child.kill();
+        let _ = child.wait();
+        assert_eq!(candidates[0], own);
+        assert!(candidates.contains(&child.id()));
+    }
+
+    #[test]
+    fn test_next_candidate_wraps() {
+        assert_eq!(next_candidate(&[1, 5, 9], 1), Some(5));
+        assert_eq!(next_candidate(&[1, 5, 9], 9), Some(1));
+        // The child exited: start over from the main process.
+        assert_eq!(next_candidate(&[1, 9], 5), Some(1));
+        assert_eq!(next_candidate(&[1], 1), Some(1));
+        assert_eq!(next_candidate(&[], 1), None);
+    }
+
+    #[test]
+    fn test_process_name_matches() {
+        assert!(process_name_matches("exact", "teams.exe", "Teams.exe"));
+        assert!(!process_name_matches("exact", "teams", "ms-teams.exe"));
+        assert!(process_name_matches("contains", "Teams", "ms-teams.exe"));
+        assert!(process_name_matches("contains", "teams", "Teams.exe"));
+        assert!(process_name_matches("glob", "zoom*", "Zoom.exe"));
+        assert!(process_name_matches("glob", "*teams?exe", "ms-teams.exe"));
+        assert!(!process_name_matches("glob", "zoom", "Zoom.exe"));
+        assert!(!process_name_matches("glob", "*teams", "ms-teams.exe"));
+    }
+
+    #[test]
+    fn test_target_started_only_for_new_matching_pids() {
+        let names = vec!["ms-teams.exe".to_string(), "zoom.exe".to_string()];
+        let previous: HashSet<u32> = [1, 2].into_iter().collect();
+        let snapshot = |procs: &[(u32, &str)]| -> Vec<(u32, String)> {
+            procs.iter().map(|(pid, n)| (*pid, n.to_string())).collect()
+        };
+
+        // Already running, or not a target: no launch.
+        assert!(!target_started(
+            &previous,
+            &snapshot(&[(1, "ms-teams.exe"), (3, "notepad.exe")]),
+            &names,
+            "exact"
+        ));
+        // New PID for a target (case-insensitive).
+        assert!(target_started(
+            &previous,
+            &snapshot(&[(1, "ms-teams.exe"), (4, "Zoom.exe")]),
+            &names,
+            "exact"
+        ));
+        let patterns = vec!["teams".to_string()];
+        let launched = snapshot(&[(5, "Teams.exe")]);
+        assert!(!target_started(&previous, &launched, &patterns, "exact"));
+        assert!(target_started(&previous, &launched, &patterns, "contains"));
+    }
+
+    #[test]
+    fn test_app_loopback_supported_by_build() {
+        assert!(app_loopback_supported(Some("22631")));
+        assert!(app_loopback_supported(Some("10.0.20348")));
+        assert!(!app_loopback_supported(Some("19045")));
+        assert!(!app_loopback_supported(Some("10.0.19045")));
+        // Unknown: try anyway.
+        assert!(app_loopback_supported(None));
+        assert!(app_loopback_supported(Some("6.8.0-generic")));
+    }
+
+    #[test]
+    fn test_app_source_from_process_name() {
+        assert_eq!(app_source("ms-teams.exe"), "teams");
+        assert_eq!(app_source("Teams.exe"), "teams");
+        assert_eq!(app_source("Zoom.exe"), "zoom");
+        assert_eq!(app_source("slack"), "slack");
+        assert_eq!(app_source("my_app.exe"), "my-app");
+    }
+
+    #[test]
+    fn test_app_sources_groups_by_source() {
+        let mut targets = TargetsConfig {
+            processes: vec![
+                "ms-teams.exe".to_string(),
+                "zoom.exe".to_string(),
+                "Teams.exe".to_string(),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            app_sources(&targets),
+            vec![
+                (
+                    "teams".to_string(),
+                    vec!["ms-teams.exe".to_string(), "Teams.exe".to_string()]
+                ),
+                ("zoom".to_string(), vec!["zoom.exe".to_string()]),
+            ]
+        );
+
+        // A label on the process (or its source) renames that app only.
+        targets
+            .labels
+            .insert("zoom.exe".to_string(), "Standup".to_string());
+        let sources: Vec<String> = app_sources(&targets).into_iter().map(|(s, _)| s).collect();
+        assert_eq!(sources, vec!["teams", "Standup"]);
     }
 
     #[test]
@@ -0,0 +1,168 @@
+// Microphone self-test: records a few seconds to a WAV and reports levels, to
+// tell "no audio reaches deskmic" apart from "deskmic drops the audio".
+//
+// `capture_samples`, `write_wav` and `format_report` are cross-platform and testable without a
+// device; `run_test_capture` wires them to `MicCapture` on Windows.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+
+use crate::audio::levels::{LevelMeter, SourceLevel, SILENCE_DBFS};
+use crate::config::Config;
+
+/// Peak below which the recording is reported as too quiet.
+const QUIET_PEAK_DBFS: f32 = -50.0;
+
+/// Collect `target_samples` samples from `capture_fn`, or fewer if
+/// `shutdown` is set first.
+pub fn capture_samples(
+    capture_fn: impl Fn() -> Result<Option<Vec<i16>>>,
+    start_fn: impl Fn() -> Result<()>,
+    target_samples: usize,
+    shutdown: &Arc<AtomicBool>,
+) -> Result<Vec<i16>> {
+    let mut captured = Vec::with_capacity(target_samples);
+    start_fn()?;
+    while captured.len() < target_samples && !shutdown.load(Ordering::Relaxed) {
+        if let Some(samples) = capture_fn()? {
+            let take = samples.len().min(target_samples - captured.len());
+            captured.extend_from_slice(&samples[..take]);
+        }
+    }
+    Ok(captured)
+}
+
+/// Write mono 16-bit PCM samples to `path`.
+pub fn write_wav(path: &Path, samples: &[i16], sample_rate: u32) -> Result<()> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    for &s in samples {
+        writer.write_sample(s)?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+/// Peak and RMS of the whole recording.
+fn measure(samples: &[i16]) -> SourceLevel {
+    let meter = LevelMeter::new();
+    meter.record("mic", samples);
+    meter.take().remove("mic").unwrap_or_else(|| SourceLevel {
+        peak_dbfs: SILENCE_DBFS,
+        rms_dbfs: SILENCE_DBFS,
+        updated_at: String::new(),
+    })
+}
+
+/// Human-readable report of a test recording: length, levels and a verdict.
+pub fn format_report(samples: &[i16], sample_rate: u32) -> String {
+    let level = measure(samples);
+    let verdict = if samples.is_empty() {
+        "No audio was captured: check the device is connected and not disabled."
+    } else if level.peak_dbfs <= SILENCE_DBFS {
+        "Digital silence: the mic is muted, or Windows is blocking microphone access."
+    } else if level.peak_dbfs < QUIET_PEAK_DBFS {
+        "Very quiet: speak up, move closer, or raise capture.gain_db."
+    } else {
+        "Audio is flowing."
+    };
+    format!(
+        "  Samples:  {} ({:.1}s at {} Hz)\n  Peak:     {:.1} dBFS\n  RMS:      {:.1} dBFS\n\n{}\n",
+        samples.len(),
+        samples.len() as f64 / sample_rate as f64,
+        sample_rate,
+        level.peak_dbfs,
+        level.rms_dbfs,
+        verdict
+    )
+}
+
+/// Entry point for `deskmic test-capture`: record `duration_secs` from the
+/// configured microphone to `output` and print its levels.
+#[cfg(target_os = "windows")]
+pub fn run_test_capture(config: &Config, duration_secs: u32, output: &Path) -> Result<()> {
+    use crate::audio::capture::MicCapture;
+
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let shutdown_ctrlc = shutdown.clone();
+    ctrlc::set_handler(move || {
+        shutdown_ctrlc.store(true, Ordering::Relaxed);
+    })?;
+
+    let device = config.capture.device.as_deref();
+    let timeout_ms = config.capture.event_timeout_ms;
+    let capture = MicCapture::new(config.capture.sample_rate, device, timeout_ms)?;
+    let sample_rate = capture.sample_rate();
+    let capture_fn = || -> Result<Option<Vec<i16>>> { Ok(capture.read_frames()?) };
+    let start_fn = || -> Result<()> { capture.start() };
+
+    println!(
+        "Recording {}s from {} (Ctrl+C to stop early)...",
+        duration_secs,
+        device.unwrap_or("the default microphone")
+    );
+    let target = sample_rate as usize * duration_secs as usize;
+    let result = capture_samples(capture_fn, start_fn, target, &shutdown);
+    if let Err(e) = capture.stop() {
+        tracing::warn!("Error stopping mic capture: {:?}", e);
+    }
+    let samples = result?;
+
+    write_wav(output, &samples, sample_rate)?;
+    println!("Saved {}", output.display());
+    print!("{}", format_report(&samples, sample_rate));
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn run_test_capture(_config: &Config, _duration_secs: u32, _output: &Path) -> Result<()> {
+    anyhow::bail!("Test capture is only supported on Windows")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_samples_trims_to_target() {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let reads = std::cell::RefCell::new(vec![Some(vec![7i16; 4]), None, Some(vec![7i16; 4])]);
+        let capture_fn = || -> Result<Option<Vec<i16>>> { Ok(reads.borrow_mut().pop().flatten()) };
+        let start_fn = || -> Result<()> { Ok(()) };
+
+        let samples = capture_samples(capture_fn, start_fn, 6, &shutdown).unwrap();
+        assert_eq!(samples, vec![7; 6]);
+    }
+
+    #[test]
+    fn test_write_wav_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.wav");
+        write_wav(&path, &[0, 1000, -1000], 16000).unwrap();
+
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().sample_rate, 16000);
+        let samples: Vec<i16> = reader.samples::<i16>().map(|s| s.unwrap()).collect();
+        assert_eq!(samples, vec![0, 1000, -1000]);
+    }
+
+    #[test]
+    fn test_format_report_verdicts() {
+        assert!(format_report(&[], 16000).contains("No audio was captured"));
+        assert!(format_report(&[0; 160], 16000).contains("Digital silence"));
+        assert!(format_report(&[10, -10], 16000).contains("Very quiet"));
+
+        let report = format_report(&[16384, -16384], 16000);
+        assert!(report.contains("Peak:     -6.0 dBFS"), "{}", report);
+        assert!(report.contains("Audio is flowing"), "{}", report);
+    }
+}
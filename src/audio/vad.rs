@@ -1,12 +1,211 @@
 // VAD (Voice Activity Detection) wrapper
 //
-// The real implementation uses `voice_activity_detector` which is only
-// available on Windows. On non-Windows platforms we provide a stub.
+// The Silero-based `Vad` uses `voice_activity_detector`, which is only
+// available on Windows. `EnergyVad` is a simple cross-platform fallback, used
+// on other platforms and when the Silero model fails to load.
+// `AdaptiveGate` refines either one's verdicts against the room's noise floor
+// (`vad.adaptive`).
+
+use std::collections::VecDeque;
+
+use anyhow::Result;
+
+use crate::audio::levels::to_dbfs;
 
 /// Trait for voice activity detection, allowing platform-specific implementations.
 pub trait VadProcessor {
     /// Returns true if the given audio chunk contains speech.
     fn is_speech(&mut self, samples: &[i16]) -> bool;
+
+    /// Change the speech probability threshold (used on config reload).
+    fn set_threshold(&mut self, _threshold: f32) {}
+}
+
+/// Samples per VAD chunk for each supported sample rate (the Silero model's
+/// 32 ms window).
+const CHUNK_SIZES: &[(u32, usize)] = &[(8000, 256), (16000, 512)];
+
+/// Samples the pipeline should feed the VAD per call at `sample_rate`.
+/// Errors for rates the VAD doesn't support.
+pub fn chunk_size_for(sample_rate: u32) -> Result<usize> {
+    CHUNK_SIZES
+        .iter()
+        .find(|(rate, _)| *rate == sample_rate)
+        .map(|(_, size)| *size)
+        .ok_or_else(|| {
+            let supported: Vec<String> = CHUNK_SIZES
+                .iter()
+                .map(|(rate, _)| rate.to_string())
+                .collect();
+            anyhow::anyhow!(
+                "VAD doesn't support a {} Hz sample rate (supported: {})",
+                sample_rate,
+                supported.join(", ")
+            )
+        })
+}
+
+/// Quietest level (dBFS) `EnergyVad` treats as speech, at `speech_threshold = 0.0`.
+const ENERGY_MIN_DBFS: f32 = -60.0;
+
+/// Loudest speech threshold (dBFS), at `speech_threshold = 1.0`.
+const ENERGY_MAX_DBFS: f32 = -20.0;
+
+/// Chunks still reported as speech after the level drops, so quiet word
+/// endings and short gaps between words aren't chopped.
+const HANGOVER_CHUNKS: u32 = 5;
+
+/// Energy-based VAD: a chunk is speech when its RMS level exceeds a threshold.
+///
+/// `speech_threshold` (0.0-1.0, as for the Silero VAD) maps linearly onto
+/// -60..-20 dBFS, so the default 0.5 means -40 dBFS.
+pub struct EnergyVad {
+    rms_threshold: f32,
+    hangover: u32,
+}
+
+impl EnergyVad {
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            rms_threshold: Self::rms_for(threshold),
+            hangover: 0,
+        }
+    }
+
+    fn rms_for(threshold: f32) -> f32 {
+        let dbfs =
+            ENERGY_MIN_DBFS + (ENERGY_MAX_DBFS - ENERGY_MIN_DBFS) * threshold.clamp(0.0, 1.0);
+        i16::MAX as f32 * 10f32.powf(dbfs / 20.0)
+    }
+}
+
+/// Root-mean-square amplitude of `samples` (0 for none).
+fn rms(samples: &[i16]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum_squares: f64 = samples.iter().map(|&s| (s as f64) * (s as f64)).sum();
+    (sum_squares / samples.len() as f64).sqrt() as f32
+}
+
+impl VadProcessor for EnergyVad {
+    fn is_speech(&mut self, samples: &[i16]) -> bool {
+        if samples.is_empty() {
+            return false;
+        }
+
+        if rms(samples) >= self.rms_threshold {
+            self.hangover = HANGOVER_CHUNKS;
+            true
+        } else if self.hangover > 0 {
+            self.hangover -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn set_threshold(&mut self, threshold: f32) {
+        self.rms_threshold = Self::rms_for(threshold);
+    }
+}
+
+/// How far (dB) above the noise floor a chunk must be to count as speech in
+/// adaptive mode.
+const ADAPTIVE_MARGIN_DB: f32 = 10.0;
+
+/// The noise floor is the quietest chunk in this many seconds: long enough to
+/// span a pause in normal speech, short enough to follow a fan switching on.
+const NOISE_FLOOR_WINDOW_SECS: f32 = 5.0;
+
+/// Longest dip inside speech that's bridged rather than counted as silence.
+const DROPOUT_BRIDGE_SECS: f32 = 0.3;
+
+/// Adaptive mode (`vad.adaptive`): second-guesses a `VadProcessor` using the
+/// ambient noise level, so the effective threshold follows the room.
+///
+/// A chunk counts as speech only when the VAD says so *and* it is
+/// `ADAPTIVE_MARGIN_DB` above the noise floor, which stops a noisy room from
+/// being recorded as one long segment. Speech must then last
+/// `min_segment_secs` before it's reported, so a click or bump doesn't start a
+/// segment, and dips of up to `DROPOUT_BRIDGE_SECS` inside speech are bridged
+/// so a short pause mid-sentence doesn't count towards the silence that ends it.
+pub struct AdaptiveGate {
+    sample_rate: u32,
+    min_segment_secs: f32,
+    /// Recent chunk levels (dBFS) with their lengths in seconds.
+    levels: VecDeque<(f32, f32)>,
+    levels_secs: f32,
+    /// Seconds of continuous speech not yet reported.
+    onset_secs: f32,
+    /// Seconds since the last speech chunk while speaking.
+    dropout_secs: f32,
+    speaking: bool,
+}
+
+impl AdaptiveGate {
+    pub fn new(sample_rate: u32, min_segment_secs: f32) -> Self {
+        Self {
+            sample_rate,
+            min_segment_secs,
+            levels: VecDeque::new(),
+            levels_secs: 0.0,
+            onset_secs: 0.0,
+            dropout_secs: 0.0,
+            speaking: false,
+        }
+    }
+
+    /// The current noise floor in dBFS, once any audio has been seen.
+    pub fn noise_floor(&self) -> Option<f32> {
+        self.levels.iter().map(|&(level, _)| level).reduce(f32::min)
+    }
+
+    /// Whether `samples` should count as speech, given the VAD's verdict.
+    pub fn apply(&mut self, samples: &[i16], is_speech: bool) -> bool {
+        if samples.is_empty() {
+            return self.speaking;
+        }
+        let secs = samples.len() as f32 / self.sample_rate as f32;
+        let level = to_dbfs(rms(samples) as f64 / i16::MAX as f64);
+        self.levels.push_back((level, secs));
+        self.levels_secs += secs;
+        while self.levels_secs > NOISE_FLOOR_WINDOW_SECS {
+            let Some((_, old)) = self.levels.pop_front() else {
+                break;
+            };
+            self.levels_secs -= old;
+        }
+        let floor = self.noise_floor().unwrap_or(level);
+
+        if is_speech && level >= floor + ADAPTIVE_MARGIN_DB {
+            self.dropout_secs = 0.0;
+            if !self.speaking {
+                self.onset_secs += secs;
+                self.speaking = self.onset_secs >= self.min_segment_secs;
+            }
+        } else {
+            self.onset_secs = 0.0;
+            if self.speaking {
+                self.dropout_secs += secs;
+                self.speaking = self.dropout_secs <= DROPOUT_BRIDGE_SECS;
+            }
+        }
+        self.speaking
+    }
+}
+
+/// The best available VAD: Silero on Windows, falling back to `EnergyVad` if
+/// the model can't be built (and everywhere else).
+pub fn new_vad(sample_rate: u32, threshold: f32) -> Box<dyn VadProcessor> {
+    #[cfg(target_os = "windows")]
+    match Vad::new(sample_rate, threshold) {
+        Ok(vad) => return Box::new(vad),
+        Err(e) => tracing::warn!("Silero VAD unavailable ({:#}), using energy-based VAD", e),
+    }
+    #[cfg(not(target_os = "windows"))]
+    let _ = sample_rate;
+    Box::new(EnergyVad::new(threshold))
 }
 
 #[cfg(target_os = "windows")]
@@ -22,11 +221,7 @@ mod platform {
 
     impl Vad {
         pub fn new(sample_rate: u32, threshold: f32) -> Result<Self> {
-            let chunk_size = match sample_rate {
-                8000 => 256usize,
-                16000 => 512usize,
-                _ => anyhow::bail!("VAD only supports 8000 or 16000 Hz sample rate"),
-            };
+            let chunk_size = chunk_size_for(sample_rate)?;
             let detector = VoiceActivityDetector::builder()
                 .sample_rate(sample_rate)
                 .chunk_size(chunk_size)
@@ -44,6 +239,10 @@ mod platform {
             let probability = self.detector.predict(samples.to_vec());
             probability >= self.threshold
         }
+
+        fn set_threshold(&mut self, threshold: f32) {
+            self.threshold = threshold;
+        }
     }
 
     #[cfg(test)]
@@ -73,3 +272,120 @@ mod platform {
 
 #[cfg(target_os = "windows")]
 pub use platform::Vad;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(amplitude: f32) -> Vec<i16> {
+        (0..512)
+            .map(|i| ((i as f32 * 0.2).sin() * amplitude) as i16)
+            .collect()
+    }
+
+    #[test]
+    fn test_chunk_size_for() {
+        assert_eq!(chunk_size_for(8000).unwrap(), 256);
+        assert_eq!(chunk_size_for(16000).unwrap(), 512);
+        assert!(chunk_size_for(44100).is_err());
+    }
+
+    #[test]
+    fn test_energy_vad_detects_loud_chunks() {
+        let mut vad = EnergyVad::new(0.5);
+        assert!(!vad.is_speech(&vec![0i16; 512]));
+        assert!(!vad.is_speech(&tone(100.0)));
+        assert!(vad.is_speech(&tone(3000.0)));
+    }
+
+    #[test]
+    fn test_energy_vad_hangover() {
+        let mut vad = EnergyVad::new(0.5);
+        assert!(vad.is_speech(&tone(3000.0)));
+        let silence = vec![0i16; 512];
+        for _ in 0..HANGOVER_CHUNKS {
+            assert!(vad.is_speech(&silence));
+        }
+        assert!(!vad.is_speech(&silence));
+    }
+
+    #[test]
+    fn test_energy_vad_threshold_change() {
+        let mut vad = EnergyVad::new(0.0);
+        assert!(vad.is_speech(&tone(100.0)));
+        vad.set_threshold(1.0);
+        // Drain the hangover from the previous chunk first.
+        for _ in 0..HANGOVER_CHUNKS {
+            vad.is_speech(&tone(100.0));
+        }
+        assert!(!vad.is_speech(&tone(3000.0)));
+    }
+
+    /// Feed `count` chunks of `tone(amplitude)` with the VAD saying `vad`,
+    /// returning the gate's verdicts.
+    fn gate_run(gate: &mut AdaptiveGate, count: usize, amplitude: f32, vad: bool) -> Vec<bool> {
+        (0..count)
+            .map(|_| gate.apply(&tone(amplitude), vad))
+            .collect()
+    }
+
+    // Chunks are 512 samples at 16 kHz: 32 ms each.
+
+    #[test]
+    fn test_adaptive_gate_clean_speech_with_dropout() {
+        let mut gate = AdaptiveGate::new(16000, 0.25);
+        assert!(gate_run(&mut gate, 50, 30.0, false).iter().all(|&s| !s));
+
+        // 0.25 s of speech (8 chunks) before it's reported.
+        let onset = gate_run(&mut gate, 10, 3000.0, true);
+        assert_eq!(onset, [vec![false; 7], vec![true; 3]].concat());
+
+        // A 200 ms pause mid-sentence is bridged...
+        assert!(gate_run(&mut gate, 6, 30.0, false).iter().all(|&s| s));
+        assert!(gate_run(&mut gate, 5, 3000.0, true).iter().all(|&s| s));
+        // ...but longer silence ends the speech after about 300 ms.
+        let tail = gate_run(&mut gate, 15, 30.0, false);
+        assert_eq!(tail, [vec![true; 9], vec![false; 6]].concat());
+    }
+
+    #[test]
+    fn test_adaptive_gate_ignores_short_blips() {
+        let mut gate = AdaptiveGate::new(16000, 0.25);
+        gate_run(&mut gate, 50, 30.0, false);
+        for _ in 0..5 {
+            // 96 ms bumps between quiet stretches never start a segment.
+            assert!(gate_run(&mut gate, 3, 5000.0, true).iter().all(|&s| !s));
+            assert!(gate_run(&mut gate, 10, 30.0, false).iter().all(|&s| !s));
+        }
+    }
+
+    #[test]
+    fn test_adaptive_gate_follows_noisy_room() {
+        let mut gate = AdaptiveGate::new(16000, 0.0);
+        gate_run(&mut gate, 50, 30.0, false);
+        let quiet_floor = gate.noise_floor().unwrap();
+
+        // A fan switches on, loud enough that the VAD calls it speech. Once it
+        // fills the 5 s noise window (156 chunks) it's the new floor, and once
+        // the dropout bridge runs out it's no longer speech.
+        let noise = gate_run(&mut gate, 200, 1500.0, true);
+        assert!(noise[0]);
+        assert!(noise[..150].iter().all(|&s| s));
+        assert!(noise[170..].iter().all(|&s| !s));
+        assert!(gate.noise_floor().unwrap() > quiet_floor + 30.0);
+
+        // Speech well above the fan still counts; speech barely above it
+        // doesn't.
+        assert!(!gate.apply(&tone(2500.0), true));
+        assert!(gate.apply(&tone(15000.0), true));
+    }
+
+    #[test]
+    fn test_adaptive_gate_needs_vad_agreement() {
+        let mut gate = AdaptiveGate::new(16000, 0.0);
+        gate_run(&mut gate, 50, 30.0, false);
+        // Loud but not speech per the VAD (e.g. music the model rejects).
+        assert!(gate_run(&mut gate, 5, 3000.0, false).iter().all(|&s| !s));
+        assert!(gate.apply(&tone(3000.0), true));
+    }
+}
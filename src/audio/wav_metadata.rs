@@ -0,0 +1,129 @@
+// RIFF `LIST/INFO` metadata for finalized WAV files.
+//
+// `hound` has no API for INFO chunks, so tags are appended after the file is
+// finalized and the RIFF header size is patched to cover them. Readers that
+// don't understand INFO (including hound) simply skip the chunk.
+
+use std::fs::OpenOptions;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+
+/// Build the INFO tags deskmic embeds: source (`ISRC`), capture date/time
+/// (`ICRD`), and the writing software (`ISFT`).
+pub fn deskmic_tags(source: &str, started: DateTime<Local>) -> Vec<([u8; 4], String)> {
+    vec![
+        (*b"ISRC", source.to_string()),
+        (*b"ICRD", started.format("%Y-%m-%d %H:%M:%S").to_string()),
+        (*b"ISFT", format!("deskmic {}", env!("CARGO_PKG_VERSION"))),
+    ]
+}
+
+/// Encode `tags` as a complete `LIST` chunk of type `INFO`.
+fn build_info_chunk(tags: &[([u8; 4], String)]) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(b"INFO");
+    for (id, value) in tags {
+        // Values are NUL-terminated; chunks are padded to an even length.
+        let mut data = value.as_bytes().to_vec();
+        data.push(0);
+        body.extend_from_slice(id);
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        body.extend_from_slice(&data);
+        if data.len() % 2 == 1 {
+            body.push(0);
+        }
+    }
+
+    let mut chunk = Vec::with_capacity(body.len() + 8);
+    chunk.extend_from_slice(b"LIST");
+    chunk.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    chunk.extend_from_slice(&body);
+    chunk
+}
+
+/// Append a `LIST/INFO` chunk to a finalized WAV file and fix up the RIFF size.
+pub fn append_info_chunk(path: &Path, tags: &[([u8; 4], String)]) -> Result<()> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let mut len = file.seek(SeekFrom::End(0))?;
+
+    // Chunks start on even offsets.
+    if len % 2 == 1 {
+        file.write_all(&[0])?;
+        len += 1;
+    }
+
+    let chunk = build_info_chunk(tags);
+    file.write_all(&chunk)?;
+    len += chunk.len() as u64;
+
+    let riff_size = u32::try_from(len - 8)
+        .map_err(|_| anyhow::anyhow!("WAV too large for RIFF header: {}", path.display()))?;
+    file.seek(SeekFrom::Start(4))?;
+    file.write_all(&riff_size.to_le_bytes())?;
+    file.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hound::{SampleFormat, WavSpec, WavWriter};
+
+    fn write_test_wav(path: &Path, samples: &[i16]) {
+        let spec = WavSpec {
+            channels: 1,
+            sample_rate: 16000,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let mut writer = WavWriter::create(path, spec).unwrap();
+        for &s in samples {
+            writer.write_sample(s).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn test_info_chunk_layout() {
+        let chunk = build_info_chunk(&[(*b"ISRC", "mic".to_string())]);
+        // "LIST" + size + "INFO" + "ISRC" + size + "mic\0"
+        assert_eq!(&chunk[0..4], b"LIST");
+        assert_eq!(u32::from_le_bytes(chunk[4..8].try_into().unwrap()), 16);
+        assert_eq!(&chunk[8..12], b"INFO");
+        assert_eq!(&chunk[12..16], b"ISRC");
+        assert_eq!(u32::from_le_bytes(chunk[16..20].try_into().unwrap()), 4);
+        assert_eq!(&chunk[20..24], b"mic\0");
+    }
+
+    #[test]
+    fn test_info_chunk_pads_odd_values() {
+        let chunk = build_info_chunk(&[(*b"ISRC", "note".to_string())]);
+        // "note\0" is 5 bytes, padded to 6.
+        assert_eq!(chunk.len(), 8 + 4 + 8 + 6);
+        assert_eq!(chunk.len() % 2, 0);
+    }
+
+    #[test]
+    fn test_append_info_chunk_keeps_wav_readable() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("mic_10-00-00.wav");
+        let samples: Vec<i16> = (0..320).collect();
+        write_test_wav(&path, &samples);
+
+        let tags = deskmic_tags("mic", Local::now());
+        append_info_chunk(&path, &tags).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let riff_size = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        assert_eq!(riff_size, bytes.len() - 8);
+        assert!(bytes.windows(4).any(|w| w == b"INFO"));
+        assert!(bytes.windows(4).any(|w| w == b"ISFT"));
+
+        let reader = hound::WavReader::open(&path).unwrap();
+        let read: Vec<i16> = reader.into_samples::<i16>().map(|s| s.unwrap()).collect();
+        assert_eq!(read, samples);
+    }
+}
@@ -12,6 +12,20 @@ pub struct Cli {
     #[arg(short, long, global = true)]
     pub config: Option<PathBuf>,
 
+    /// Log format: "text", or "json" for one JSON object per line
+    #[arg(
+        long,
+        global = true,
+        env = "DESKMIC_LOG_FORMAT",
+        default_value = "text",
+        value_parser = ["text", "json"]
+    )]
+    pub log_format: String,
+
+    /// Also write logs to daily-rotated files in <recordings>/logs/
+    #[arg(long, global = true)]
+    pub log_file: bool,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -28,7 +42,17 @@ pub enum Commands {
     Uninstall,
 
     /// Show recording status, disk usage, file count
-    Status,
+    Status {
+        /// Print a JSON object instead of the human-readable summary
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List capture devices (microphones) and show which is the default
+    Devices,
+
+    /// Check microphone access, model, credentials and output directory
+    Doctor,
 
     /// Transcribe pending audio files
     Transcribe {
@@ -43,17 +67,118 @@ pub enum Commands {
 
     /// Summarize transcripts and email the summary
     Summarize {
-        /// Date range: "daily", "weekly", "YYYY-MM-DD", or "YYYY-MM-DD..YYYY-MM-DD"
+        /// Date range: "daily", "weekly", "monthly", "last:N", "YYYY-MM-DD", or
+        /// "YYYY-MM-DD..YYYY-MM-DD"
         #[arg(default_value = "daily")]
         range: String,
+
+        /// Write the summary to this file (or directory) instead of recordings/summaries/
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        /// Hours of the day (0-23) to leave out, e.g. "12,13" (overrides config)
+        #[arg(long, value_delimiter = ',', value_parser = clap::value_parser!(u32).range(0..=23))]
+        exclude_hours: Vec<u32>,
+
+        /// Sources to leave out, e.g. "system" or "mic,teams" (overrides config)
+        #[arg(long, value_delimiter = ',')]
+        exclude_source: Vec<String>,
+
+        /// Fold today's new transcripts into today's rolling summary (no email)
+        #[arg(long, conflicts_with = "range")]
+        rolling: bool,
+
+        /// Print the summary instead of emailing it (still saved locally)
+        #[arg(long, conflicts_with = "rolling")]
+        dry_run: bool,
+    },
+
+    /// Record a fixed-length note from the microphone (no VAD) for transcription
+    Clip {
+        /// Clip length in seconds
+        #[arg(long, default_value = "60", value_parser = clap::value_parser!(u32).range(1..=3600))]
+        duration: u32,
+    },
+
+    /// Record a few seconds from the microphone to a WAV and report its levels
+    TestCapture {
+        /// Recording length in seconds
+        #[arg(long, default_value = "5", value_parser = clap::value_parser!(u32).range(1..=60))]
+        duration: u32,
+
+        /// WAV file to write
+        #[arg(long, default_value = "test.wav")]
+        output: PathBuf,
     },
 
+    /// Send a test message through the configured summary transport
+    TestEmail,
+
     /// Interactive setup wizard (download model, create config, etc.)
-    Setup,
+    Setup {
+        /// Take every answer from the flags below (or their environment
+        /// variables) instead of prompting, for unattended installs
+        #[arg(long)]
+        noninteractive: bool,
+
+        /// Whisper model to download (with --noninteractive)
+        #[arg(long, env = "DESKMIC_SETUP_MODEL", default_value = "base.en")]
+        model: String,
+
+        /// Add deskmic to Windows startup (with --noninteractive)
+        #[arg(long, env = "DESKMIC_SETUP_STARTUP")]
+        install_startup: bool,
+
+        /// Re-download the model and overwrite an existing config (with --noninteractive)
+        #[arg(long)]
+        force: bool,
+
+        /// Update an existing config to the current template, keeping its
+        /// values (with --noninteractive)
+        #[arg(long)]
+        update_config: bool,
+
+        /// Azure OpenAI endpoint for email summaries (with --noninteractive)
+        #[arg(long, env = "DESKMIC_AZURE_ENDPOINT")]
+        azure_endpoint: Option<String>,
+
+        /// Azure OpenAI API key
+        #[arg(long, env = "DESKMIC_AZURE_API_KEY", hide_env_values = true)]
+        azure_api_key: Option<String>,
+
+        /// Chat deployment name
+        #[arg(long, env = "DESKMIC_CHAT_DEPLOYMENT")]
+        chat_deployment: Option<String>,
+
+        /// Azure Communication Services endpoint
+        #[arg(long, env = "DESKMIC_ACS_ENDPOINT")]
+        acs_endpoint: Option<String>,
+
+        /// Azure Communication Services API key
+        #[arg(long, env = "DESKMIC_ACS_API_KEY", hide_env_values = true)]
+        acs_api_key: Option<String>,
+
+        /// Summary sender address
+        #[arg(long, env = "DESKMIC_SENDER_ADDRESS")]
+        sender_address: Option<String>,
+
+        /// Summary recipient address(es), comma-separated
+        #[arg(long, env = "DESKMIC_RECIPIENT_ADDRESS")]
+        recipient_address: Option<String>,
+    },
 
     /// Build or update the transcript search index
     Index,
 
+    /// Copy existing JSONL transcripts into the SQLite transcript store
+    MigrateTranscripts,
+
+    /// Work with stored transcripts
+    Transcripts {
+        #[command(subcommand)]
+        command: TranscriptsCommand,
+    },
+
     /// Search transcripts by semantic similarity
     Search {
         /// The search query
@@ -80,3 +205,20 @@ pub enum Commands {
         json: bool,
     },
 }
+
+#[derive(Subcommand, Debug)]
+pub enum TranscriptsCommand {
+    /// Write a day's transcripts as one chronological text file in recordings/summaries/
+    Export {
+        /// Day to export (YYYY-MM-DD, default today)
+        date: Option<String>,
+
+        /// Output format: "txt" or "md"
+        #[arg(long, default_value = "txt")]
+        format: String,
+
+        /// Write the transcript to this file (or directory) instead of recordings/summaries/
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
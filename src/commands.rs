@@ -1,4 +1,5 @@
 use anyhow::Result;
+use serde::Serialize;
 
 /// Add a shortcut to the Windows Startup folder.
 #[cfg(target_os = "windows")]
@@ -79,37 +80,152 @@ pub fn uninstall_startup() -> Result<()> {
     anyhow::bail!("Install/uninstall is only supported on Windows")
 }
 
-/// Show current recording status.
-pub fn show_status(recordings_dir: &std::path::Path) -> Result<()> {
-    let (file_count, total_bytes) = crate::storage::get_storage_stats(recordings_dir)?;
-    let total_mb = total_bytes as f64 / 1_048_576.0;
+/// List capture devices, marking the current default.
+#[cfg(target_os = "windows")]
+pub fn list_devices() -> Result<()> {
+    let devices = crate::audio::capture::list_capture_devices()?;
+    if devices.is_empty() {
+        println!("No capture devices found.");
+        return Ok(());
+    }
+
+    println!("Capture devices:");
+    for device in &devices {
+        let marker = if device.is_default { " (default)" } else { "" };
+        println!("  {}{}", device.name, marker);
+        println!("    ID: {}", device.id);
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn list_devices() -> Result<()> {
+    anyhow::bail!("Listing capture devices is only supported on Windows")
+}
+
+/// Number of most recent days listed in the status breakdown.
+const STATUS_RECENT_DAYS: usize = 7;
+
+/// Machine-readable snapshot printed by `deskmic status --json`.
+#[derive(Debug, Serialize)]
+struct StatusReport<'a> {
+    recordings_dir: &'a std::path::Path,
+    files: usize,
+    total_bytes: u64,
+    by_source: std::collections::BTreeMap<String, crate::storage::FileStats>,
+    by_date: std::collections::BTreeMap<String, crate::storage::FileStats>,
+    /// Last status written by the transcriber, if it has run.
+    transcription: Option<crate::transcribe::status::TranscriptionStatus>,
+}
+
+/// Build the JSON status snapshot for the recordings directory.
+fn status_json(
+    recordings_dir: &std::path::Path,
+    machine_id: Option<&str>,
+    filename_template: &str,
+) -> Result<String> {
+    let stats = crate::storage::get_storage_stats(recordings_dir, filename_template)?;
+    let report = StatusReport {
+        recordings_dir,
+        files: stats.files,
+        total_bytes: stats.bytes,
+        by_source: stats.by_source,
+        by_date: stats.by_date,
+        transcription: crate::transcribe::status::TranscriptionStatus::read(
+            recordings_dir,
+            machine_id,
+        ),
+    };
+    Ok(serde_json::to_string_pretty(&report)?)
+}
+
+/// Show current recording status, as text or (with `json`) as a JSON object.
+/// Recordings are attributed to sources by reading their names with
+/// `filename_template`.
+pub fn show_status(
+    recordings_dir: &std::path::Path,
+    machine_id: Option<&str>,
+    filename_template: &str,
+    json: bool,
+) -> Result<()> {
+    if json {
+        println!(
+            "{}",
+            status_json(recordings_dir, machine_id, filename_template)?
+        );
+        return Ok(());
+    }
+
+    let stats = crate::storage::get_storage_stats(recordings_dir, filename_template)?;
+    let total_mb = stats.bytes as f64 / 1_048_576.0;
 
     println!("deskmic status:");
     println!("  Recordings dir: {}", recordings_dir.display());
-    println!("  Total files:    {}", file_count);
+    println!("  Total files:    {}", stats.files);
     println!("  Total size:     {:.1} MB", total_mb);
 
+    let recent_days = stats.by_date.iter().rev().take(STATUS_RECENT_DAYS);
+    for line in breakdown_table("Source", stats.by_source.iter())
+        .into_iter()
+        .chain(breakdown_table("Day", recent_days))
+    {
+        println!("{}", line);
+    }
+
     Ok(())
 }
 
+/// Render recording counts and sizes as an aligned table, one line per row.
+/// Empty when there are no rows.
+fn breakdown_table<'a>(
+    heading: &str,
+    rows: impl Iterator<Item = (&'a String, &'a crate::storage::FileStats)>,
+) -> Vec<String> {
+    let rows: Vec<_> = rows.collect();
+    if rows.is_empty() {
+        return Vec::new();
+    }
+    let width = rows
+        .iter()
+        .map(|(name, _)| name.len())
+        .chain(std::iter::once(heading.len()))
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = vec![
+        String::new(),
+        format!("  {:<width$}  {:>6}  {:>10}", heading, "Files", "Size"),
+    ];
+    for (name, stats) in rows {
+        let mb = stats.bytes as f64 / 1_048_576.0;
+        lines.push(format!(
+            "  {:<width$}  {:>6}  {:>7.1} MB",
+            name, stats.files, mb
+        ));
+    }
+    lines
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::DEFAULT_FILENAME_TEMPLATE as TEMPLATE;
     use tempfile::TempDir;
 
     #[test]
     fn test_show_status_empty_dir() {
         let tmp = TempDir::new().unwrap();
         // Should not error on an empty directory
-        show_status(tmp.path()).unwrap();
+        show_status(tmp.path(), None, TEMPLATE, false).unwrap();
+        show_status(tmp.path(), None, TEMPLATE, true).unwrap();
     }
 
     #[test]
     fn test_show_status_nonexistent_dir() {
         let tmp = TempDir::new().unwrap();
         let nonexistent = tmp.path().join("nonexistent");
-        // Should not error on a nonexistent directory (get_storage_stats returns (0, 0))
-        show_status(&nonexistent).unwrap();
+        // Should not error on a nonexistent directory (get_storage_stats returns empty stats)
+        show_status(&nonexistent, None, TEMPLATE, false).unwrap();
     }
 
     #[test]
@@ -120,7 +236,52 @@ mod tests {
         std::fs::create_dir_all(&date_dir).unwrap();
         std::fs::write(date_dir.join("test.wav"), &[0u8; 1024]).unwrap();
 
-        show_status(tmp.path()).unwrap();
+        show_status(tmp.path(), None, TEMPLATE, false).unwrap();
+    }
+
+    #[test]
+    fn test_status_json() {
+        use crate::transcribe::status::TranscriptionStatus;
+
+        let tmp = TempDir::new().unwrap();
+        let date_dir = tmp.path().join("2025-06-01");
+        std::fs::create_dir_all(&date_dir).unwrap();
+        std::fs::write(date_dir.join("mic_09-00-00.wav"), [0u8; 1024]).unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&status_json(tmp.path(), None, TEMPLATE).unwrap()).unwrap();
+        assert_eq!(json["files"], 1);
+        assert_eq!(json["total_bytes"], 1024);
+        assert_eq!(json["by_source"]["mic"]["files"], 1);
+        assert_eq!(json["by_date"]["2025-06-01"]["bytes"], 1024);
+        assert!(json["transcription"].is_null());
+
+        let mut status = TranscriptionStatus::new();
+        status.queue_length = 4;
+        status.write(tmp.path(), None).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&status_json(tmp.path(), None, TEMPLATE).unwrap()).unwrap();
+        assert_eq!(json["transcription"]["queue_length"], 4);
+        assert_eq!(json["transcription"]["state"], "idle");
+    }
+
+    #[test]
+    fn test_breakdown_table() {
+        use crate::storage::FileStats;
+        assert!(breakdown_table("Source", std::iter::empty()).is_empty());
+
+        let row = |name: &str, files, bytes| (name.to_string(), FileStats { files, bytes });
+        let rows = [row("mic", 3, 2_097_152), row("teams", 12, 524_288)];
+        let lines = breakdown_table("Source", rows.iter().map(|(n, s)| (n, s)));
+        assert_eq!(
+            lines,
+            vec![
+                "",
+                "  Source   Files        Size",
+                "  mic          3      2.0 MB",
+                "  teams       12      0.5 MB",
+            ]
+        );
     }
 
     #[cfg(not(target_os = "windows"))]
@@ -134,6 +295,16 @@ mod tests {
             .contains("only supported on Windows"));
     }
 
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_list_devices_fails_on_non_windows() {
+        let result = list_devices();
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("only supported on Windows"));
+    }
+
     #[cfg(not(target_os = "windows"))]
     #[test]
     fn test_uninstall_startup_fails_on_non_windows() {
@@ -1,10 +1,24 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
 use std::path::{Path, PathBuf};
 
+/// Config file format version written by this build. Bump it, and teach
+/// `Config::migrate` (or `RENAMED_KEYS`) the change, when a setting is
+/// renamed or changes meaning.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Settings renamed in a format version: `(version, old path, new path)`,
+/// applied to files older than `version` before they are parsed.
+const RENAMED_KEYS: &[(u32, &str, &str)] = &[];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct Config {
+    /// Format version of the file this was read from; absent (0) in files
+    /// written before versioning.
+    #[serde(default)]
+    pub version: u32,
     pub capture: CaptureConfig,
     pub vad: VadConfig,
     pub output: OutputConfig,
@@ -17,6 +31,8 @@ pub struct Config {
     pub monitoring: MonitoringConfig,
     #[serde(default)]
     pub search: SearchConfig,
+    #[serde(default)]
+    pub hotkeys: HotkeysConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,14 +41,92 @@ pub struct CaptureConfig {
     pub sample_rate: u32,
     pub bit_depth: u16,
     pub channels: u16,
+    /// Pause recording while the Windows session is locked.
+    pub pause_when_locked: bool,
+    /// Microphone to record from: a friendly-name substring or endpoint ID as
+    /// shown by `deskmic devices`. `None` uses the default recording device.
+    pub device: Option<String>,
+    /// Record several microphones at once, one source per entry (same matching
+    /// as `device`). Takes precedence over `device` when non-empty.
+    pub devices: Vec<String>,
+    /// Input gain in dB applied before VAD and recording (0 = unchanged).
+    pub gain_db: f32,
+    /// Longest single wait for a capture buffer, in milliseconds. Capture
+    /// threads check for shutdown between waits, so this bounds how long
+    /// stopping deskmic can take.
+    pub event_timeout_ms: u32,
+    /// Seconds of recent audio every source keeps, speech or not, for "Save
+    /// last N seconds" from the tray or `hotkeys.save_clip` (0 = off).
+    pub save_clip_secs: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// One microphone pipeline the recorder should run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MicSource {
+    /// Device name/ID to open, or `None` for the default device.
+    pub device: Option<String>,
+    /// Recording file-name prefix, e.g. "mic" or "mic-headset".
+    pub source: String,
+    /// Fail (and retry) instead of falling back to the default device when
+    /// `device` isn't connected, so two pipelines never record the same mic.
+    pub exact: bool,
+}
+
+impl Config {
+    /// The microphone pipelines to run: one per `capture.devices` entry, or a
+    /// single "mic" source on `capture.device` (or the default device).
+    pub fn mic_sources(&self) -> Vec<MicSource> {
+        if !self.targets.mic_enabled {
+            return Vec::new();
+        }
+        if self.capture.devices.is_empty() {
+            return vec![MicSource {
+                device: self.capture.device.clone(),
+                source: self.targets.source_label("mic", &[]),
+                exact: false,
+            }];
+        }
+        self.capture
+            .devices
+            .iter()
+            .map(|device| {
+                let slug = sanitize_label(device).to_lowercase();
+                let default_source = if slug.is_empty() {
+                    "mic".to_string()
+                } else {
+                    format!("mic-{}", slug)
+                };
+                MicSource {
+                    device: Some(device.clone()),
+                    source: self
+                        .targets
+                        .source_label(&default_source, std::slice::from_ref(device)),
+                    exact: true,
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(default)]
 pub struct VadConfig {
+    /// When false, capture is recorded continuously and split only by
+    /// `output.max_file_duration_mins` (no speech detection).
+    pub enabled: bool,
     pub pre_speech_buffer_secs: f32,
     pub silence_threshold_secs: f32,
     pub speech_threshold: f32,
+    /// Track the ambient noise floor and only count speech well above it,
+    /// bridging dropouts shorter than a few hundred milliseconds.
+    pub adaptive: bool,
+    /// In adaptive mode, seconds of continuous speech needed before a segment
+    /// starts, so clicks and bumps don't make files (0 = start at once).
+    pub min_segment_secs: f32,
+    /// Speech resuming within this many seconds of a segment ending is
+    /// appended to the same file rather than starting a new one (0 = off).
+    /// Read at startup.
+    pub merge_gap_secs: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +135,55 @@ pub struct OutputConfig {
     pub directory: PathBuf,
     pub max_file_duration_mins: u32,
     pub organize_by_date: bool,
+    /// Embed source, capture time, and deskmic version in each recording (RIFF
+    /// LIST/INFO for WAV, Vorbis comments for FLAC).
+    pub embed_metadata: bool,
+    /// Identifies this machine when several share one (synced) recordings
+    /// directory: "" = single machine, "auto" = hostname, or a custom id.
+    pub machine_id: String,
+    /// Recording file format: "wav", "flac" (lossless, roughly half the size) or
+    /// "opus" (Ogg Opus at ~24 kbps for long-term archival, Windows only).
+    pub format: String,
+    /// Recording file name, with `{source}`, `{date}` (YYYY-MM-DD), `{time}`
+    /// (HH-MM-SS) and `{ext}` placeholders. A machine id goes before the extension.
+    pub filename_template: String,
+}
+
+impl OutputConfig {
+    /// File extension for new recordings.
+    pub fn extension(&self) -> &str {
+        match self.format.as_str() {
+            "flac" => "flac",
+            "opus" => "ogg",
+            _ => "wav",
+        }
+    }
+
+    /// This machine's id for naming recordings, state, and status files, or
+    /// `None` when `machine_id` is unset (single-machine layout).
+    pub fn machine_id(&self) -> Option<String> {
+        let raw = match self.machine_id.trim() {
+            "" => return None,
+            "auto" => sysinfo::System::host_name().unwrap_or_default(),
+            id => id.to_string(),
+        };
+        let id = sanitize_label(&raw).to_lowercase();
+        if id.is_empty() {
+            tracing::warn!("machine_id '{}' is not usable, ignoring", self.machine_id);
+            return None;
+        }
+        Some(id)
+    }
+}
+
+/// Insert `machine_id` before the extension of a shared file name, e.g.
+/// `.deskmic-state.json` → `.deskmic-state.laptop.json`. Unchanged for `None`.
+pub fn machine_scoped(file_name: &str, machine_id: Option<&str>) -> String {
+    match (machine_id, file_name.rsplit_once('.')) {
+        (Some(id), Some((stem, ext))) if !stem.is_empty() => format!("{}.{}.{}", stem, id, ext),
+        (Some(id), _) => format!("{}.{}", file_name, id),
+        (None, _) => file_name.to_string(),
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +191,201 @@ pub struct OutputConfig {
 pub struct TargetsConfig {
     pub processes: Vec<String>,
     pub mic_enabled: bool,
+    /// Also record everything playing on the default playback device (WASAPI
+    /// loopback) as the "system" source.
+    pub system_loopback: bool,
+    /// Seconds between checks for started/stopped target apps. On Windows,
+    /// app launches are also picked up within about a second between polls.
+    pub poll_interval_secs: u64,
+    /// How `processes` entries match running process names, ignoring case:
+    /// "exact" (whole name), "contains" (substring) or "glob" (`*` and `?`).
+    pub match_mode: String,
+    /// Seconds an app may stay silent before the next of its other processes
+    /// is probed for audio, in turn; recording moves to the one that produces
+    /// it. `0` captures only the main process.
+    pub audio_probe_secs: u64,
+    /// Friendly labels used as the recording file-name prefix, keyed by source
+    /// name ("mic", "teams") or process name ("ms-teams.exe").
+    pub labels: HashMap<String, String>,
+}
+
+impl TargetsConfig {
+    /// File-name prefix for a capture source.
+    ///
+    /// Looks up `source`, then each of `process_names` (case-insensitive), in
+    /// `labels`. The label is made filesystem-safe and underscore-free so
+    /// timestamp parsing of `<prefix>_<HH-MM-SS>.wav` keeps working. Falls
+    /// back to `source` when no usable label is configured.
+    pub fn source_label(&self, source: &str, process_names: &[String]) -> String {
+        let keys = std::iter::once(source).chain(process_names.iter().map(String::as_str));
+        for key in keys {
+            let label = self
+                .labels
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| sanitize_label(v));
+            match label {
+                Some(label) if !label.is_empty() => return label,
+                Some(_) => tracing::warn!("Ignoring empty label for '{}'", key),
+                None => {}
+            }
+        }
+        source.to_string()
+    }
+}
+
+/// Placeholders `output.filename_template` understands.
+pub const FILENAME_PLACEHOLDERS: &[&str] = &["source", "date", "time", "ext"];
+
+/// Default `output.filename_template`, e.g. `mic_14-30-05.wav`.
+pub const DEFAULT_FILENAME_TEMPLATE: &str = "{source}_{time}.{ext}";
+
+/// Replace each `{name}` in `template` with `value(name)`. Fails on the first
+/// placeholder `value` doesn't know, or an unclosed `{`.
+pub fn fill_template(
+    template: &str,
+    value: impl Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| format!("unclosed '{{' in {:?}", template))?;
+        let name = &after[..end];
+        let filled = value(name).ok_or_else(|| format!("unknown placeholder {{{}}}", name))?;
+        out.push_str(&filled);
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// What a recording's file name says about it, read back through the
+/// `output.filename_template` it was written with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordingName {
+    pub source: String,
+    /// Start time as (hour, minute, second).
+    pub time: (u32, u32, u32),
+    /// The `_{machine}` tag added when `output.machine_id` is set.
+    pub machine: Option<String>,
+}
+
+/// Parse `filename` (with its extension) as written by `template`. Names that
+/// don't fit are tried against `DEFAULT_FILENAME_TEMPLATE` too, so recordings
+/// made before the template was changed are still understood. A `.N` counter
+/// before the extension (added when two files start in the same second) is
+/// ignored.
+pub fn parse_recording_name(template: &str, filename: &str) -> Option<RecordingName> {
+    let (stem, _ext) = filename.rsplit_once('.')?;
+    let uncounted = stem
+        .rsplit_once('.')
+        .filter(|(_, counter)| !counter.is_empty() && counter.bytes().all(|b| b.is_ascii_digit()))
+        .map(|(stem, _)| stem);
+    uncounted
+        .and_then(|stem| parse_stem(template, stem))
+        .or_else(|| parse_stem(template, stem))
+}
+
+fn parse_stem(template: &str, stem: &str) -> Option<RecordingName> {
+    [template, DEFAULT_FILENAME_TEMPLATE]
+        .into_iter()
+        .filter_map(|template| template.strip_suffix(".{ext}"))
+        .find_map(|pattern| {
+            let untagged = match_name(pattern, stem, None);
+            untagged.or_else(|| {
+                let (rest, machine) = stem.rsplit_once('_')?;
+                match_name(pattern, rest, Some(machine))
+            })
+        })
+}
+
+fn match_name(pattern: &str, stem: &str, machine: Option<&str>) -> Option<RecordingName> {
+    let mut name = RecordingName {
+        source: String::new(),
+        time: (0, 0, 0),
+        machine: machine.map(str::to_string),
+    };
+    match_fields(pattern, stem, &mut name).then_some(name)
+}
+
+/// Match `stem` against `pattern`, filling `name` in. `{date}` and `{time}`
+/// have fixed widths; `{source}` is a run of letters, digits and `-` (see
+/// `sanitize_label`), so each length is tried in turn.
+fn match_fields(pattern: &str, stem: &str, name: &mut RecordingName) -> bool {
+    let Some(start) = pattern.find('{') else {
+        return pattern == stem;
+    };
+    let Some(rest) = stem.strip_prefix(&pattern[..start]) else {
+        return false;
+    };
+    let after = &pattern[start + 1..];
+    let Some(end) = after.find('}') else {
+        return false;
+    };
+    let (placeholder, pattern) = (&after[..end], &after[end + 1..]);
+    let fixed = |width: usize| rest.get(..width).map(|field| (field, &rest[width..]));
+    match placeholder {
+        "time" => {
+            let Some((field, rest)) = fixed(8) else {
+                return false;
+            };
+            match parse_time(field) {
+                Some(time) => {
+                    name.time = time;
+                    match_fields(pattern, rest, name)
+                }
+                None => false,
+            }
+        }
+        "date" => match fixed(10) {
+            Some((field, rest)) if chrono::NaiveDate::parse_from_str(field, "%Y-%m-%d").is_ok() => {
+                match_fields(pattern, rest, name)
+            }
+            _ => false,
+        },
+        "source" => {
+            let run = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+                .unwrap_or(rest.len());
+            (1..=run).rev().any(|len| {
+                name.source = rest[..len].to_string();
+                match_fields(pattern, &rest[len..], name)
+            })
+        }
+        _ => false,
+    }
+}
+
+/// Parse `HH-MM-SS`.
+fn parse_time(field: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = field.split('-').map(|part| match part.len() {
+        2 => part.parse::<u32>().ok(),
+        _ => None,
+    });
+    let (hour, minute, second) = (parts.next()??, parts.next()??, parts.next()??);
+    let valid = parts.next().is_none() && hour < 24 && minute < 60 && second < 60;
+    valid.then_some((hour, minute, second))
+}
+
+/// Replace anything but ASCII letters, digits and '-' with '-', and trim
+/// leading/trailing dashes.
+pub fn sanitize_label(label: &str) -> String {
+    let cleaned: String = label
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    cleaned.trim_matches('-').to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +394,12 @@ pub struct StorageConfig {
     pub retention_days: u32,
     pub cleanup_interval_hours: u32,
     pub max_disk_usage_gb: Option<f64>,
+    /// Run cleanup right away, rather than on the next scheduled run, when
+    /// free space on the recordings drive drops below this many GB.
+    pub min_free_gb: Option<f64>,
+    /// Keep date folders past `retention_days`, or over `max_disk_usage_gb`,
+    /// while they still hold recordings that haven't been transcribed.
+    pub require_transcribed_before_delete: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,6 +407,19 @@ pub struct StorageConfig {
 pub struct TranscriptionConfig {
     pub backend: String,
     pub model: String,
+    /// Where transcripts are stored: "jsonl" (one file per day) or "sqlite".
+    pub store: String,
+    /// Also write `.srt` and `.vtt` subtitles per recording under
+    /// `transcripts/<date>/`.
+    pub emit_subtitles: bool,
+    /// Threads for the local Whisper backend (0 = one per physical core).
+    pub whisper_threads: u32,
+    /// Recordings transcribed at once (1-16). With the local backend the
+    /// Whisper threads are split between them.
+    pub concurrency: u32,
+    /// Spoken language for the local Whisper backend, e.g. "en" or "de", or
+    /// "auto" to let Whisper detect it.
+    pub language: String,
     pub azure: AzureConfig,
     pub idle_watch: IdleWatchConfig,
 }
@@ -73,6 +430,13 @@ pub struct AzureConfig {
     pub endpoint: String,
     pub api_key: String,
     pub deployment: String,
+    /// Maximum transcription requests per minute (0 = unlimited).
+    pub requests_per_minute: u32,
+    /// Maximum transcription requests in flight at once (0 = unlimited).
+    pub max_concurrent_requests: u32,
+    /// `api-version` query parameter for Azure OpenAI requests (transcription,
+    /// summarization, and search embeddings).
+    pub api_version: String,
 }
 
 impl fmt::Debug for AzureConfig {
@@ -81,6 +445,9 @@ impl fmt::Debug for AzureConfig {
             .field("endpoint", &self.endpoint)
             .field("api_key", &"[REDACTED]")
             .field("deployment", &self.deployment)
+            .field("requests_per_minute", &self.requests_per_minute)
+            .field("max_concurrent_requests", &self.max_concurrent_requests)
+            .field("api_version", &self.api_version)
             .finish()
     }
 }
@@ -95,30 +462,118 @@ pub struct IdleWatchConfig {
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct SummarizationConfig {
+    /// LLM that writes summaries: "azure" (Azure OpenAI, using
+    /// `[transcription.azure]` and `deployment`), "openai", or "anthropic"
+    /// (using `model` and `api_key`).
+    pub provider: String,
     /// Azure OpenAI deployment name for chat completions (e.g. "gpt-4o").
     pub deployment: String,
+    /// Model name for the "openai" and "anthropic" providers.
+    pub model: String,
+    /// API key for the "openai" and "anthropic" providers (or set
+    /// DESKMIC_OPENAI_KEY / DESKMIC_ANTHROPIC_KEY).
+    pub api_key: String,
     /// ACS Communication Services endpoint for sending email.
     pub acs_endpoint: String,
     /// ACS access key (or set DESKMIC_ACS_KEY environment variable).
     pub acs_api_key: String,
     /// Sender email address from the ACS Email verified domain.
     pub sender_address: String,
-    /// Recipient email address for summary delivery.
+    /// Recipient email address for summary delivery. Several recipients can
+    /// be given comma-separated (see `recipients`).
     pub recipient_address: String,
     /// Custom system prompt for summarization. Use {date_label} as placeholder.
     /// Leave empty to use the built-in default prompt.
     pub system_prompt: String,
+    /// Hours between scheduled rolling summary updates (`summarize --rolling`),
+    /// registered by `deskmic setup`. 0 disables the scheduled task.
+    pub rolling_interval_hours: u32,
+    /// Custom prompt for the combine pass that merges per-chunk summaries of a
+    /// large day. Use {date_label} as placeholder. Leave empty for the default.
+    pub combine_prompt: String,
+    /// Include the previous day's saved summary as context for daily summaries,
+    /// so carried-over items and follow-ups are picked up.
+    pub include_previous: bool,
+    /// Hours of the day (0-23) whose transcripts are left out of summaries.
+    pub exclude_hours: Vec<u32>,
+    /// Transcript sources (e.g. "system") left out of summaries.
+    pub exclude_sources: Vec<String>,
+    /// Extra transcripts to treat as noise, on top of the built-in list (e.g.
+    /// "[BLANK_AUDIO]"). Each matches a whole transcript, or is a `/regex/`.
+    pub noise_patterns: Vec<String>,
+    /// Format of the summary file saved locally: "markdown" (`.md`), "html"
+    /// (`.html`, the email layout), or "text" (`.txt`, Markdown stripped).
+    pub output_format: String,
+    /// How summaries are delivered: "acs" (Azure Communication Services email),
+    /// "smtp" (see `smtp`), or "webhook" (JSON POST to `webhook_url`).
+    pub transport: String,
+    /// URL that receives summaries: the transport when `transport = "webhook"`,
+    /// otherwise an extra channel alongside email.
+    pub webhook_url: String,
+    /// Webhook payload: "json" (`subject`/`text`/`html`) or "slack" (Slack
+    /// incoming-webhook blocks).
+    pub webhook_format: String,
+    /// SMTP server settings used when `transport = "smtp"`.
+    pub smtp: SmtpConfig,
+    /// Notes vault (Obsidian, Logseq, ...) whose daily notes (`<date>.md`)
+    /// get the summary appended. Unset to disable.
+    pub notes_vault_dir: Option<PathBuf>,
+    /// Heading the summary is placed under in the daily note.
+    pub notes_heading: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmtpConfig {
+    /// SMTP server hostname (e.g. "smtp.gmail.com").
+    pub host: String,
+    /// SMTP server port (587 for STARTTLS, 465 for implicit TLS).
+    pub port: u16,
+    /// TLS mode: "starttls", "tls" (implicit), or "none".
+    pub tls: String,
+    /// SMTP username. Leave empty to send without authentication.
+    pub username: String,
+    /// SMTP password (or set DESKMIC_SMTP_PASSWORD environment variable).
+    pub password: String,
+}
+
+impl fmt::Debug for SmtpConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SmtpConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("tls", &self.tls)
+            .field("username", &self.username)
+            .field("password", &"[REDACTED]")
+            .finish()
+    }
 }
 
 impl fmt::Debug for SummarizationConfig {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("SummarizationConfig")
+            .field("provider", &self.provider)
             .field("deployment", &self.deployment)
+            .field("model", &self.model)
+            .field("api_key", &"[REDACTED]")
             .field("acs_endpoint", &self.acs_endpoint)
             .field("acs_api_key", &"[REDACTED]")
             .field("sender_address", &self.sender_address)
             .field("recipient_address", &self.recipient_address)
             .field("system_prompt", &self.system_prompt)
+            .field("combine_prompt", &self.combine_prompt)
+            .field("rolling_interval_hours", &self.rolling_interval_hours)
+            .field("include_previous", &self.include_previous)
+            .field("exclude_hours", &self.exclude_hours)
+            .field("exclude_sources", &self.exclude_sources)
+            .field("noise_patterns", &self.noise_patterns)
+            .field("output_format", &self.output_format)
+            .field("transport", &self.transport)
+            .field("webhook_url", &"[REDACTED]")
+            .field("webhook_format", &self.webhook_format)
+            .field("smtp", &self.smtp)
+            .field("notes_vault_dir", &self.notes_vault_dir)
+            .field("notes_heading", &self.notes_heading)
             .finish()
     }
 }
@@ -127,13 +582,20 @@ impl fmt::Debug for SummarizationConfig {
 #[serde(default)]
 pub struct MonitoringConfig {
     /// Minutes without a new WAV file before firing a toast notification.
+    /// Not reported when nothing audible was captured in that time.
     pub recording_gap_alert_mins: u32,
+    /// Seconds between pipeline thread health checks.
+    pub watchdog_interval_secs: u64,
+    /// Seconds between checks for a recording gap.
+    pub gap_check_interval_secs: u64,
 }
 
 impl Default for MonitoringConfig {
     fn default() -> Self {
         Self {
             recording_gap_alert_mins: 30,
+            watchdog_interval_secs: 10,
+            gap_check_interval_secs: 60,
         }
     }
 }
@@ -159,11 +621,97 @@ impl Default for SearchConfig {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotkeysConfig {
+    /// Global hotkey that toggles pause/resume, e.g. "Ctrl+Alt+P".
+    /// Empty disables it.
+    pub pause_toggle: String,
+    /// Global hotkey that saves the last `capture.save_clip_secs` of audio.
+    /// Empty disables it.
+    pub save_clip: String,
+}
+
+impl Default for HotkeysConfig {
+    fn default() -> Self {
+        Self {
+            pause_toggle: "Ctrl+Alt+P".to_string(),
+            save_clip: "Ctrl+Alt+S".to_string(),
+        }
+    }
+}
+
+/// Hotkey modifier flags, with the same values as Win32 `MOD_*`.
+pub const MOD_ALT: u32 = 0x0001;
+pub const MOD_CONTROL: u32 = 0x0002;
+pub const MOD_SHIFT: u32 = 0x0004;
+pub const MOD_WIN: u32 = 0x0008;
+
+/// A parsed key combination: `MOD_*` flags plus a Win32 virtual-key code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hotkey {
+    pub modifiers: u32,
+    pub vk: u32,
+}
+
+/// Parse a combination such as "Ctrl+Alt+P" or "Win+Shift+F9".
+///
+/// Modifiers are Ctrl (or Control), Alt, Shift and Win; the key is a letter,
+/// a digit or F1-F24. Case and spaces around `+` are ignored. At least one
+/// modifier is required so a plain key press is never swallowed.
+pub fn parse_hotkey(spec: &str) -> anyhow::Result<Hotkey> {
+    let mut modifiers = 0;
+    let mut vk = None;
+    for part in spec.split('+').map(str::trim) {
+        let upper = part.to_ascii_uppercase();
+        let flag = match upper.as_str() {
+            "CTRL" | "CONTROL" => MOD_CONTROL,
+            "ALT" => MOD_ALT,
+            "SHIFT" => MOD_SHIFT,
+            "WIN" => MOD_WIN,
+            _ => {
+                if vk.is_some() {
+                    anyhow::bail!("more than one key in {:?}", spec);
+                }
+                vk = Some(
+                    key_code(&upper)
+                        .ok_or_else(|| anyhow::anyhow!("unknown key {:?} in {:?}", part, spec))?,
+                );
+                continue;
+            }
+        };
+        if modifiers & flag != 0 {
+            anyhow::bail!("modifier {:?} repeated in {:?}", part, spec);
+        }
+        modifiers |= flag;
+    }
+
+    let vk = vk.ok_or_else(|| anyhow::anyhow!("no key in {:?}", spec))?;
+    if modifiers == 0 {
+        anyhow::bail!("{:?} needs at least one of Ctrl, Alt, Shift or Win", spec);
+    }
+    Ok(Hotkey { modifiers, vk })
+}
+
+/// Virtual-key code for an upper-cased key name.
+fn key_code(name: &str) -> Option<u32> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        // VK_0..VK_9 and VK_A..VK_Z match their ASCII codes.
+        return c.is_ascii_alphanumeric().then_some(c as u32);
+    }
+    match name.strip_prefix('F')?.parse::<u32>() {
+        Ok(n @ 1..=24) => Some(0x70 + n - 1), // VK_F1 = 0x70
+        _ => None,
+    }
+}
+
 // --- Default implementations ---
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             capture: CaptureConfig::default(),
             vad: VadConfig::default(),
             output: OutputConfig::default(),
@@ -173,6 +721,7 @@ impl Default for Config {
             summarization: SummarizationConfig::default(),
             monitoring: MonitoringConfig::default(),
             search: SearchConfig::default(),
+            hotkeys: HotkeysConfig::default(),
         }
     }
 }
@@ -183,6 +732,12 @@ impl Default for CaptureConfig {
             sample_rate: 16000,
             bit_depth: 16,
             channels: 1,
+            pause_when_locked: false,
+            device: None,
+            devices: Vec::new(),
+            gain_db: 0.0,
+            event_timeout_ms: 100,
+            save_clip_secs: 30,
         }
     }
 }
@@ -190,9 +745,13 @@ impl Default for CaptureConfig {
 impl Default for VadConfig {
     fn default() -> Self {
         Self {
+            enabled: true,
             pre_speech_buffer_secs: 5.0,
             silence_threshold_secs: 3.0,
             speech_threshold: 0.5,
+            adaptive: false,
+            min_segment_secs: 0.25,
+            merge_gap_secs: 0.0,
         }
     }
 }
@@ -207,6 +766,10 @@ impl Default for OutputConfig {
             directory,
             max_file_duration_mins: 30,
             organize_by_date: true,
+            embed_metadata: false,
+            machine_id: String::new(),
+            format: "wav".to_string(),
+            filename_template: DEFAULT_FILENAME_TEMPLATE.to_string(),
         }
     }
 }
@@ -216,6 +779,11 @@ impl Default for TargetsConfig {
         Self {
             processes: vec!["ms-teams.exe".to_string()],
             mic_enabled: true,
+            system_loopback: false,
+            poll_interval_secs: 5,
+            match_mode: "exact".to_string(),
+            audio_probe_secs: 10,
+            labels: HashMap::new(),
         }
     }
 }
@@ -226,6 +794,8 @@ impl Default for StorageConfig {
             retention_days: 30,
             cleanup_interval_hours: 6,
             max_disk_usage_gb: None,
+            min_free_gb: None,
+            require_transcribed_before_delete: true,
         }
     }
 }
@@ -235,6 +805,11 @@ impl Default for TranscriptionConfig {
         Self {
             backend: "local".to_string(),
             model: "base.en".to_string(),
+            store: "jsonl".to_string(),
+            emit_subtitles: false,
+            whisper_threads: 0,
+            concurrency: 1,
+            language: "en".to_string(),
             azure: AzureConfig::default(),
             idle_watch: IdleWatchConfig::default(),
         }
@@ -247,6 +822,9 @@ impl Default for AzureConfig {
             endpoint: String::new(),
             api_key: String::new(),
             deployment: String::new(),
+            requests_per_minute: 0,
+            max_concurrent_requests: 1,
+            api_version: "2024-06-01".to_string(),
         }
     }
 }
@@ -260,15 +838,80 @@ impl Default for IdleWatchConfig {
     }
 }
 
+impl SummarizationConfig {
+    /// The addresses in `recipient_address` (see `split_addresses`).
+    pub fn recipients(&self) -> Vec<String> {
+        split_addresses(&self.recipient_address)
+    }
+}
+
+/// One `summarization.noise_patterns` entry.
+#[derive(Debug)]
+pub enum NoisePattern {
+    /// A phrase matched against the whole transcript.
+    Exact(String),
+    /// Written as `/regex/`; searched for anywhere, case-insensitively.
+    Regex(regex::Regex),
+}
+
+impl NoisePattern {
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        match pattern.strip_prefix('/').and_then(|p| p.strip_suffix('/')) {
+            Some(re) => regex::Regex::new(&format!("(?i){}", re))
+                .map(Self::Regex)
+                .map_err(|e| format!("Invalid noise pattern {}: {}", pattern, e)),
+            None => Ok(Self::Exact(pattern.to_string())),
+        }
+    }
+}
+
+/// Split a recipient list on commas and semicolons, dropping blanks. Shared by
+/// both email transports (through `recipients`) and the setup wizard.
+pub fn split_addresses(list: &str) -> Vec<String> {
+    list.split([',', ';'])
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 impl Default for SummarizationConfig {
     fn default() -> Self {
         Self {
+            provider: "azure".to_string(),
             deployment: String::new(),
+            model: String::new(),
+            api_key: String::new(),
             acs_endpoint: String::new(),
             acs_api_key: String::new(),
             sender_address: String::new(),
             recipient_address: String::new(),
             system_prompt: String::new(),
+            combine_prompt: String::new(),
+            rolling_interval_hours: 0,
+            include_previous: false,
+            exclude_hours: Vec::new(),
+            exclude_sources: Vec::new(),
+            noise_patterns: Vec::new(),
+            output_format: "markdown".to_string(),
+            transport: "acs".to_string(),
+            webhook_url: String::new(),
+            webhook_format: "json".to_string(),
+            smtp: SmtpConfig::default(),
+            notes_vault_dir: None,
+            notes_heading: "## deskmic summary".to_string(),
+        }
+    }
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: 587,
+            tls: "starttls".to_string(),
+            username: String::new(),
+            password: String::new(),
         }
     }
 }
@@ -277,14 +920,20 @@ impl Default for SummarizationConfig {
 
 impl Config {
     /// Load config and return the resolved file path (if any).
+    ///
+    /// `DESKMIC_*` environment overrides are applied on top of whichever
+    /// source was used; see [`Config::apply_env_overrides`].
     pub fn load_with_path(path: Option<&Path>) -> anyhow::Result<(Self, Option<PathBuf>)> {
+        let (mut config, resolved) = Self::load_from_disk(path)?;
+        config.apply_env_overrides()?;
+        Ok((config, resolved))
+    }
+
+    /// Read the first config file found (or defaults), without env overrides.
+    fn load_from_disk(path: Option<&Path>) -> anyhow::Result<(Self, Option<PathBuf>)> {
         // 1. Check explicit path
         if let Some(p) = path {
-            let content = std::fs::read_to_string(p).map_err(|e| {
-                anyhow::anyhow!("Failed to read config file {}: {}", p.display(), e)
-            })?;
-            let config: Config = toml::from_str(&content)?;
-            return Ok((config, Some(p.to_path_buf())));
+            return Ok((Self::read_file(p)?, Some(p.to_path_buf())));
         }
 
         // 2. Check beside the executable
@@ -292,9 +941,7 @@ impl Config {
             let beside_exe = exe_path.parent().map(|p| p.join("deskmic.toml"));
             if let Some(p) = beside_exe {
                 if p.exists() {
-                    let content = std::fs::read_to_string(&p)?;
-                    let config: Config = toml::from_str(&content)?;
-                    return Ok((config, Some(p)));
+                    return Ok((Self::read_file(&p)?, Some(p)));
                 }
             }
         }
@@ -303,9 +950,7 @@ impl Config {
         if let Some(config_dir) = dirs::config_dir() {
             let platform_config = config_dir.join("deskmic").join("config.toml");
             if platform_config.exists() {
-                let content = std::fs::read_to_string(&platform_config)?;
-                let config: Config = toml::from_str(&content)?;
-                return Ok((config, Some(platform_config)));
+                return Ok((Self::read_file(&platform_config)?, Some(platform_config)));
             }
         }
 
@@ -314,11 +959,300 @@ impl Config {
         Ok((Config::default(), None))
     }
 
+    /// Read the config file at `path`. A file from an older format version is
+    /// migrated, then rewritten so it also gets the settings added since;
+    /// failing to rewrite it is only a warning. The original is kept as
+    /// `<path>.v<old version>.bak`, so it can't overwrite setup's `<path>.bak`.
+    fn read_file(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read config file {}: {}", path.display(), e))?;
+        let (config, changes) = Self::parse(&content)?;
+        if changes.is_empty() {
+            return Ok(config);
+        }
+
+        tracing::info!(
+            "Migrating config {} to version {}:",
+            path.display(),
+            CONFIG_VERSION
+        );
+        for change in &changes {
+            tracing::info!("  {}", change);
+        }
+        let from_version = table_version(&toml::from_str(&content)?)?;
+        let backup = PathBuf::from(format!("{}.v{}.bak", path.display(), from_version));
+        let rewritten = config.to_commented_toml().and_then(|text| {
+            std::fs::write(&backup, &content)?;
+            std::fs::write(path, text)?;
+            Ok(())
+        });
+        match rewritten {
+            Ok(()) => tracing::info!("Previous config saved as {}", backup.display()),
+            Err(e) => tracing::warn!(
+                "Could not rewrite migrated config {}: {:#}",
+                path.display(),
+                e
+            ),
+        }
+        Ok(config)
+    }
+
+    /// Parse a config file's text, migrating it if it is from an older
+    /// format version. Also returns a description of each migration change,
+    /// empty when the file is current.
+    pub fn parse(text: &str) -> anyhow::Result<(Self, Vec<String>)> {
+        let mut table: toml::Table = toml::from_str(text)?;
+        let from_version = table_version(&table)?;
+        if from_version >= CONFIG_VERSION {
+            if from_version > CONFIG_VERSION {
+                tracing::warn!(
+                    "Config is version {}, newer than this deskmic ({}); \
+                     settings it doesn't know are ignored",
+                    from_version,
+                    CONFIG_VERSION
+                );
+            }
+            return Ok((table.try_into()?, Vec::new()));
+        }
+
+        let mut changes = rename_keys(&mut table, from_version, RENAMED_KEYS);
+        let mut config: Config = table.clone().try_into()?;
+        changes.extend(config.migrate(from_version));
+        added_settings("", &toml::Table::try_from(&config)?, &table, &mut changes);
+        Ok((config, changes))
+    }
+
+    /// Apply the value changes between format `from_version` and
+    /// `CONFIG_VERSION` (renames are done on the raw file, see
+    /// `RENAMED_KEYS`). Returns a description of each change.
+    pub fn migrate(&mut self, from_version: u32) -> Vec<String> {
+        let mut changes = Vec::new();
+        // Version 1 only added `version` itself: older files just lack the
+        // settings added since, which take their defaults.
+        if from_version < CONFIG_VERSION {
+            changes.push(format!("version {} -> {}", from_version, CONFIG_VERSION));
+            self.version = CONFIG_VERSION;
+        }
+        changes
+    }
+
     /// Load config (without tracking the resolved path). Kept for backward compat.
     pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
         Self::load_with_path(path).map(|(config, _)| config)
     }
 
+    /// Override config fields from `DESKMIC_*` environment variables.
+    ///
+    /// The variable name is `DESKMIC_` followed by the field's TOML path in
+    /// upper case, with each `.` replaced by `_`:
+    ///
+    /// - `output.directory` → `DESKMIC_OUTPUT_DIRECTORY`
+    /// - `capture.sample_rate` → `DESKMIC_CAPTURE_SAMPLE_RATE`
+    /// - `transcription.azure.endpoint` → `DESKMIC_TRANSCRIPTION_AZURE_ENDPOINT`
+    /// - `targets.labels.mic` → `DESKMIC_TARGETS_LABELS_MIC`
+    ///
+    /// Values are parsed according to the field's type: numbers, `true`/`false`
+    /// (also `1`/`0`, `yes`/`no`), and lists as comma-separated items or a TOML
+    /// array. Variables that don't name a config section are ignored, so
+    /// `DESKMIC_AZURE_KEY` and friends keep their existing meaning.
+    pub fn apply_env_overrides(&mut self) -> anyhow::Result<()> {
+        self.apply_overrides(std::env::vars())
+    }
+
+    /// Apply `(name, value)` overrides; split out from
+    /// [`Config::apply_env_overrides`] so tests don't touch the process env.
+    fn apply_overrides(
+        &mut self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> anyhow::Result<()> {
+        let mut root = toml::Table::try_from(&*self)?;
+        let mut applied = false;
+
+        for (name, raw) in vars {
+            let Some(key) = name.strip_prefix("DESKMIC_") else {
+                continue;
+            };
+            // Every field lives in a section, so the top level must match a table.
+            let section = root.iter_mut().find_map(|(k, v)| {
+                let rest = key.strip_prefix(&k.to_uppercase())?.strip_prefix('_')?;
+                Some((k.clone(), v.as_table_mut()?, rest))
+            });
+            let Some((section, table, rest)) = section else {
+                continue;
+            };
+            let field = override_field(table, rest, &raw)
+                .map_err(|e| anyhow::anyhow!("Invalid value for {}: {}", name, e))?;
+            tracing::info!("Config override: {}.{} (from {})", section, field, name);
+            applied = true;
+        }
+
+        if applied {
+            *self = root
+                .try_into()
+                .map_err(|e| anyhow::anyhow!("Invalid environment override: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Check settings that parse fine but would fail (or misbehave) at runtime.
+    /// Returns every problem found, not just the first.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if !matches!(self.capture.sample_rate, 8000 | 16000) {
+            errors.push(format!(
+                "capture.sample_rate must be 8000 or 16000 (got {})",
+                self.capture.sample_rate
+            ));
+        }
+        if !(-20.0..=40.0).contains(&self.capture.gain_db) {
+            errors.push(format!(
+                "capture.gain_db must be between -20 and 40 (got {})",
+                self.capture.gain_db
+            ));
+        }
+        if !(10..=1000).contains(&self.capture.event_timeout_ms) {
+            errors.push(format!(
+                "capture.event_timeout_ms must be between 10 and 1000 (got {})",
+                self.capture.event_timeout_ms
+            ));
+        }
+        if self.capture.save_clip_secs > 600 {
+            errors.push(format!(
+                "capture.save_clip_secs must be at most 600 (got {})",
+                self.capture.save_clip_secs
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.vad.speech_threshold) {
+            errors.push(format!(
+                "vad.speech_threshold must be between 0.0 and 1.0 (got {})",
+                self.vad.speech_threshold
+            ));
+        }
+        let positive = |v: f32| v.is_finite() && v > 0.0;
+        if !positive(self.vad.pre_speech_buffer_secs) {
+            errors.push(format!(
+                "vad.pre_speech_buffer_secs must be positive (got {})",
+                self.vad.pre_speech_buffer_secs
+            ));
+        }
+        if !positive(self.vad.silence_threshold_secs) {
+            errors.push(format!(
+                "vad.silence_threshold_secs must be positive (got {})",
+                self.vad.silence_threshold_secs
+            ));
+        }
+        if !(0.0..=self.vad.pre_speech_buffer_secs).contains(&self.vad.min_segment_secs) {
+            errors.push(format!(
+                "vad.min_segment_secs must be between 0 and vad.pre_speech_buffer_secs (got {})",
+                self.vad.min_segment_secs
+            ));
+        }
+        if !(0.0..=60.0).contains(&self.vad.merge_gap_secs) {
+            errors.push(format!(
+                "vad.merge_gap_secs must be between 0 and 60 (got {})",
+                self.vad.merge_gap_secs
+            ));
+        }
+        if !matches!(self.output.format.as_str(), "wav" | "flac" | "opus") {
+            errors.push(format!(
+                "output.format must be \"wav\", \"flac\" or \"opus\" (got {:?})",
+                self.output.format
+            ));
+        }
+        let template = &self.output.filename_template;
+        let known = |name: &str| FILENAME_PLACEHOLDERS.contains(&name).then(String::new);
+        if let Err(e) = fill_template(template, known) {
+            errors.push(format!("output.filename_template: {}", e));
+        } else if !template.contains("{source}") || !template.contains("{time}") {
+            // Without both, two sources (or segments) could map to one name.
+            errors.push(format!(
+                "output.filename_template must contain {{source}} and {{time}} (got {:?})",
+                template
+            ));
+        } else if !template.ends_with(".{ext}") || template.contains(['/', '\\']) {
+            errors.push(format!(
+                "output.filename_template must end with .{{ext}} and can't contain path \
+                 separators (got {:?})",
+                template
+            ));
+        }
+        if self.targets.poll_interval_secs == 0 {
+            errors.push("targets.poll_interval_secs must be at least 1".to_string());
+        }
+        if !matches!(
+            self.targets.match_mode.as_str(),
+            "exact" | "contains" | "glob"
+        ) {
+            errors.push(format!(
+                "targets.match_mode must be \"exact\", \"contains\" or \"glob\" (got {:?})",
+                self.targets.match_mode
+            ));
+        }
+        if self.storage.retention_days == 0 {
+            errors.push("storage.retention_days must be at least 1".to_string());
+        }
+        if let Some(gb) = self.storage.min_free_gb.filter(|gb| *gb <= 0.0) {
+            errors.push(format!("storage.min_free_gb must be positive (got {})", gb));
+        }
+        if self.monitoring.watchdog_interval_secs == 0 {
+            errors.push("monitoring.watchdog_interval_secs must be at least 1".to_string());
+        }
+        if self.monitoring.gap_check_interval_secs == 0 {
+            errors.push("monitoring.gap_check_interval_secs must be at least 1".to_string());
+        }
+        if !(1..=16).contains(&self.transcription.concurrency) {
+            errors.push(format!(
+                "transcription.concurrency must be between 1 and 16 (got {})",
+                self.transcription.concurrency
+            ));
+        }
+        if self.transcription.backend == "azure" {
+            let endpoint = &self.transcription.azure.endpoint;
+            match url::Url::parse(endpoint) {
+                Ok(u) if u.scheme() == "https" && u.host_str().is_some() => {}
+                _ => errors.push(format!(
+                    "transcription.azure.endpoint must be an https URL when backend = \"azure\" \
+                     (got {:?})",
+                    endpoint
+                )),
+            }
+        }
+
+        for pattern in &self.summarization.noise_patterns {
+            if let Err(e) = NoisePattern::parse(pattern) {
+                errors.push(format!("summarization.noise_patterns: {}", e));
+            }
+        }
+        let pause_toggle = &self.hotkeys.pause_toggle;
+        let pause_key = match parse_hotkey(pause_toggle) {
+            Ok(key) => Some(key),
+            Err(e) => {
+                if !pause_toggle.is_empty() {
+                    errors.push(format!("hotkeys.pause_toggle: {}", e));
+                }
+                None
+            }
+        };
+        let save_clip = &self.hotkeys.save_clip;
+        if !save_clip.is_empty() {
+            match parse_hotkey(save_clip) {
+                Ok(key) if Some(key) == pause_key => errors.push(format!(
+                    "hotkeys.save_clip: {:?} is already hotkeys.pause_toggle",
+                    save_clip
+                )),
+                Ok(_) => {}
+                Err(e) => errors.push(format!("hotkeys.save_clip: {}", e)),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Generate a default config file with all fields and inline documentation.
     pub fn generate_default_commented() -> String {
         let default_output_dir = dirs::data_local_dir()
@@ -330,7 +1264,12 @@ impl Config {
         format!(
             r#"# deskmic configuration
 # Edit this file to customize recording, transcription, and storage settings.
-# Restart deskmic after saving changes for them to take effect.
+# [vad], capture.gain_db, storage.retention_days and
+# monitoring.recording_gap_alert_mins are picked up while deskmic is running;
+# other changes need a restart.
+
+# Format version of this file, used to migrate it when settings change.
+version = {version}
 
 [capture]
 # Audio capture sample rate in Hz. 16000 is required for VAD compatibility.
@@ -339,8 +1278,28 @@ sample_rate = 16000
 bit_depth = 16
 # Number of audio channels (1 = mono).
 channels = 1
+# Pause recording while the workstation is locked (Windows only).
+pause_when_locked = false
+# Microphone to record from, by name (case-insensitive substring) or ID.
+# Run `deskmic devices` to list them. Unset = Windows default recording device.
+# device = "Headset"
+# Record several microphones as separate sources (mic-headset, mic-desk, ...).
+# devices = ["Headset", "Desk"]
+# Input gain in dB for quiet microphones (-20 to 40). Applied before speech
+# detection and to the recordings; loud peaks are clipped.
+gain_db = 0.0
+# Longest wait for audio from a device, in milliseconds (10 to 1000). Shorter
+# waits let deskmic stop faster; a device silent for a full second is reopened.
+event_timeout_ms = 100
+# Seconds of recent audio kept from every source, speech or not, so "Save Last
+# 30s" in the tray (or hotkeys.save_clip) can keep something VAD skipped
+# (0 to 600, 0 = off).
+save_clip_secs = 30
 
 [vad]
+# Set to false to record continuously (e.g. a whole lecture), split only by
+# output.max_file_duration_mins instead of at pauses in speech.
+enabled = true
 # Seconds of audio to keep in the ring buffer before speech is detected.
 # This "pre-roll" ensures you don't lose the beginning of a sentence.
 pre_speech_buffer_secs = 5.0
@@ -350,37 +1309,113 @@ silence_threshold_secs = 3.0
 # Voice activity detection confidence threshold (0.0 to 1.0).
 # Lower = more sensitive (catches quiet speech), higher = fewer false positives.
 speech_threshold = 0.5
+# Adapt to the room: follow the background noise level and only count speech
+# clearly above it, and don't let brief dropouts mid-sentence count as silence.
+adaptive = false
+# With adaptive = true, seconds of continuous speech before a recording starts,
+# so clicks and bumps are ignored (the pre-roll still keeps the start).
+min_segment_secs = 0.25
+# Append speech that resumes within this many seconds of a segment ending to
+# the same file, so brief pauses don't split a conversation (0 = off). Gaps up
+# to pre_speech_buffer_secs keep the real audio; longer ones are padded with
+# silence. Needs a restart.
+merge_gap_secs = 0.0
 
 [output]
-# Directory where WAV recordings are saved.
+# Directory where recordings are saved.
 directory = "{output_dir}"
 # Maximum duration of a single recording file in minutes.
 # Recordings are split into new files when this limit is reached.
 max_file_duration_mins = 30
 # Organize recordings into date-based subdirectories (YYYY-MM-DD).
 organize_by_date = true
+# Recording format: "wav", "flac" (lossless, roughly half the disk space) or
+# "opus" (Ogg Opus at ~24 kbps, a fraction of the space, for long-term archival).
+format = "wav"
+# Embed source, capture time, and deskmic version as WAV INFO tags (or FLAC
+# Vorbis comments).
+embed_metadata = false
+# Recording file name. Placeholders: {{source}}, {{date}} (YYYY-MM-DD),
+# {{time}} (HH-MM-SS), {{ext}}. Must contain {{source}} and {{time}} and end
+# with .{{ext}}. Summaries, search and transcription read names back with this
+# template (falling back to the default layout for older files).
+filename_template = "{{source}}_{{time}}.{{ext}}"
+# Set when several machines sync recordings into the same directory: "auto" uses
+# the hostname, or give a custom id. Recordings, transcripts, and state files get
+# the id in their names so machines don't overwrite each other's files.
+# Use the default "jsonl" transcript store when sharing a directory.
+# machine_id = "auto"
 
 [targets]
 # List of process names to capture audio from (application loopback).
+# Each app is recorded concurrently as its own source, e.g.
+# ["ms-teams.exe", "zoom.exe"] records "teams" and "zoom" files.
 processes = ["ms-teams.exe"]
 # Whether to also capture from the default microphone.
 mic_enabled = true
+# Also record all desktop audio (default playback device) as the "system"
+# source, e.g. for browser-based meetings.
+system_loopback = false
+# Seconds between checks for apps starting or exiting. On Windows, app launches
+# are also detected within about a second, so short calls aren't missed.
+poll_interval_secs = 5
+# How process names above are matched, ignoring case: "exact" (the whole name),
+# "contains" (e.g. "teams" finds ms-teams.exe and Teams.exe) or "glob" ("zoom*").
+match_mode = "exact"
+# Some apps (like new Teams) play call audio from a helper process. While an
+# app has been silent for this many seconds, its child processes are probed in
+# turn, and recording moves to the one that produces audio. 0 disables.
+audio_probe_secs = 10
+
+[targets.labels]
+# Friendly file-name prefixes, keyed by source ("mic", "teams", "system") or
+# process name.
+# Labels are made filesystem-safe; underscores and spaces become '-'.
+# mic = "Desk"
+# "ms-teams.exe" = "Meetings"
 
 [storage]
 # Number of days to keep recordings before automatic cleanup.
 retention_days = 30
 # How often (in hours) to run the cleanup job.
 cleanup_interval_hours = 6
-# Maximum total disk usage in GB. Oldest files are deleted first.
+# Maximum total disk usage in GB, counting transcripts and summaries too.
+# Only recordings are deleted, oldest day first (never today's).
 # Comment out or remove to disable disk usage limits.
 # max_disk_usage_gb = 50.0
+# Run cleanup as soon as free space on the recordings drive drops below this
+# many GB, instead of waiting for the next scheduled run.
+# min_free_gb = 5.0
+# Don't delete a day (expired, or to meet max_disk_usage_gb) while any of its
+# recordings are untranscribed (recordings that failed transcription for good
+# don't count). Set to false if you don't run transcription, or old
+# recordings are never cleaned up.
+# require_transcribed_before_delete = true
 
 [transcription]
 # Transcription backend: "local" (whisper.cpp on device) or "azure" (cloud API).
 backend = "local"
 # Whisper model name (for local backend). Options: tiny.en, base.en, small.en, medium.en
+# Looked up as ggml-<name>.bin beside the exe, then in %APPDATA%\deskmic\models.
 # Or an absolute path to a .bin model file.
 model = "base.en"
+# Transcript storage: "jsonl" (one file per day) or "sqlite" (single database,
+# faster for large histories). Run 'deskmic migrate-transcripts' to copy
+# existing JSONL transcripts into SQLite before switching.
+store = "jsonl"
+# Also write SubRip (.srt) and WebVTT (.vtt) subtitles for each recording to
+# transcripts/<date>/, e.g. to overlay on a screen recording. Timings are per
+# segment with the local backend; Azure transcripts get one cue per recording.
+emit_subtitles = false
+# Threads for the local backend. 0 = one per physical core.
+whisper_threads = 0
+# Recordings transcribed at once (1-16). The local backend splits its threads
+# between them, so this doesn't use more CPU; it helps most with Azure (raise
+# max_concurrent_requests below too).
+concurrency = 1
+# Spoken language for the local backend ("en", "de", ...), or "auto" to detect
+# it. English-only models (*.en) always transcribe English.
+language = "en"
 
 [transcription.azure]
 # Azure OpenAI Whisper endpoint URL.
@@ -389,6 +1424,13 @@ model = "base.en"
 # api_key = ""
 # Deployment name for the Whisper model.
 # deployment = "whisper"
+# Client-side rate limit to stay within the deployment's quota (0 = unlimited).
+# requests_per_minute = 0
+# Maximum transcription requests in flight at once (0 = unlimited).
+# max_concurrent_requests = 1
+# Azure OpenAI API version. Newer transcription deployments may require a newer one.
+# Also used for summarization and search embeddings requests.
+# api_version = "2024-06-01"
 
 [transcription.idle_watch]
 # Only run transcription when average CPU usage is below this percentage.
@@ -398,25 +1440,85 @@ cpu_threshold_percent = 50.0
 idle_check_interval_secs = 30
 
 [summarization]
+# LLM that writes summaries: "azure" (Azure OpenAI), "openai", or "anthropic".
+# provider = "azure"
 # Azure OpenAI deployment name for chat completions (used by 'deskmic summarize').
 # This reuses the endpoint and api_key from [transcription.azure].
 # deployment = "gpt-4o"
+# Model and API key for provider = "openai" or "anthropic". The key can also be
+# set with DESKMIC_OPENAI_KEY or DESKMIC_ANTHROPIC_KEY.
+# model = ""
+# api_key = ""
 # ACS (Azure Communication Services) endpoint for sending summary emails.
 # acs_endpoint = "https://your-acs.unitedstates.communication.azure.com"
 # ACS access key (or set DESKMIC_ACS_KEY environment variable).
 # acs_api_key = ""
 # Sender email address from the ACS Email verified domain.
 # sender_address = "DoNotReply@your-domain.azurecomm.net"
-# Recipient email address for summary delivery.
+# Recipient email address for summary delivery. Separate several with commas.
 # recipient_address = "you@example.com"
 # Custom system prompt for the LLM summarizer. Use {{date_label}} as a placeholder
 # for the date range being summarized. Leave empty to use the built-in default.
 # system_prompt = ""
+# Custom prompt for the combine pass used on large days, which are summarized in
+# chunks and then merged. Same {{date_label}} placeholder; empty = built-in default.
+# combine_prompt = ""
+# Keep a rolling summary of today, updated every N hours by a scheduled task that
+# 'deskmic setup' creates ('deskmic summarize --rolling'). The next morning's daily
+# summary then only adds what's new. 0 = no scheduled rolling updates.
+# rolling_interval_hours = 0
+# Include the previous day's summary as context when generating a daily summary.
+# include_previous = false
+# Leave these hours of the day (0-23) or sources out of summaries, e.g. a lunch
+# break or a noisy source. 'deskmic summarize --exclude-hours/--exclude-source'
+# override these for a single run.
+# exclude_hours = [12, 13]
+# exclude_sources = ["system"]
+# Transcripts dropped as noise, on top of built-ins like "[BLANK_AUDIO]" and
+# "(coughing)". A pattern matches a whole transcript (case and spacing inside
+# brackets don't matter); write it as "/.../" for a regex searched anywhere.
+# noise_patterns = ["(door closes)", '/^\(.*(tapping|clicking)\)$/']
+# Format of the summary saved under summaries/: "markdown" (.md), "html" (.html,
+# same layout as the email), or "text" (.txt, Markdown stripped). Emails always
+# carry both the text and HTML versions.
+# output_format = "markdown"
+# How summaries are delivered: "acs" (email via the ACS settings above),
+# "smtp" (email via [summarization.smtp]), or "webhook" (POST to webhook_url).
+# Try it with 'deskmic test-email'.
+# transport = "acs"
+# With an email transport, a webhook_url is posted to as well; one channel
+# failing doesn't stop the other.
+# webhook_url = ""
+# Webhook payload: "json" (subject/text/html fields) or "slack" (a Slack
+# incoming webhook message).
+# webhook_format = "json"
+# Also append each summary to the daily note (<date>.md) in a notes vault such as
+# Obsidian or Logseq. Re-running a summary replaces its block instead of duplicating.
+# notes_vault_dir = "C:\\Users\\you\\Documents\\Vault"
+# notes_heading = '## deskmic summary'
+
+[summarization.smtp]
+# SMTP server for transport = "smtp". sender_address/recipient_address above
+# are used as From/To.
+# host = "smtp.gmail.com"
+# port = 587
+# TLS mode: "starttls" (port 587), "tls" (implicit TLS, port 465), or "none".
+# tls = "starttls"
+# username = "you@gmail.com"
+# Password or app password (or set DESKMIC_SMTP_PASSWORD environment variable).
+# password = ""
 
 [monitoring]
-# Minutes without a new WAV recording before showing a toast notification.
-# Set to 0 to disable gap alerts.
+# Minutes without a new WAV recording before showing a toast notification,
+# unless nothing audible was captured in that time (a stalled microphone or
+# app capture is reported on its own after a minute). Set to 0 to disable gap
+# alerts.
 recording_gap_alert_mins = 30
+# Seconds between checks that the recording threads are alive (a dead one is
+# restarted). Takes effect after a restart.
+watchdog_interval_secs = 10
+# Seconds between checks for a recording gap. Takes effect after a restart.
+gap_check_interval_secs = 60
 
 [search]
 # Azure OpenAI deployment name for text embeddings (used by 'deskmic index').
@@ -426,10 +1528,348 @@ recording_gap_alert_mins = 30
 chunk_gap_secs = 60
 # Maximum duration in seconds for a single chunk before it is split.
 chunk_max_duration_secs = 300
+
+[hotkeys]
+# Global hotkey that pauses or resumes recording, like the tray menu.
+# Combine Ctrl, Alt, Shift or Win with a letter, digit or F1-F24.
+# Set to "" to disable.
+pause_toggle = "Ctrl+Alt+P"
+# Global hotkey that saves the last capture.save_clip_secs of every source.
+save_clip = "Ctrl+Alt+S"
 "#,
-            output_dir = output_dir_str
+            output_dir = output_dir_str,
+            version = CONFIG_VERSION
         )
     }
+
+    /// `generate_default_commented()` with every setting that differs from
+    /// the defaults filled in, so an existing config can be regenerated with
+    /// new fields and their docs while keeping its values. Comments written
+    /// by hand are not kept.
+    pub fn to_commented_toml(&self) -> anyhow::Result<String> {
+        let defaults = toml::Table::try_from(Config::default())?;
+        let current = toml::Table::try_from(self)?;
+        let mut changed = Vec::new();
+        changed_settings("", &current, &defaults, &mut changed);
+
+        let template = Self::generate_default_commented();
+        let mut lines: Vec<String> = template.lines().map(String::from).collect();
+        for (section, key, value) in &changed {
+            set_config_line(&mut lines, section, key, &value.to_string());
+        }
+        let mut text = lines.join("\n");
+        text.push('\n');
+        Ok(text)
+    }
+}
+
+/// Move each `(version, old, new)` dotted path in `renames` that is newer
+/// than `from_version`. A value already set under the new name wins.
+fn rename_keys(
+    table: &mut toml::Table,
+    from_version: u32,
+    renames: &[(u32, &str, &str)],
+) -> Vec<String> {
+    let mut changes = Vec::new();
+    'renames: for &(version, old, new) in renames {
+        if from_version >= version {
+            continue;
+        }
+        let Some(value) = take_path(table, old) else {
+            continue;
+        };
+        let (parents, key) = new.rsplit_once('.').unwrap_or(("", new));
+        let mut target = &mut *table;
+        for part in parents.split('.').filter(|p| !p.is_empty()) {
+            let entry = target
+                .entry(part)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            let Some(sub) = entry.as_table_mut() else {
+                changes.push(format!("dropped {} ({} is not a section)", old, parents));
+                continue 'renames;
+            };
+            target = sub;
+        }
+        if target.contains_key(key) {
+            changes.push(format!("dropped {} ({} is already set)", old, new));
+        } else {
+            target.insert(key.to_string(), value);
+            changes.push(format!("renamed {} to {}", old, new));
+        }
+    }
+    changes
+}
+
+/// Remove and return the value at dotted `path`.
+fn take_path(table: &mut toml::Table, path: &str) -> Option<toml::Value> {
+    let (parents, key) = path.rsplit_once('.').unwrap_or(("", path));
+    let mut table = table;
+    for part in parents.split('.').filter(|p| !p.is_empty()) {
+        table = table.get_mut(part)?.as_table_mut()?;
+    }
+    table.remove(key)
+}
+
+/// Describe every setting in `full` that `file` doesn't set, i.e. that a
+/// migrated file gains with its default value.
+fn added_settings(section: &str, full: &toml::Table, file: &toml::Table, out: &mut Vec<String>) {
+    for (key, value) in full {
+        let path = match section {
+            "" => key.clone(),
+            _ => format!("{}.{}", section, key),
+        };
+        match (value, file.get(key)) {
+            (toml::Value::Table(sub), Some(toml::Value::Table(file_sub))) => {
+                added_settings(&path, sub, file_sub, out)
+            }
+            (toml::Value::Table(_), None) => out.push(format!("added [{}] with defaults", path)),
+            (_, None) if key != "version" => out.push(format!("added {} = {}", path, value)),
+            _ => {}
+        }
+    }
+}
+
+/// Collect `(section, key, value)` for every value in `current` that differs
+/// from `defaults` or is missing there, descending into sub-tables.
+fn changed_settings(
+    section: &str,
+    current: &toml::Table,
+    defaults: &toml::Table,
+    out: &mut Vec<(String, String, toml::Value)>,
+) {
+    let empty = toml::Table::new();
+    for (key, value) in current {
+        let default = defaults.get(key);
+        if let toml::Value::Table(table) = value {
+            let path = match section {
+                "" => toml_key(key),
+                _ => format!("{}.{}", section, toml_key(key)),
+            };
+            let default = default.and_then(toml::Value::as_table).unwrap_or(&empty);
+            changed_settings(&path, table, default, out);
+        } else if default != Some(value) {
+            out.push((section.to_string(), toml_key(key), value.clone()));
+        }
+    }
+}
+
+/// `key` as written in a TOML file: bare when it can be, quoted otherwise
+/// (e.g. a `targets.labels` entry for "Teams.exe").
+fn toml_key(key: &str) -> String {
+    let bare = key
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare && !key.is_empty() {
+        key.to_string()
+    } else {
+        toml::Value::String(key.to_string()).to_string()
+    }
+}
+
+/// A config file's format `version`; 0 for files written before versioning.
+fn table_version(table: &toml::Table) -> anyhow::Result<u32> {
+    match table.get("version") {
+        None => Ok(0),
+        Some(v) => v
+            .as_integer()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| anyhow::anyhow!("version must be a whole number (got {})", v)),
+    }
+}
+
+/// Set `key = value` under `[section]` in a config file's `lines`, where
+/// `value` is already TOML-encoded. Replaces the line setting `key` or its
+/// commented-out `# key = ...` example; a missing key is added at the end
+/// of its section and a missing section at the end of the file.
+pub fn set_config_line(lines: &mut Vec<String>, section: &str, key: &str, value: &str) {
+    let new_line = format!("{key} = {value}");
+    match find_key_line(lines, section, key) {
+        KeyLine::Found(i) => {
+            // A multi-line string value's other lines go with it.
+            let mut end = i + 1;
+            let mut open = open_multiline_string(&lines[i], None);
+            while open.is_some() && end < lines.len() {
+                open = open_multiline_string(&lines[end], open);
+                end += 1;
+            }
+            lines.splice(i..end, [new_line]);
+        }
+        KeyLine::Missing(at) => lines.insert(at, new_line),
+        KeyLine::NoSection => {
+            lines.push(String::new());
+            lines.push(format!("[{section}]"));
+            lines.push(new_line);
+        }
+    }
+}
+
+/// Where `key` is, or should go, in a config file's lines.
+enum KeyLine {
+    /// The line setting `key`, or the commented-out example of it.
+    Found(usize),
+    /// The section exists without `key`, which belongs at this index, after
+    /// the section's last non-blank line.
+    Missing(usize),
+    NoSection,
+}
+
+/// Find `key` in `[section]`, or among the top-level keys before the first
+/// header when `section` is "". A line that sets it wins over a
+/// commented-out `# key = ...` example, so filling it in never duplicates
+/// the key.
+fn find_key_line(lines: &[String], section: &str, key: &str) -> KeyLine {
+    // `key = ...`, not a longer key that merely starts with `key`.
+    let is_key = |s: &str| {
+        s.strip_prefix(key)
+            .is_some_and(|rest| rest.trim_start().starts_with('='))
+    };
+
+    let mut current = Some("");
+    let mut insert_at = section.is_empty().then_some(0);
+    let mut commented = None;
+    let mut multiline = None;
+    for (i, line) in lines.iter().enumerate() {
+        // Lines inside a multi-line string are part of a value, however
+        // they look.
+        let in_string = multiline.is_some();
+        multiline = open_multiline_string(line, multiline);
+        if in_string {
+            if current == Some(section) {
+                insert_at = Some(i + 1);
+            }
+            continue;
+        }
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            // `[name]`, `[[name]]`, possibly followed by a comment.
+            let name = trimmed.trim_start_matches('[').split(']').next();
+            current = name.map(str::trim);
+            if current == Some(section) && insert_at.is_none() {
+                insert_at = Some(i + 1);
+            }
+            continue;
+        }
+        if current != Some(section) {
+            continue;
+        }
+        if !trimmed.is_empty() {
+            insert_at = Some(i + 1);
+        }
+        if is_key(trimmed) {
+            return KeyLine::Found(i);
+        }
+        if let Some(inner) = trimmed.strip_prefix('#') {
+            if commented.is_none() && is_key(inner.trim_start_matches('#').trim()) {
+                commented = Some(i);
+            }
+        }
+    }
+
+    match (commented, insert_at) {
+        (Some(i), _) => KeyLine::Found(i),
+        (None, Some(at)) => KeyLine::Missing(at),
+        (None, None) => KeyLine::NoSection,
+    }
+}
+
+/// The multi-line string delimiter (`"""` or `'''`) still open at the end of
+/// `line`, given the one open at its start. Delimiters after a `#` outside
+/// a string are in a comment.
+fn open_multiline_string(line: &str, mut open: Option<&'static str>) -> Option<&'static str> {
+    let mut rest = line;
+    loop {
+        match open {
+            Some(delim) => {
+                let Some(at) = rest.find(delim) else {
+                    return open;
+                };
+                rest = &rest[at + delim.len()..];
+                open = None;
+            }
+            None => {
+                let (at, delim) = ["\"\"\"", "'''"]
+                    .into_iter()
+                    .filter_map(|delim| rest.find(delim).map(|at| (at, delim)))
+                    .min()?;
+                if rest[..at].contains('#') {
+                    return None;
+                }
+                rest = &rest[at + delim.len()..];
+                open = Some(delim);
+            }
+        }
+    }
+}
+
+/// Set the field named by `key` (upper case, `_`-separated) inside `table`,
+/// descending into sub-tables whose name prefixes `key`. Returns the dotted
+/// path of the field that was set, relative to `table`.
+fn override_field(table: &mut toml::Table, key: &str, raw: &str) -> Result<String, String> {
+    for (name, value) in table.iter_mut() {
+        let Some(sub) = value.as_table_mut() else {
+            continue;
+        };
+        let Some(rest) = key
+            .strip_prefix(&name.to_uppercase())
+            .and_then(|r| r.strip_prefix('_'))
+        else {
+            continue;
+        };
+        return override_field(sub, rest, raw).map(|path| format!("{}.{}", name, path));
+    }
+
+    let field = key.to_lowercase();
+    let value = match table.get(&field) {
+        Some(toml::Value::Table(_)) => return Err(format!("{} is a section", field)),
+        Some(existing) => parse_like(existing, raw)?,
+        // Unset optional fields (and map entries like labels) aren't serialized,
+        // so infer their type from the value itself.
+        None => infer_value(raw),
+    };
+    table.insert(field.clone(), value);
+    Ok(field)
+}
+
+/// Parse `raw` as the same TOML type as `existing`.
+fn parse_like(existing: &toml::Value, raw: &str) -> Result<toml::Value, String> {
+    let trimmed = raw.trim();
+    match existing {
+        toml::Value::String(_) => Ok(toml::Value::String(raw.to_string())),
+        toml::Value::Integer(_) => trimmed
+            .parse()
+            .map(toml::Value::Integer)
+            .map_err(|_| format!("expected an integer, got {:?}", raw)),
+        toml::Value::Float(_) => trimmed
+            .parse()
+            .map(toml::Value::Float)
+            .map_err(|_| format!("expected a number, got {:?}", raw)),
+        toml::Value::Boolean(_) => match trimmed.to_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Ok(toml::Value::Boolean(true)),
+            "false" | "0" | "no" | "off" => Ok(toml::Value::Boolean(false)),
+            _ => Err(format!("expected true or false, got {:?}", raw)),
+        },
+        toml::Value::Array(_) if trimmed.starts_with('[') => match infer_value(trimmed) {
+            array @ toml::Value::Array(_) => Ok(array),
+            _ => Err(format!("expected a list, got {:?}", raw)),
+        },
+        toml::Value::Array(_) => Ok(toml::Value::Array(
+            trimmed
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(infer_value)
+                .collect(),
+        )),
+        _ => Ok(infer_value(raw)),
+    }
+}
+
+/// Read `raw` as a TOML value literal, falling back to a plain string.
+fn infer_value(raw: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("v = {}", raw))
+        .ok()
+        .and_then(|mut t| t.remove("v"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
 }
 
 #[cfg(test)]
@@ -439,22 +1879,71 @@ mod tests {
     #[test]
     fn test_default_config_is_valid() {
         let config = Config::default();
+        assert_eq!(config.version, CONFIG_VERSION);
         assert_eq!(config.capture.sample_rate, 16000);
         assert_eq!(config.capture.bit_depth, 16);
         assert_eq!(config.capture.channels, 1);
+        assert!(!config.capture.pause_when_locked);
+        assert!(config.capture.device.is_none());
+        assert!(config.capture.devices.is_empty());
+        assert_eq!(config.capture.gain_db, 0.0);
+        assert_eq!(config.capture.event_timeout_ms, 100);
+        assert_eq!(config.capture.save_clip_secs, 30);
+        assert!(config.vad.enabled);
         assert_eq!(config.vad.speech_threshold, 0.5);
         assert_eq!(config.vad.pre_speech_buffer_secs, 5.0);
         assert_eq!(config.vad.silence_threshold_secs, 3.0);
+        assert!(!config.vad.adaptive);
+        assert_eq!(config.vad.min_segment_secs, 0.25);
+        assert_eq!(config.vad.merge_gap_secs, 0.0);
         assert_eq!(config.storage.retention_days, 30);
         assert_eq!(config.storage.cleanup_interval_hours, 6);
         assert!(config.storage.max_disk_usage_gb.is_none());
+        assert!(config.storage.min_free_gb.is_none());
+        assert!(config.storage.require_transcribed_before_delete);
         assert_eq!(config.output.max_file_duration_mins, 30);
         assert!(config.output.organize_by_date);
+        assert!(!config.output.embed_metadata);
+        assert!(config.output.machine_id().is_none());
+        assert_eq!(config.output.format, "wav");
+        assert_eq!(config.output.filename_template, "{source}_{time}.{ext}");
         assert!(config.targets.mic_enabled);
         assert_eq!(config.targets.processes, vec!["ms-teams.exe"]);
+        assert!(!config.targets.system_loopback);
+        assert_eq!(config.targets.poll_interval_secs, 5);
+        assert_eq!(config.targets.match_mode, "exact");
+        assert_eq!(config.targets.audio_probe_secs, 10);
+        assert!(config.targets.labels.is_empty());
         assert_eq!(config.transcription.backend, "local");
         assert_eq!(config.transcription.model, "base.en");
+        assert_eq!(config.transcription.store, "jsonl");
+        assert!(!config.transcription.emit_subtitles);
+        assert_eq!(config.transcription.whisper_threads, 0);
+        assert_eq!(config.transcription.concurrency, 1);
+        assert_eq!(config.transcription.language, "en");
+        assert!(!config.summarization.include_previous);
+        assert!(config.summarization.combine_prompt.is_empty());
+        assert!(config.summarization.exclude_hours.is_empty());
+        assert!(config.summarization.exclude_sources.is_empty());
+        assert!(config.summarization.noise_patterns.is_empty());
+        assert_eq!(config.summarization.rolling_interval_hours, 0);
+        assert_eq!(config.summarization.transport, "acs");
+        assert_eq!(config.summarization.provider, "azure");
+        assert!(config.summarization.model.is_empty());
+        assert_eq!(config.summarization.webhook_format, "json");
+        assert_eq!(config.summarization.output_format, "markdown");
+        assert_eq!(config.summarization.smtp.port, 587);
+        assert_eq!(config.summarization.smtp.tls, "starttls");
+        assert!(config.summarization.notes_vault_dir.is_none());
+        assert_eq!(config.summarization.notes_heading, "## deskmic summary");
+        assert_eq!(config.transcription.azure.requests_per_minute, 0);
+        assert_eq!(config.transcription.azure.max_concurrent_requests, 1);
+        assert_eq!(config.transcription.azure.api_version, "2024-06-01");
         assert_eq!(config.monitoring.recording_gap_alert_mins, 30);
+        assert_eq!(config.monitoring.watchdog_interval_secs, 10);
+        assert_eq!(config.monitoring.gap_check_interval_secs, 60);
+        assert_eq!(config.hotkeys.pause_toggle, "Ctrl+Alt+P");
+        assert_eq!(config.hotkeys.save_clip, "Ctrl+Alt+S");
     }
 
     #[test]
@@ -479,59 +1968,163 @@ mod tests {
     #[test]
     fn test_parse_full_toml_config() {
         let toml_str = r#"
+            version = 1
+
             [capture]
             sample_rate = 44100
             bit_depth = 24
             channels = 2
+            pause_when_locked = true
+            device = "Jabra"
+            devices = ["Jabra", "Yeti"]
+            gain_db = 12.0
+            event_timeout_ms = 250
+            save_clip_secs = 60
 
             [vad]
+            enabled = false
             pre_speech_buffer_secs = 3.0
             silence_threshold_secs = 2.0
             speech_threshold = 0.6
+            adaptive = true
+            min_segment_secs = 0.5
+            merge_gap_secs = 2.0
 
             [output]
             directory = "/tmp/deskmic"
             max_file_duration_mins = 60
             organize_by_date = false
+            embed_metadata = true
+            machine_id = "Work Laptop"
+            format = "flac"
+            filename_template = "{date}-{time}-{source}.{ext}"
 
             [targets]
             processes = ["zoom.exe", "slack.exe"]
             mic_enabled = false
+            system_loopback = true
+            poll_interval_secs = 2
+            match_mode = "glob"
+            audio_probe_secs = 0
+
+            [targets.labels]
+            "zoom.exe" = "Standup"
 
             [storage]
             retention_days = 7
             cleanup_interval_hours = 12
             max_disk_usage_gb = 50.0
+            min_free_gb = 5.0
+            require_transcribed_before_delete = false
 
             [transcription]
             backend = "azure"
             model = "large-v3"
+            store = "sqlite"
+            emit_subtitles = true
+            whisper_threads = 12
+            concurrency = 3
+            language = "auto"
 
             [transcription.azure]
             endpoint = "https://example.openai.azure.com"
             api_key = "test-key"
             deployment = "whisper-large"
+            requests_per_minute = 3
+            max_concurrent_requests = 2
+            api_version = "2025-03-01-preview"
 
             [transcription.idle_watch]
             cpu_threshold_percent = 10.0
             idle_check_interval_secs = 60
+
+            [summarization]
+            notes_vault_dir = "/home/me/vault/journals"
+            notes_heading = '### Recap'
+            combine_prompt = "Merge {date_label}"
+            exclude_hours = [12, 13]
+            exclude_sources = ["system"]
+            rolling_interval_hours = 3
+            webhook_url = "https://hooks.slack.com/services/T0/B0/x"
+            webhook_format = "slack"
+            provider = "anthropic"
+            model = "claude-model"
+            output_format = "html"
+            noise_patterns = ["(typing)", "/^\\[.*\\]$/"]
+
+            [hotkeys]
+            pause_toggle = "Win+Shift+F9"
+            save_clip = "Win+Shift+F10"
         "#;
         let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.version, 1);
         assert_eq!(config.capture.sample_rate, 44100);
         assert_eq!(config.capture.bit_depth, 24);
         assert_eq!(config.capture.channels, 2);
+        assert!(config.capture.pause_when_locked);
+        assert_eq!(config.capture.device.as_deref(), Some("Jabra"));
+        assert_eq!(config.capture.devices, vec!["Jabra", "Yeti"]);
+        assert_eq!(config.capture.gain_db, 12.0);
+        assert_eq!(config.capture.event_timeout_ms, 250);
+        assert_eq!(config.capture.save_clip_secs, 60);
+        assert!(!config.vad.enabled);
         assert_eq!(config.vad.pre_speech_buffer_secs, 3.0);
+        assert!(config.vad.adaptive);
+        assert_eq!(config.vad.min_segment_secs, 0.5);
+        assert_eq!(config.vad.merge_gap_secs, 2.0);
         assert!(!config.output.organize_by_date);
+        assert!(config.output.embed_metadata);
+        assert_eq!(config.output.machine_id().as_deref(), Some("work-laptop"));
+        assert_eq!(config.output.format, "flac");
+        assert_eq!(config.output.extension(), "flac");
+        assert_eq!(
+            config.output.filename_template,
+            "{date}-{time}-{source}.{ext}"
+        );
         assert_eq!(config.targets.processes, vec!["zoom.exe", "slack.exe"]);
         assert!(!config.targets.mic_enabled);
+        assert!(config.targets.system_loopback);
+        assert_eq!(config.targets.poll_interval_secs, 2);
+        assert_eq!(config.targets.match_mode, "glob");
+        assert_eq!(config.targets.audio_probe_secs, 0);
+        assert_eq!(config.targets.labels["zoom.exe"], "Standup");
         assert_eq!(config.storage.retention_days, 7);
         assert_eq!(config.storage.max_disk_usage_gb, Some(50.0));
+        assert_eq!(config.storage.min_free_gb, Some(5.0));
+        assert!(!config.storage.require_transcribed_before_delete);
         assert_eq!(config.transcription.backend, "azure");
+        assert_eq!(config.transcription.store, "sqlite");
+        assert!(config.transcription.emit_subtitles);
+        assert_eq!(config.transcription.whisper_threads, 12);
+        assert_eq!(config.transcription.concurrency, 3);
+        assert_eq!(config.transcription.language, "auto");
         assert_eq!(
             config.transcription.azure.endpoint,
             "https://example.openai.azure.com"
         );
+        assert_eq!(config.transcription.azure.requests_per_minute, 3);
+        assert_eq!(config.transcription.azure.max_concurrent_requests, 2);
+        assert_eq!(config.transcription.azure.api_version, "2025-03-01-preview");
         assert_eq!(config.transcription.idle_watch.cpu_threshold_percent, 10.0);
+        assert_eq!(
+            config.summarization.notes_vault_dir,
+            Some(PathBuf::from("/home/me/vault/journals"))
+        );
+        assert_eq!(config.summarization.notes_heading, "### Recap");
+        assert_eq!(config.summarization.combine_prompt, "Merge {date_label}");
+        assert_eq!(config.summarization.exclude_hours, vec![12, 13]);
+        assert_eq!(config.summarization.exclude_sources, vec!["system"]);
+        assert_eq!(config.summarization.rolling_interval_hours, 3);
+        assert_eq!(config.summarization.webhook_format, "slack");
+        assert_eq!(config.summarization.provider, "anthropic");
+        assert_eq!(config.summarization.model, "claude-model");
+        assert_eq!(config.summarization.output_format, "html");
+        assert_eq!(
+            config.summarization.noise_patterns,
+            vec!["(typing)", "/^\\[.*\\]$/"]
+        );
+        assert_eq!(config.hotkeys.pause_toggle, "Win+Shift+F9");
+        assert_eq!(config.hotkeys.save_clip, "Win+Shift+F10");
     }
 
     #[test]
@@ -567,6 +2160,65 @@ mod tests {
         assert_eq!(resolved, Some(config_file));
     }
 
+    #[test]
+    fn test_parse_migrates_unversioned_config() {
+        let (config, changes) = Config::parse("[capture]\nsample_rate = 8000\n").unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.capture.sample_rate, 8000);
+        assert!(
+            changes.contains(&"version 0 -> 1".to_string()),
+            "{:?}",
+            changes
+        );
+        assert!(changes.contains(&"added [monitoring] with defaults".to_string()));
+        assert!(changes.contains(&"added capture.channels = 1".to_string()));
+
+        let (_, changes) = Config::parse(&Config::generate_default_commented()).unwrap();
+        assert!(changes.is_empty(), "{:?}", changes);
+        assert!(Config::parse("version = \"one\"\n").is_err());
+    }
+
+    #[test]
+    fn test_rename_keys() {
+        let mut table: toml::Table =
+            toml::from_str("[capture]\nmic = \"Jabra\"\ngain = 3.0\n[output]\ngain = 1\n").unwrap();
+        let renames = [
+            (1, "capture.mic", "capture.device"),
+            (1, "capture.gain", "output.gain"),
+            (0, "output.gain", "output.old"),
+        ];
+        let changes = rename_keys(&mut table, 0, &renames);
+        assert_eq!(
+            changes,
+            vec![
+                "renamed capture.mic to capture.device",
+                "dropped capture.gain (output.gain is already set)",
+            ]
+        );
+        assert_eq!(table["capture"]["device"].as_str(), Some("Jabra"));
+        assert!(table["capture"].get("gain").is_none());
+        assert_eq!(table["output"]["gain"].as_integer(), Some(1));
+    }
+
+    #[test]
+    fn test_load_rewrites_migrated_config() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config_file = tmp.path().join("deskmic.toml");
+        let old = "[capture]\nsample_rate = 8000\n";
+        std::fs::write(&config_file, old).unwrap();
+
+        let config = Config::load(Some(config_file.as_path())).unwrap();
+        assert_eq!(config.capture.sample_rate, 8000);
+
+        let backup = tmp.path().join("deskmic.toml.v0.bak");
+        assert_eq!(std::fs::read_to_string(backup).unwrap(), old);
+        let rewritten = std::fs::read_to_string(&config_file).unwrap();
+        let (reparsed, changes) = Config::parse(&rewritten).unwrap();
+        assert!(changes.is_empty(), "{:?}", changes);
+        assert_eq!(reparsed.capture.sample_rate, 8000);
+        assert!(rewritten.contains("[monitoring]"));
+    }
+
     #[test]
     fn test_load_with_path_none_returns_none_when_no_file() {
         let (config, resolved) = Config::load_with_path(None).unwrap();
@@ -574,6 +2226,279 @@ mod tests {
         let _ = resolved;
     }
 
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_env_overrides_parse_by_field_type() {
+        let mut config = Config::default();
+        config
+            .apply_overrides(vars(&[
+                ("DESKMIC_OUTPUT_DIRECTORY", "D:\\rec"),
+                ("DESKMIC_CAPTURE_SAMPLE_RATE", "8000"),
+                ("DESKMIC_VAD_SPEECH_THRESHOLD", "0.7"),
+                ("DESKMIC_OUTPUT_ORGANIZE_BY_DATE", "false"),
+                ("DESKMIC_TARGETS_PROCESSES", "ms-teams.exe, zoom.exe"),
+                ("DESKMIC_TRANSCRIPTION_BACKEND", "azure"),
+            ]))
+            .unwrap();
+
+        assert_eq!(config.output.directory, PathBuf::from("D:\\rec"));
+        assert_eq!(config.capture.sample_rate, 8000);
+        assert!((config.vad.speech_threshold - 0.7).abs() < 1e-6);
+        assert!(!config.output.organize_by_date);
+        assert_eq!(config.targets.processes, vec!["ms-teams.exe", "zoom.exe"]);
+        assert_eq!(config.transcription.backend, "azure");
+    }
+
+    #[test]
+    fn test_env_overrides_nested_and_optional_fields() {
+        let mut config = Config::default();
+        config
+            .apply_overrides(vars(&[
+                (
+                    "DESKMIC_TRANSCRIPTION_AZURE_ENDPOINT",
+                    "https://x.openai.azure.com",
+                ),
+                (
+                    "DESKMIC_TRANSCRIPTION_IDLE_WATCH_IDLE_CHECK_INTERVAL_SECS",
+                    "5",
+                ),
+                ("DESKMIC_SUMMARIZATION_SMTP_PORT", "465"),
+                ("DESKMIC_STORAGE_MAX_DISK_USAGE_GB", "12.5"),
+                ("DESKMIC_SUMMARIZATION_EXCLUDE_HOURS", "12,13"),
+                ("DESKMIC_TARGETS_LABELS_MIC", "Desk"),
+            ]))
+            .unwrap();
+
+        assert_eq!(
+            config.transcription.azure.endpoint,
+            "https://x.openai.azure.com"
+        );
+        assert_eq!(config.transcription.idle_watch.idle_check_interval_secs, 5);
+        assert_eq!(config.summarization.smtp.port, 465);
+        assert_eq!(config.storage.max_disk_usage_gb, Some(12.5));
+        assert_eq!(config.summarization.exclude_hours, vec![12, 13]);
+        assert_eq!(
+            config.targets.labels.get("mic").map(String::as_str),
+            Some("Desk")
+        );
+    }
+
+    #[test]
+    fn test_env_overrides_ignore_unrelated_vars() {
+        let mut config = Config::default();
+        config
+            .apply_overrides(vars(&[
+                ("DESKMIC_AZURE_KEY", "secret"),
+                ("DESKMIC_ACS_KEY", "secret"),
+                ("PATH", "/usr/bin"),
+            ]))
+            .unwrap();
+        assert_eq!(config.transcription.azure.api_key, "");
+    }
+
+    #[test]
+    fn test_env_overrides_reject_bad_number() {
+        let mut config = Config::default();
+        let err = config
+            .apply_overrides(vars(&[("DESKMIC_CAPTURE_SAMPLE_RATE", "fast")]))
+            .unwrap_err();
+        assert!(err.to_string().contains("DESKMIC_CAPTURE_SAMPLE_RATE"));
+    }
+
+    #[test]
+    fn test_mic_sources_default_single_mic() {
+        let mut config = Config::default();
+        config.capture.device = Some("Jabra".to_string());
+        assert_eq!(
+            config.mic_sources(),
+            vec![MicSource {
+                device: Some("Jabra".to_string()),
+                source: "mic".to_string(),
+                exact: false,
+            }]
+        );
+
+        config.targets.mic_enabled = false;
+        assert!(config.mic_sources().is_empty());
+    }
+
+    #[test]
+    fn test_mic_sources_one_per_device() {
+        let mut config = Config::default();
+        config.capture.devices = vec!["Headset".to_string(), "Desk Mic".to_string()];
+        config
+            .targets
+            .labels
+            .insert("Desk Mic".to_string(), "Desk".to_string());
+
+        let sources = config.mic_sources();
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].source, "mic-headset");
+        assert_eq!(sources[0].device.as_deref(), Some("Headset"));
+        assert!(sources[0].exact);
+        assert_eq!(sources[1].source, "Desk");
+    }
+
+    #[test]
+    fn test_validate_accepts_defaults() {
+        assert!(Config::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_collects_all_errors() {
+        let mut config = Config::default();
+        config.capture.sample_rate = 44100;
+        config.vad.speech_threshold = 5.0;
+        config.vad.pre_speech_buffer_secs = 0.0;
+        config.vad.silence_threshold_secs = -1.0;
+        config.vad.merge_gap_secs = -1.0;
+        config.storage.retention_days = 0;
+        config.output.format = "mp3".to_string();
+        config.targets.poll_interval_secs = 0;
+        config.targets.match_mode = "regex".to_string();
+        config.capture.event_timeout_ms = 5;
+        config.storage.min_free_gb = Some(0.0);
+        config.summarization.noise_patterns = vec!["/[unclosed/".to_string()];
+        config.hotkeys.pause_toggle = "Ctrl+Esc".to_string();
+        config.transcription.concurrency = 0;
+        config.monitoring.gap_check_interval_secs = 0;
+
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors.len(), 16);
+        assert!(errors[0].contains("sample_rate"));
+        assert!(errors[1].contains("event_timeout_ms"));
+        assert!(errors[2].contains("speech_threshold"));
+    }
+
+    #[test]
+    fn test_validate_save_clip_hotkey() {
+        let mut config = Config::default();
+        config.hotkeys.save_clip = "alt+ctrl+p".to_string();
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("already hotkeys.pause_toggle"));
+
+        config.hotkeys.pause_toggle = String::new();
+        assert!(config.validate().is_ok());
+        config.hotkeys.save_clip = "Ctrl+Esc".to_string();
+        assert!(config.validate().unwrap_err()[0].contains("hotkeys.save_clip"));
+        config.hotkeys.save_clip = String::new();
+        config.capture.save_clip_secs = 601;
+        assert!(config.validate().unwrap_err()[0].contains("save_clip_secs"));
+    }
+
+    #[test]
+    fn test_validate_azure_endpoint() {
+        let mut config = Config::default();
+        config.transcription.backend = "azure".to_string();
+        config.transcription.azure.endpoint = "http://example.openai.azure.com".to_string();
+        assert_eq!(config.validate().unwrap_err().len(), 1);
+
+        config.transcription.azure.endpoint = "https://example.openai.azure.com".to_string();
+        assert!(config.validate().is_ok());
+
+        // The endpoint is only required for the azure backend.
+        config.transcription.backend = "local".to_string();
+        config.transcription.azure.endpoint = String::new();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_parse_recording_name() {
+        let name = |source: &str, time, machine: Option<&str>| RecordingName {
+            source: source.to_string(),
+            time,
+            machine: machine.map(str::to_string),
+        };
+        let parse = parse_recording_name;
+        let default = DEFAULT_FILENAME_TEMPLATE;
+        assert_eq!(
+            parse(default, "mic-headset_09-15-30.wav"),
+            Some(name("mic-headset", (9, 15, 30), None))
+        );
+        assert_eq!(
+            parse(default, "teams_14-30-05_desk-1.flac"),
+            Some(name("teams", (14, 30, 5), Some("desk-1")))
+        );
+        // Counter added when two files start in the same second.
+        assert_eq!(
+            parse(default, "mic_14-30-05_desk.2.wav"),
+            Some(name("mic", (14, 30, 5), Some("desk")))
+        );
+        assert_eq!(parse(default, "mic_25-00-00.wav"), None);
+        assert_eq!(parse(default, "recording.wav"), None);
+
+        let custom = "{date}-{time}-{source}.{ext}";
+        assert_eq!(
+            parse(custom, "2026-02-17-14-30-05-mic-desk.ogg"),
+            Some(name("mic-desk", (14, 30, 5), None))
+        );
+        assert_eq!(
+            parse(custom, "2026-02-17-14-30-05-zoom_laptop.wav"),
+            Some(name("zoom", (14, 30, 5), Some("laptop")))
+        );
+        // Recordings named before the template changed.
+        assert_eq!(
+            parse(custom, "mic_08-00-00.wav"),
+            Some(name("mic", (8, 0, 0), None))
+        );
+        assert_eq!(
+            parse("rec {source} at {time}.{ext}", "rec mic at 08-00-00.wav")
+                .unwrap()
+                .source,
+            "mic"
+        );
+    }
+
+    #[test]
+    fn test_fill_template() {
+        let value = |name: &str| match name {
+            "source" => Some("mic".to_string()),
+            "time" => Some("14-30-05".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            fill_template("{source}_{time}.wav", value).unwrap(),
+            "mic_14-30-05.wav"
+        );
+        assert_eq!(fill_template("plain", value).unwrap(), "plain");
+        let err = fill_template("{source}_{host}", value).unwrap_err();
+        assert!(err.contains("{host}"));
+        assert!(fill_template("{source", value).is_err());
+    }
+
+    #[test]
+    fn test_validate_filename_template() {
+        let mut config = Config::default();
+        config.output.filename_template = "{date}-{time}-{source}.{ext}".to_string();
+        assert!(config.validate().is_ok());
+
+        for bad in [
+            "{source}.{ext}",
+            "{source}_{time}_{year}.{ext}",
+            "{source}_{time}.wav",
+            "{date}/{source}_{time}.{ext}",
+        ] {
+            config.output.filename_template = bad.to_string();
+            assert_eq!(config.validate().unwrap_err().len(), 1, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_output_format_extension() {
+        let mut config = Config::default();
+        assert_eq!(config.output.extension(), "wav");
+        config.output.format = "opus".to_string();
+        assert_eq!(config.output.extension(), "ogg");
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_generate_default_commented_is_valid_toml() {
         let content = Config::generate_default_commented();
@@ -582,10 +2507,86 @@ mod tests {
         assert_eq!(config.capture.sample_rate, 16000);
         assert_eq!(config.vad.pre_speech_buffer_secs, 5.0);
         assert_eq!(config.output.max_file_duration_mins, 30);
+        assert_eq!(config.output.filename_template, DEFAULT_FILENAME_TEMPLATE);
         assert_eq!(config.transcription.backend, "local");
         assert_eq!(config.transcription.idle_watch.idle_check_interval_secs, 30);
     }
 
+    #[test]
+    fn test_generate_default_commented_matches_defaults() {
+        // to_commented_toml only fills in values that differ from these.
+        let parsed: Config = toml::from_str(&Config::generate_default_commented()).unwrap();
+        assert_eq!(
+            toml::Table::try_from(&parsed).unwrap(),
+            toml::Table::try_from(Config::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_to_commented_toml_keeps_changed_values() {
+        let toml_str = r#"
+            [capture]
+            sample_rate = 8000
+            device = "USB Headset"
+
+            [targets]
+            processes = ["zoom.exe", "ms-teams.exe"]
+
+            [targets.labels]
+            "Teams.exe" = "teams"
+
+            [storage]
+            max_disk_usage_gb = 20.5
+
+            [transcription.azure]
+            endpoint = "https://example.openai.azure.com"
+            deployment = "whisper-1"
+
+            [summarization]
+            system_prompt = "Say \"hi\"\nthen summarize"
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let text = config.to_commented_toml().unwrap();
+        let reparsed: Config = toml::from_str(&text).unwrap();
+        assert_eq!(
+            toml::Table::try_from(&reparsed).unwrap(),
+            toml::Table::try_from(&config).unwrap()
+        );
+        // Still the documented template, with values filled in place.
+        assert!(text.contains("# Recording format:"));
+        assert!(text.contains("device = \"USB Headset\""), "{}", text);
+        assert!(!text.contains("# device ="), "{}", text);
+    }
+
+    #[test]
+    fn test_set_config_line() {
+        let mut lines: Vec<String> = "[a]\n# x = 1\nx = 2\n\n[b]\ny = 1\n"
+            .lines()
+            .map(String::from)
+            .collect();
+        set_config_line(&mut lines, "a", "x", "3");
+        set_config_line(&mut lines, "b", "z", "4");
+        set_config_line(&mut lines, "c", "x", "5");
+        assert_eq!(
+            lines.join("\n"),
+            "[a]\n# x = 1\nx = 3\n\n[b]\ny = 1\nz = 4\n\n[c]\nx = 5"
+        );
+    }
+
+    #[test]
+    fn test_set_config_line_skips_multiline_strings() {
+        let text = "[a]\nnote = \"\"\"\n[b]\nx = 1\n\"\"\"\n\n[b]\ny = '''\nz = 2'''\n";
+        let mut lines: Vec<String> = text.lines().map(String::from).collect();
+        set_config_line(&mut lines, "a", "x", "3");
+        set_config_line(&mut lines, "b", "z", "4");
+        set_config_line(&mut lines, "b", "y", "\"short\"");
+        assert_eq!(
+            lines.join("\n"),
+            "[a]\nnote = \"\"\"\n[b]\nx = 1\n\"\"\"\nx = 3\n\n[b]\ny = \"short\"\nz = 4"
+        );
+        assert!(toml::from_str::<toml::Table>(&lines.join("\n")).is_ok());
+    }
+
     #[test]
     fn test_generate_default_commented_has_all_sections() {
         let content = Config::generate_default_commented();
@@ -600,6 +2601,7 @@ mod tests {
         assert!(content.contains("[summarization]"));
         assert!(content.contains("[monitoring]"));
         assert!(content.contains("[search]"));
+        assert!(content.contains("[hotkeys]"));
     }
 
     #[test]
@@ -608,6 +2610,7 @@ mod tests {
             endpoint: "https://example.openai.azure.com".to_string(),
             api_key: "super-secret-key-12345".to_string(),
             deployment: "whisper".to_string(),
+            ..Default::default()
         };
         let debug_output = format!("{:?}", config);
         assert!(
@@ -624,11 +2627,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_summarization_recipients() {
+        let config = SummarizationConfig {
+            recipient_address: "you@example.com, boss@example.com;;".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.recipients(),
+            vec!["you@example.com", "boss@example.com"]
+        );
+        assert!(SummarizationConfig::default().recipients().is_empty());
+    }
+
     #[test]
     fn test_summarization_config_debug_redacts_acs_key() {
         let config = SummarizationConfig {
             acs_api_key: "acs-super-secret-key-67890".to_string(),
             acs_endpoint: "https://my-acs.communication.azure.com".to_string(),
+            webhook_url: "https://hooks.slack.com/services/T0/B0/secret-token".to_string(),
+            api_key: "sk-provider-secret".to_string(),
             ..Default::default()
         };
         let debug_output = format!("{:?}", config);
@@ -636,6 +2654,14 @@ mod tests {
             !debug_output.contains("acs-super-secret-key-67890"),
             "Debug output should not contain the ACS API key"
         );
+        assert!(
+            !debug_output.contains("secret-token"),
+            "Debug output should not contain the webhook URL"
+        );
+        assert!(
+            !debug_output.contains("sk-provider-secret"),
+            "Debug output should not contain the provider API key"
+        );
         assert!(
             debug_output.contains("[REDACTED]"),
             "Debug output should show [REDACTED] for acs_api_key"
@@ -651,6 +2677,7 @@ mod tests {
         let mut config = Config::default();
         config.transcription.azure.api_key = "nested-secret-key".to_string();
         config.summarization.acs_api_key = "nested-acs-secret".to_string();
+        config.summarization.smtp.password = "nested-smtp-secret".to_string();
         let debug_output = format!("{:?}", config);
         assert!(
             !debug_output.contains("nested-secret-key"),
@@ -660,6 +2687,51 @@ mod tests {
             !debug_output.contains("nested-acs-secret"),
             "Config debug should not contain nested ACS API key"
         );
+        assert!(
+            !debug_output.contains("nested-smtp-secret"),
+            "Config debug should not contain nested SMTP password"
+        );
+    }
+
+    #[test]
+    fn test_source_label_lookup() {
+        let mut targets = TargetsConfig::default();
+        targets.labels.insert("mic".to_string(), "Desk".to_string());
+        targets
+            .labels
+            .insert("MS-Teams.exe".to_string(), "1on1".to_string());
+
+        assert_eq!(targets.source_label("mic", &[]), "Desk");
+        assert_eq!(targets.source_label("teams", &targets.processes), "1on1");
+        assert_eq!(targets.source_label("note", &[]), "note");
+    }
+
+    #[test]
+    fn test_source_label_is_filesystem_safe() {
+        let mut targets = TargetsConfig::default();
+        targets
+            .labels
+            .insert("teams".to_string(), " Team_Sync: A/B ".to_string());
+        targets.labels.insert("mic".to_string(), "__".to_string());
+
+        assert_eq!(targets.source_label("teams", &[]), "Team-Sync--A-B");
+        assert_eq!(targets.source_label("mic", &[]), "mic");
+    }
+
+    #[test]
+    fn test_machine_scoped() {
+        assert_eq!(
+            machine_scoped(".deskmic-state.json", None),
+            ".deskmic-state.json"
+        );
+        assert_eq!(
+            machine_scoped(".deskmic-state.json", Some("desk")),
+            ".deskmic-state.desk.json"
+        );
+        assert_eq!(
+            machine_scoped("2026-02-17.jsonl", Some("desk")),
+            "2026-02-17.desk.jsonl"
+        );
     }
 
     #[test]
@@ -667,9 +2739,13 @@ mod tests {
         let toml_str = r#"
             [monitoring]
             recording_gap_alert_mins = 15
+            watchdog_interval_secs = 2
+            gap_check_interval_secs = 5
         "#;
         let config: Config = toml::from_str(toml_str).unwrap();
         assert_eq!(config.monitoring.recording_gap_alert_mins, 15);
+        assert_eq!(config.monitoring.watchdog_interval_secs, 2);
+        assert_eq!(config.monitoring.gap_check_interval_secs, 5);
     }
 
     #[test]
@@ -713,4 +2789,38 @@ mod tests {
         let config: Config = toml::from_str(toml_str).unwrap();
         assert_eq!(config.search.chunk_gap_secs, 60);
     }
+
+    #[test]
+    fn test_parse_hotkey() {
+        assert_eq!(
+            parse_hotkey("Ctrl+Alt+P").unwrap(),
+            Hotkey {
+                modifiers: MOD_CONTROL | MOD_ALT,
+                vk: 'P' as u32
+            }
+        );
+        assert_eq!(
+            parse_hotkey(" win + shift + f9 ").unwrap(),
+            Hotkey {
+                modifiers: MOD_WIN | MOD_SHIFT,
+                vk: 0x78
+            }
+        );
+        assert_eq!(parse_hotkey("Control+1").unwrap().vk, '1' as u32);
+    }
+
+    #[test]
+    fn test_parse_hotkey_rejects_bad_specs() {
+        for spec in [
+            "P",
+            "Ctrl+Alt",
+            "Ctrl+P+Q",
+            "Ctrl+Ctrl+P",
+            "Ctrl+F25",
+            "Ctrl+Esc",
+            "",
+        ] {
+            assert!(parse_hotkey(spec).is_err(), "{:?} should be rejected", spec);
+        }
+    }
 }
@@ -0,0 +1,237 @@
+// Config hot-reload: polls the resolved config file and publishes the settings
+// that running threads can pick up without a restart.
+//
+// - `LiveSettings`: shared, swappable subset of the config (vad.*,
+//   capture.gain_db, storage.retention_days,
+//   monitoring.recording_gap_alert_mins).
+// - `run_config_watcher`: re-reads the file when its mtime changes and warns
+//   about edited fields that only take effect after a restart.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::Result;
+
+use crate::config::{Config, VadConfig};
+
+/// How often the config file's modification time is checked.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Config values that can change while the recorder is running.
+pub struct LiveSettings {
+    vad: RwLock<VadConfig>,
+    /// `capture.gain_db` as `f32` bits.
+    gain_db: AtomicU32,
+    retention_days: AtomicU32,
+    recording_gap_alert_mins: AtomicU32,
+}
+
+impl LiveSettings {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            vad: RwLock::new(config.vad.clone()),
+            gain_db: AtomicU32::new(config.capture.gain_db.to_bits()),
+            retention_days: AtomicU32::new(config.storage.retention_days),
+            recording_gap_alert_mins: AtomicU32::new(config.monitoring.recording_gap_alert_mins),
+        }
+    }
+
+    /// Swap in the live-reloadable values from `config`.
+    pub fn update(&self, config: &Config) {
+        *self.vad.write().unwrap_or_else(|e| e.into_inner()) = config.vad.clone();
+        self.gain_db
+            .store(config.capture.gain_db.to_bits(), Ordering::Relaxed);
+        self.retention_days
+            .store(config.storage.retention_days, Ordering::Relaxed);
+        self.recording_gap_alert_mins.store(
+            config.monitoring.recording_gap_alert_mins,
+            Ordering::Relaxed,
+        );
+    }
+
+    pub fn vad(&self) -> VadConfig {
+        self.vad.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn gain_db(&self) -> f32 {
+        f32::from_bits(self.gain_db.load(Ordering::Relaxed))
+    }
+
+    pub fn retention_days(&self) -> u32 {
+        self.retention_days.load(Ordering::Relaxed)
+    }
+
+    pub fn recording_gap_alert_mins(&self) -> u32 {
+        self.recording_gap_alert_mins.load(Ordering::Relaxed)
+    }
+}
+
+/// Whether the dotted config path is applied live by `LiveSettings`.
+fn is_live_field(path: &str) -> bool {
+    (path.starts_with("vad.") && path != "vad.merge_gap_secs")
+        || path == "capture.gain_db"
+        || path == "storage.retention_days"
+        || path == "monitoring.recording_gap_alert_mins"
+}
+
+/// Dotted paths of fields that differ between `old` and `new` but are only
+/// read at startup, so changing them needs a restart.
+pub fn restart_required_changes(old: &Config, new: &Config) -> Vec<String> {
+    let (old, new) = (flatten(old), flatten(new));
+    let mut paths: Vec<String> = old
+        .keys()
+        .chain(new.keys())
+        .filter(|path| !is_live_field(path) && old.get(*path) != new.get(*path))
+        .cloned()
+        .collect();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// Config as a map of dotted field path → value.
+fn flatten(config: &Config) -> BTreeMap<String, toml::Value> {
+    fn walk(prefix: &str, table: toml::Table, out: &mut BTreeMap<String, toml::Value>) {
+        for (key, value) in table {
+            let path = if prefix.is_empty() {
+                key
+            } else {
+                format!("{}.{}", prefix, key)
+            };
+            match value {
+                toml::Value::Table(sub) => walk(&path, sub, out),
+                other => {
+                    out.insert(path, other);
+                }
+            }
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    if let Ok(table) = toml::Table::try_from(config) {
+        walk("", table, &mut out);
+    }
+    out
+}
+
+/// Re-read and validate the config at `path`.
+fn reload(path: &Path) -> Result<Config> {
+    let (config, _) = Config::load_with_path(Some(path))?;
+    config
+        .validate()
+        .map_err(|errors| anyhow::anyhow!(errors.join("; ")))?;
+    Ok(config)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Watch `path` for changes until shutdown, pushing live-reloadable settings
+/// into `live`. Invalid edits are logged and ignored, keeping the last good
+/// config.
+pub fn run_config_watcher(
+    path: PathBuf,
+    mut current: Config,
+    live: Arc<LiveSettings>,
+    shutdown: Arc<AtomicBool>,
+) {
+    let mut last_modified = modified(&path);
+
+    while !shutdown.load(Ordering::Relaxed) {
+        let start = Instant::now();
+        while start.elapsed() < POLL_INTERVAL && !shutdown.load(Ordering::Relaxed) {
+            std::thread::sleep(Duration::from_millis(500));
+        }
+
+        let now_modified = modified(&path);
+        if now_modified == last_modified {
+            continue;
+        }
+        last_modified = now_modified;
+
+        match reload(&path) {
+            Ok(new) => {
+                for field in restart_required_changes(&current, &new) {
+                    tracing::warn!("Config change to {} takes effect after a restart", field);
+                }
+                live.update(&new);
+                tracing::info!("Reloaded config from {}", path.display());
+                current = new;
+            }
+            Err(e) => {
+                tracing::warn!("Ignoring config change in {}: {:#}", path.display(), e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_live_fields_do_not_require_restart() {
+        let old = Config::default();
+        let mut new = old.clone();
+        new.vad.speech_threshold = 0.8;
+        new.capture.gain_db = 6.0;
+        new.storage.retention_days = 7;
+        new.monitoring.recording_gap_alert_mins = 15;
+
+        assert!(restart_required_changes(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn test_restart_required_fields_are_reported() {
+        let old = Config::default();
+        let mut new = old.clone();
+        new.capture.sample_rate = 8000;
+        new.transcription.azure.endpoint = "https://example.openai.azure.com".to_string();
+        new.storage.max_disk_usage_gb = Some(10.0);
+        new.vad.merge_gap_secs = 2.0;
+
+        assert_eq!(
+            restart_required_changes(&old, &new),
+            vec![
+                "capture.sample_rate",
+                "storage.max_disk_usage_gb",
+                "transcription.azure.endpoint",
+                "vad.merge_gap_secs",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_update_swaps_live_values() {
+        let mut config = Config::default();
+        let live = LiveSettings::new(&config);
+
+        config.vad.silence_threshold_secs = 1.5;
+        config.capture.gain_db = -3.0;
+        config.storage.retention_days = 3;
+        config.monitoring.recording_gap_alert_mins = 0;
+        live.update(&config);
+
+        assert_eq!(live.gain_db(), -3.0);
+
+        assert_eq!(live.vad().silence_threshold_secs, 1.5);
+        assert_eq!(live.retention_days(), 3);
+        assert_eq!(live.recording_gap_alert_mins(), 0);
+    }
+
+    #[test]
+    fn test_reload_rejects_invalid_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("deskmic.toml");
+        std::fs::write(&path, "[vad]\nspeech_threshold = 5.0\n").unwrap();
+        assert!(reload(&path).is_err());
+
+        std::fs::write(&path, "[vad]\nspeech_threshold = 0.6\n").unwrap();
+        let config = reload(&path).unwrap();
+        assert!((config.vad.speech_threshold - 0.6).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,384 @@
+// `deskmic doctor`: self-checks for the silent failure modes new installs hit
+// (no microphone access, missing model, unset credentials, unwritable output
+// directory, a recorder already running) with a one-line fix for each.
+
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::config::{Config, SummarizationConfig};
+
+/// Outcome of one check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    /// Works, but something optional is missing or degraded.
+    Warn,
+    Fail,
+}
+
+/// One line of the doctor report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// How to fix a warning or failure.
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &str, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &str, detail: impl Into<String>, hint: &str) -> Self {
+        Self {
+            status: CheckStatus::Warn,
+            hint: Some(hint.to_string()),
+            ..Self::pass(name, detail)
+        }
+    }
+
+    fn fail(name: &str, detail: impl Into<String>, hint: &str) -> Self {
+        Self {
+            status: CheckStatus::Fail,
+            ..Self::warn(name, detail, hint)
+        }
+    }
+}
+
+/// Entry point for `deskmic doctor`: run every check and print the report.
+/// Fails if any check failed.
+pub fn run_doctor(config: &Config, config_path: Option<&Path>) -> Result<()> {
+    let mut checks = vec![check_config(config, config_path)];
+    checks.push(check_output_dir(&config.output.directory));
+    checks.extend(check_microphones(config));
+    checks.push(check_app_loopback(config));
+    checks.push(check_transcription(config));
+    checks.push(check_summary_provider(config));
+    checks.push(check_summary_delivery(config));
+    checks.push(check_running_instance());
+
+    println!("deskmic doctor:");
+    print!("{}", format_report(&checks));
+
+    let failed = checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Fail)
+        .count();
+    if failed > 0 {
+        anyhow::bail!("{} check(s) failed", failed);
+    }
+    Ok(())
+}
+
+/// Render checks as `[PASS] name: detail` lines, hints indented below, and a
+/// closing tally.
+fn format_report(checks: &[Check]) -> String {
+    let mut out = String::new();
+    for check in checks {
+        let label = match check.status {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Warn => "WARN",
+            CheckStatus::Fail => "FAIL",
+        };
+        out.push_str(&format!("  [{}] {}: {}\n", label, check.name, check.detail));
+        if let Some(hint) = &check.hint {
+            out.push_str(&format!("         -> {}\n", hint));
+        }
+    }
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    out.push_str(&format!(
+        "\n{} passed, {} warning(s), {} failed\n",
+        count(CheckStatus::Pass),
+        count(CheckStatus::Warn),
+        count(CheckStatus::Fail)
+    ));
+    out
+}
+
+fn check_config(config: &Config, config_path: Option<&Path>) -> Check {
+    let source = match config_path {
+        Some(path) => path.display().to_string(),
+        None => "built-in defaults".to_string(),
+    };
+    match config.validate() {
+        Ok(()) => Check::pass("Config", format!("valid ({})", source)),
+        Err(errors) => Check::fail(
+            "Config",
+            format!("{} ({})", errors.join("; "), source),
+            "Fix these settings in the config file (deskmic won't record until they're valid)",
+        ),
+    }
+}
+
+/// The output directory exists (or can be created) and accepts new files.
+fn check_output_dir(dir: &Path) -> Check {
+    let probe = dir.join(".deskmic-doctor");
+    let result = std::fs::create_dir_all(dir)
+        .and_then(|()| std::fs::write(&probe, b"ok"))
+        .and_then(|()| std::fs::remove_file(&probe));
+    match result {
+        Ok(()) => Check::pass("Output directory", format!("{} is writable", dir.display())),
+        Err(e) => Check::fail(
+            "Output directory",
+            format!("can't write to {}: {}", dir.display(), e),
+            "Set [output] directory to a folder you can write to",
+        ),
+    }
+}
+
+/// Each configured microphone can be opened.
+#[cfg(target_os = "windows")]
+fn check_microphones(config: &Config) -> Vec<Check> {
+    use crate::audio::capture::MicCapture;
+
+    let sample_rate = config.capture.sample_rate;
+    let timeout_ms = config.capture.event_timeout_ms;
+    config
+        .mic_sources()
+        .into_iter()
+        .map(|mic| {
+            let name = format!("Microphone ({})", mic.source);
+            let device = mic.device.as_deref().unwrap_or("default device");
+            let opened = match (&mic.device, mic.exact) {
+                (Some(device), true) => {
+                    MicCapture::new_exact(sample_rate, device, timeout_ms).map(|_| ())
+                }
+                (device, _) => {
+                    MicCapture::new(sample_rate, device.as_deref(), timeout_ms).map(|_| ())
+                }
+            };
+            match opened {
+                Ok(()) => Check::pass(&name, format!("opened {}", device)),
+                Err(e) => Check::fail(
+                    &name,
+                    format!("can't open {}: {:#}", device, e),
+                    "Allow desktop apps to use the microphone (Settings > Privacy > Microphone) \
+                     and check capture.device against `deskmic devices`",
+                ),
+            }
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "windows"))]
+fn check_microphones(config: &Config) -> Vec<Check> {
+    if config.mic_sources().is_empty() {
+        return Vec::new();
+    }
+    vec![Check::warn(
+        "Microphone",
+        "audio capture is only supported on Windows",
+        "Run deskmic on Windows to record",
+    )]
+}
+
+/// App audio capture (Teams etc.) needs Windows 11's process loopback API.
+fn check_app_loopback(config: &Config) -> Check {
+    use crate::audio::teams_monitor::{app_loopback_supported, app_sources};
+
+    if app_sources(&config.targets).is_empty() {
+        return Check::pass("App audio", "no app targets configured");
+    }
+    let build = sysinfo::System::kernel_version();
+    if app_loopback_supported(build.as_deref()) {
+        Check::pass("App audio", "process loopback capture is supported")
+    } else {
+        Check::warn(
+            "App audio",
+            format!(
+                "needs Windows build {} or later (this is {})",
+                crate::audio::teams_monitor::MIN_APP_LOOPBACK_BUILD,
+                build.unwrap_or_default()
+            ),
+            "Set targets.system_loopback = true to record all desktop audio instead",
+        )
+    }
+}
+
+/// The configured transcription backend has what it needs.
+fn check_transcription(config: &Config) -> Check {
+    match config.transcription.backend.as_str() {
+        "azure" => {
+            let azure = &config.transcription.azure;
+            let has_key = !azure.api_key.is_empty() || std::env::var("DESKMIC_AZURE_KEY").is_ok();
+            if azure.endpoint.is_empty() || !has_key {
+                Check::fail(
+                    "Transcription",
+                    "Azure backend without an endpoint or API key",
+                    "Set [transcription.azure] endpoint and api_key (or DESKMIC_AZURE_KEY)",
+                )
+            } else {
+                Check::pass("Transcription", format!("Azure ({})", azure.endpoint))
+            }
+        }
+        _ => check_local_model(&config.transcription.model),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn check_local_model(model: &str) -> Check {
+    let resolved = crate::transcribe::whisper_local::resolve_model_path(model).and_then(|path| {
+        crate::transcribe::models::check_model_file(&path)?;
+        Ok(path)
+    });
+    match resolved {
+        Ok(path) => Check::pass("Transcription", format!("local model {}", path.display())),
+        Err(e) => Check::fail(
+            "Transcription",
+            format!("{:#}", e),
+            "Run `deskmic setup` to download a Whisper model",
+        ),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn check_local_model(model: &str) -> Check {
+    Check::warn(
+        "Transcription",
+        format!("local model '{}' can only be used on Windows", model),
+        "Use backend = \"azure\" on this platform",
+    )
+}
+
+/// Summaries can reach an LLM. Optional, so problems are warnings.
+fn check_summary_provider(config: &Config) -> Check {
+    match crate::summarize::llm::build_provider(config) {
+        Ok(_) => Check::pass(
+            "Summaries",
+            format!("{} provider configured", config.summarization.provider),
+        ),
+        Err(e) => Check::warn(
+            "Summaries",
+            format!("{:#}", e),
+            "Configure [summarization] (or run `deskmic setup`) to get daily summaries",
+        ),
+    }
+}
+
+/// Summaries have somewhere to go.
+fn check_summary_delivery(config: &Config) -> Check {
+    let bad_urls = invalid_endpoints(&config.summarization);
+    if !bad_urls.is_empty() {
+        return Check::warn(
+            "Summary delivery",
+            bad_urls.join("; "),
+            "Use full URLs including https://",
+        );
+    }
+    match crate::summarize::transport::build_transport(&config.summarization) {
+        Ok(transport) => Check::pass("Summary delivery", format!("via {}", transport.name())),
+        Err(e) => Check::warn(
+            "Summary delivery",
+            format!("{:#}", e),
+            "Fill in the [summarization] settings for the chosen transport, then run \
+             `deskmic test-email`",
+        ),
+    }
+}
+
+/// Summary endpoints that are set but aren't http(s) URLs.
+fn invalid_endpoints(summarization: &SummarizationConfig) -> Vec<String> {
+    [
+        ("summarization.acs_endpoint", &summarization.acs_endpoint),
+        ("summarization.webhook_url", &summarization.webhook_url),
+    ]
+    .into_iter()
+    .filter(|(_, url)| !url.is_empty())
+    .filter(|(_, url)| match url::Url::parse(url) {
+        Ok(u) => !matches!(u.scheme(), "http" | "https") || u.host_str().is_none(),
+        Err(_) => true,
+    })
+    .map(|(field, url)| format!("{} is not a valid URL ({:?})", field, url))
+    .collect()
+}
+
+/// Whether a recorder already holds the single-instance mutex.
+#[cfg(target_os = "windows")]
+fn check_running_instance() -> Check {
+    use windows::core::w;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{OpenMutexW, SYNCHRONIZATION_SYNCHRONIZE};
+
+    match unsafe { OpenMutexW(SYNCHRONIZATION_SYNCHRONIZE, false, w!("Global\\deskmic")) } {
+        Ok(handle) => {
+            unsafe {
+                let _ = CloseHandle(handle);
+            }
+            Check::warn(
+                "Recorder",
+                "a deskmic recorder is already running; starting another one will exit",
+                "Quit it from the tray icon (or end deskmic.exe in Task Manager) first",
+            )
+        }
+        Err(_) => Check::pass("Recorder", "no other recorder running"),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn check_running_instance() -> Check {
+    Check::pass("Recorder", "single-instance check only applies on Windows")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_output_dir() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let check = check_output_dir(&tmp.path().join("recordings"));
+        assert_eq!(check.status, CheckStatus::Pass);
+        assert!(!tmp.path().join("recordings/.deskmic-doctor").exists());
+
+        // A file where the directory should be.
+        let file = tmp.path().join("not-a-dir");
+        std::fs::write(&file, b"x").unwrap();
+        let check = check_output_dir(&file);
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.hint.is_some());
+    }
+
+    #[test]
+    fn test_check_config_reports_validation_errors() {
+        let mut config = Config::default();
+        assert_eq!(check_config(&config, None).status, CheckStatus::Pass);
+        config.storage.retention_days = 0;
+        let check = check_config(&config, None);
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.detail.contains("retention_days"), "{}", check.detail);
+    }
+
+    #[test]
+    fn test_invalid_endpoints() {
+        let mut summarization = SummarizationConfig::default();
+        assert!(invalid_endpoints(&summarization).is_empty());
+        summarization.acs_endpoint = "https://example.communication.azure.com".to_string();
+        summarization.webhook_url = "hooks.slack.com/services/x".to_string();
+        let errors = invalid_endpoints(&summarization);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("webhook_url"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn test_format_report() {
+        let checks = vec![
+            Check::pass("Config", "valid"),
+            Check::warn("Summaries", "no API key", "Set api_key"),
+        ];
+        assert_eq!(
+            format_report(&checks),
+            "  [PASS] Config: valid\n\
+             \x20 [WARN] Summaries: no API key\n\
+             \x20        -> Set api_key\n\
+             \n1 passed, 1 warning(s), 0 failed\n"
+        );
+    }
+}
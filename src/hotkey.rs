@@ -0,0 +1,33 @@
+// Global hotkeys, e.g. `hotkeys.pause_toggle = "Ctrl+Alt+P"`.
+//
+// Parsing (`parse_hotkey`) lives in config so `Config::validate` can check
+// the hotkeys. `register_hotkey` is Windows-only: `RegisterHotKey` without a
+// window posts `WM_HOTKEY` to the calling thread's queue, which the tray's
+// pump reads.
+
+pub use crate::config::{parse_hotkey, Hotkey};
+
+/// Register `hotkey` for the current thread under `id`. Fails when another
+/// application already owns the combination.
+#[cfg(target_os = "windows")]
+pub fn register_hotkey(id: i32, hotkey: Hotkey) -> windows::core::Result<()> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        RegisterHotKey, HOT_KEY_MODIFIERS, MOD_NOREPEAT,
+    };
+    unsafe {
+        RegisterHotKey(
+            None,
+            id,
+            HOT_KEY_MODIFIERS(hotkey.modifiers) | MOD_NOREPEAT,
+            hotkey.vk,
+        )
+    }
+}
+
+/// Release a hotkey registered with `register_hotkey` on this thread.
+#[cfg(target_os = "windows")]
+pub fn unregister_hotkey(id: i32) {
+    unsafe {
+        let _ = windows::Win32::UI::Input::KeyboardAndMouse::UnregisterHotKey(None, id);
+    }
+}
@@ -2,8 +2,14 @@ pub mod audio;
 pub mod cli;
 pub mod commands;
 pub mod config;
+pub mod config_watch;
+pub mod doctor;
+pub mod hotkey;
+pub mod logging;
 pub mod monitoring;
 pub mod recorder;
+pub mod session;
+pub mod shutdown;
 pub mod storage;
 pub mod summarize;
 pub mod transcribe;
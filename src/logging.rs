@@ -0,0 +1,98 @@
+// Logging setup: console output in text or JSON, plus optional rotating log
+// files.
+//
+// The subscriber is installed before the config is loaded so config overrides
+// are logged; a file layer is attached afterwards, once its directory is known.
+// The recorder always logs to a file, since it usually runs without a console.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Layer, Registry};
+
+/// Daily log files kept before the oldest is deleted.
+const MAX_LOG_FILES: usize = 7;
+
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Where the recorder logs when `--log-file` isn't given:
+/// `%APPDATA%\deskmic\logs`.
+pub fn default_log_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("deskmic").join("logs"))
+}
+
+/// Handle to the installed subscriber.
+pub struct Logging {
+    json: bool,
+    file_layer: reload::Handle<Option<BoxedLayer>, Registry>,
+}
+
+/// Install the global subscriber, logging to stdout. `format` is "text" or
+/// "json" (one JSON object per line).
+pub fn init(format: &str) -> Result<Logging> {
+    let json = match format {
+        "text" => false,
+        "json" => true,
+        other => anyhow::bail!("Unknown log format: {} (expected text or json)", other),
+    };
+
+    let (file_layer, handle) = reload::Layer::new(None::<BoxedLayer>);
+    let stdout_layer = if json {
+        fmt::layer().json().boxed()
+    } else {
+        fmt::layer().boxed()
+    };
+    tracing_subscriber::registry()
+        .with(file_layer)
+        .with(stdout_layer)
+        .with(EnvFilter::from_default_env().add_directive("deskmic=info".parse()?))
+        .try_init()?;
+
+    Ok(Logging {
+        json,
+        file_layer: handle,
+    })
+}
+
+impl Logging {
+    /// Also write logs to `<dir>/<prefix>.<date>.log`, rotated daily and
+    /// keeping the last `MAX_LOG_FILES` days.
+    pub fn log_to_dir(&self, dir: &Path, prefix: &str) -> Result<()> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create log directory {}", dir.display()))?;
+        let appender = RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(prefix)
+            .filename_suffix("log")
+            .max_log_files(MAX_LOG_FILES)
+            .build(dir)
+            .with_context(|| format!("Failed to open log file in {}", dir.display()))?;
+
+        let layer = fmt::layer().with_writer(appender).with_ansi(false);
+        let layer: BoxedLayer = if self.json {
+            layer.json().boxed()
+        } else {
+            layer.boxed()
+        };
+        self.file_layer
+            .reload(Some(layer))
+            .context("Failed to attach log file")?;
+        tracing::info!("Logging to {}", dir.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_init_rejects_unknown_format() {
+        // Fails before installing anything, so it can't clash with other tests.
+        let err = init("xml").err().unwrap();
+        assert!(err.to_string().contains("Unknown log format"), "{}", err);
+    }
+}
@@ -3,7 +3,7 @@
 #![cfg_attr(target_os = "windows", windows_subsystem = "windows")]
 
 use clap::Parser;
-use deskmic::cli::{Cli, Commands};
+use deskmic::cli::{Cli, Commands, TranscriptsCommand};
 use deskmic::config::Config;
 
 /// Re-attach to the parent console (if launched from a terminal) so that
@@ -103,14 +103,33 @@ fn main() -> anyhow::Result<()> {
     };
 
     // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive("deskmic=info".parse()?),
-        )
-        .init();
+    let logging = deskmic::logging::init(&cli.log_format)?;
 
     let (config, resolved_config_path) = Config::load_with_path(cli.config.as_deref())?;
+    // Doctor reports validation errors itself, alongside its other checks.
+    let doctor = matches!(cli.command, Some(Commands::Doctor));
+    if let (false, Err(errors)) = (doctor, config.validate()) {
+        eprintln!("Invalid configuration:");
+        for error in &errors {
+            eprintln!("  - {}", error);
+        }
+        std::process::exit(1);
+    }
+    let recording = matches!(cli.command, None | Some(Commands::Record));
+    if cli.log_file {
+        logging.log_to_dir(&config.output.directory.join("logs"), "deskmic")?;
+    } else if recording {
+        // Started from Startup there is no console, so the recorder keeps a
+        // log file by default; failing to open one shouldn't stop recording.
+        match deskmic::logging::default_log_dir() {
+            Some(dir) => {
+                if let Err(e) = logging.log_to_dir(&dir, "deskmic") {
+                    tracing::warn!("File logging disabled: {:#}", e);
+                }
+            }
+            None => tracing::warn!("File logging disabled: no config directory found"),
+        }
+    }
 
     match cli.command.unwrap_or(Commands::Record) {
         Commands::Record => {
@@ -119,7 +138,14 @@ fn main() -> anyhow::Result<()> {
         }
         Commands::Install => deskmic::commands::install_startup(),
         Commands::Uninstall => deskmic::commands::uninstall_startup(),
-        Commands::Status => deskmic::commands::show_status(&config.output.directory),
+        Commands::Status { json } => deskmic::commands::show_status(
+            &config.output.directory,
+            config.output.machine_id().as_deref(),
+            &config.output.filename_template,
+            json,
+        ),
+        Commands::Devices => deskmic::commands::list_devices(),
+        Commands::Doctor => deskmic::doctor::run_doctor(&config, resolved_config_path.as_deref()),
         Commands::Transcribe { watch, backend } => {
             if watch {
                 deskmic::transcribe::runner::run_transcribe_watch(&config, backend.as_deref())
@@ -127,9 +153,82 @@ fn main() -> anyhow::Result<()> {
                 deskmic::transcribe::runner::run_transcribe_oneshot(&config, backend.as_deref())
             }
         }
-        Commands::Summarize { range } => deskmic::summarize::runner::run_summarize(&config, &range),
-        Commands::Setup => deskmic::setup::run_setup(),
+        Commands::Summarize {
+            range,
+            output,
+            exclude_hours,
+            exclude_source,
+            rolling,
+            dry_run,
+        } => {
+            let mut config = config;
+            if !exclude_hours.is_empty() {
+                config.summarization.exclude_hours = exclude_hours;
+            }
+            if !exclude_source.is_empty() {
+                config.summarization.exclude_sources = exclude_source;
+            }
+            if rolling {
+                deskmic::summarize::runner::run_rolling_summary(&config, output.as_deref())
+            } else {
+                deskmic::summarize::runner::run_summarize(
+                    &config,
+                    &range,
+                    output.as_deref(),
+                    dry_run,
+                )
+            }
+        }
+        Commands::Clip { duration } => deskmic::audio::clip::run_clip(&config, duration),
+        Commands::TestCapture { duration, output } => {
+            deskmic::audio::test_capture::run_test_capture(&config, duration, &output)
+        }
+        Commands::TestEmail => deskmic::summarize::transport::run_test_email(&config.summarization),
+        Commands::Setup {
+            noninteractive: false,
+            ..
+        } => deskmic::setup::run_setup(),
+        Commands::Setup {
+            noninteractive: true,
+            model,
+            install_startup,
+            force,
+            update_config,
+            azure_endpoint,
+            azure_api_key,
+            chat_deployment,
+            acs_endpoint,
+            acs_api_key,
+            sender_address,
+            recipient_address,
+        } => deskmic::setup::run_setup_noninteractive(&deskmic::setup::SetupAnswers {
+            model,
+            install_startup,
+            force,
+            update_config,
+            azure_endpoint,
+            azure_api_key,
+            chat_deployment,
+            acs_endpoint,
+            acs_api_key,
+            sender_address,
+            recipient_address,
+        }),
         Commands::Index => deskmic::search::run_index(&config),
+        Commands::MigrateTranscripts => deskmic::transcribe::store::run_migrate(&config),
+        Commands::Transcripts {
+            command:
+                TranscriptsCommand::Export {
+                    date,
+                    format,
+                    output,
+                },
+        } => deskmic::summarize::export::run_export(
+            &config,
+            date.as_deref(),
+            &format,
+            output.as_deref(),
+        ),
         Commands::Search {
             query,
             from,
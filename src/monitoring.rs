@@ -1,20 +1,133 @@
 // Pipeline health monitoring: watchdog, recording gap timer, toast notifications.
 //
-// - `run_watchdog`: checks pipeline thread health, triggers self-restart on failure.
-// - `run_gap_timer`: checks for recording gaps, fires toast notifications.
-// - `send_toast`: Windows toast notification helper.
-
+// - `run_watchdog`: checks pipeline thread health, restarts a dead thread in-process
+//   and falls back to a whole-process restart if that keeps failing.
+// - `run_gap_timer`: checks for stalled capture sources (via `Heartbeats`) and
+//   recording gaps, fires toast notifications.
+// - `send_toast`: Windows toast notification helper; identical toasts within
+//   `TOAST_DEDUP_WINDOW` are suppressed by `ToastThrottle`.
+// - `latest_recording`: the newest recording on disk (for the tray).
+
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use std::time::{Duration, SystemTime};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use chrono::NaiveDate;
+
+use crate::audio::levels::LevelMeter;
+use crate::config_watch::LiveSettings;
+
+/// AppUserModelID that deskmic's toasts are attributed to ("deskmic" in the
+/// notification and in the Action Center).
+#[cfg(target_os = "windows")]
+pub const APP_USER_MODEL_ID: &str = "deskmic.deskmic";
+
+/// Windows PowerShell's AUMID, which works without registration; used if
+/// registering ours fails.
+#[cfg(target_os = "windows")]
+const POWERSHELL_APP_USER_MODEL_ID: &str =
+    "{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\\WindowsPowerShell\\v1.0\\powershell.exe";
+
+/// The AUMID to show toasts under. Registers deskmic's the first time it's
+/// called in a process, falling back to PowerShell's if that fails.
+#[cfg(target_os = "windows")]
+pub fn toast_app_id() -> &'static str {
+    static APP_ID: OnceLock<&'static str> = OnceLock::new();
+    APP_ID.get_or_init(|| match register_app_user_model_id() {
+        Ok(()) => APP_USER_MODEL_ID,
+        Err(e) => {
+            tracing::warn!(
+                "Failed to register notification app id, toasts will show as PowerShell: {:?}",
+                e
+            );
+            POWERSHELL_APP_USER_MODEL_ID
+        }
+    })
+}
+
+/// Register `APP_USER_MODEL_ID` for the current user. An unpackaged app may
+/// raise toasts under an AUMID listed in
+/// `HKCU\Software\Classes\AppUserModelId` with a `DisplayName`.
+#[cfg(target_os = "windows")]
+fn register_app_user_model_id() -> windows::core::Result<()> {
+    use windows::core::{w, HSTRING};
+    use windows::Win32::System::Registry::{RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ};
+
+    let subkey = HSTRING::from(format!(
+        "Software\\Classes\\AppUserModelId\\{}",
+        APP_USER_MODEL_ID
+    ));
+    let display_name: Vec<u16> = "deskmic".encode_utf16().chain(Some(0)).collect();
+    unsafe {
+        RegSetKeyValueW(
+            HKEY_CURRENT_USER,
+            &subkey,
+            w!("DisplayName"),
+            REG_SZ.0,
+            Some(display_name.as_ptr().cast()),
+            (display_name.len() * 2) as u32,
+        )
+        .ok()
+    }
+}
+
+/// Identical toasts (same title and body) within this window are dropped.
+const TOAST_DEDUP_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// Remembers when each distinct toast was last shown so bursts of the same
+/// notification (e.g. during a sleep/wake device storm) show only once.
+pub struct ToastThrottle {
+    window: Duration,
+    last_sent: HashMap<String, Instant>,
+}
+
+impl ToastThrottle {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            last_sent: HashMap::new(),
+        }
+    }
+
+    /// Whether a toast with `key` may be shown at `now`; if so, records it.
+    pub fn allow(&mut self, key: &str, now: Instant) -> bool {
+        let window = self.window;
+        // Forget expired entries so the map doesn't grow without bound.
+        self.last_sent
+            .retain(|_, sent| now.saturating_duration_since(*sent) < window);
+        if self.last_sent.contains_key(key) {
+            return false;
+        }
+        self.last_sent.insert(key.to_string(), now);
+        true
+    }
+}
+
+/// Sends a toast notification, unless an identical one was sent within
+/// `TOAST_DEDUP_WINDOW`.
+pub fn send_toast(title: &str, body: &str) {
+    static THROTTLE: OnceLock<Mutex<ToastThrottle>> = OnceLock::new();
+    let throttle = THROTTLE.get_or_init(|| Mutex::new(ToastThrottle::new(TOAST_DEDUP_WINDOW)));
+    let key = format!("{}\n{}", title, body);
+    let allowed = throttle
+        .lock()
+        .map(|mut t| t.allow(&key, Instant::now()))
+        .unwrap_or(true);
+    if allowed {
+        show_toast(title, body);
+    } else {
+        tracing::debug!("Suppressed repeated toast: {}", title);
+    }
+}
 
-/// Sends a Windows toast notification with the given title and body.
+/// Shows a Windows toast notification with the given title and body.
 ///
 /// Uses the Windows `ToastNotification` API via the `windows` crate.
 /// Falls back to tracing::warn if toast fails (e.g. no notification permission).
 #[cfg(target_os = "windows")]
-pub fn send_toast(title: &str, body: &str) {
+fn show_toast(title: &str, body: &str) {
     use windows::Data::Xml::Dom::XmlDocument;
     use windows::UI::Notifications::{ToastNotification, ToastNotificationManager};
 
@@ -37,10 +150,8 @@ pub fn send_toast(title: &str, body: &str) {
 
         let toast = ToastNotification::CreateToastNotification(&doc)?;
 
-        // Use a well-known AppUserModelId. The Windows PowerShell AUMID works
-        // without needing to register our own.
         let notifier = ToastNotificationManager::CreateToastNotifierWithId(
-            &windows::core::HSTRING::from("{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\\WindowsPowerShell\\v1.0\\powershell.exe"),
+            &windows::core::HSTRING::from(toast_app_id()),
         )?;
 
         notifier.Show(&toast)?;
@@ -54,7 +165,7 @@ pub fn send_toast(title: &str, body: &str) {
 
 /// No-op on non-Windows platforms.
 #[cfg(not(target_os = "windows"))]
-pub fn send_toast(title: &str, body: &str) {
+fn show_toast(title: &str, body: &str) {
     tracing::info!("Toast (non-Windows): {} - {}", title, body);
 }
 
@@ -67,52 +178,101 @@ fn xml_escape(s: &str) -> String {
         .replace('\'', "&apos;")
 }
 
-/// Watchdog: monitors pipeline thread health. If any thread has exited unexpectedly,
-/// fires a toast and triggers a self-restart.
+/// In-process restarts allowed per thread within `RESTART_WINDOW` before the
+/// watchdog gives up and restarts the whole process.
+const MAX_IN_PROCESS_RESTARTS: usize = 3;
+const RESTART_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// A thread the watchdog keeps alive.
+pub struct WatchedThread {
+    pub name: String,
+    pub is_alive: Box<dyn Fn() -> bool + Send>,
+    /// Spawns a replacement for the dead thread.
+    pub restart: Box<dyn FnMut() -> anyhow::Result<()> + Send>,
+}
+
+/// Watchdog: monitors pipeline thread health. A thread that has exited
+/// unexpectedly is restarted in-process via its `restart` closure, which keeps
+/// the other pipelines and the file writer's open recordings intact. If that
+/// fails, or the thread keeps dying, it fires a toast and restarts the process.
 ///
-/// `thread_handles` is a list of (name, is_finished_fn) for each thread to monitor.
-/// Using closures allows testing without real threads.
-pub fn run_watchdog<F>(
+/// Checks every `interval`. Using closures allows testing without real threads.
+pub fn run_watchdog(
     shutdown: Arc<AtomicBool>,
-    is_any_thread_dead: F,
-) where
-    F: Fn() -> Option<String>, // returns Some(thread_name) if a thread has died
-{
+    mut threads: Vec<WatchedThread>,
+    interval: Duration,
+) {
+    let mut restart_history: Vec<Vec<Instant>> = vec![Vec::new(); threads.len()];
+
     while !shutdown.load(Ordering::Relaxed) {
-        std::thread::sleep(Duration::from_secs(10));
+        std::thread::sleep(interval);
 
         if shutdown.load(Ordering::Relaxed) {
             break;
         }
 
-        if let Some(dead_thread) = is_any_thread_dead() {
-            tracing::error!(
-                "Watchdog: {} thread has died unexpectedly, triggering restart",
-                dead_thread
-            );
-            send_toast(
-                "deskmic: Recording stopped",
-                &format!(
-                    "The {} thread died unexpectedly. Restarting...",
-                    dead_thread
-                ),
-            );
+        for (thread, history) in threads.iter_mut().zip(&mut restart_history) {
+            if (thread.is_alive)() {
+                continue;
+            }
 
-            // Attempt self-restart.
-            match self_restart() {
-                Ok(()) => {
-                    tracing::info!("Watchdog: self-restart initiated, exiting current process");
-                    std::process::exit(0);
-                }
-                Err(e) => {
-                    tracing::error!("Watchdog: self-restart failed: {:?}", e);
-                    send_toast(
-                        "deskmic: Restart failed",
-                        "Recording failed and could not auto-restart. Please restart manually.",
-                    );
-                    // Continue running in degraded state rather than exiting.
+            if allow_in_process_restart(history, Instant::now()) {
+                tracing::warn!(
+                    "Watchdog: {} thread has died unexpectedly, restarting it",
+                    thread.name
+                );
+                match (thread.restart)() {
+                    Ok(()) => continue,
+                    Err(e) => {
+                        tracing::error!("Watchdog: restarting {} failed: {:?}", thread.name, e)
+                    }
                 }
+            } else {
+                tracing::error!(
+                    "Watchdog: {} thread died {} times within {} minutes",
+                    thread.name,
+                    MAX_IN_PROCESS_RESTARTS + 1,
+                    RESTART_WINDOW.as_secs() / 60
+                );
             }
+            restart_process(&thread.name);
+        }
+    }
+}
+
+/// Whether a thread with these recent in-process restarts may be restarted
+/// in-process again at `now`; if so, records the restart.
+fn allow_in_process_restart(history: &mut Vec<Instant>, now: Instant) -> bool {
+    history.retain(|t| now.saturating_duration_since(*t) < RESTART_WINDOW);
+    if history.len() >= MAX_IN_PROCESS_RESTARTS {
+        return false;
+    }
+    history.push(now);
+    true
+}
+
+/// Last resort: start a fresh deskmic process and exit this one.
+fn restart_process(dead_thread: &str) {
+    send_toast(
+        "deskmic: Recording stopped",
+        &format!(
+            "The {} thread died unexpectedly. Restarting...",
+            dead_thread
+        ),
+    );
+
+    match self_restart() {
+        Ok(()) => {
+            tracing::info!("Watchdog: self-restart initiated, exiting current process");
+            std::process::exit(0);
+        }
+        Err(e) => {
+            tracing::error!("Watchdog: self-restart failed: {:?}", e);
+            send_toast(
+                "deskmic: Restart failed",
+                "Recording failed and could not auto-restart. Please restart manually.",
+            );
+            // Continue running in degraded state rather than exiting.
         }
     }
 }
@@ -124,6 +284,46 @@ fn self_restart() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// How long a capture source can go without a heartbeat before it's reported
+/// as stalled.
+pub const CAPTURE_STALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// When each running capture source last sent an `AudioMessage::Heartbeat`,
+/// as recorded by the file writer.
+#[derive(Default)]
+pub struct Heartbeats {
+    last: Mutex<HashMap<String, Instant>>,
+}
+
+impl Heartbeats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn beat(&self, source: &str) {
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        last.insert(source.to_string(), Instant::now());
+    }
+
+    /// Forget a source that stopped on purpose.
+    pub fn stopped(&self, source: &str) {
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        last.remove(source);
+    }
+
+    /// Sources with no heartbeat for longer than `timeout` at `now`, sorted.
+    pub fn stalled(&self, now: Instant, timeout: Duration) -> Vec<String> {
+        let last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stalled: Vec<String> = last
+            .iter()
+            .filter(|(_, &at)| now.saturating_duration_since(at) > timeout)
+            .map(|(source, _)| source.clone())
+            .collect();
+        stalled.sort();
+        stalled
+    }
+}
+
 /// Determines whether a recording gap alert should be fired.
 ///
 /// Returns `true` if:
@@ -149,30 +349,58 @@ pub fn should_alert_gap(
     }
 }
 
-/// Find the newest `.wav` file in today's date folder under `recordings_dir`.
-/// Returns its modification time, or `None` if no WAV files exist.
+/// Whether anything audible was captured (`last_heard`, from
+/// `LevelMeter::last_heard_any`) within `gap_mins` of `now`. If not, the room
+/// was quiet and a recording gap is expected.
+pub fn heard_within_gap(last_heard: Option<Instant>, now: Instant, gap_mins: u32) -> bool {
+    let gap = Duration::from_secs(gap_mins as u64 * 60);
+    last_heard.is_some_and(|at| now.saturating_duration_since(at) <= gap)
+}
+
+/// Find the newest recording (`.wav` or `.flac`) in today's date folder under
+/// `recordings_dir`. Returns its modification time, or `None` if there are none.
 pub fn newest_wav_in_today(recordings_dir: &Path) -> Option<SystemTime> {
     let today = chrono::Local::now().format("%Y-%m-%d").to_string();
-    let today_dir = recordings_dir.join(&today);
-
-    if !today_dir.exists() {
-        return None;
-    }
+    newest_recording_in(&recordings_dir.join(&today)).map(|(_, modified)| modified)
+}
 
-    let mut newest: Option<SystemTime> = None;
+/// Find the most recent recording: the newest file in the latest date folder
+/// that has any, or in `recordings_dir` itself when recordings aren't
+/// organized by date. Returns `None` if there are no recordings at all.
+pub fn latest_recording(recordings_dir: &Path) -> Option<PathBuf> {
+    let mut date_dirs: Vec<PathBuf> = std::fs::read_dir(recordings_dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_dir()
+                && path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| NaiveDate::parse_from_str(n, "%Y-%m-%d").is_ok())
+        })
+        .collect();
+    // YYYY-MM-DD names sort chronologically; newest first.
+    date_dirs.sort_by(|a, b| b.cmp(a));
+
+    date_dirs
+        .iter()
+        .find_map(|dir| newest_recording_in(dir))
+        .or_else(|| newest_recording_in(recordings_dir))
+        .map(|(path, _)| path)
+}
 
-    if let Ok(entries) = std::fs::read_dir(&today_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().and_then(|e| e.to_str()) == Some("wav") {
-                if let Ok(metadata) = path.metadata() {
-                    if let Ok(modified) = metadata.modified() {
-                        newest = Some(match newest {
-                            Some(prev) if modified > prev => modified,
-                            Some(prev) => prev,
-                            None => modified,
-                        });
-                    }
+/// The newest recording (`.wav`, `.flac` or Ogg Opus `.ogg`) directly inside
+/// `dir`, with its modification time.
+fn newest_recording_in(dir: &Path) -> Option<(PathBuf, SystemTime)> {
+    let mut newest: Option<(PathBuf, SystemTime)> = None;
+
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        if crate::audio::decode::is_recording(&path) {
+            if let Ok(modified) = path.metadata().and_then(|m| m.modified()) {
+                if newest.as_ref().is_none_or(|(_, prev)| modified > *prev) {
+                    newest = Some((path, modified));
                 }
             }
         }
@@ -181,30 +409,75 @@ pub fn newest_wav_in_today(recordings_dir: &Path) -> Option<SystemTime> {
     newest
 }
 
-/// Recording gap timer: checks for recording gaps and fires toast notifications.
+/// Recording gap timer: checks for stalled capture and recording gaps and
+/// fires toast notifications.
 ///
-/// Every 60 seconds, checks if the newest WAV file in today's folder is older
-/// than `gap_mins` minutes. If so, fires a toast notification (once per gap).
+/// Every `interval`, any source in `heartbeats` that hasn't sent one for
+/// `CAPTURE_STALL_TIMEOUT` is reported as stalled (once per stall). Separately,
+/// checks if the newest WAV file in today's folder is older than the live
+/// `recording_gap_alert_mins`. If so, fires a toast notification (once per
+/// gap). A value of 0 disables gap alerts. A gap with nothing audible in
+/// `levels` during it is a quiet room, not a problem, and isn't reported.
 pub fn run_gap_timer(
     recordings_dir: PathBuf,
-    gap_mins: u32,
+    live: Arc<LiveSettings>,
+    heartbeats: Arc<Heartbeats>,
+    levels: Arc<LevelMeter>,
     shutdown: Arc<AtomicBool>,
+    interval: Duration,
 ) {
-    if gap_mins == 0 {
+    if live.recording_gap_alert_mins() == 0 {
         tracing::info!("Recording gap alerts disabled (gap_mins = 0)");
-        return;
     }
 
     let process_start = SystemTime::now();
     let mut alerted = false;
+    let mut stall_alerted = false;
+    let mut last_tick = Instant::now();
 
     while !shutdown.load(Ordering::Relaxed) {
-        std::thread::sleep(Duration::from_secs(60));
+        std::thread::sleep(interval);
 
         if shutdown.load(Ordering::Relaxed) {
             break;
         }
 
+        // A tick far later than planned means the machine slept; pipelines
+        // haven't had a chance to send heartbeats since waking.
+        let now = Instant::now();
+        let woke = now.duration_since(last_tick) > interval + CAPTURE_STALL_TIMEOUT;
+        last_tick = now;
+        let stalled = if woke {
+            Vec::new()
+        } else {
+            heartbeats.stalled(now, CAPTURE_STALL_TIMEOUT)
+        };
+        if stalled.is_empty() {
+            stall_alerted = false;
+        } else if !stall_alerted {
+            let sources = stalled.join(", ");
+            tracing::warn!(
+                "No heartbeat from {} capture for over {}s",
+                sources,
+                CAPTURE_STALL_TIMEOUT.as_secs()
+            );
+            send_toast(
+                "deskmic: Capture stalled",
+                &format!(
+                    "{} capture stopped responding. Recording from it has stopped.",
+                    sources
+                ),
+            );
+            stall_alerted = true;
+        }
+
+        // Re-read each tick so a config reload can change or disable alerts.
+        let gap_mins = live.recording_gap_alert_mins();
+        if gap_mins == 0 || !heard_within_gap(levels.last_heard_any(), now, gap_mins) {
+            alerted = false;
+            continue;
+        }
+
         let newest = newest_wav_in_today(&recordings_dir);
         let now = SystemTime::now();
 
@@ -284,6 +557,63 @@ mod tests {
         assert!(should_alert_gap(None, start, now, 15));
     }
 
+    #[test]
+    fn test_heard_within_gap() {
+        let now = Instant::now() + Duration::from_secs(60 * 60);
+        assert!(!heard_within_gap(None, now, 30));
+        let recently = now - Duration::from_secs(60 * 10);
+        assert!(heard_within_gap(Some(recently), now, 30));
+        let long_ago = now - Duration::from_secs(60 * 40);
+        assert!(!heard_within_gap(Some(long_ago), now, 30));
+    }
+
+    #[test]
+    fn test_heartbeats_report_stalled_sources() {
+        let heartbeats = Heartbeats::new();
+        let timeout = CAPTURE_STALL_TIMEOUT;
+        let start = Instant::now();
+        assert!(heartbeats.stalled(start, timeout).is_empty());
+
+        heartbeats.beat("mic");
+        heartbeats.beat("teams");
+        let now = Instant::now();
+        assert!(heartbeats.stalled(now, timeout).is_empty());
+
+        let later = now + timeout + Duration::from_secs(1);
+        assert_eq!(heartbeats.stalled(later, timeout), vec!["mic", "teams"]);
+
+        // A source that stopped on purpose isn't stalled.
+        heartbeats.stopped("teams");
+        assert_eq!(heartbeats.stalled(later, timeout), vec!["mic"]);
+    }
+
+    #[test]
+    fn test_allow_in_process_restart_falls_back_after_repeated_deaths() {
+        let mut history = Vec::new();
+        let start = Instant::now();
+        for i in 0..MAX_IN_PROCESS_RESTARTS as u64 {
+            assert!(allow_in_process_restart(&mut history, start + Duration::from_secs(i)));
+        }
+        assert!(!allow_in_process_restart(&mut history, start + Duration::from_secs(60)));
+        // Old restarts stop counting once they leave the window.
+        let later = start + RESTART_WINDOW + Duration::from_secs(1);
+        assert!(allow_in_process_restart(&mut history, later));
+    }
+
+    #[test]
+    fn test_toast_throttle_suppresses_repeats_within_window() {
+        let mut throttle = ToastThrottle::new(Duration::from_secs(300));
+        let start = Instant::now();
+
+        assert!(throttle.allow("Recording stopped", start));
+        assert!(!throttle.allow("Recording stopped", start + Duration::from_secs(10)));
+        // A different toast isn't affected.
+        assert!(throttle.allow("Recording gap", start + Duration::from_secs(10)));
+        // Once the window has passed, the same toast shows again.
+        assert!(throttle.allow("Recording stopped", start + Duration::from_secs(300)));
+        assert!(!throttle.allow("Recording stopped", start + Duration::from_secs(301)));
+    }
+
     #[test]
     fn test_xml_escape() {
         assert_eq!(xml_escape("hello"), "hello");
@@ -333,4 +663,39 @@ mod tests {
         let expected = new_meta.modified().unwrap();
         assert_eq!(result.unwrap(), expected);
     }
+
+    #[test]
+    fn test_latest_recording_falls_back_to_earlier_days() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        assert!(latest_recording(tmp.path()).is_none());
+
+        let older = tmp.path().join("2026-02-16");
+        let newer = tmp.path().join("2026-02-17");
+        let empty = tmp.path().join("2026-02-18");
+        for dir in [&older, &newer, &empty] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        std::fs::write(newer.join("mic_09-00-00.wav"), b"a").unwrap();
+        std::fs::write(empty.join("notes.txt"), b"not audio").unwrap();
+        // Written last, but in an older folder.
+        std::thread::sleep(Duration::from_millis(50));
+        std::fs::write(older.join("mic_17-00-00.wav"), b"b").unwrap();
+
+        assert_eq!(
+            latest_recording(tmp.path()),
+            Some(newer.join("mic_09-00-00.wav"))
+        );
+    }
+
+    #[test]
+    fn test_latest_recording_without_date_folders() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("mic_09-00-00.flac"), b"a").unwrap();
+        std::fs::create_dir_all(tmp.path().join("transcripts")).unwrap();
+
+        assert_eq!(
+            latest_recording(tmp.path()),
+            Some(tmp.path().join("mic_09-00-00.flac"))
+        );
+    }
 }
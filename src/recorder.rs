@@ -3,25 +3,43 @@
 // Cross-platform structure:
 // - File writer thread (cross-platform)
 // - Cleanup thread (cross-platform)
-// - Mic capture pipeline thread (Windows only)
+// - Mic capture pipeline threads, one per microphone (Windows only)
+// - System loopback capture pipeline thread (Windows only, optional)
 // - Teams monitor thread (Windows only)
 // - System tray thread (Windows only)
+// - Session lock monitor thread (Windows only, optional)
 // - Transcription child process watchdog thread (cross-platform)
+// - Config file watcher thread (cross-platform, when a config file is in use)
+// - Audio level writer thread (cross-platform)
 
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc;
 use std::sync::Arc;
+#[cfg(target_os = "windows")]
+use std::sync::Mutex;
+#[cfg(target_os = "windows")]
+use std::thread::JoinHandle;
 
 use anyhow::Result;
 
 use crate::audio::file_writer::run_file_writer;
+use crate::audio::levels::LevelMeter;
 use crate::audio::pipeline::AudioMessage;
 use crate::config::Config;
+#[cfg(target_os = "windows")]
+use crate::audio::capture::CaptureStream;
+#[cfg(target_os = "windows")]
+use crate::audio::pipeline::{ClipRequests, PipelineContext};
+use crate::config_watch::LiveSettings;
 
-pub fn run_recorder(config: Config, _config_path: Option<std::path::PathBuf>) -> Result<()> {
+pub fn run_recorder(config: Config, config_path: Option<std::path::PathBuf>) -> Result<()> {
     let shutdown = Arc::new(AtomicBool::new(false));
-    #[allow(unused_variables)]
+    let live = Arc::new(LiveSettings::new(&config));
+    let levels = Arc::new(LevelMeter::new());
     let paused = Arc::new(AtomicBool::new(false));
+    // "Save last N seconds" requests from the tray to every capture pipeline.
+    #[cfg(target_os = "windows")]
+    let clips = Arc::new(ClipRequests::new(config.capture.save_clip_secs));
 
     // Set up Ctrl+C handler.
     let shutdown_ctrlc = shutdown.clone();
@@ -30,68 +48,217 @@ pub fn run_recorder(config: Config, _config_path: Option<std::path::PathBuf>) ->
         shutdown_ctrlc.store(true, Ordering::Relaxed);
     })?;
 
+    // Session end (logoff / system shutdown) sets the same flag, then waits
+    // for the file writer to finalize before letting Windows terminate us.
+    crate::shutdown::register(shutdown.clone());
+    #[cfg(target_os = "windows")]
+    if let Err(e) = crate::shutdown::install_console_handler() {
+        tracing::warn!("Failed to install session-end console handler: {:?}", e);
+    }
+
     let (sender, receiver) = mpsc::channel::<AudioMessage>();
 
+    // --- Config watcher thread (cross-platform) ---
+    // Applies VAD/retention/gap-alert edits live; other fields need a restart.
+    let config_watch_handle = match &config_path {
+        Some(path) => {
+            let watch_path = path.clone();
+            let watch_config = config.clone();
+            let watch_live = live.clone();
+            let watch_shutdown = shutdown.clone();
+            Some(
+                std::thread::Builder::new()
+                    .name("config-watch".into())
+                    .spawn(move || {
+                        crate::config_watch::run_config_watcher(
+                            watch_path,
+                            watch_config,
+                            watch_live,
+                            watch_shutdown,
+                        );
+                    })?,
+            )
+        }
+        None => None,
+    };
+
     // --- File writer thread (cross-platform) ---
+    // The writer wakes the cleanup thread early when the disk runs low.
+    let (cleanup_tx, cleanup_rx) = mpsc::channel();
     let output_config = config.output.clone();
+    let merge_gap = std::time::Duration::from_secs_f32(config.vad.merge_gap_secs);
     let sample_rate = config.capture.sample_rate;
+    let writer_paused = paused.clone();
+    let low_disk = crate::storage::LowDiskTrigger::new(&config.storage, cleanup_tx);
+    // The writer records pipeline heartbeats for the gap timer.
+    let heartbeats = Arc::new(crate::monitoring::Heartbeats::new());
+    let writer_heartbeats = heartbeats.clone();
     let writer_handle = std::thread::Builder::new()
         .name("file-writer".into())
         .spawn(move || {
-            if let Err(e) = run_file_writer(receiver, &output_config, sample_rate) {
+            if let Err(e) = run_file_writer(
+                receiver,
+                &output_config,
+                sample_rate,
+                merge_gap,
+                writer_paused,
+                Some(low_disk),
+                Some(writer_heartbeats),
+            ) {
                 tracing::error!("File writer error: {:?}", e);
             }
         })?;
 
+    // Register the toast app id now rather than on the first alert.
+    #[cfg(target_os = "windows")]
+    crate::monitoring::toast_app_id();
+
     // --- System tray thread (Windows only) ---
     #[cfg(target_os = "windows")]
     let tray_handle = {
         let recordings_dir = config.output.directory.clone();
+        let machine_id = config.output.machine_id();
+        let hotkeys = config.hotkeys.clone();
+        let tray_clips = clips.clone();
         let tray_shutdown = shutdown.clone();
         let tray_paused = paused.clone();
         std::thread::Builder::new()
             .name("tray".into())
             .spawn(move || {
-                if let Err(e) =
-                    crate::tray::run_tray(recordings_dir, _config_path.clone(), tray_shutdown, tray_paused)
-                {
+                if let Err(e) = crate::tray::run_tray(
+                    recordings_dir,
+                    machine_id,
+                    config_path.clone(),
+                    hotkeys,
+                    tray_clips,
+                    tray_shutdown,
+                    tray_paused,
+                ) {
                     tracing::error!("Tray error: {:?}", e);
                 }
             })?
     };
 
-    // --- Mic capture pipeline thread (Windows only) ---
+    // --- Session lock monitor thread (Windows only) ---
+    #[cfg(target_os = "windows")]
+    let session_handle = if config.capture.pause_when_locked {
+        let session_shutdown = shutdown.clone();
+        let session_paused = paused.clone();
+        Some(
+            std::thread::Builder::new()
+                .name("session-monitor".into())
+                .spawn(move || {
+                    if let Err(e) =
+                        crate::session::run_session_monitor(session_shutdown, session_paused)
+                    {
+                        tracing::error!("Session monitor error: {:?}", e);
+                    }
+                })?,
+        )
+    } else {
+        None
+    };
+
+    // --- Mic capture pipeline threads (Windows only) ---
+    // One per configured microphone, each with its own liveness flag. Each
+    // spawn closure is also handed to the watchdog to restart a dead thread;
+    // replacement handles go in `respawned` so shutdown can join them.
+    #[cfg(target_os = "windows")]
+    let pipeline_ctx = PipelineContext {
+        settings: live.clone(),
+        levels: levels.clone(),
+        clips: clips.clone(),
+        sender: sender.clone(),
+        shutdown: shutdown.clone(),
+        paused: paused.clone(),
+    };
+    #[cfg(target_os = "windows")]
+    let mut capture_handles = Vec::new();
+    #[cfg(target_os = "windows")]
+    let mut watched = Vec::new();
     #[cfg(target_os = "windows")]
-    let mic_alive = Arc::new(AtomicBool::new(true));
+    let respawned: Arc<Mutex<Vec<JoinHandle<()>>>> = Arc::new(Mutex::new(Vec::new()));
+    #[cfg(target_os = "windows")]
+    for mic in config.mic_sources() {
+        let alive = Arc::new(AtomicBool::new(true));
+        let sample_rate = config.capture.sample_rate;
+        let timeout_ms = config.capture.event_timeout_ms;
+        let source = mic.source.clone();
+        let open_capture = move || match (&mic.device, mic.exact) {
+            (Some(device), true) => {
+                crate::audio::capture::MicCapture::new_exact(sample_rate, device, timeout_ms)
+            }
+            (device, _) => {
+                crate::audio::capture::MicCapture::new(sample_rate, device.as_deref(), timeout_ms)
+            }
+        };
+        let spawn = capture_spawner(&config, &pipeline_ctx, &source, open_capture, &alive);
+        capture_handles.push(spawn()?);
+        let name = format!("{}-capture", source);
+        watched.push(watched_thread(name, alive, spawn, respawned.clone()));
+    }
+
+    // --- System loopback capture pipeline thread (Windows only, optional) ---
     #[cfg(target_os = "windows")]
-    let mic_handle = spawn_mic_pipeline(
-        &config,
-        sender.clone(),
-        shutdown.clone(),
-        paused.clone(),
-        mic_alive.clone(),
-    )?;
+    if config.targets.system_loopback {
+        let alive = Arc::new(AtomicBool::new(true));
+        let sample_rate = config.capture.sample_rate;
+        let timeout_ms = config.capture.event_timeout_ms;
+        let source = config.targets.source_label("system", &[]);
+        let open_capture =
+            move || crate::audio::capture::SystemLoopbackCapture::new(sample_rate, timeout_ms);
+        let spawn = capture_spawner(&config, &pipeline_ctx, &source, open_capture, &alive);
+        capture_handles.push(spawn()?);
+        let name = format!("{}-capture", source);
+        watched.push(watched_thread(name, alive, spawn, respawned.clone()));
+    }
 
     // --- Teams monitor thread (Windows only) ---
     #[cfg(target_os = "windows")]
-    let teams_alive = Arc::new(AtomicBool::new(true));
-    #[cfg(target_os = "windows")]
-    let teams_handle = spawn_teams_monitor(
-        &config,
-        sender.clone(),
-        shutdown.clone(),
-        paused.clone(),
-        teams_alive.clone(),
-    )?;
+    {
+        let alive = Arc::new(AtomicBool::new(true));
+        let spawn = {
+            let (config, ctx, alive) = (config.clone(), pipeline_ctx.clone(), alive.clone());
+            move || spawn_teams_monitor(&config, ctx.clone(), alive.clone())
+        };
+        capture_handles.push(spawn()?);
+        let name = "teams-monitor".to_string();
+        watched.push(watched_thread(name, alive, spawn, respawned.clone()));
+    }
+
+    // --- Audio level writer thread (cross-platform) ---
+    let levels_handle = {
+        let recordings_dir = config.output.directory.clone();
+        let machine_id = config.output.machine_id();
+        let levels = levels.clone();
+        let levels_shutdown = shutdown.clone();
+        std::thread::Builder::new()
+            .name("level-writer".into())
+            .spawn(move || {
+                crate::audio::levels::run_level_writer(
+                    recordings_dir,
+                    machine_id,
+                    levels,
+                    levels_shutdown,
+                );
+            })?
+    };
 
     // --- Cleanup thread (cross-platform) ---
     let cleanup_dir = config.output.directory.clone();
     let cleanup_config = config.storage.clone();
+    let cleanup_live = live.clone();
     let cleanup_shutdown = shutdown.clone();
     let cleanup_handle = std::thread::Builder::new()
         .name("cleanup".into())
         .spawn(move || {
-            crate::storage::run_cleanup_loop(cleanup_dir, cleanup_config, cleanup_shutdown);
+            crate::storage::run_cleanup_loop(
+                cleanup_dir,
+                cleanup_config,
+                cleanup_live,
+                cleanup_rx,
+                cleanup_shutdown,
+            );
         })?;
 
     // --- Transcription child process watchdog thread ---
@@ -103,50 +270,40 @@ pub fn run_recorder(config: Config, _config_path: Option<std::path::PathBuf>) ->
         })?;
 
     // --- Pipeline health watchdog thread ---
-    // Monitors pipeline threads and triggers self-restart if any die.
+    // Restarts pipeline threads that die, falling back to a process restart.
     #[cfg(target_os = "windows")]
     let watchdog_handle = {
         let wd_shutdown = shutdown.clone();
-        let wd_mic_alive = mic_alive.clone();
-        let wd_teams_alive = teams_alive.clone();
-        let mic_enabled = config.targets.mic_enabled;
-
+        let interval = std::time::Duration::from_secs(config.monitoring.watchdog_interval_secs);
         std::thread::Builder::new()
             .name("watchdog".into())
             .spawn(move || {
-                crate::monitoring::run_watchdog(wd_shutdown, move || {
-                    if mic_enabled && !wd_mic_alive.load(Ordering::Relaxed) {
-                        return Some("mic-capture".to_string());
-                    }
-                    if !wd_teams_alive.load(Ordering::Relaxed) {
-                        return Some("teams-monitor".to_string());
-                    }
-                    None
-                });
+                crate::monitoring::run_watchdog(wd_shutdown, watched, interval);
             })?
     };
 
     // --- Recording gap timer thread ---
     let gap_timer_handle = {
         let gap_shutdown = shutdown.clone();
-        let gap_mins = config.monitoring.recording_gap_alert_mins;
+        let gap_live = live.clone();
+        let gap_levels = levels.clone();
         let recordings_dir = config.output.directory.clone();
+        let interval = std::time::Duration::from_secs(config.monitoring.gap_check_interval_secs);
 
         std::thread::Builder::new()
             .name("gap-timer".into())
             .spawn(move || {
                 crate::monitoring::run_gap_timer(
                     recordings_dir,
-                    gap_mins,
+                    gap_live,
+                    heartbeats,
+                    gap_levels,
                     gap_shutdown,
+                    interval,
                 );
             })?
     };
 
-    // Drop our copy of the sender so the file writer's channel closes when
-    // all capture threads finish.
-    drop(sender);
-
     // Wait for shutdown signal.
     while !shutdown.load(Ordering::Relaxed) {
         std::thread::sleep(std::time::Duration::from_millis(100));
@@ -155,20 +312,51 @@ pub fn run_recorder(config: Config, _config_path: Option<std::path::PathBuf>) ->
     tracing::info!("Shutting down...");
 
     // Join threads. On non-Windows the mic/teams/tray/watchdog handles don't exist.
+    // Capture threads and the file writer go first so session-end handlers
+    // (which may be blocking the tray thread) are released as soon as every
+    // file is finalized.
     #[cfg(target_os = "windows")]
     {
-        if let Some(h) = mic_handle {
-            let _ = h.join();
+        let restarted = std::mem::take(&mut *respawned.lock().unwrap());
+        let handles = capture_handles.into_iter().chain(restarted).collect();
+        let timeout = crate::shutdown::CAPTURE_JOIN_TIMEOUT;
+        let stuck = crate::shutdown::join_with_timeout(handles, timeout);
+        if !stuck.is_empty() {
+            tracing::warn!(
+                "Capture threads still running after {}s, shutting down without them: {}",
+                timeout.as_secs(),
+                stuck.join(", ")
+            );
         }
-        let _ = teams_handle.join();
+    }
+    // Tell the writer to finalize open files. The channel never closes on its
+    // own (this thread and every PipelineContext clone hold a sender), and a
+    // capture thread that panicked or hung mid-segment must not leave a
+    // recording unfinished, so this message is what stops the writer.
+    let _ = sender.send(AudioMessage::Shutdown);
+    let _ = writer_handle.join();
+    crate::shutdown::mark_finalized();
+
+    #[cfg(target_os = "windows")]
+    {
         let _ = tray_handle.join();
+        // Join anything the watchdog restarted while shutdown was starting.
         let _ = watchdog_handle.join();
+        for h in std::mem::take(&mut *respawned.lock().unwrap()) {
+            let _ = h.join();
+        }
+        if let Some(h) = session_handle {
+            let _ = h.join();
+        }
     }
 
     let _ = cleanup_handle.join();
-    let _ = writer_handle.join();
     let _ = transcribe_handle.join();
     let _ = gap_timer_handle.join();
+    let _ = levels_handle.join();
+    if let Some(h) = config_watch_handle {
+        let _ = h.join();
+    }
 
     tracing::info!("Shutdown complete");
     Ok(())
@@ -259,28 +447,45 @@ fn run_transcription_watchdog(shutdown: Arc<AtomicBool>) {
     }
 }
 
-/// Spawn the mic capture pipeline thread with crash-recovery outer loop.
-/// Returns `None` if mic capture is disabled in config.
+/// The spawn closure for one capture pipeline: it starts the pipeline now and
+/// again whenever the watchdog restarts it.
 #[cfg(target_os = "windows")]
-fn spawn_mic_pipeline(
+fn capture_spawner<C: CaptureStream>(
     config: &Config,
-    sender: mpsc::Sender<AudioMessage>,
-    shutdown: Arc<AtomicBool>,
-    paused: Arc<AtomicBool>,
-    alive: Arc<AtomicBool>,
-) -> Result<Option<std::thread::JoinHandle<()>>> {
-    if !config.targets.mic_enabled {
-        alive.store(false, Ordering::Relaxed); // not started, so not "alive"
-        return Ok(None);
+    ctx: &PipelineContext,
+    source_name: &str,
+    open_capture: impl Fn() -> Result<C> + Clone + Send + 'static,
+    alive: &Arc<AtomicBool>,
+) -> impl Fn() -> Result<JoinHandle<()>> + Send + 'static {
+    let (config, ctx, alive) = (config.clone(), ctx.clone(), alive.clone());
+    let source_name = source_name.to_string();
+    move || {
+        spawn_capture_pipeline(
+            &config,
+            ctx.clone(),
+            source_name.clone(),
+            open_capture.clone(),
+            alive.clone(),
+        )
     }
+}
 
+/// Spawn a capture pipeline thread for one microphone or the system loopback,
+/// with crash-recovery outer loop. `open_capture` (re)opens the device.
+#[cfg(target_os = "windows")]
+fn spawn_capture_pipeline<C: CaptureStream>(
+    config: &Config,
+    ctx: PipelineContext,
+    source_name: String,
+    open_capture: impl Fn() -> Result<C> + Send + 'static,
+    alive: Arc<AtomicBool>,
+) -> Result<std::thread::JoinHandle<()>> {
     let sample_rate = config.capture.sample_rate;
-    let pre_speech_buffer_secs = config.vad.pre_speech_buffer_secs;
-    let silence_threshold_secs = config.vad.silence_threshold_secs;
-    let speech_threshold = config.vad.speech_threshold;
+    let chunk_size = crate::audio::vad::chunk_size_for(sample_rate)?;
+    let shutdown = ctx.shutdown.clone();
 
     let handle = std::thread::Builder::new()
-        .name("mic-capture".into())
+        .name(format!("{}-capture", source_name))
         .spawn(move || {
             // Exponential backoff: starts at 2s, doubles each failure, caps at 30s.
             const INITIAL_BACKOFF_SECS: u64 = 2;
@@ -289,60 +494,43 @@ fn spawn_mic_pipeline(
 
             // Outer recovery loop: restart on transient errors.
             while !shutdown.load(Ordering::Relaxed) {
-                match crate::audio::capture::MicCapture::new(sample_rate) {
+                match open_capture() {
                     Ok(capture) => {
                         let capture_fn =
                             || -> Result<Option<Vec<i16>>> { Ok(capture.read_frames()?) };
                         let start_fn = || -> Result<()> { capture.start() };
 
-                        let chunk_size: usize = match sample_rate {
-                            8000 => 256,
-                            16000 => 512,
-                            _ => 512,
-                        };
-
                         // If we got this far, device initialised — reset backoff.
                         backoff_secs = INITIAL_BACKOFF_SECS;
 
-                        match crate::audio::vad::Vad::new(sample_rate, speech_threshold) {
-                            Ok(mut vad) => {
-                                match crate::audio::pipeline::run_capture_pipeline(
-                                    "mic".to_string(),
-                                    capture_fn,
-                                    start_fn,
-                                    sample_rate,
-                                    pre_speech_buffer_secs,
-                                    silence_threshold_secs,
-                                    &mut vad,
-                                    chunk_size,
-                                    sender.clone(),
-                                    shutdown.clone(),
-                                    paused.clone(),
-                                ) {
-                                    Ok(()) => {
-                                        // Pipeline exited cleanly (shutdown flag set) — this is normal.
-                                        // But if shutdown wasn't requested, this is unexpected and we
-                                        // should retry (the pipeline shouldn't exit on its own).
-                                        if shutdown.load(Ordering::Relaxed) {
-                                            break;
-                                        }
-                                        tracing::warn!(
-                                            "Mic pipeline exited unexpectedly, retrying in {}s",
-                                            backoff_secs
-                                        );
-                                    }
-                                    Err(e) => {
-                                        tracing::error!(
-                                            "Mic pipeline error: {:?}, restarting in {}s",
-                                            e,
-                                            backoff_secs
-                                        );
-                                    }
+                        let speech_threshold = ctx.settings.vad().speech_threshold;
+                        let mut vad = crate::audio::vad::new_vad(sample_rate, speech_threshold);
+                        match crate::audio::pipeline::run_capture_pipeline(
+                            source_name.clone(),
+                            capture_fn,
+                            start_fn,
+                            sample_rate,
+                            vad.as_mut(),
+                            chunk_size,
+                            &ctx,
+                        ) {
+                            Ok(()) => {
+                                // Pipeline exited cleanly (shutdown flag set) — this is normal.
+                                // But if shutdown wasn't requested, this is unexpected and we
+                                // should retry (the pipeline shouldn't exit on its own).
+                                if shutdown.load(Ordering::Relaxed) {
+                                    break;
                                 }
+                                tracing::warn!(
+                                    "Capture pipeline {} exited unexpectedly, retrying in {}s",
+                                    source_name,
+                                    backoff_secs
+                                );
                             }
                             Err(e) => {
                                 tracing::error!(
-                                    "Failed to create VAD: {:?}, retrying in {}s",
+                                    "Capture pipeline {} error: {:?}, restarting in {}s",
+                                    source_name,
                                     e,
                                     backoff_secs
                                 );
@@ -350,17 +538,23 @@ fn spawn_mic_pipeline(
                         }
 
                         if let Err(e) = capture.stop() {
-                            tracing::warn!("Error stopping mic capture: {:?}", e);
+                            tracing::warn!("Error stopping {} capture: {:?}", source_name, e);
                         }
                     }
                     Err(e) => {
-                        tracing::error!("Mic init failed: {:?}, retrying in {}s", e, backoff_secs);
+                        tracing::error!(
+                            "Capture {} init failed: {:?}, retrying in {}s",
+                            source_name,
+                            e,
+                            backoff_secs
+                        );
                     }
                 }
 
                 if !shutdown.load(Ordering::Relaxed) {
                     tracing::info!(
-                        "Mic recovery: sleeping {}s before retry (device may be waking up)",
+                        "{} recovery: sleeping {}s before retry (device may be waking up)",
+                        source_name,
                         backoff_secs
                     );
                     std::thread::sleep(std::time::Duration::from_secs(backoff_secs));
@@ -372,28 +566,54 @@ fn spawn_mic_pipeline(
             alive.store(false, Ordering::Relaxed);
         })?;
 
-    Ok(Some(handle))
+    Ok(handle)
+}
+
+/// Watchdog entry for a thread started by `spawn`, which shares the `alive`
+/// flag the thread clears on exit. Replacement handles are pushed onto
+/// `respawned` for shutdown to join.
+#[cfg(target_os = "windows")]
+fn watched_thread(
+    name: String,
+    alive: Arc<AtomicBool>,
+    spawn: impl Fn() -> Result<JoinHandle<()>> + Send + 'static,
+    respawned: Arc<Mutex<Vec<JoinHandle<()>>>>,
+) -> crate::monitoring::WatchedThread {
+    let is_alive = {
+        let alive = alive.clone();
+        move || alive.load(Ordering::Relaxed)
+    };
+    crate::monitoring::WatchedThread {
+        name,
+        is_alive: Box::new(is_alive),
+        restart: Box::new(move || {
+            alive.store(true, Ordering::Relaxed);
+            match spawn() {
+                Ok(handle) => {
+                    respawned.lock().unwrap().push(handle);
+                    Ok(())
+                }
+                Err(e) => {
+                    alive.store(false, Ordering::Relaxed);
+                    Err(e)
+                }
+            }
+        }),
+    }
 }
 
 /// Spawn the Teams process monitor thread.
 #[cfg(target_os = "windows")]
 fn spawn_teams_monitor(
     config: &Config,
-    sender: mpsc::Sender<AudioMessage>,
-    shutdown: Arc<AtomicBool>,
-    paused: Arc<AtomicBool>,
+    ctx: PipelineContext,
     alive: Arc<AtomicBool>,
 ) -> Result<std::thread::JoinHandle<()>> {
     let teams_config = config.clone();
     let handle = std::thread::Builder::new()
         .name("teams-monitor".into())
         .spawn(move || {
-            if let Err(e) = crate::audio::teams_monitor::run_teams_monitor(
-                teams_config,
-                sender,
-                shutdown,
-                paused,
-            ) {
+            if let Err(e) = crate::audio::teams_monitor::run_teams_monitor(teams_config, ctx) {
                 tracing::error!("Teams monitor error: {:?}", e);
             }
             // Thread is exiting — mark as not alive.
@@ -1,5 +1,6 @@
 use sha2::{Digest, Sha256};
 
+use crate::config::parse_recording_name;
 use crate::transcribe::backend::Transcript;
 
 /// A conversation chunk — a group of temporally-close utterances from the same source.
@@ -23,25 +24,15 @@ pub struct Chunk {
     pub files: Vec<String>,
 }
 
-/// Extract time from filename: "mic_09-37-31.wav" -> "09-37-31"
-fn extract_time_from_filename(filename: &str) -> Option<String> {
-    // Strip the extension, then take everything after the last '_'
-    let stem = filename.strip_suffix(".wav")?;
-    let time_part = stem.rsplit('_').next()?;
-    // Validate format: HH-MM-SS (8 chars, digits and dashes)
-    if time_part.len() != 8 {
+/// Extract time from filename, read with `output.filename_template`:
+/// "mic_09-37-31.wav" -> "09-37-31"
+fn extract_time_from_filename(template: &str, filename: &str) -> Option<String> {
+    let (_, ext) = filename.rsplit_once('.')?;
+    if !crate::audio::decode::RECORDING_EXTENSIONS.contains(&ext) {
         return None;
     }
-    let parts: Vec<&str> = time_part.split('-').collect();
-    if parts.len() != 3 {
-        return None;
-    }
-    for part in &parts {
-        if part.len() != 2 || !part.chars().all(|c| c.is_ascii_digit()) {
-            return None;
-        }
-    }
-    Some(time_part.to_string())
+    let (hour, minute, second) = parse_recording_name(template, filename)?.time;
+    Some(format!("{:02}-{:02}-{:02}", hour, minute, second))
 }
 
 /// Convert "HH-MM-SS" to seconds since midnight.
@@ -80,18 +71,22 @@ fn chunk_id(date: &str, source: &str, start_file: &str) -> String {
 /// 1. Gap between end of previous utterance and start of next exceeds `chunk_gap_secs`
 /// 2. Current chunk exceeds `chunk_max_duration_secs` of total audio duration
 /// 3. Source changes (e.g. mic -> teams)
+///
+/// Start times are read from file names with `filename_template`.
 pub fn chunk_transcripts(
     transcripts: &[Transcript],
     chunk_gap_secs: u64,
     chunk_max_duration_secs: u64,
+    filename_template: &str,
 ) -> Vec<Chunk> {
     if transcripts.is_empty() {
         return Vec::new();
     }
 
-    // Sort transcripts by filename (lexicographic = chronological).
+    // Sort transcripts by the time in their file name, then the name.
+    let time = |t: &Transcript| extract_time_from_filename(filename_template, &t.file);
     let mut sorted: Vec<&Transcript> = transcripts.iter().collect();
-    sorted.sort_by(|a, b| a.file.cmp(&b.file));
+    sorted.sort_by_cached_key(|t| (time(t), t.file.clone()));
 
     let mut chunks: Vec<Chunk> = Vec::new();
 
@@ -106,7 +101,7 @@ pub fn chunk_transcripts(
     let mut prev_end_secs: f64 = 0.0;
 
     for (i, t) in sorted.iter().enumerate() {
-        let t_time = extract_time_from_filename(&t.file).unwrap_or_default();
+        let t_time = time(t).unwrap_or_default();
         let t_start_secs = time_to_secs(&t_time).unwrap_or(0.0);
 
         if i == 0 {
@@ -177,6 +172,7 @@ pub fn chunk_transcripts(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::DEFAULT_FILENAME_TEMPLATE as TEMPLATE;
     use crate::transcribe::backend::Transcript;
 
     fn make_transcript(source: &str, file: &str, text: &str, duration: f64) -> Transcript {
@@ -186,6 +182,8 @@ mod tests {
             duration_secs: duration,
             file: file.to_string(),
             text: text.to_string(),
+            engine: None,
+            segments: Vec::new(),
         }
     }
 
@@ -195,23 +193,34 @@ mod tests {
     fn test_extract_time_from_filename() {
         // Valid patterns
         assert_eq!(
-            extract_time_from_filename("mic_09-37-31.wav"),
+            extract_time_from_filename(TEMPLATE, "mic_09-37-31.wav"),
             Some("09-37-31".to_string())
         );
         assert_eq!(
-            extract_time_from_filename("teams_14-05-00.wav"),
+            extract_time_from_filename(TEMPLATE, "teams_14-05-00.wav"),
             Some("14-05-00".to_string())
         );
         assert_eq!(
-            extract_time_from_filename("some_prefix_23-59-59.wav"),
+            extract_time_from_filename(TEMPLATE, "mic_23-59-59_laptop.wav"),
             Some("23-59-59".to_string())
         );
+        assert_eq!(
+            extract_time_from_filename(TEMPLATE, "mic_09-37-31.flac"),
+            Some("09-37-31".to_string())
+        );
 
         // Invalid patterns
-        assert_eq!(extract_time_from_filename("mic_09-37.wav"), None); // too short
-        assert_eq!(extract_time_from_filename("mic_notime.wav"), None); // not a time
-        assert_eq!(extract_time_from_filename("badfile.txt"), None); // wrong extension
-        assert_eq!(extract_time_from_filename("mic_09-37-31"), None); // no .wav
+        assert_eq!(extract_time_from_filename(TEMPLATE, "mic_09-37.wav"), None); // too short
+        assert_eq!(extract_time_from_filename(TEMPLATE, "mic_notime.wav"), None); // not a time
+        assert_eq!(extract_time_from_filename(TEMPLATE, "badfile.txt"), None); // wrong extension
+        assert_eq!(extract_time_from_filename(TEMPLATE, "mic_09-37-31"), None); // no extension
+
+        // A custom template
+        let custom = "{date}-{time}-{source}.{ext}";
+        assert_eq!(
+            extract_time_from_filename(custom, "2026-02-17-09-37-31-mic.ogg"),
+            Some("09-37-31".to_string())
+        );
     }
 
     // ── 2. time_to_secs / secs_to_time roundtrips ─────────────────────
@@ -270,7 +279,7 @@ mod tests {
 
     #[test]
     fn test_empty_transcripts_returns_empty() {
-        let chunks = chunk_transcripts(&[], 300, 300);
+        let chunks = chunk_transcripts(&[], 300, 300, TEMPLATE);
         assert!(chunks.is_empty());
     }
 
@@ -284,7 +293,7 @@ mod tests {
             "hello world",
             10.0,
         )];
-        let chunks = chunk_transcripts(&transcripts, 300, 300);
+        let chunks = chunk_transcripts(&transcripts, 300, 300, TEMPLATE);
         assert_eq!(chunks.len(), 1);
         assert_eq!(chunks[0].text, "hello world");
         assert_eq!(chunks[0].source, "mic");
@@ -304,7 +313,7 @@ mod tests {
             make_transcript("mic", "mic_09-00-10.wav", "second", 5.0),
             make_transcript("mic", "mic_09-00-20.wav", "third", 5.0),
         ];
-        let chunks = chunk_transcripts(&transcripts, 300, 300);
+        let chunks = chunk_transcripts(&transcripts, 300, 300, TEMPLATE);
         assert_eq!(chunks.len(), 1);
         assert_eq!(chunks[0].text, "first second third");
         assert_eq!(chunks[0].files.len(), 3);
@@ -322,7 +331,7 @@ mod tests {
             make_transcript("mic", "mic_09-00-00.wav", "morning", 10.0),
             make_transcript("mic", "mic_09-07-00.wav", "later", 10.0),
         ];
-        let chunks = chunk_transcripts(&transcripts, 300, 300);
+        let chunks = chunk_transcripts(&transcripts, 300, 300, TEMPLATE);
         assert_eq!(chunks.len(), 2);
         assert_eq!(chunks[0].text, "morning");
         assert_eq!(chunks[1].text, "later");
@@ -336,7 +345,7 @@ mod tests {
             make_transcript("mic", "mic_09-00-00.wav", "from mic", 5.0),
             make_transcript("teams", "teams_09-00-10.wav", "from teams", 5.0),
         ];
-        let chunks = chunk_transcripts(&transcripts, 300, 300);
+        let chunks = chunk_transcripts(&transcripts, 300, 300, TEMPLATE);
         assert_eq!(chunks.len(), 2);
         assert_eq!(chunks[0].source, "mic");
         assert_eq!(chunks[0].text, "from mic");
@@ -358,7 +367,7 @@ mod tests {
             transcripts.push(make_transcript("mic", &file, &format!("utt{}", i), 10.0));
         }
 
-        let chunks = chunk_transcripts(&transcripts, 300, 300);
+        let chunks = chunk_transcripts(&transcripts, 300, 300, TEMPLATE);
         assert!(
             chunks.len() >= 2,
             "Expected at least 2 chunks, got {}",
@@ -390,7 +399,7 @@ mod tests {
             make_transcript("mic", "mic_09-00-00.wav", "first", 5.0),
             make_transcript("mic", "mic_09-00-10.wav", "second", 5.0),
         ];
-        let chunks = chunk_transcripts(&transcripts, 300, 300);
+        let chunks = chunk_transcripts(&transcripts, 300, 300, TEMPLATE);
         assert_eq!(chunks.len(), 1);
         assert_eq!(chunks[0].text, "first second third");
         assert_eq!(chunks[0].start_time, "09-00-00");
@@ -410,7 +419,7 @@ mod tests {
             make_transcript("mic", "mic_09-00-00.wav", "a", 5.0),
             make_transcript("mic", "mic_09-00-10.wav", "b", 5.0),
         ];
-        let chunks = chunk_transcripts(&transcripts, 300, 300);
+        let chunks = chunk_transcripts(&transcripts, 300, 300, TEMPLATE);
         assert_eq!(chunks.len(), 1);
         assert_eq!(chunks[0].start_time, "09-00-00");
         assert_eq!(chunks[0].end_time, "09-00-15");
@@ -42,6 +42,7 @@ pub struct EmbeddingClient {
     endpoint: String,
     api_key: String,
     deployment: String,
+    api_version: String,
     client: reqwest::blocking::Client,
 }
 
@@ -51,6 +52,7 @@ impl std::fmt::Debug for EmbeddingClient {
             .field("endpoint", &self.endpoint)
             .field("api_key", &"[REDACTED]")
             .field("deployment", &self.deployment)
+            .field("api_version", &self.api_version)
             .finish_non_exhaustive()
     }
 }
@@ -98,6 +100,7 @@ impl EmbeddingClient {
             endpoint,
             api_key,
             deployment,
+            api_version: azure.api_version.clone(),
             client,
         })
     }
@@ -118,8 +121,8 @@ impl EmbeddingClient {
     /// always match the input order.
     pub fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
         let url = format!(
-            "{}/openai/deployments/{}/embeddings?api-version=2024-06-01",
-            self.endpoint, self.deployment
+            "{}/openai/deployments/{}/embeddings?api-version={}",
+            self.endpoint, self.deployment, self.api_version
         );
 
         let mut all_embeddings: Vec<Vec<f32>> = Vec::with_capacity(texts.len());
@@ -233,6 +236,7 @@ mod tests {
             endpoint: "https://example.openai.azure.com".to_string(),
             api_key: "test-key".to_string(),
             deployment: "whisper".to_string(),
+            ..Default::default()
         };
         // embedding_deployment is empty by default
         let result = EmbeddingClient::from_config(&config);
@@ -250,6 +254,7 @@ mod tests {
             endpoint: "https://example.openai.azure.com".to_string(),
             api_key: "test-key".to_string(),
             deployment: "whisper".to_string(),
+            ..Default::default()
         };
         config.search = SearchConfig {
             embedding_deployment: "text-embedding-3-large".to_string(),
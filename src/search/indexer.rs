@@ -1,61 +1,37 @@
-use std::path::Path;
-
-use anyhow::{Context, Result};
+use anyhow::Result;
 
 use crate::config::Config;
 use crate::search::chunker::{chunk_transcripts, Chunk};
 use crate::search::db::SearchDb;
 use crate::search::embeddings::EmbeddingClient;
-use crate::transcribe::backend::Transcript;
+use crate::transcribe::store::open_store;
 
 /// Run the full indexing pipeline.
 pub fn run_index(config: &Config) -> Result<()> {
     let recordings_dir = &config.output.directory;
-    let transcript_dir = recordings_dir.join("transcripts");
     let db_path = recordings_dir.join("deskmic-search.db");
 
-    if !transcript_dir.exists() {
-        tracing::info!(
-            "No transcripts directory found at {}",
-            transcript_dir.display()
-        );
+    let store = open_store(config)?;
+    let dates = store.dates()?;
+    if dates.is_empty() {
+        tracing::info!("No transcripts found in {}", recordings_dir.display());
         return Ok(());
     }
 
     let db = SearchDb::open(&db_path)?;
     let embedder = EmbeddingClient::from_config(config)?;
 
-    // Scan for .jsonl files.
-    let mut jsonl_files: Vec<_> = std::fs::read_dir(&transcript_dir)?
-        .filter_map(|entry| {
-            let entry = entry.ok()?;
-            let path = entry.path();
-            if path.extension().and_then(|e| e.to_str()) == Some("jsonl") {
-                Some(path)
-            } else {
-                None
-            }
-        })
-        .collect();
-    jsonl_files.sort();
-
     let mut total_new_chunks = 0usize;
     let mut total_files_indexed = 0usize;
     let _total_existing = db.count_chunks()?;
 
-    for jsonl_path in &jsonl_files {
-        let file_name = jsonl_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("");
+    for date in &dates {
+        // Revision key per date and store, e.g. "2026-03-16.jsonl".
+        let file_name = format!("{}.{}", date, store.name());
+        let file_name = file_name.as_str();
 
-        // Check mtime for idempotency.
-        let metadata = std::fs::metadata(jsonl_path)
-            .with_context(|| format!("Failed to stat {}", jsonl_path.display()))?;
-        let mtime = metadata
-            .modified()?
-            .duration_since(std::time::UNIX_EPOCH)?
-            .as_secs() as i64;
+        // Check revision for idempotency.
+        let mtime = store.revision(date)?;
 
         let stored_mtime = db.get_file_mtime(file_name)?;
         if stored_mtime == Some(mtime) {
@@ -63,19 +39,16 @@ pub fn run_index(config: &Config) -> Result<()> {
             continue;
         }
 
-        tracing::info!("Indexing {} (mtime changed or new)", file_name);
+        tracing::info!("Indexing {} (revision changed or new)", file_name);
 
-        // Load transcripts from this file.
-        let transcripts = load_jsonl(jsonl_path)?;
+        // Load transcripts for this date.
+        let transcripts = store.load_date(date)?;
         if transcripts.is_empty() {
             tracing::debug!("No transcripts in {}", file_name);
             db.set_file_mtime(file_name, mtime)?;
             continue;
         }
 
-        // Extract date from filename (e.g. "2026-03-16.jsonl" -> "2026-03-16").
-        let date = file_name.strip_suffix(".jsonl").unwrap_or(file_name);
-
         // Delete old chunks for this date (idempotent re-index).
         let deleted = db.delete_chunks_for_date(date)?;
         if deleted > 0 {
@@ -87,6 +60,7 @@ pub fn run_index(config: &Config) -> Result<()> {
             &transcripts,
             config.search.chunk_gap_secs,
             config.search.chunk_max_duration_secs,
+            &config.output.filename_template,
         )
         .into_iter()
         .filter(|c| !c.text.trim().is_empty())
@@ -134,77 +108,3 @@ pub fn run_index(config: &Config) -> Result<()> {
 
     Ok(())
 }
-
-/// Load transcripts from a JSONL file.
-fn load_jsonl(path: &Path) -> Result<Vec<Transcript>> {
-    let content = std::fs::read_to_string(path)
-        .with_context(|| format!("Failed to read {}", path.display()))?;
-
-    let mut transcripts = Vec::new();
-    for (line_num, line) in content.lines().enumerate() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-        match serde_json::from_str::<Transcript>(trimmed) {
-            Ok(t) => transcripts.push(t),
-            Err(e) => {
-                tracing::warn!(
-                    "Failed to parse line {} of {}: {}",
-                    line_num + 1,
-                    path.display(),
-                    e
-                );
-            }
-        }
-    }
-
-    Ok(transcripts)
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_load_jsonl_valid() {
-        let dir = tempfile::TempDir::new().unwrap();
-        let path = dir.path().join("2026-03-16.jsonl");
-        std::fs::write(
-            &path,
-            r#"{"timestamp":"2026-03-16","source":"mic","duration_secs":5.0,"file":"mic_09-37-31.wav","text":"Hello world"}
-{"timestamp":"2026-03-16","source":"mic","duration_secs":3.0,"file":"mic_09-37-40.wav","text":"Testing"}
-"#,
-        )
-        .unwrap();
-        let transcripts = load_jsonl(&path).unwrap();
-        assert_eq!(transcripts.len(), 2);
-        assert_eq!(transcripts[0].text, "Hello world");
-        assert_eq!(transcripts[1].text, "Testing");
-    }
-
-    #[test]
-    fn test_load_jsonl_skips_invalid_lines() {
-        let dir = tempfile::TempDir::new().unwrap();
-        let path = dir.path().join("bad.jsonl");
-        std::fs::write(
-            &path,
-            r#"{"timestamp":"2026-03-16","source":"mic","duration_secs":5.0,"file":"mic_09-37-31.wav","text":"Good"}
-not valid json
-{"timestamp":"2026-03-16","source":"mic","duration_secs":3.0,"file":"mic_09-37-40.wav","text":"Also good"}
-"#,
-        )
-        .unwrap();
-        let transcripts = load_jsonl(&path).unwrap();
-        assert_eq!(transcripts.len(), 2);
-    }
-
-    #[test]
-    fn test_load_jsonl_empty_file() {
-        let dir = tempfile::TempDir::new().unwrap();
-        let path = dir.path().join("empty.jsonl");
-        std::fs::write(&path, "").unwrap();
-        let transcripts = load_jsonl(&path).unwrap();
-        assert!(transcripts.is_empty());
-    }
-}
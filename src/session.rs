@@ -0,0 +1,190 @@
+// Session lock monitor: pauses recording while the workstation is locked.
+//
+// `decide_lock_action` is cross-platform pure logic (unit-testable).
+// `run_session_monitor` is Windows-only because it listens for
+// `WM_WTSSESSION_CHANGE` via `WTSRegisterSessionNotification`.
+
+/// Action to take when the session lock state changes.
+#[derive(Debug, PartialEq)]
+pub enum LockAction {
+    /// Session locked while recording — set the shared `paused` flag.
+    Pause,
+    /// Session unlocked and we were the ones who paused — clear the flag.
+    Resume,
+    /// Nothing to do.
+    NoChange,
+}
+
+/// Decide how to react to a lock/unlock event.
+///
+/// `paused_by_lock` tracks whether the current pause was caused by a lock.
+/// A manual pause (via the tray) is never undone by an unlock, and a lock
+/// while already paused doesn't take ownership of the pause.
+pub fn decide_lock_action(locked: bool, paused: bool, paused_by_lock: bool) -> LockAction {
+    match (locked, paused, paused_by_lock) {
+        (true, false, _) => LockAction::Pause,
+        (false, true, true) => LockAction::Resume,
+        _ => LockAction::NoChange,
+    }
+}
+
+// --- Windows-only monitor that listens for session lock/unlock ---
+
+#[cfg(target_os = "windows")]
+mod monitor {
+    use std::cell::Cell;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    use anyhow::Result;
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::System::RemoteDesktop::{
+        WTSRegisterSessionNotification, WTSUnRegisterSessionNotification,
+        NOTIFY_FOR_THIS_SESSION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::*;
+
+    use super::{decide_lock_action, LockAction};
+
+    thread_local! {
+        /// Latest lock state reported to the window procedure. `None` until
+        /// the first `WM_WTSSESSION_CHANGE` arrives.
+        static LOCK_EVENT: Cell<Option<bool>> = const { Cell::new(None) };
+    }
+
+    unsafe extern "system" fn wndproc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        if msg == WM_WTSSESSION_CHANGE {
+            match wparam.0 as u32 {
+                WTS_SESSION_LOCK => LOCK_EVENT.with(|e| e.set(Some(true))),
+                WTS_SESSION_UNLOCK => LOCK_EVENT.with(|e| e.set(Some(false))),
+                _ => {}
+            }
+            return LRESULT(0);
+        }
+        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+    }
+
+    /// Listen for session lock/unlock and toggle `paused` accordingly.
+    ///
+    /// Creates a hidden message-only window registered for session
+    /// notifications and pumps its messages until `shutdown` is set.
+    pub fn run_session_monitor(shutdown: Arc<AtomicBool>, paused: Arc<AtomicBool>) -> Result<()> {
+        let class_name: Vec<u16> = "deskmic-session-monitor"
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+
+        let hwnd = unsafe {
+            let hinstance = GetModuleHandleW(None)?;
+            let wc = WNDCLASSW {
+                lpfnWndProc: Some(wndproc),
+                hInstance: hinstance.into(),
+                lpszClassName: PCWSTR(class_name.as_ptr()),
+                ..Default::default()
+            };
+            if RegisterClassW(&wc) == 0 {
+                anyhow::bail!("Failed to register session monitor window class");
+            }
+            CreateWindowExW(
+                WINDOW_EX_STYLE::default(),
+                PCWSTR(class_name.as_ptr()),
+                PCWSTR::null(),
+                WINDOW_STYLE::default(),
+                0,
+                0,
+                0,
+                0,
+                Some(HWND_MESSAGE),
+                None,
+                Some(hinstance.into()),
+                None,
+            )?
+        };
+
+        unsafe { WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION)? };
+        tracing::info!("Session monitor started (recording pauses while locked)");
+
+        let mut paused_by_lock = false;
+
+        while !shutdown.load(Ordering::Relaxed) {
+            unsafe {
+                let mut msg = MSG::default();
+                while PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).into() {
+                    let _ = TranslateMessage(&msg);
+                    DispatchMessageW(&msg);
+                }
+            }
+
+            if let Some(locked) = LOCK_EVENT.with(|e| e.take()) {
+                let action =
+                    decide_lock_action(locked, paused.load(Ordering::Relaxed), paused_by_lock);
+                match action {
+                    LockAction::Pause => {
+                        tracing::info!("Session locked, pausing recording");
+                        paused.store(true, Ordering::Relaxed);
+                        paused_by_lock = true;
+                    }
+                    LockAction::Resume => {
+                        tracing::info!("Session unlocked, resuming recording");
+                        paused.store(false, Ordering::Relaxed);
+                        paused_by_lock = false;
+                    }
+                    LockAction::NoChange => {
+                        // Unlocking after a manual pause hands ownership back to the user.
+                        if !locked {
+                            paused_by_lock = false;
+                        }
+                    }
+                }
+            }
+
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+
+        unsafe {
+            let _ = WTSUnRegisterSessionNotification(hwnd);
+            let _ = DestroyWindow(hwnd);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub use monitor::run_session_monitor;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_while_recording_pauses() {
+        assert_eq!(decide_lock_action(true, false, false), LockAction::Pause);
+    }
+
+    #[test]
+    fn test_lock_while_manually_paused_no_change() {
+        assert_eq!(decide_lock_action(true, true, false), LockAction::NoChange);
+    }
+
+    #[test]
+    fn test_unlock_after_lock_pause_resumes() {
+        assert_eq!(decide_lock_action(false, true, true), LockAction::Resume);
+    }
+
+    #[test]
+    fn test_unlock_keeps_manual_pause() {
+        assert_eq!(decide_lock_action(false, true, false), LockAction::NoChange);
+    }
+
+    #[test]
+    fn test_unlock_while_recording_no_change() {
+        assert_eq!(decide_lock_action(false, false, false), LockAction::NoChange);
+    }
+}
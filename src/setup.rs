@@ -1,7 +1,10 @@
-use anyhow::Result;
-use std::io::{self, Read, Write};
+use anyhow::{Context, Result};
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 
+use crate::config::Config;
+use crate::transcribe::models;
+
 // ---------------------------------------------------------------------------
 // Validation helpers (standalone fns so they are unit-testable)
 // ---------------------------------------------------------------------------
@@ -14,6 +17,12 @@ fn validate_email(s: &str) -> bool {
     !s.is_empty() && s.contains('@')
 }
 
+/// One or more addresses separated by commas, each passing `validate_email`.
+fn validate_email_list(s: &str) -> bool {
+    let addresses = crate::config::split_addresses(s);
+    !addresses.is_empty() && addresses.iter().all(|a| validate_email(a))
+}
+
 // ---------------------------------------------------------------------------
 // Input helpers
 // ---------------------------------------------------------------------------
@@ -99,10 +108,6 @@ fn config_path() -> Result<PathBuf> {
     Ok(exe_dir()?.join("deskmic.toml"))
 }
 
-fn model_path(name: &str) -> Result<PathBuf> {
-    Ok(exe_dir()?.join(format!("ggml-{name}.bin")))
-}
-
 // ---------------------------------------------------------------------------
 // Config file updater – operates on the raw TOML text produced by
 // Config::generate_default_commented() so we can uncomment and fill values.
@@ -119,34 +124,28 @@ struct SummarizationCredentials {
 }
 
 fn update_config_with_summarization(config_text: &str, creds: &SummarizationCredentials) -> String {
-    // Map of commented-out key → new value to substitute.
-    // Each entry is (key_name, replacement_value).
-    let replacements: &[(&str, &str)] = &[
-        ("endpoint", &creds.azure_endpoint),
-        ("api_key", &creds.azure_api_key),
-        ("deployment", &creds.chat_deployment),
-        ("acs_endpoint", &creds.acs_endpoint),
-        ("acs_api_key", &creds.acs_api_key),
-        ("sender_address", &creds.sender_address),
-        ("recipient_address", &creds.recipient_address),
+    // Each entry is (section, key_name, replacement_value). Keys like
+    // `endpoint` and `deployment` appear in several sections, so each is
+    // only looked for under its own section header.
+    let replacements: &[(&str, &str, &str)] = &[
+        ("transcription.azure", "endpoint", &creds.azure_endpoint),
+        ("transcription.azure", "api_key", &creds.azure_api_key),
+        ("summarization", "deployment", &creds.chat_deployment),
+        ("summarization", "acs_endpoint", &creds.acs_endpoint),
+        ("summarization", "acs_api_key", &creds.acs_api_key),
+        ("summarization", "sender_address", &creds.sender_address),
+        (
+            "summarization",
+            "recipient_address",
+            &creds.recipient_address,
+        ),
     ];
 
     let mut lines: Vec<String> = config_text.lines().map(String::from).collect();
 
-    for (key, value) in replacements {
-        for line in lines.iter_mut() {
-            let trimmed = line.trim();
-            // Match lines like:  # key = "..."
-            // We look for the commented-out pattern: starts with '#' and contains `key =`
-            if trimmed.starts_with('#') {
-                // Strip the leading '#' and optional space to get the inner content.
-                let inner = trimmed.trim_start_matches('#').trim();
-                // Check if this inner content starts with `key = ` or `key ="`
-                if inner.starts_with(&format!("{key} =")) {
-                    *line = format!("{key} = \"{value}\"");
-                }
-            }
-        }
+    for (section, key, value) in replacements {
+        let value = toml::Value::String(value.to_string()).to_string();
+        crate::config::set_config_line(&mut lines, section, key, &value);
     }
 
     let mut result = lines.join("\n");
@@ -157,19 +156,55 @@ fn update_config_with_summarization(config_text: &str, creds: &SummarizationCred
     result
 }
 
+/// The commented default config with `transcription.model` set to `model`.
+fn default_config_text(model: &str) -> Result<String> {
+    let mut config = Config::default();
+    config.transcription.model = model.to_string();
+    config.to_commented_toml()
+}
+
+/// Regenerate the config at `path` from the current template, keeping its
+/// non-default values, after backing it up to `<path>.bak`.
+fn merge_config(path: &Path) -> Result<()> {
+    let existing = std::fs::read_to_string(path).context("could not read config")?;
+    let (config, _) = Config::parse(&existing).context("could not parse config")?;
+    let merged = config.to_commented_toml()?;
+    let backup = PathBuf::from(format!("{}.bak", path.display()));
+    std::fs::write(&backup, &existing).context("could not back up config")?;
+    std::fs::write(path, merged).context("could not write config")?;
+    println!(
+        "  Updated {} (previous version saved as {})",
+        path.display(),
+        backup.display()
+    );
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Step 1 – Download Whisper model
 // ---------------------------------------------------------------------------
 
-const HF_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/";
-
 const MODEL_OPTIONS: &[(&str, &str, &str)] = &[
     ("tiny.en", "ggml-tiny.en.bin", "~75 MB"),
     ("base.en", "ggml-base.en.bin", "~142 MB"),
     ("small.en", "ggml-small.en.bin", "~466 MB"),
 ];
 
-fn step_download_model() {
+/// Print a download progress line, overwriting the previous one with `\r`.
+fn print_progress(downloaded: u64, total: Option<u64>) {
+    let mb_done = downloaded as f64 / 1_048_576.0;
+    if let Some(total) = total {
+        let pct = (downloaded as f64 / total as f64 * 100.0) as u32;
+        let mb_total = total as f64 / 1_048_576.0;
+        print!("\r  [{pct:>3}%] {mb_done:.1} / {mb_total:.1} MB");
+    } else {
+        print!("\r  {mb_done:.1} MB downloaded");
+    }
+    let _ = io::stdout().flush();
+}
+
+/// Returns the chosen model name.
+fn step_download_model() -> &'static str {
     println!();
     println!("  [1/4] Whisper Model");
     println!("  -------------------");
@@ -184,11 +219,11 @@ fn step_download_model() {
 
     let (name, filename, _) = MODEL_OPTIONS[choice];
 
-    let dest = match model_path(name) {
+    let dest = match models::model_path(name) {
         Ok(p) => p,
         Err(e) => {
             println!("  Warning: could not determine model path: {e}");
-            return;
+            return name;
         }
     };
 
@@ -196,72 +231,27 @@ fn step_download_model() {
     if dest.exists() {
         if !prompt_yn(&format!("  {filename} already exists. Re-download?"), false) {
             println!("  Skipping download.");
-            return;
+            return name;
         }
     }
 
-    let url = format!("{HF_BASE_URL}{filename}");
     println!("  Downloading {filename} from Hugging Face...");
 
-    if let Err(e) = download_file(&url, &dest) {
+    let result = models::download_model(name, &dest, &mut print_progress);
+    println!(); // finish the progress line
+    if let Err(e) = result {
         println!("  Warning: download failed: {e}");
     } else {
         println!("  Saved to {}", dest.display());
     }
-}
-
-fn download_file(url: &str, dest: &Path) -> Result<()> {
-    let part_path = dest.with_extension("bin.part");
-
-    let response = reqwest::blocking::get(url)?;
-    let status = response.status();
-    if !status.is_success() {
-        anyhow::bail!("HTTP {status}");
-    }
-
-    let total_bytes = response
-        .headers()
-        .get(reqwest::header::CONTENT_LENGTH)
-        .and_then(|v| v.to_str().ok())
-        .and_then(|v| v.parse::<u64>().ok());
-
-    let mut file = std::fs::File::create(&part_path)?;
-    let mut downloaded: u64 = 0;
-    let mut reader = response;
-    let mut buf = [0u8; 8192];
-
-    loop {
-        let n = reader.read(&mut buf)?;
-        if n == 0 {
-            break;
-        }
-        file.write_all(&buf[..n])?;
-        downloaded += n as u64;
-
-        // Progress line using \r.
-        if let Some(total) = total_bytes {
-            let pct = (downloaded as f64 / total as f64 * 100.0) as u32;
-            let mb_done = downloaded as f64 / 1_048_576.0;
-            let mb_total = total as f64 / 1_048_576.0;
-            print!("\r  [{pct:>3}%] {mb_done:.1} / {mb_total:.1} MB",);
-        } else {
-            let mb_done = downloaded as f64 / 1_048_576.0;
-            print!("\r  {mb_done:.1} MB downloaded");
-        }
-        let _ = io::stdout().flush();
-    }
-    println!(); // finish the progress line
-
-    drop(file);
-    std::fs::rename(&part_path, dest)?;
-    Ok(())
+    name
 }
 
 // ---------------------------------------------------------------------------
 // Step 2 – Generate default config
 // ---------------------------------------------------------------------------
 
-fn step_generate_config() {
+fn step_generate_config(model: &str) {
     println!();
     println!("  [2/4] Configuration File");
     println!("  ------------------------");
@@ -276,16 +266,37 @@ fn step_generate_config() {
     };
 
     if path.exists() {
-        if !prompt_yn(
-            &format!("{} already exists. Overwrite?", path.display()),
-            false,
-        ) {
-            println!("  Skipping config generation.");
-            return;
+        let choice = prompt_choice(
+            &format!("{} already exists.", path.display()),
+            &[
+                "Keep it",
+                "Update it: add new settings and their docs, keep your values",
+                "Overwrite it with defaults",
+            ],
+            0,
+        );
+        match choice {
+            0 => {
+                println!("  Skipping config generation.");
+                return;
+            }
+            1 => {
+                if let Err(e) = merge_config(&path) {
+                    println!("  Warning: {e:#}");
+                }
+                return;
+            }
+            _ => {}
         }
     }
 
-    let content = crate::config::Config::generate_default_commented();
+    let content = match default_config_text(model) {
+        Ok(content) => content,
+        Err(e) => {
+            println!("  Warning: could not generate config: {e}");
+            return;
+        }
+    };
     match std::fs::write(&path, &content) {
         Ok(_) => println!("  Wrote {}", path.display()),
         Err(e) => println!("  Warning: could not write config: {e}"),
@@ -338,49 +349,42 @@ fn step_summarization() {
             "Must be a valid email address (contains @)",
         ),
         recipient_address: prompt_validated(
-            "Recipient email address",
-            validate_email,
-            "Must be a valid email address (contains @)",
+            "Recipient email address(es), comma-separated",
+            validate_email_list,
+            "Each address must be a valid email address (contains @)",
         ),
     };
 
-    // Update config file with the collected credentials.
-    let path = match config_path() {
-        Ok(p) => p,
-        Err(e) => {
-            println!("  Warning: could not determine config path: {e}");
-            return;
-        }
-    };
+    if let Err(e) = apply_summarization(&creds) {
+        println!("  Warning: {e:#}");
+    }
+}
 
+/// Write `creds` into the config file and create the summary scheduled tasks.
+fn apply_summarization(creds: &SummarizationCredentials) -> Result<()> {
+    let path = config_path().context("could not determine config path")?;
     if !path.exists() {
-        println!(
-            "  Warning: config file not found at {}. Run step 2 first.",
+        anyhow::bail!(
+            "config file not found at {}. Run step 2 first.",
             path.display()
         );
-        return;
     }
 
-    match std::fs::read_to_string(&path) {
-        Ok(content) => {
-            let updated = update_config_with_summarization(&content, &creds);
-            match std::fs::write(&path, &updated) {
-                Ok(_) => println!("  Updated {}", path.display()),
-                Err(e) => println!("  Warning: could not write config: {e}"),
-            }
-        }
-        Err(e) => {
-            println!("  Warning: could not read config: {e}");
-            return;
-        }
-    }
+    let content = std::fs::read_to_string(&path).context("could not read config")?;
+    let updated = update_config_with_summarization(&content, creds);
+    std::fs::write(&path, &updated).context("could not write config")?;
+    println!("  Updated {}", path.display());
 
     // Create scheduled tasks (Windows only).
-    create_scheduled_tasks();
+    let rolling_interval_hours = crate::config::Config::load(Some(&path))
+        .map(|c| c.summarization.rolling_interval_hours)
+        .unwrap_or(0);
+    create_scheduled_tasks(rolling_interval_hours);
+    Ok(())
 }
 
 #[cfg(target_os = "windows")]
-fn create_scheduled_tasks() {
+fn create_scheduled_tasks(rolling_interval_hours: u32) {
     let exe = match std::env::current_exe() {
         Ok(p) => p,
         Err(e) => {
@@ -390,7 +394,9 @@ fn create_scheduled_tasks() {
     };
     let exe_str = exe.display().to_string();
 
-    let tasks = [
+    // schtasks accepts an hourly modifier of 1-23.
+    let rolling_modifier = rolling_interval_hours.min(23).to_string();
+    let mut tasks = vec![
         (
             "deskmic-daily-summary",
             format!("\"{}\" summarize daily", exe_str),
@@ -402,6 +408,13 @@ fn create_scheduled_tasks() {
             vec!["/SC", "WEEKLY", "/D", "MON", "/ST", "07:00"],
         ),
     ];
+    if rolling_interval_hours > 0 {
+        tasks.push((
+            "deskmic-rolling-summary",
+            format!("\"{}\" summarize --rolling", exe_str),
+            vec!["/SC", "HOURLY", "/MO", rolling_modifier.as_str()],
+        ));
+    }
 
     for (name, tr, extra) in &tasks {
         let mut cmd = std::process::Command::new("schtasks");
@@ -443,7 +456,7 @@ fn create_scheduled_tasks() {
 }
 
 #[cfg(not(target_os = "windows"))]
-fn create_scheduled_tasks() {
+fn create_scheduled_tasks(_rolling_interval_hours: u32) {
     println!("  Scheduled tasks are only supported on Windows.");
 }
 
@@ -487,8 +500,8 @@ pub fn run_setup() -> Result<()> {
     println!("  deskmic setup");
     println!("  =============");
 
-    step_download_model();
-    step_generate_config();
+    let model = step_download_model();
+    step_generate_config(model);
     step_summarization();
     step_autostart();
 
@@ -499,6 +512,172 @@ pub fn run_setup() -> Result<()> {
     Ok(())
 }
 
+// ---------------------------------------------------------------------------
+// Non-interactive entry point (`deskmic setup --noninteractive`)
+// ---------------------------------------------------------------------------
+
+/// Answers to the wizard's questions, taken from flags or the environment.
+pub struct SetupAnswers {
+    pub model: String,
+    pub install_startup: bool,
+    /// Re-download an existing model and overwrite an existing config.
+    pub force: bool,
+    /// Regenerate an existing config, keeping its values.
+    pub update_config: bool,
+    pub azure_endpoint: Option<String>,
+    pub azure_api_key: Option<String>,
+    pub chat_deployment: Option<String>,
+    pub acs_endpoint: Option<String>,
+    pub acs_api_key: Option<String>,
+    pub sender_address: Option<String>,
+    pub recipient_address: Option<String>,
+}
+
+impl SetupAnswers {
+    /// Check every answer, reporting all problems at once. Returns the
+    /// summarization credentials, `None` when none were given.
+    fn validate(&self) -> Result<Option<SummarizationCredentials>> {
+        let mut errors = Vec::new();
+        if !models::is_known_model(&self.model) {
+            errors.push(format!(
+                "--model: unknown Whisper model '{}'; known models: {}",
+                self.model,
+                models::KNOWN_MODELS.join(", ")
+            ));
+        }
+
+        type Field<'a> = (&'a str, &'a Option<String>, fn(&str) -> bool, &'a str);
+        let fields: [Field; 7] = [
+            (
+                "--azure-endpoint",
+                &self.azure_endpoint,
+                validate_url,
+                "must start with https://",
+            ),
+            (
+                "--azure-api-key",
+                &self.azure_api_key,
+                nonempty,
+                "cannot be empty",
+            ),
+            (
+                "--chat-deployment",
+                &self.chat_deployment,
+                nonempty,
+                "cannot be empty",
+            ),
+            (
+                "--acs-endpoint",
+                &self.acs_endpoint,
+                validate_url,
+                "must start with https://",
+            ),
+            (
+                "--acs-api-key",
+                &self.acs_api_key,
+                nonempty,
+                "cannot be empty",
+            ),
+            (
+                "--sender-address",
+                &self.sender_address,
+                validate_email,
+                "must contain @",
+            ),
+            (
+                "--recipient-address",
+                &self.recipient_address,
+                validate_email_list,
+                "each address must contain @",
+            ),
+        ];
+        let wants_summaries = fields.iter().any(|(_, value, _, _)| value.is_some());
+        if wants_summaries {
+            for (flag, value, valid, message) in &fields {
+                match value {
+                    None => errors.push(format!("{flag} is required for email summaries")),
+                    Some(v) if !valid(v.trim()) => errors.push(format!("{flag} {message}")),
+                    Some(_) => {}
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            anyhow::bail!("Invalid setup options:\n  {}", errors.join("\n  "));
+        }
+        if !wants_summaries {
+            return Ok(None);
+        }
+        let answer = |v: &Option<String>| v.as_deref().unwrap_or_default().trim().to_string();
+        Ok(Some(SummarizationCredentials {
+            azure_endpoint: answer(&self.azure_endpoint),
+            azure_api_key: answer(&self.azure_api_key),
+            chat_deployment: answer(&self.chat_deployment),
+            acs_endpoint: answer(&self.acs_endpoint),
+            acs_api_key: answer(&self.acs_api_key),
+            sender_address: answer(&self.sender_address),
+            recipient_address: answer(&self.recipient_address),
+        }))
+    }
+}
+
+/// Run the wizard's four steps without reading stdin, for unattended
+/// installs. Unlike the interactive wizard, any failed step is an error.
+pub fn run_setup_noninteractive(answers: &SetupAnswers) -> Result<()> {
+    let creds = answers.validate()?;
+
+    println!();
+    println!("  deskmic setup (non-interactive)");
+    println!("  ===============================");
+
+    println!();
+    println!("  [1/4] Whisper model {}", answers.model);
+    let dest = models::model_path(&answers.model)?;
+    if dest.exists() && !answers.force {
+        println!("  {} already exists, keeping it.", dest.display());
+    } else {
+        println!("  Downloading from Hugging Face...");
+        let result = models::download_model(&answers.model, &dest, &mut print_progress);
+        println!(); // finish the progress line
+        result.context("Model download failed")?;
+        println!("  Saved to {}", dest.display());
+    }
+
+    println!();
+    println!("  [2/4] Configuration file");
+    let path = config_path()?;
+    if path.exists() && answers.update_config && !answers.force {
+        merge_config(&path).with_context(|| format!("Could not update {}", path.display()))?;
+    } else if path.exists() && !answers.force {
+        println!("  {} already exists, keeping it.", path.display());
+    } else {
+        std::fs::write(&path, default_config_text(&answers.model)?)
+            .with_context(|| format!("Could not write {}", path.display()))?;
+        println!("  Wrote {}", path.display());
+    }
+
+    println!();
+    println!("  [3/4] Email summaries");
+    match &creds {
+        Some(creds) => apply_summarization(creds)?,
+        None => println!("  No credentials given, skipping."),
+    }
+
+    println!();
+    println!("  [4/4] Windows startup");
+    if answers.install_startup {
+        crate::commands::install_startup().context("Could not add deskmic to startup")?;
+        println!("  Added to Windows startup.");
+    } else {
+        println!("  Not requested, skipping.");
+    }
+
+    println!();
+    println!("  Setup complete!");
+    println!();
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -525,6 +704,24 @@ mod tests {
         assert!(!validate_email(""));
     }
 
+    #[test]
+    fn test_validate_email_list() {
+        assert!(validate_email_list("a@b.com"));
+        assert!(validate_email_list("a@b.com, boss@b.com"));
+        assert!(!validate_email_list("a@b.com, nope"));
+        assert!(!validate_email_list(" , "));
+    }
+
+    // -- model options ------------------------------------------------------
+
+    #[test]
+    fn test_model_options_are_pinned() {
+        for (name, filename, _) in MODEL_OPTIONS {
+            assert!(models::pinned_sha256(name).is_some(), "{name} has no pin");
+            assert_eq!(*filename, format!("ggml-{name}.bin"));
+        }
+    }
+
     // -- config update ------------------------------------------------------
 
     #[test]
@@ -595,4 +792,154 @@ mod tests {
             "acs_endpoint should be uncommented"
         );
     }
+
+    fn test_creds() -> SummarizationCredentials {
+        SummarizationCredentials {
+            azure_endpoint: "https://my-oai.openai.azure.com".into(),
+            azure_api_key: "oai-secret".into(),
+            chat_deployment: "gpt-4o-mini".into(),
+            acs_endpoint: "https://my-acs.communication.azure.com".into(),
+            acs_api_key: "acs-secret".into(),
+            sender_address: "bot@contoso.azurecomm.net".into(),
+            recipient_address: "alice@example.com".into(),
+        }
+    }
+
+    #[test]
+    fn test_config_update_is_section_aware() {
+        // Summarization before the Azure block, which sets its own
+        // deployment and already has an api_key.
+        let sample = r#"[summarization]
+# deployment = "gpt-4o"
+# api_key = ""
+# acs_endpoint = ""
+
+[transcription.azure]
+# endpoint = "https://your-resource.openai.azure.com"
+# api_key = ""
+api_key = "old-key"
+deployment = "whisper"
+"#;
+
+        let updated = update_config_with_summarization(sample, &test_creds());
+        let config: crate::config::Config = toml::from_str(&updated).unwrap();
+
+        let azure = &config.transcription.azure;
+        assert_eq!(azure.endpoint, "https://my-oai.openai.azure.com");
+        assert_eq!(azure.api_key, "oai-secret");
+        assert_eq!(azure.deployment, "whisper");
+        assert_eq!(config.summarization.deployment, "gpt-4o-mini");
+        assert_eq!(
+            config.summarization.acs_endpoint,
+            "https://my-acs.communication.azure.com"
+        );
+        // Keys missing from the section are added to it.
+        assert_eq!(
+            config.summarization.sender_address,
+            "bot@contoso.azurecomm.net"
+        );
+        // The summarization api_key example is for other providers.
+        assert!(updated.contains("\n# api_key = \"\"\n"), "{updated}");
+    }
+
+    #[test]
+    fn test_config_update_adds_missing_sections() {
+        let updated = update_config_with_summarization("[output]\n", &test_creds());
+        let config: crate::config::Config = toml::from_str(&updated).unwrap();
+        assert_eq!(
+            config.transcription.azure.endpoint,
+            "https://my-oai.openai.azure.com"
+        );
+        assert_eq!(config.summarization.recipient_address, "alice@example.com");
+    }
+
+    #[test]
+    fn test_merge_config_keeps_values_and_backs_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("deskmic.toml");
+        let old = "# my notes\n[capture]\nsample_rate = 8000\n\n[hotkeys]\npause_toggle = \"\"\n";
+        std::fs::write(&path, old).unwrap();
+
+        merge_config(&path).unwrap();
+
+        let backup = dir.path().join("deskmic.toml.bak");
+        assert_eq!(std::fs::read_to_string(backup).unwrap(), old);
+        let merged = std::fs::read_to_string(&path).unwrap();
+        let config: Config = toml::from_str(&merged).unwrap();
+        assert_eq!(config.capture.sample_rate, 8000);
+        assert!(config.hotkeys.pause_toggle.is_empty());
+        // Sections the old file lacked come with their docs.
+        assert!(merged.contains("[transcription.idle_watch]"), "{merged}");
+    }
+
+    #[test]
+    fn test_default_config_text_sets_model() {
+        let text = default_config_text("tiny.en").unwrap();
+        let config: crate::config::Config = toml::from_str(&text).unwrap();
+        assert_eq!(config.transcription.model, "tiny.en");
+    }
+
+    // -- non-interactive answers ---------------------------------------------
+
+    fn answers() -> SetupAnswers {
+        SetupAnswers {
+            model: "base.en".into(),
+            install_startup: false,
+            force: false,
+            update_config: false,
+            azure_endpoint: None,
+            azure_api_key: None,
+            chat_deployment: None,
+            acs_endpoint: None,
+            acs_api_key: None,
+            sender_address: None,
+            recipient_address: None,
+        }
+    }
+
+    #[test]
+    fn test_answers_without_summaries() {
+        assert!(answers().validate().unwrap().is_none());
+
+        let unknown = SetupAnswers {
+            model: "huge".into(),
+            ..answers()
+        };
+        let Err(err) = unknown.validate() else {
+            panic!("expected an unknown model error");
+        };
+        assert!(err.to_string().contains("unknown Whisper model"), "{err}");
+    }
+
+    #[test]
+    fn test_answers_with_summaries() {
+        let full = SetupAnswers {
+            azure_endpoint: Some("https://my-oai.openai.azure.com".into()),
+            azure_api_key: Some("oai-secret".into()),
+            chat_deployment: Some("gpt-4o".into()),
+            acs_endpoint: Some("https://my-acs.communication.azure.com".into()),
+            acs_api_key: Some("acs-secret".into()),
+            sender_address: Some("bot@contoso.azurecomm.net".into()),
+            recipient_address: Some("alice@example.com, bob@example.com".into()),
+            ..answers()
+        };
+        let creds = full.validate().unwrap().unwrap();
+        assert_eq!(creds.chat_deployment, "gpt-4o");
+
+        // Partial or invalid credentials are reported together.
+        let partial = SetupAnswers {
+            acs_endpoint: Some("http://insecure".into()),
+            acs_api_key: None,
+            ..full
+        };
+        let Err(err) = partial.validate() else {
+            panic!("expected partial credentials to be rejected");
+        };
+        let err = err.to_string();
+        assert!(
+            err.contains("--acs-endpoint must start with https://"),
+            "{err}"
+        );
+        assert!(err.contains("--acs-api-key is required"), "{err}");
+    }
 }
@@ -0,0 +1,244 @@
+// Safe shutdown on Windows session end (logoff / system shutdown).
+//
+// Windows only gives a process a few seconds after `WM_ENDSESSION` or
+// `CTRL_SHUTDOWN_EVENT` before terminating it. The handlers here set the
+// shared shutdown flag and then block until the recorder reports that the
+// file writer has finalized every open WAV (or the grace period runs out).
+//
+// `request_and_wait` / `mark_finalized` are cross-platform; the handlers that
+// call them are Windows-only. `join_with_timeout` lets the recorder move on
+// from capture threads stuck in a driver call.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// How long a session-end handler blocks waiting for files to be finalized.
+pub const FINALIZE_GRACE: Duration = Duration::from_secs(4);
+
+/// How long shutdown waits for capture threads to exit before moving on, so a
+/// wedged audio device can't keep recordings from being finalized. Files are
+/// only finalized after this, so it must leave most of `FINALIZE_GRACE` for it.
+pub const CAPTURE_JOIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+static SHUTDOWN: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+static FINALIZED: AtomicBool = AtomicBool::new(false);
+
+/// Register the recorder's shutdown flag so session-end handlers can set it.
+pub fn register(shutdown: Arc<AtomicBool>) {
+    let _ = SHUTDOWN.set(shutdown);
+}
+
+/// Called by the recorder once the file writer has finished.
+pub fn mark_finalized() {
+    FINALIZED.store(true, Ordering::Relaxed);
+}
+
+/// Set the shutdown flag and block until `mark_finalized` is called or
+/// `timeout` elapses. Returns `true` if finalization completed in time.
+pub fn request_and_wait(timeout: Duration) -> bool {
+    match SHUTDOWN.get() {
+        Some(shutdown) => shutdown.store(true, Ordering::Relaxed),
+        None => return true,
+    }
+    wait_for(&FINALIZED, timeout)
+}
+
+/// Poll `flag` until it is set or `timeout` elapses.
+fn wait_for(flag: &AtomicBool, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while !flag.load(Ordering::Relaxed) {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    true
+}
+
+/// Join `handles`, giving up on any that are still running after `timeout`.
+/// Returns the names of the threads left behind; they end with the process.
+pub fn join_with_timeout(handles: Vec<JoinHandle<()>>, timeout: Duration) -> Vec<String> {
+    let deadline = Instant::now() + timeout;
+    let mut pending = handles;
+    loop {
+        let (done, running): (Vec<_>, Vec<_>) = pending.into_iter().partition(|h| h.is_finished());
+        for h in done {
+            let _ = h.join();
+        }
+        pending = running;
+        if pending.is_empty() || Instant::now() >= deadline {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    pending
+        .iter()
+        .map(|h| h.thread().name().unwrap_or("unnamed").to_string())
+        .collect()
+}
+
+// --- Windows-only session-end handlers ---
+
+#[cfg(target_os = "windows")]
+mod handlers {
+    use anyhow::Result;
+    use windows::core::{BOOL, PCWSTR};
+    use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::System::Console::{
+        SetConsoleCtrlHandler, CTRL_CLOSE_EVENT, CTRL_LOGOFF_EVENT, CTRL_SHUTDOWN_EVENT,
+    };
+    use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::UI::WindowsAndMessaging::*;
+
+    use super::{request_and_wait, FINALIZE_GRACE};
+
+    unsafe extern "system" fn console_handler(ctrl_type: u32) -> BOOL {
+        match ctrl_type {
+            CTRL_CLOSE_EVENT | CTRL_LOGOFF_EVENT | CTRL_SHUTDOWN_EVENT => {
+                tracing::info!("Session ending (console event {}), finalizing files", ctrl_type);
+                if !request_and_wait(FINALIZE_GRACE) {
+                    tracing::warn!("Timed out waiting for files to finalize");
+                }
+                BOOL(1)
+            }
+            // Ctrl+C / Ctrl+Break fall through to the ctrlc handler.
+            _ => BOOL(0),
+        }
+    }
+
+    /// Install a console control handler for close/logoff/shutdown events.
+    ///
+    /// Must be called after `ctrlc::set_handler` so it runs first: handlers
+    /// are invoked in reverse registration order, and the ctrlc handler
+    /// returns immediately, which would let Windows terminate the process
+    /// before the file writer finishes.
+    pub fn install_console_handler() -> Result<()> {
+        unsafe { SetConsoleCtrlHandler(Some(console_handler), true)? };
+        Ok(())
+    }
+
+    unsafe extern "system" fn wndproc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        match msg {
+            // Never veto the session ending.
+            WM_QUERYENDSESSION => LRESULT(1),
+            WM_ENDSESSION => {
+                if wparam.0 != 0 {
+                    tracing::info!("Session ending (WM_ENDSESSION), finalizing files");
+                    if !request_and_wait(FINALIZE_GRACE) {
+                        tracing::warn!("Timed out waiting for files to finalize");
+                    }
+                }
+                LRESULT(0)
+            }
+            _ => unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) },
+        }
+    }
+
+    /// Create a hidden top-level window that receives `WM_QUERYENDSESSION` /
+    /// `WM_ENDSESSION`. Message-only windows don't get these broadcasts, so
+    /// this is a regular (never shown) window. Messages are delivered by the
+    /// message pump of the calling thread.
+    pub fn create_end_session_window() -> Result<HWND> {
+        let class_name: Vec<u16> = "deskmic-end-session"
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+
+        unsafe {
+            let hinstance = GetModuleHandleW(None)?;
+            let wc = WNDCLASSW {
+                lpfnWndProc: Some(wndproc),
+                hInstance: hinstance.into(),
+                lpszClassName: PCWSTR(class_name.as_ptr()),
+                ..Default::default()
+            };
+            if RegisterClassW(&wc) == 0 {
+                anyhow::bail!("Failed to register end-session window class");
+            }
+            let hwnd = CreateWindowExW(
+                WS_EX_TOOLWINDOW,
+                PCWSTR(class_name.as_ptr()),
+                PCWSTR::null(),
+                WINDOW_STYLE::default(),
+                0,
+                0,
+                0,
+                0,
+                None,
+                None,
+                Some(hinstance.into()),
+                None,
+            )?;
+            Ok(hwnd)
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub use handlers::{create_end_session_window, install_console_handler};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wait_for_returns_immediately_when_set() {
+        let flag = AtomicBool::new(true);
+        let start = Instant::now();
+        assert!(wait_for(&flag, Duration::from_secs(5)));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_wait_for_times_out() {
+        let flag = AtomicBool::new(false);
+        assert!(!wait_for(&flag, Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn test_wait_for_sees_flag_set_by_other_thread() {
+        let flag = Arc::new(AtomicBool::new(false));
+        let setter = flag.clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            setter.store(true, Ordering::Relaxed);
+        });
+        assert!(wait_for(&flag, Duration::from_secs(5)));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_capture_join_leaves_time_to_finalize() {
+        assert!(CAPTURE_JOIN_TIMEOUT * 2 <= FINALIZE_GRACE);
+    }
+
+    #[test]
+    fn test_join_with_timeout_leaves_stuck_threads_behind() {
+        let release = Arc::new(AtomicBool::new(false));
+        let stuck_release = release.clone();
+        let builder = |name: &str| std::thread::Builder::new().name(name.into());
+        let handles = vec![
+            builder("quick").spawn(|| {}).unwrap(),
+            builder("stuck")
+                .spawn(move || {
+                    wait_for(&stuck_release, Duration::from_secs(30));
+                })
+                .unwrap(),
+        ];
+
+        let start = Instant::now();
+        let stuck = join_with_timeout(handles, Duration::from_millis(200));
+        assert_eq!(stuck, vec!["stuck"]);
+        assert!(start.elapsed() < Duration::from_secs(2));
+        release.store(true, Ordering::Relaxed);
+
+        assert!(join_with_timeout(Vec::new(), Duration::from_secs(5)).is_empty());
+    }
+}
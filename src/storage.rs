@@ -1,19 +1,62 @@
+use std::collections::BTreeMap;
 use std::path::Path;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use chrono::{Local, NaiveDate};
+use serde::Serialize;
 
 use crate::config::StorageConfig;
+use crate::transcribe::state::TranscriptionState;
+
+/// What a cleanup run deleted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CleanupReport {
+    pub bytes_freed: u64,
+    pub folders_deleted: usize,
+}
+
+impl CleanupReport {
+    fn record(&mut self, bytes: u64) {
+        self.bytes_freed += bytes;
+        self.folders_deleted += 1;
+    }
+
+    fn merge(&mut self, other: CleanupReport) {
+        self.bytes_freed += other.bytes_freed;
+        self.folders_deleted += other.folders_deleted;
+    }
+
+    /// e.g. "Freed 12.3 MB across 2 folders".
+    pub fn summary(&self) -> String {
+        format!(
+            "Freed {:.1} MB across {} folder{}",
+            self.bytes_freed as f64 / 1_048_576.0,
+            self.folders_deleted,
+            if self.folders_deleted == 1 { "" } else { "s" }
+        )
+    }
+}
 
 /// Deletes recording folders older than retention_days.
-pub fn cleanup_old_recordings(recordings_dir: &Path, config: &StorageConfig) -> Result<u64> {
+///
+/// With `require_transcribed_before_delete`, a folder still holding
+/// recordings that no machine has transcribed (and that haven't failed for
+/// good) is kept until a later run.
+pub fn cleanup_old_recordings(
+    recordings_dir: &Path,
+    config: &StorageConfig,
+) -> Result<CleanupReport> {
     let cutoff = Local::now().date_naive() - chrono::Duration::days(config.retention_days as i64);
-    let mut bytes_freed: u64 = 0;
+    let mut report = CleanupReport::default();
 
     if !recordings_dir.exists() {
-        return Ok(0);
+        return Ok(report);
     }
 
+    let state = transcription_state(recordings_dir, config)?;
+
     for entry in std::fs::read_dir(recordings_dir)? {
         let entry = entry?;
         let name = entry.file_name();
@@ -21,49 +64,128 @@ pub fn cleanup_old_recordings(recordings_dir: &Path, config: &StorageConfig) ->
 
         if let Ok(folder_date) = NaiveDate::parse_from_str(&name_str, "%Y-%m-%d") {
             if folder_date < cutoff {
+                if let Some(state) = &state {
+                    let pending = untranscribed_recordings(&entry.path(), &name_str, state)?;
+                    if pending > 0 {
+                        tracing::warn!(
+                            "Keeping old recordings {}: {} not transcribed yet",
+                            name_str,
+                            pending
+                        );
+                        continue;
+                    }
+                }
                 let size = dir_size(&entry.path())?;
                 std::fs::remove_dir_all(entry.path())?;
-                bytes_freed += size;
+                report.record(size);
                 tracing::info!("Deleted old recordings: {} ({} bytes)", name_str, size);
             }
         }
     }
-    Ok(bytes_freed)
+    Ok(report)
 }
 
-/// Enforce max disk usage by deleting oldest folders first.
-pub fn enforce_disk_limit(recordings_dir: &Path, max_bytes: u64) -> Result<()> {
+/// Every machine's transcription state, if `require_transcribed_before_delete`
+/// asks cleanup to check it.
+fn transcription_state(
+    recordings_dir: &Path,
+    config: &StorageConfig,
+) -> Result<Option<TranscriptionState>> {
+    if !config.require_transcribed_before_delete {
+        return Ok(None);
+    }
+    // Loaded without a machine id, this covers every machine's state file.
+    Ok(Some(TranscriptionState::load(recordings_dir, None)?))
+}
+
+/// Enforce max disk usage by deleting the oldest recording folders first.
+///
+/// The limit applies to the whole recordings tree, including `transcripts/`
+/// and `summaries/`, but only date folders of audio are ever deleted, and
+/// never today's (it's being recorded into). Given a transcription `state`
+/// (see `require_transcribed_before_delete`), folders still holding
+/// recordings that aren't settled are kept too. If the limit can't be met
+/// even by deleting every eligible folder, nothing is deleted and a warning is
+/// logged instead, so the recordings aren't thrown away for nothing.
+pub fn enforce_disk_limit(
+    recordings_dir: &Path,
+    max_bytes: u64,
+    state: Option<&TranscriptionState>,
+) -> Result<CleanupReport> {
+    let mut report = CleanupReport::default();
     if !recordings_dir.exists() {
-        return Ok(());
+        return Ok(report);
     }
 
     let current = dir_size(recordings_dir)?;
     if current <= max_bytes {
-        return Ok(());
+        return Ok(report);
     }
 
-    let mut folders: Vec<(NaiveDate, std::path::PathBuf)> = Vec::new();
+    let today = Local::now().date_naive();
+    let mut folders: Vec<(NaiveDate, std::path::PathBuf, u64)> = Vec::new();
     for entry in std::fs::read_dir(recordings_dir)? {
         let entry = entry?;
         let name = entry.file_name();
         let name_str = name.to_string_lossy();
         if let Ok(date) = NaiveDate::parse_from_str(&name_str, "%Y-%m-%d") {
-            folders.push((date, entry.path()));
+            if date < today && entry.file_type()?.is_dir() {
+                if let Some(state) = state {
+                    let pending = untranscribed_recordings(&entry.path(), &name_str, state)?;
+                    if pending > 0 {
+                        tracing::warn!(
+                            "Disk limit: keeping {}, {} recordings not transcribed yet",
+                            name_str,
+                            pending
+                        );
+                        continue;
+                    }
+                }
+                folders.push((date, entry.path(), dir_size(&entry.path())?));
+            }
         }
     }
-    folders.sort_by_key(|(date, _)| *date);
+    folders.sort_by_key(|(date, _, _)| *date);
+
+    let deletable: u64 = folders.iter().map(|(_, _, size)| size).sum();
+    if current - deletable > max_bytes {
+        tracing::warn!(
+            "Disk limit of {} bytes can't be met by deleting old recordings: \
+             {} bytes are transcripts, summaries, today's or untranscribed recordings",
+            max_bytes,
+            current - deletable
+        );
+        return Ok(report);
+    }
 
     let mut remaining = current;
-    for (date, path) in folders {
+    for (date, path, size) in folders {
         if remaining <= max_bytes {
             break;
         }
-        let size = dir_size(&path)?;
         std::fs::remove_dir_all(&path)?;
         remaining -= size;
+        report.record(size);
         tracing::info!("Deleted {} to free space ({} bytes)", date, size);
     }
-    Ok(())
+    Ok(report)
+}
+
+/// Number of recordings in the date folder `dir` (named `folder`) that no
+/// machine has transcribed or given up on.
+fn untranscribed_recordings(dir: &Path, folder: &str, state: &TranscriptionState) -> Result<usize> {
+    let mut count = 0;
+    for file in std::fs::read_dir(dir)? {
+        let path = file?.path();
+        if !crate::audio::decode::is_recording(&path) {
+            continue;
+        }
+        let relative = format!("{}/{}", folder, path.file_name().unwrap().to_string_lossy());
+        if !state.is_settled(&relative) {
+            count += 1;
+        }
+    }
+    Ok(count)
 }
 
 fn dir_size(path: &Path) -> Result<u64> {
@@ -82,66 +204,233 @@ fn dir_size(path: &Path) -> Result<u64> {
     Ok(total)
 }
 
-/// Returns (total_files, total_bytes) for the recordings directory.
-pub fn get_storage_stats(recordings_dir: &Path) -> Result<(usize, u64)> {
-    let mut count = 0;
-    let mut bytes = 0;
+/// A number of files and their total size.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FileStats {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+impl FileStats {
+    fn add(&mut self, bytes: u64) {
+        self.files += 1;
+        self.bytes += bytes;
+    }
+}
+
+/// What the recordings directory holds.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct StorageStats {
+    /// Every file one folder down (recordings, transcripts, summaries, ...).
+    pub files: usize,
+    pub bytes: u64,
+    /// Recordings per capture source, from the file name prefix.
+    pub by_source: BTreeMap<String, FileStats>,
+    /// Recordings per `YYYY-MM-DD` date folder.
+    pub by_date: BTreeMap<String, FileStats>,
+}
+
+/// Totals for the recordings directory, with recordings broken down by
+/// source (read from their names with `filename_template`) and by day.
+pub fn get_storage_stats(recordings_dir: &Path, filename_template: &str) -> Result<StorageStats> {
+    let mut stats = StorageStats::default();
 
     if !recordings_dir.exists() {
-        return Ok((0, 0));
+        return Ok(stats);
     }
 
     for entry in std::fs::read_dir(recordings_dir)? {
         let entry = entry?;
-        if entry.file_type()?.is_dir() {
-            for file in std::fs::read_dir(entry.path())? {
-                let file = file?;
-                if file.file_type()?.is_file() {
-                    count += 1;
-                    bytes += file.metadata()?.len();
-                }
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let folder = entry.file_name().to_string_lossy().to_string();
+        let is_date = NaiveDate::parse_from_str(&folder, "%Y-%m-%d").is_ok();
+        for file in std::fs::read_dir(entry.path())? {
+            let file = file?;
+            if !file.file_type()?.is_file() {
+                continue;
+            }
+            let bytes = file.metadata()?.len();
+            stats.files += 1;
+            stats.bytes += bytes;
+            if is_date && crate::audio::decode::is_recording(&file.path()) {
+                let name = file.file_name().to_string_lossy().to_string();
+                let source =
+                    crate::transcribe::backend::source_from_filename(filename_template, &name);
+                stats.by_source.entry(source).or_default().add(bytes);
+                stats.by_date.entry(folder.clone()).or_default().add(bytes);
             }
         }
     }
+    Ok(stats)
+}
+
+/// Returns (files, bytes) recorded on `date`, i.e. in its `YYYY-MM-DD` folder.
+pub fn get_day_stats(recordings_dir: &Path, date: NaiveDate) -> Result<(usize, u64)> {
+    let day_dir = recordings_dir.join(date.format("%Y-%m-%d").to_string());
+    let mut count = 0;
+    let mut bytes = 0;
+
+    if !day_dir.is_dir() {
+        return Ok((0, 0));
+    }
+
+    for file in std::fs::read_dir(&day_dir)? {
+        let file = file?;
+        if file.file_type()?.is_file() {
+            count += 1;
+            bytes += file.metadata()?.len();
+        }
+    }
     Ok((count, bytes))
 }
 
+/// Bytes free on the volume holding `path`, or `None` if no mounted disk
+/// contains it.
+pub fn free_space(path: &Path) -> Option<u64> {
+    let path = std::path::absolute(path).ok()?;
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+/// How often the file writer may check free space for `LowDiskTrigger`.
+const LOW_DISK_CHECK_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Asks the cleanup thread to run right away, e.g. when free space on the
+/// recordings drive drops below `storage.min_free_gb`.
+pub struct LowDiskTrigger {
+    min_free_bytes: Option<u64>,
+    cleanup: Sender<()>,
+    last_check: Option<Instant>,
+}
+
+impl LowDiskTrigger {
+    /// A trigger that wakes the `run_cleanup_loop` holding `cleanup`'s receiver.
+    pub fn new(config: &StorageConfig, cleanup: Sender<()>) -> Self {
+        Self {
+            min_free_bytes: config.min_free_gb.map(|gb| (gb * 1_073_741_824.0) as u64),
+            cleanup,
+            last_check: None,
+        }
+    }
+
+    /// Check free space on `recordings_dir`'s drive (at most every
+    /// `LOW_DISK_CHECK_INTERVAL`) and run cleanup now if it's below
+    /// `min_free_gb`.
+    pub fn check(&mut self, recordings_dir: &Path) {
+        let Some(min_free) = self.min_free_bytes else {
+            return;
+        };
+        if self
+            .last_check
+            .is_some_and(|at| at.elapsed() < LOW_DISK_CHECK_INTERVAL)
+        {
+            return;
+        }
+        self.last_check = Some(Instant::now());
+        match free_space(recordings_dir) {
+            Some(free) if free < min_free => {
+                tracing::warn!(
+                    "Only {:.1} GB free for recordings, running cleanup now",
+                    free as f64 / 1_073_741_824.0
+                );
+                self.run_now();
+            }
+            _ => {}
+        }
+    }
+
+    /// Run cleanup now, whatever the free space.
+    pub fn run_now(&self) {
+        let _ = self.cleanup.send(());
+    }
+}
+
 /// Run cleanup loop on a dedicated thread.
+///
+/// Besides every `cleanup_interval_hours`, cleanup runs as soon as a message
+/// arrives on `wake` (see `LowDiskTrigger`).
 pub fn run_cleanup_loop(
     recordings_dir: std::path::PathBuf,
-    config: StorageConfig,
+    mut config: StorageConfig,
+    live: std::sync::Arc<crate::config_watch::LiveSettings>,
+    wake: Receiver<()>,
     shutdown: std::sync::Arc<std::sync::atomic::AtomicBool>,
 ) {
     let interval = std::time::Duration::from_secs(config.cleanup_interval_hours as u64 * 3600);
 
-    run_cleanup_once(&recordings_dir, &config);
+    notify_cleanup(&run_cleanup_once(&recordings_dir, &config));
 
     while !shutdown.load(std::sync::atomic::Ordering::Relaxed) {
         let start = std::time::Instant::now();
         while start.elapsed() < interval && !shutdown.load(std::sync::atomic::Ordering::Relaxed) {
-            std::thread::sleep(std::time::Duration::from_secs(10));
+            // A reloaded retention period is applied right away.
+            if live.retention_days() != config.retention_days {
+                config.retention_days = live.retention_days();
+                tracing::info!("Retention changed to {} days", config.retention_days);
+                break;
+            }
+            match wake.recv_timeout(std::time::Duration::from_secs(10)) {
+                Ok(()) => {
+                    // Requests that piled up are served by this one run.
+                    while wake.try_recv().is_ok() {}
+                    break;
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                // No trigger left (the writer has stopped): just wait.
+                Err(RecvTimeoutError::Disconnected) => {
+                    std::thread::sleep(std::time::Duration::from_secs(10))
+                }
+            }
         }
         if !shutdown.load(std::sync::atomic::Ordering::Relaxed) {
-            run_cleanup_once(&recordings_dir, &config);
+            notify_cleanup(&run_cleanup_once(&recordings_dir, &config));
         }
     }
 }
 
-fn run_cleanup_once(recordings_dir: &Path, config: &StorageConfig) {
-    if let Err(e) = cleanup_old_recordings(recordings_dir, config) {
-        tracing::error!("Cleanup error: {:?}", e);
+/// Toast a rollup of what a cleanup run deleted (nothing is shown when
+/// nothing was).
+fn notify_cleanup(report: &CleanupReport) {
+    if report.folders_deleted > 0 {
+        crate::monitoring::send_toast("deskmic cleanup", &report.summary());
+    }
+}
+
+/// Run retention cleanup and the disk limit, then log and return a rollup of
+/// what was deleted. The caller decides whether to show it.
+pub fn run_cleanup_once(recordings_dir: &Path, config: &StorageConfig) -> CleanupReport {
+    let mut report = CleanupReport::default();
+    match cleanup_old_recordings(recordings_dir, config) {
+        Ok(r) => report.merge(r),
+        Err(e) => tracing::error!("Cleanup error: {:?}", e),
     }
     if let Some(max_gb) = config.max_disk_usage_gb {
         let max_bytes = (max_gb * 1_073_741_824.0) as u64;
-        if let Err(e) = enforce_disk_limit(recordings_dir, max_bytes) {
-            tracing::error!("Disk limit enforcement error: {:?}", e);
+        let result = transcription_state(recordings_dir, config)
+            .and_then(|state| enforce_disk_limit(recordings_dir, max_bytes, state.as_ref()));
+        match result {
+            Ok(r) => report.merge(r),
+            Err(e) => tracing::error!("Disk limit enforcement error: {:?}", e),
         }
     }
+
+    if report.folders_deleted > 0 {
+        tracing::info!("Cleanup: {}", report.summary());
+    }
+    report
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::DEFAULT_FILENAME_TEMPLATE;
     use std::fs;
     use tempfile::TempDir;
 
@@ -150,6 +439,8 @@ mod tests {
             retention_days,
             cleanup_interval_hours: 24,
             max_disk_usage_gb: None,
+            min_free_gb: None,
+            require_transcribed_before_delete: false,
         }
     }
 
@@ -172,11 +463,12 @@ mod tests {
         let recent_dir = create_date_folder(tmp.path(), recent_date, b"fake audio data");
 
         let config = make_config(30);
-        let freed = cleanup_old_recordings(tmp.path(), &config).unwrap();
+        let report = cleanup_old_recordings(tmp.path(), &config).unwrap();
 
         assert!(!old_dir.exists(), "Old folder should be deleted");
         assert!(recent_dir.exists(), "Recent folder should be kept");
-        assert_eq!(freed, b"fake audio data".len() as u64);
+        assert_eq!(report.bytes_freed, b"fake audio data".len() as u64);
+        assert_eq!(report.folders_deleted, 1);
     }
 
     #[test]
@@ -184,8 +476,8 @@ mod tests {
         let tmp = TempDir::new().unwrap();
         let nonexistent = tmp.path().join("does_not_exist");
         let config = make_config(30);
-        let freed = cleanup_old_recordings(&nonexistent, &config).unwrap();
-        assert_eq!(freed, 0);
+        let report = cleanup_old_recordings(&nonexistent, &config).unwrap();
+        assert_eq!(report, CleanupReport::default());
     }
 
     #[test]
@@ -221,6 +513,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cleanup_keeps_untranscribed_recordings() {
+        let tmp = TempDir::new().unwrap();
+        let old_date = Local::now().date_naive() - chrono::Duration::days(35);
+        let old_dir = create_date_folder(tmp.path(), old_date, b"fake audio data");
+        let folder = old_date.format("%Y-%m-%d").to_string();
+
+        let mut config = make_config(30);
+        config.require_transcribed_before_delete = true;
+        let report = cleanup_old_recordings(tmp.path(), &config).unwrap();
+        assert_eq!(report.folders_deleted, 0);
+        assert!(old_dir.exists(), "Untranscribed recordings should be kept");
+
+        let mut state = TranscriptionState::default();
+        state.mark_transcribed(format!("{}/test.wav", folder));
+        state.save(tmp.path(), None).unwrap();
+        let report = cleanup_old_recordings(tmp.path(), &config).unwrap();
+        assert_eq!(report.folders_deleted, 1);
+        assert!(
+            !old_dir.exists(),
+            "Transcribed recordings should be deleted"
+        );
+    }
+
+    #[test]
+    fn test_cleanup_deletes_poisoned_recordings() {
+        let tmp = TempDir::new().unwrap();
+        let old_date = Local::now().date_naive() - chrono::Duration::days(35);
+        let old_dir = create_date_folder(tmp.path(), old_date, b"corrupt");
+        let relative = format!("{}/test.wav", old_date.format("%Y-%m-%d"));
+
+        let mut state = TranscriptionState::default();
+        for _ in 0..crate::transcribe::state::MAX_FAILURES {
+            state.record_failure(&relative, "bad header");
+        }
+        state.save(tmp.path(), None).unwrap();
+
+        let mut config = make_config(30);
+        config.require_transcribed_before_delete = true;
+        cleanup_old_recordings(tmp.path(), &config).unwrap();
+        assert!(
+            !old_dir.exists(),
+            "Recordings that can't be transcribed shouldn't block"
+        );
+
+        // The same, given up on by another machine sharing the directory.
+        let old_dir = create_date_folder(tmp.path(), old_date, b"corrupt");
+        fs::remove_file(tmp.path().join(".deskmic-state.json")).unwrap();
+        state.save(tmp.path(), Some("laptop")).unwrap();
+        cleanup_old_recordings(tmp.path(), &config).unwrap();
+        assert!(!old_dir.exists(), "Another machine's failures count too");
+    }
+
     #[test]
     fn test_enforce_disk_limit_deletes_oldest_first() {
         let tmp = TempDir::new().unwrap();
@@ -234,13 +579,80 @@ mod tests {
         let dir3 = create_date_folder(tmp.path(), date3, &[0u8; 100]);
 
         // Total = 300 bytes, limit to 150 => must delete oldest until under 150
-        enforce_disk_limit(tmp.path(), 150).unwrap();
+        enforce_disk_limit(tmp.path(), 150, None).unwrap();
 
         assert!(!dir1.exists(), "Oldest folder should be deleted first");
         assert!(!dir2.exists(), "Second oldest should also be deleted");
         assert!(dir3.exists(), "Newest folder should remain");
     }
 
+    #[test]
+    fn test_enforce_disk_limit_counts_but_keeps_transcripts() {
+        let tmp = TempDir::new().unwrap();
+        let transcripts = tmp.path().join("transcripts");
+        fs::create_dir_all(&transcripts).unwrap();
+        fs::write(transcripts.join("2025-01-01.jsonl"), [0u8; 100]).unwrap();
+
+        let date1 = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let date2 = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+        let dir1 = create_date_folder(tmp.path(), date1, &[0u8; 100]);
+        let dir2 = create_date_folder(tmp.path(), date2, &[0u8; 100]);
+
+        // 300 bytes in total: transcripts count, so one day has to go.
+        enforce_disk_limit(tmp.path(), 250, None).unwrap();
+
+        assert!(!dir1.exists(), "Oldest recordings should be deleted");
+        assert!(dir2.exists());
+        assert!(transcripts.join("2025-01-01.jsonl").exists());
+    }
+
+    #[test]
+    fn test_enforce_disk_limit_unreachable_deletes_nothing() {
+        let tmp = TempDir::new().unwrap();
+        let summaries = tmp.path().join("summaries");
+        fs::create_dir_all(&summaries).unwrap();
+        fs::write(summaries.join("2025-01-01-daily.md"), [0u8; 500]).unwrap();
+
+        let date1 = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let dir1 = create_date_folder(tmp.path(), date1, &[0u8; 100]);
+        let today = create_date_folder(tmp.path(), Local::now().date_naive(), &[0u8; 100]);
+
+        // Summaries alone exceed the limit: deleting recordings can't help.
+        enforce_disk_limit(tmp.path(), 400, None).unwrap();
+
+        assert!(dir1.exists(), "Recordings shouldn't be deleted for nothing");
+        assert!(today.exists());
+    }
+
+    #[test]
+    fn test_enforce_disk_limit_keeps_untranscribed_recordings() {
+        let tmp = TempDir::new().unwrap();
+        let date1 = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let date2 = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+        let dir1 = create_date_folder(tmp.path(), date1, &[0u8; 100]);
+        let dir2 = create_date_folder(tmp.path(), date2, &[0u8; 100]);
+
+        let mut state = TranscriptionState::default();
+        state.mark_transcribed("2025-01-02/test.wav".to_string());
+        enforce_disk_limit(tmp.path(), 150, Some(&state)).unwrap();
+
+        assert!(dir1.exists(), "Untranscribed recordings should be kept");
+        assert!(!dir2.exists(), "Transcribed recordings go instead");
+    }
+
+    #[test]
+    fn test_enforce_disk_limit_keeps_today() {
+        let tmp = TempDir::new().unwrap();
+        let date1 = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let dir1 = create_date_folder(tmp.path(), date1, &[0u8; 100]);
+        let today = create_date_folder(tmp.path(), Local::now().date_naive(), &[0u8; 100]);
+
+        enforce_disk_limit(tmp.path(), 150, None).unwrap();
+
+        assert!(!dir1.exists());
+        assert!(today.exists(), "Today's recordings are never deleted");
+    }
+
     #[test]
     fn test_enforce_disk_limit_no_op_when_under() {
         let tmp = TempDir::new().unwrap();
@@ -249,7 +661,7 @@ mod tests {
         let dir1 = create_date_folder(tmp.path(), date1, &[0u8; 50]);
 
         // Well under limit
-        enforce_disk_limit(tmp.path(), 10000).unwrap();
+        enforce_disk_limit(tmp.path(), 10000, None).unwrap();
 
         assert!(dir1.exists(), "Should not delete anything when under limit");
     }
@@ -258,7 +670,7 @@ mod tests {
     fn test_enforce_disk_limit_nonexistent_dir() {
         let tmp = TempDir::new().unwrap();
         let nonexistent = tmp.path().join("nope");
-        let result = enforce_disk_limit(&nonexistent, 100);
+        let result = enforce_disk_limit(&nonexistent, 100, None);
         assert!(result.is_ok());
     }
 
@@ -275,26 +687,67 @@ mod tests {
 
         create_date_folder(tmp.path(), date2, &[0u8; 200]);
 
-        let (count, bytes) = get_storage_stats(tmp.path()).unwrap();
-        assert_eq!(count, 3, "Should count 3 files total");
-        assert_eq!(bytes, 350, "Should sum to 350 bytes");
+        let stats = get_storage_stats(tmp.path(), DEFAULT_FILENAME_TEMPLATE).unwrap();
+        assert_eq!(stats.files, 3, "Should count 3 files total");
+        assert_eq!(stats.bytes, 350, "Should sum to 350 bytes");
+    }
+
+    #[test]
+    fn test_get_storage_stats_breakdown() {
+        let tmp = TempDir::new().unwrap();
+        let day1 = tmp.path().join("2025-03-01");
+        let day2 = tmp.path().join("2025-03-02");
+        fs::create_dir_all(&day1).unwrap();
+        fs::create_dir_all(&day2).unwrap();
+        fs::write(day1.join("mic_09-00-00.wav"), [0u8; 100]).unwrap();
+        fs::write(day1.join("teams_10-00-00_laptop.flac"), [0u8; 40]).unwrap();
+        fs::write(day2.join("mic_11-00-00.wav"), [0u8; 60]).unwrap();
+        // Not recordings: only in the totals.
+        fs::write(day2.join("mic_11-00-00.srt"), [0u8; 5]).unwrap();
+        fs::create_dir_all(tmp.path().join("transcripts")).unwrap();
+        fs::write(tmp.path().join("transcripts/2025-03-01.jsonl"), [0u8; 7]).unwrap();
+
+        let stats = get_storage_stats(tmp.path(), DEFAULT_FILENAME_TEMPLATE).unwrap();
+        assert_eq!((stats.files, stats.bytes), (5, 212));
+        let file_stats = |files, bytes| FileStats { files, bytes };
+        assert_eq!(stats.by_source["mic"], file_stats(2, 160));
+        assert_eq!(stats.by_source["teams"], file_stats(1, 40));
+        assert_eq!(stats.by_source.len(), 2);
+        assert_eq!(stats.by_date["2025-03-01"], file_stats(2, 140));
+        assert_eq!(stats.by_date["2025-03-02"], file_stats(1, 60));
+        assert_eq!(stats.by_date.len(), 2);
     }
 
     #[test]
     fn test_get_storage_stats_empty_dir() {
         let tmp = TempDir::new().unwrap();
-        let (count, bytes) = get_storage_stats(tmp.path()).unwrap();
-        assert_eq!(count, 0);
-        assert_eq!(bytes, 0);
+        let stats = get_storage_stats(tmp.path(), DEFAULT_FILENAME_TEMPLATE).unwrap();
+        assert_eq!(stats, StorageStats::default());
+    }
+
+    #[test]
+    fn test_get_day_stats_counts_only_that_day() {
+        let tmp = TempDir::new().unwrap();
+
+        let date1 = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let date2 = NaiveDate::from_ymd_opt(2025, 3, 2).unwrap();
+        let dir1 = create_date_folder(tmp.path(), date1, &[0u8; 100]);
+        fs::write(dir1.join("extra.wav"), &[0u8; 50]).unwrap();
+        create_date_folder(tmp.path(), date2, &[0u8; 200]);
+
+        assert_eq!(get_day_stats(tmp.path(), date1).unwrap(), (2, 150));
+        assert_eq!(get_day_stats(tmp.path(), date2).unwrap(), (1, 200));
+        let date3 = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        assert_eq!(get_day_stats(tmp.path(), date3).unwrap(), (0, 0));
     }
 
     #[test]
     fn test_get_storage_stats_nonexistent_dir() {
         let tmp = TempDir::new().unwrap();
         let nonexistent = tmp.path().join("nonexistent");
-        let (count, bytes) = get_storage_stats(&nonexistent).unwrap();
-        assert_eq!(count, 0);
-        assert_eq!(bytes, 0);
+        let stats = get_storage_stats(&nonexistent, DEFAULT_FILENAME_TEMPLATE).unwrap();
+        assert_eq!(stats.files, 0);
+        assert_eq!(stats.bytes, 0);
     }
 
     #[test]
@@ -321,15 +774,71 @@ mod tests {
         let recent_date = Local::now().date_naive();
         let recent_dir = create_date_folder(tmp.path(), recent_date, &[0u8; 100]);
 
-        let config = StorageConfig {
-            retention_days: 30,
-            cleanup_interval_hours: 24,
-            max_disk_usage_gb: None,
-        };
+        let config = make_config(30);
 
-        run_cleanup_once(tmp.path(), &config);
+        let report = run_cleanup_once(tmp.path(), &config);
 
         assert!(!old_dir.exists(), "Old folder should be cleaned up");
         assert!(recent_dir.exists(), "Recent folder should remain");
+        assert_eq!(
+            report,
+            CleanupReport {
+                bytes_freed: 500,
+                folders_deleted: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_run_cleanup_once_rolls_up_both_cleanups() {
+        let tmp = TempDir::new().unwrap();
+        let expired = Local::now().date_naive() - chrono::Duration::days(40);
+        create_date_folder(tmp.path(), expired, &[0u8; 300]);
+        let recent = Local::now().date_naive() - chrono::Duration::days(2);
+        create_date_folder(tmp.path(), recent, &[0u8; 200]);
+        create_date_folder(tmp.path(), Local::now().date_naive(), &[0u8; 100]);
+
+        let mut config = make_config(30);
+        config.max_disk_usage_gb = Some(150.0 / 1_073_741_824.0);
+        let report = run_cleanup_once(tmp.path(), &config);
+        assert_eq!(report.bytes_freed, 500);
+        assert_eq!(report.folders_deleted, 2);
+        assert_eq!(report.summary(), "Freed 0.0 MB across 2 folders");
+
+        assert_eq!(
+            run_cleanup_once(tmp.path(), &config),
+            CleanupReport::default()
+        );
+    }
+
+    #[test]
+    fn test_low_disk_trigger() {
+        let tmp = TempDir::new().unwrap();
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        // No threshold configured: never triggers.
+        let mut trigger = LowDiskTrigger::new(&make_config(30), tx.clone());
+        trigger.check(tmp.path());
+        assert!(rx.try_recv().is_err());
+
+        // A threshold no drive meets triggers once per check interval.
+        let mut config = make_config(30);
+        config.min_free_gb = Some(1e12);
+        let mut trigger = LowDiskTrigger::new(&config, tx);
+        trigger.check(tmp.path());
+        assert!(rx.try_recv().is_ok());
+        trigger.check(tmp.path());
+        assert!(rx.try_recv().is_err());
+
+        trigger.run_now();
+        assert!(rx.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_free_space_of_existing_dir() {
+        let tmp = TempDir::new().unwrap();
+        assert!(free_space(tmp.path()).is_some());
+        // A recordings directory that doesn't exist yet is on its parent's volume.
+        assert!(free_space(&tmp.path().join("not-yet")).is_some());
     }
 }
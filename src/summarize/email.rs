@@ -12,7 +12,7 @@ pub struct EmailClient {
     endpoint: String,
     access_key: String,
     sender_address: String,
-    recipient_address: String,
+    recipients: Vec<String>,
     client: reqwest::blocking::Client,
 }
 
@@ -64,7 +64,8 @@ impl EmailClient {
         if config.sender_address.is_empty() {
             anyhow::bail!("Sender address not configured. Set [summarization] sender_address in deskmic.toml");
         }
-        if config.recipient_address.is_empty() {
+        let recipients = config.recipients();
+        if recipients.is_empty() {
             anyhow::bail!("Recipient address not configured. Set [summarization] recipient_address in deskmic.toml");
         }
 
@@ -76,7 +77,7 @@ impl EmailClient {
             endpoint: config.acs_endpoint.trim_end_matches('/').to_string(),
             access_key: acs_key,
             sender_address: config.sender_address.clone(),
-            recipient_address: config.recipient_address.clone(),
+            recipients,
             client,
         })
     }
@@ -96,9 +97,13 @@ impl EmailClient {
         let body = SendEmailRequest {
             sender_address: self.sender_address.clone(),
             recipients: EmailRecipients {
-                to: vec![EmailAddress {
-                    address: self.recipient_address.clone(),
-                }],
+                to: self
+                    .recipients
+                    .iter()
+                    .map(|address| EmailAddress {
+                        address: address.clone(),
+                    })
+                    .collect(),
             },
             content: EmailContent {
                 subject: subject.to_string(),
@@ -134,7 +139,7 @@ impl EmailClient {
             signature
         );
 
-        tracing::info!("Sending email via ACS to {}", self.recipient_address);
+        tracing::info!("Sending email via ACS to {}", self.recipients.join(", "));
 
         let response = self
             .client
@@ -260,6 +265,29 @@ mod tests {
         assert_eq!(parts.len(), 5, "UUID should have 5 parts: {}", id);
     }
 
+    #[test]
+    fn test_recipients_serialize_as_to_list() {
+        let request = SendEmailRequest {
+            sender_address: "deskmic@example.com".to_string(),
+            recipients: EmailRecipients {
+                to: ["you@example.com", "boss@example.com"]
+                    .iter()
+                    .map(|a| EmailAddress {
+                        address: a.to_string(),
+                    })
+                    .collect(),
+            },
+            content: EmailContent {
+                subject: "deskmic daily".to_string(),
+                plain_text: "Summary".to_string(),
+                html: None,
+            },
+        };
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["recipients"]["to"][1]["address"], "boss@example.com");
+        assert_eq!(json["senderAddress"], "deskmic@example.com");
+    }
+
     #[test]
     fn test_from_config_missing_endpoint() {
         let config = SummarizationConfig::default();
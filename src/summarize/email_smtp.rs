@@ -0,0 +1,184 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use lettre::message::{Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, Transport};
+
+use crate::config::{SmtpConfig, SummarizationConfig};
+use crate::summarize::transport::SummaryTransport;
+
+/// SMTP email client (Gmail app passwords, company relays, etc.).
+///
+/// Uses `[summarization.smtp]` for the server and credentials, and the
+/// shared `sender_address` / `recipient_address` for the envelope.
+pub struct SmtpEmailClient {
+    mailer: lettre::SmtpTransport,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl SmtpEmailClient {
+    pub fn from_config(config: &SummarizationConfig) -> Result<Self> {
+        let smtp = &config.smtp;
+        if smtp.host.is_empty() {
+            anyhow::bail!(
+                "SMTP host not configured. Set [summarization.smtp] host in deskmic.toml"
+            );
+        }
+        if config.sender_address.is_empty() {
+            anyhow::bail!(
+                "Sender address not configured. \
+                 Set [summarization] sender_address in deskmic.toml"
+            );
+        }
+        let recipients = config.recipients();
+        if recipients.is_empty() {
+            anyhow::bail!(
+                "Recipient address not configured. \
+                 Set [summarization] recipient_address in deskmic.toml"
+            );
+        }
+
+        let from: Mailbox = config
+            .sender_address
+            .parse()
+            .with_context(|| format!("Invalid sender address '{}'", config.sender_address))?;
+        let to = recipients
+            .iter()
+            .map(|address| {
+                address
+                    .parse::<Mailbox>()
+                    .with_context(|| format!("Invalid recipient address '{}'", address))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let builder = match smtp.tls.as_str() {
+            "starttls" => lettre::SmtpTransport::starttls_relay(&smtp.host)?,
+            "tls" => lettre::SmtpTransport::relay(&smtp.host)?,
+            "none" => lettre::SmtpTransport::builder_dangerous(&smtp.host),
+            other => anyhow::bail!(
+                "Unknown SMTP tls mode: {} (expected starttls, tls, or none)",
+                other
+            ),
+        };
+
+        let mut builder = builder
+            .port(smtp.port)
+            .timeout(Some(Duration::from_secs(60)));
+        if !smtp.username.is_empty() {
+            builder = builder.credentials(Credentials::new(
+                smtp.username.clone(),
+                resolve_password(smtp),
+            ));
+        }
+
+        Ok(Self {
+            mailer: builder.build(),
+            from,
+            to,
+        })
+    }
+
+    /// Send an email with the given subject and body (plain text + optional HTML).
+    pub fn send_email(
+        &self,
+        subject: &str,
+        plain_text: &str,
+        html: Option<&str>,
+    ) -> Result<String> {
+        let mut builder = Message::builder().from(self.from.clone()).subject(subject);
+        for to in &self.to {
+            builder = builder.to(to.clone());
+        }
+
+        let message = match html {
+            Some(html) => builder.multipart(MultiPart::alternative_plain_html(
+                plain_text.to_string(),
+                html.to_string(),
+            ))?,
+            None => builder.singlepart(SinglePart::plain(plain_text.to_string()))?,
+        };
+
+        let to: Vec<String> = self.to.iter().map(|m| m.to_string()).collect();
+        tracing::info!("Sending email via SMTP to {}", to.join(", "));
+        let response = self
+            .mailer
+            .send(&message)
+            .context("Failed to send email via SMTP")?;
+        Ok(response.code().to_string())
+    }
+}
+
+impl SummaryTransport for SmtpEmailClient {
+    fn name(&self) -> &str {
+        "smtp"
+    }
+
+    fn send(&self, subject: &str, text: &str, html: Option<&str>) -> Result<String> {
+        self.send_email(subject, text, html)
+    }
+}
+
+/// The configured password, falling back to `DESKMIC_SMTP_PASSWORD`.
+fn resolve_password(smtp: &SmtpConfig) -> String {
+    if smtp.password.is_empty() {
+        std::env::var("DESKMIC_SMTP_PASSWORD").unwrap_or_default()
+    } else {
+        smtp.password.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn smtp_config() -> SummarizationConfig {
+        SummarizationConfig {
+            transport: "smtp".to_string(),
+            sender_address: "deskmic@example.com".to_string(),
+            recipient_address: "you@example.com".to_string(),
+            smtp: SmtpConfig {
+                host: "smtp.example.com".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_from_config_ok() {
+        assert!(SmtpEmailClient::from_config(&smtp_config()).is_ok());
+    }
+
+    #[test]
+    fn test_from_config_requires_host() {
+        let mut config = smtp_config();
+        config.smtp.host = String::new();
+        let err = SmtpEmailClient::from_config(&config).err().unwrap();
+        assert!(format!("{}", err).contains("SMTP host"));
+    }
+
+    #[test]
+    fn test_from_config_rejects_bad_address() {
+        let mut config = smtp_config();
+        config.recipient_address = "not an address".to_string();
+        assert!(SmtpEmailClient::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_from_config_multiple_recipients() {
+        let mut config = smtp_config();
+        config.recipient_address = "you@example.com, boss@example.com".to_string();
+        let client = SmtpEmailClient::from_config(&config).unwrap();
+        assert_eq!(client.to.len(), 2);
+    }
+
+    #[test]
+    fn test_from_config_rejects_unknown_tls_mode() {
+        let mut config = smtp_config();
+        config.smtp.tls = "ssl3".to_string();
+        let err = SmtpEmailClient::from_config(&config).err().unwrap();
+        assert!(format!("{}", err).contains("tls mode"));
+    }
+}
@@ -0,0 +1,181 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDate};
+
+use crate::config::Config;
+use crate::summarize::prompt::{self, NoiseFilter};
+use crate::summarize::runner::{load_transcripts, resolve_output_path, validate_output_path};
+use crate::transcribe::backend::Transcript;
+use crate::transcribe::store::open_store;
+
+/// Entry point for `deskmic transcripts export`: write a day's transcripts as
+/// one chronological, human-readable file, `summaries/<date>-transcript.<ext>`.
+///
+/// `date` is `YYYY-MM-DD` (default today); `format` is "txt" or "md";
+/// `output` overrides where the file goes, as for `deskmic summarize`.
+pub fn run_export(
+    config: &Config,
+    date: Option<&str>,
+    format: &str,
+    output: Option<&Path>,
+) -> Result<()> {
+    if !matches!(format, "txt" | "md") {
+        anyhow::bail!("Unknown export format: {} (expected txt or md)", format);
+    }
+    if let Some(output) = output {
+        validate_output_path(output)?;
+    }
+    let date = match date {
+        Some(d) => NaiveDate::parse_from_str(d, "%Y-%m-%d")
+            .with_context(|| format!("Invalid date '{}'. Expected YYYY-MM-DD", d))?,
+        None => Local::now().date_naive(),
+    };
+    let label = date.format("%Y-%m-%d").to_string();
+
+    let noise = NoiseFilter::new(&config.summarization.noise_patterns)
+        .context("Invalid [summarization] noise_patterns")?;
+    let store = open_store(config)?;
+    let transcripts = load_transcripts(store.as_ref(), &[date])?;
+    let total = transcripts.len();
+    let transcripts: Vec<Transcript> = transcripts
+        .into_iter()
+        .filter(|t| !noise.is_noise(&t.text))
+        .collect();
+
+    if transcripts.is_empty() {
+        println!("No transcripts recorded for {}", label);
+        return Ok(());
+    }
+
+    let template = &config.output.filename_template;
+    let content = render_transcript(&label, &transcripts, template, format == "md");
+    let file_name = format!("{}-transcript.{}", label, format);
+    let path = match output {
+        Some(output) => resolve_output_path(output, &file_name),
+        None => {
+            let summary_dir = config.output.directory.join("summaries");
+            std::fs::create_dir_all(&summary_dir)
+                .with_context(|| format!("Failed to create {}", summary_dir.display()))?;
+            summary_dir.join(file_name)
+        }
+    };
+    std::fs::write(&path, content)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    println!(
+        "Exported {} transcript(s) ({} noise skipped) to {}",
+        transcripts.len(),
+        total - transcripts.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// One `[HH:MM source] text` line per transcript, ordered by the time in the
+/// file name (read with `filename_template`). Transcripts without a parseable
+/// time keep their order at the end. Markdown gets a title and blank lines
+/// between entries.
+fn render_transcript(
+    label: &str,
+    transcripts: &[Transcript],
+    filename_template: &str,
+    markdown: bool,
+) -> String {
+    let time = |t: &Transcript| prompt::extract_time(filename_template, &t.file);
+    let mut sorted: Vec<&Transcript> = transcripts.iter().collect();
+    sorted.sort_by_key(|t| time(t).unwrap_or((24, 0, 0)));
+
+    let lines: Vec<String> = sorted
+        .iter()
+        .map(|t| {
+            let time = match time(t) {
+                Some((hour, minute, _)) => format!("{:02}:{:02}", hour, minute),
+                None => "--:--".to_string(),
+            };
+            format!("[{} {}] {}", time, t.source, t.text.trim())
+        })
+        .collect();
+
+    if markdown {
+        format!("# Transcript — {}\n\n{}\n", label, lines.join("\n\n"))
+    } else {
+        format!("{}\n", lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DEFAULT_FILENAME_TEMPLATE as TEMPLATE;
+    use crate::transcribe::store::{JsonlStore, TranscriptStore};
+
+    fn transcript(source: &str, file: &str, text: &str) -> Transcript {
+        Transcript {
+            timestamp: "2026-02-17".to_string(),
+            source: source.to_string(),
+            duration_secs: 5.0,
+            file: file.to_string(),
+            text: text.to_string(),
+            engine: None,
+            segments: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_render_transcript_sorts_by_file_time() {
+        let transcripts = vec![
+            transcript("teams", "teams_14-05-30.wav", "Budget review"),
+            transcript("mic", "mic_09-30-00_laptop.wav", " Standup notes "),
+            transcript("mic", "odd-name.wav", "Unknown time"),
+            transcript("mic", "mic_09-02-10.wav", "Coffee"),
+        ];
+        assert_eq!(
+            render_transcript("2026-02-17", &transcripts, TEMPLATE, false),
+            "[09:02 mic] Coffee\n\
+             [09:30 mic] Standup notes\n\
+             [14:05 teams] Budget review\n\
+             [--:-- mic] Unknown time\n"
+        );
+    }
+
+    #[test]
+    fn test_run_export_to_output_path() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.output.directory = tmp.path().join("recordings");
+        JsonlStore::new(&config.output.directory)
+            .append(
+                "2026-02-17",
+                &transcript("mic", "mic_10-00-00.wav", "Hello"),
+            )
+            .unwrap();
+
+        let file = tmp.path().join("day.txt");
+        run_export(&config, Some("2026-02-17"), "txt", Some(&file)).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "[10:00 mic] Hello\n"
+        );
+
+        // A directory gets the default file name inside it.
+        run_export(&config, Some("2026-02-17"), "md", Some(tmp.path())).unwrap();
+        assert!(tmp.path().join("2026-02-17-transcript.md").exists());
+        assert!(!config.output.directory.join("summaries").exists());
+
+        let missing = tmp.path().join("missing/day.txt");
+        assert!(run_export(&config, Some("2026-02-17"), "txt", Some(&missing)).is_err());
+    }
+
+    #[test]
+    fn test_render_transcript_markdown() {
+        let transcripts = vec![
+            transcript("mic", "mic_10-00-00.wav", "One"),
+            transcript("mic", "mic_10-01-00.wav", "Two"),
+        ];
+        assert_eq!(
+            render_transcript("2026-02-17", &transcripts, TEMPLATE, true),
+            "# Transcript — 2026-02-17\n\n[10:00 mic] One\n\n[10:01 mic] Two\n"
+        );
+    }
+}
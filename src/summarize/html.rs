@@ -309,6 +309,35 @@ fn inline_format(text: &str) -> String {
     result
 }
 
+/// Strip the same markdown subset down to plain text: heading markers and
+/// `**` are removed, `*` bullets become `-`, and rules become blank lines.
+pub fn markdown_to_text(markdown: &str) -> String {
+    let mut text = markdown
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim();
+            if trimmed == "---" || trimmed == "***" || trimmed == "___" {
+                return String::new();
+            }
+            let heading = trimmed.trim_start_matches('#');
+            let line = if heading.len() < trimmed.len() && heading.starts_with(' ') {
+                heading.trim().to_string()
+            } else if let Some(item) = line.trim_start().strip_prefix("* ") {
+                let indent = &line[..line.len() - line.trim_start().len()];
+                format!("{}- {}", indent, item)
+            } else {
+                line.to_string()
+            };
+            line.replace("**", "")
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if markdown.ends_with('\n') {
+        text.push('\n');
+    }
+    text
+}
+
 /// Escape HTML special characters.
 fn escape_html(text: &str) -> String {
     text.replace('&', "&amp;")
@@ -421,4 +450,13 @@ mod tests {
         assert!(html.contains("<strong>Key point</strong>"));
         assert!(html.contains("Some details here"));
     }
+
+    #[test]
+    fn test_markdown_to_text() {
+        let md = "## Morning\n\n**Standup** with the team\n* Ship v2\n  - **Docs**\n---\nDone";
+        assert_eq!(
+            markdown_to_text(md),
+            "Morning\n\nStandup with the team\n- Ship v2\n  - Docs\n\nDone"
+        );
+    }
 }
@@ -3,16 +3,125 @@ use serde::{Deserialize, Serialize};
 
 use crate::config::Config;
 
+/// Longest reply requested from any provider.
+const MAX_COMPLETION_TOKENS: u32 = 4096;
+
+const OPENAI_URL: &str = "https://api.openai.com/v1/chat/completions";
+const ANTHROPIC_URL: &str = "https://api.anthropic.com/v1/messages";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Follow-up requests made for a reply cut off at `MAX_COMPLETION_TOKENS`.
+const MAX_CONTINUATIONS: usize = 3;
+
+const CONTINUE_PROMPT: &str = "Your reply was cut off. Continue exactly where you left off, \
+     without repeating anything or adding a preamble.";
+
+/// Appended when a reply is still cut off after every continuation.
+const TRUNCATION_NOTE: &str = "\n\n_[Summary truncated: the model's output limit was reached.]_";
+
+/// A chat model that summaries are generated with.
+///
+/// Selected by `[summarization] provider`:
+/// - `"azure"` — Azure OpenAI chat completions (`AzureOpenAIProvider`)
+/// - `"openai"` — OpenAI chat completions (`OpenAIProvider`)
+/// - `"anthropic"` — Anthropic Messages API (`AnthropicProvider`)
+pub trait LlmProvider {
+    /// Provider name for logs.
+    fn name(&self) -> &str;
+
+    /// Send one request: a system prompt and the conversation so far
+    /// (alternating user and assistant turns, starting with the user).
+    fn complete(&self, system_prompt: &str, messages: &[ChatMessage]) -> Result<Completion>;
+
+    /// Send a system and user prompt and return the reply text. A reply cut
+    /// off at the token limit is continued with follow-up requests, up to
+    /// `MAX_CONTINUATIONS` times; if it is still cut off after that, it ends
+    /// with a note saying so.
+    fn chat(&self, system_prompt: &str, user_prompt: &str) -> Result<String> {
+        let mut messages = vec![ChatMessage::new("user", user_prompt)];
+        let mut reply = String::new();
+        for attempt in 0..=MAX_CONTINUATIONS {
+            let completion = self.complete(system_prompt, &messages)?;
+            reply.push_str(&completion.text);
+            if !completion.truncated {
+                return Ok(reply);
+            }
+            if attempt < MAX_CONTINUATIONS {
+                tracing::info!(
+                    "Reply hit the output limit, continuing ({}/{})",
+                    attempt + 1,
+                    MAX_CONTINUATIONS
+                );
+                messages.truncate(1);
+                messages.push(ChatMessage::new("assistant", &reply));
+                messages.push(ChatMessage::new("user", CONTINUE_PROMPT));
+            }
+        }
+        tracing::warn!(
+            "Reply still cut off after {} continuations",
+            MAX_CONTINUATIONS
+        );
+        reply.push_str(TRUNCATION_NOTE);
+        Ok(reply)
+    }
+}
+
+/// One model reply.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub text: String,
+    /// The reply stopped at the output token limit rather than finishing.
+    pub truncated: bool,
+}
+
+/// Build the provider configured in `[summarization] provider`.
+pub fn build_provider(config: &Config) -> Result<Box<dyn LlmProvider>> {
+    match config.summarization.provider.as_str() {
+        "azure" => Ok(Box::new(AzureOpenAIProvider::from_config(config)?)),
+        "openai" => Ok(Box::new(OpenAIProvider::from_config(config)?)),
+        "anthropic" => Ok(Box::new(AnthropicProvider::from_config(config)?)),
+        other => anyhow::bail!(
+            "Unknown LLM provider: {} (expected azure, openai, or anthropic)",
+            other
+        ),
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct ChatRequest {
+    /// Model name; Azure takes it from the deployment in the URL instead.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
     messages: Vec<ChatMessage>,
     max_completion_tokens: u32,
 }
 
+impl ChatRequest {
+    fn new(model: Option<String>, system_prompt: &str, messages: &[ChatMessage]) -> Self {
+        let mut all = vec![ChatMessage::new("system", system_prompt)];
+        all.extend_from_slice(messages);
+        Self {
+            model,
+            messages: all,
+            max_completion_tokens: MAX_COMPLETION_TOKENS,
+        }
+    }
+}
+
+/// A turn in a conversation with the model.
 #[derive(Debug, Serialize, Deserialize, Clone)]
-struct ChatMessage {
-    role: String,
-    content: String,
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn new(role: &str, content: &str) -> Self {
+        Self {
+            role: role.to_string(),
+            content: content.to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -34,25 +143,29 @@ struct ChatUsage {
     total_tokens: u64,
 }
 
-pub struct LlmClient {
+/// Azure OpenAI chat completions, using the endpoint and key from
+/// `[transcription.azure]` and `[summarization] deployment`.
+pub struct AzureOpenAIProvider {
     endpoint: String,
     api_key: String,
     deployment: String,
+    api_version: String,
     client: reqwest::blocking::Client,
 }
 
-impl std::fmt::Debug for LlmClient {
+impl std::fmt::Debug for AzureOpenAIProvider {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("LlmClient")
+        f.debug_struct("AzureOpenAIProvider")
             .field("endpoint", &self.endpoint)
             .field("api_key", &"[REDACTED]")
             .field("deployment", &self.deployment)
+            .field("api_version", &self.api_version)
             .finish_non_exhaustive()
     }
 }
 
-impl LlmClient {
-    /// Create a new LLM client from config.
+impl AzureOpenAIProvider {
+    /// Create a new Azure OpenAI provider from config.
     /// Uses the Azure OpenAI endpoint/api_key from [transcription.azure]
     /// and the deployment name from [summarization].
     pub fn from_config(config: &Config) -> Result<Self> {
@@ -92,30 +205,23 @@ impl LlmClient {
             endpoint,
             api_key,
             deployment,
+            api_version: azure.api_version.clone(),
             client,
         })
     }
+}
+
+impl LlmProvider for AzureOpenAIProvider {
+    fn name(&self) -> &str {
+        "azure"
+    }
 
-    /// Send a chat completion request and return the response text.
-    pub fn chat(&self, system_prompt: &str, user_prompt: &str) -> Result<String> {
+    fn complete(&self, system_prompt: &str, messages: &[ChatMessage]) -> Result<Completion> {
         let url = format!(
-            "{}/openai/deployments/{}/chat/completions?api-version=2024-06-01",
-            self.endpoint, self.deployment
+            "{}/openai/deployments/{}/chat/completions?api-version={}",
+            self.endpoint, self.deployment, self.api_version
         );
-
-        let request = ChatRequest {
-            messages: vec![
-                ChatMessage {
-                    role: "system".to_string(),
-                    content: system_prompt.to_string(),
-                },
-                ChatMessage {
-                    role: "user".to_string(),
-                    content: user_prompt.to_string(),
-                },
-            ],
-            max_completion_tokens: 4096,
-        };
+        let request = ChatRequest::new(None, system_prompt, messages);
 
         tracing::info!(
             "Sending chat completion request to {}/{}",
@@ -130,6 +236,198 @@ impl LlmClient {
             .json(&request)
             .send()
             .context("Failed to send chat completion request")?;
+        read_chat_completion(response, "Azure OpenAI")
+    }
+}
+
+/// The reply from a chat-completions response (Azure and OpenAI share the
+/// format), logging token usage.
+fn read_chat_completion(
+    response: reqwest::blocking::Response,
+    service: &str,
+) -> Result<Completion> {
+    let status = response.status();
+    if !status.is_success() {
+        let error_body = response
+            .text()
+            .unwrap_or_else(|_| "unable to read response body".to_string());
+        anyhow::bail!(
+            "{} returned HTTP {}: {}",
+            service,
+            status.as_u16(),
+            error_body
+        );
+    }
+
+    let chat_response: ChatResponse = response
+        .json()
+        .context("Failed to parse chat completion response")?;
+
+    if let Some(usage) = &chat_response.usage {
+        tracing::info!(
+            "Token usage: prompt={}, completion={}, total={}",
+            usage.prompt_tokens,
+            usage.completion_tokens,
+            usage.total_tokens
+        );
+    }
+
+    let choice = chat_response
+        .choices
+        .first()
+        .context("No choices in chat completion response")?;
+
+    let finish_reason = choice.finish_reason.as_deref().unwrap_or("stop");
+    if finish_reason != "stop" && finish_reason != "length" {
+        tracing::warn!("Chat completion finish_reason: {}", finish_reason);
+    }
+
+    Ok(Completion {
+        text: choice.message.content.clone(),
+        truncated: finish_reason == "length",
+    })
+}
+
+/// `[summarization] api_key`, falling back to the environment variable `env`.
+fn provider_api_key(config: &Config, env: &str) -> Result<String> {
+    if !config.summarization.api_key.is_empty() {
+        return Ok(config.summarization.api_key.clone());
+    }
+    std::env::var(env).with_context(|| {
+        format!(
+            "{} API key not configured. Set [summarization] api_key or {}",
+            config.summarization.provider, env
+        )
+    })
+}
+
+/// `[summarization] model`, required by the OpenAI and Anthropic providers.
+fn provider_model(config: &Config) -> Result<String> {
+    if config.summarization.model.is_empty() {
+        anyhow::bail!(
+            "Summarization model not configured. Set [summarization] model in deskmic.toml"
+        );
+    }
+    Ok(config.summarization.model.clone())
+}
+
+fn http_client() -> Result<reqwest::blocking::Client> {
+    Ok(reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(300))
+        .build()?)
+}
+
+/// OpenAI chat completions with `[summarization] model` and `api_key` (or
+/// `DESKMIC_OPENAI_KEY`).
+pub struct OpenAIProvider {
+    api_key: String,
+    model: String,
+    client: reqwest::blocking::Client,
+}
+
+impl OpenAIProvider {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Ok(Self {
+            model: provider_model(config)?,
+            api_key: provider_api_key(config, "DESKMIC_OPENAI_KEY")?,
+            client: http_client()?,
+        })
+    }
+}
+
+impl LlmProvider for OpenAIProvider {
+    fn name(&self) -> &str {
+        "openai"
+    }
+
+    fn complete(&self, system_prompt: &str, messages: &[ChatMessage]) -> Result<Completion> {
+        let request = ChatRequest::new(Some(self.model.clone()), system_prompt, messages);
+        tracing::info!("Sending chat completion request to OpenAI/{}", self.model);
+
+        let response = self
+            .client
+            .post(OPENAI_URL)
+            .bearer_auth(&self.api_key)
+            .json(&request)
+            .send()
+            .context("Failed to send chat completion request")?;
+        read_chat_completion(response, "OpenAI")
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct AnthropicRequest {
+    model: String,
+    max_tokens: u32,
+    system: String,
+    messages: Vec<ChatMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicResponse {
+    content: Vec<AnthropicContent>,
+    stop_reason: Option<String>,
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicContent {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicUsage {
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+/// Anthropic Messages API with `[summarization] model` and `api_key` (or
+/// `DESKMIC_ANTHROPIC_KEY`).
+pub struct AnthropicProvider {
+    api_key: String,
+    model: String,
+    client: reqwest::blocking::Client,
+}
+
+impl AnthropicProvider {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Ok(Self {
+            model: provider_model(config)?,
+            api_key: provider_api_key(config, "DESKMIC_ANTHROPIC_KEY")?,
+            client: http_client()?,
+        })
+    }
+
+    fn request(&self, system_prompt: &str, messages: &[ChatMessage]) -> AnthropicRequest {
+        AnthropicRequest {
+            model: self.model.clone(),
+            max_tokens: MAX_COMPLETION_TOKENS,
+            system: system_prompt.to_string(),
+            messages: messages.to_vec(),
+        }
+    }
+}
+
+impl LlmProvider for AnthropicProvider {
+    fn name(&self) -> &str {
+        "anthropic"
+    }
+
+    fn complete(&self, system_prompt: &str, messages: &[ChatMessage]) -> Result<Completion> {
+        let request = self.request(system_prompt, messages);
+        tracing::info!("Sending messages request to Anthropic/{}", self.model);
+
+        let response = self
+            .client
+            .post(ANTHROPIC_URL)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&request)
+            .send()
+            .context("Failed to send Anthropic messages request")?;
 
         let status = response.status();
         if !status.is_success() {
@@ -137,40 +435,43 @@ impl LlmClient {
                 .text()
                 .unwrap_or_else(|_| "unable to read response body".to_string());
             anyhow::bail!(
-                "Azure OpenAI returned HTTP {}: {}",
+                "Anthropic returned HTTP {}: {}",
                 status.as_u16(),
                 error_body
             );
         }
 
-        let chat_response: ChatResponse = response
+        let reply: AnthropicResponse = response
             .json()
-            .context("Failed to parse chat completion response")?;
-
-        if let Some(usage) = &chat_response.usage {
+            .context("Failed to parse Anthropic messages response")?;
+        if let Some(usage) = &reply.usage {
             tracing::info!(
-                "Token usage: prompt={}, completion={}, total={}",
-                usage.prompt_tokens,
-                usage.completion_tokens,
-                usage.total_tokens
+                "Token usage: input={}, output={}",
+                usage.input_tokens,
+                usage.output_tokens
             );
         }
-
-        let choice = chat_response
-            .choices
-            .first()
-            .context("No choices in chat completion response")?;
-
-        if let Some(reason) = &choice.finish_reason {
-            if reason != "stop" {
-                tracing::warn!("Chat completion finish_reason: {}", reason);
-            }
+        let stop_reason = reply.stop_reason.as_deref().unwrap_or("end_turn");
+        if stop_reason != "end_turn" && stop_reason != "max_tokens" {
+            tracing::warn!("Anthropic stop_reason: {}", stop_reason);
         }
-
-        Ok(choice.message.content.clone())
+        Ok(Completion {
+            text: anthropic_text(&reply),
+            truncated: stop_reason == "max_tokens",
+        })
     }
 }
 
+/// The concatenated text blocks of an Anthropic reply.
+fn anthropic_text(reply: &AnthropicResponse) -> String {
+    reply
+        .content
+        .iter()
+        .filter(|c| c.kind == "text")
+        .map(|c| c.text.as_str())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -180,7 +481,7 @@ mod tests {
     fn test_from_config_missing_endpoint() {
         let mut config = Config::default();
         config.summarization.deployment = "gpt-4o".to_string();
-        let result = LlmClient::from_config(&config);
+        let result = AzureOpenAIProvider::from_config(&config);
         assert!(result.is_err());
         assert!(
             result.unwrap_err().to_string().contains("endpoint"),
@@ -195,9 +496,10 @@ mod tests {
             endpoint: "https://example.openai.azure.com".to_string(),
             api_key: "test-key".to_string(),
             deployment: "whisper".to_string(),
+            ..Default::default()
         };
         // deployment is empty by default in SummarizationConfig
-        let result = LlmClient::from_config(&config);
+        let result = AzureOpenAIProvider::from_config(&config);
         assert!(result.is_err());
         assert!(
             result.unwrap_err().to_string().contains("deployment"),
@@ -212,12 +514,124 @@ mod tests {
             endpoint: "https://example.openai.azure.com".to_string(),
             api_key: "test-key".to_string(),
             deployment: "whisper".to_string(),
+            ..Default::default()
         };
         config.summarization = SummarizationConfig {
             deployment: "gpt-4o".to_string(),
             ..Default::default()
         };
-        let client = LlmClient::from_config(&config);
+        let client = AzureOpenAIProvider::from_config(&config);
         assert!(client.is_ok());
     }
+
+    #[test]
+    fn test_build_provider_unknown() {
+        let mut config = Config::default();
+        config.summarization.provider = "eliza".to_string();
+        let err = build_provider(&config).err().unwrap();
+        assert!(err.to_string().contains("Unknown LLM provider"));
+    }
+
+    #[test]
+    fn test_build_provider_requires_model() {
+        let mut config = Config::default();
+        config.summarization.provider = "anthropic".to_string();
+        config.summarization.api_key = "test-key".to_string();
+        let err = build_provider(&config).err().unwrap();
+        assert!(err.to_string().contains("model"), "{}", err);
+
+        config.summarization.model = "claude-model".to_string();
+        assert_eq!(build_provider(&config).unwrap().name(), "anthropic");
+        config.summarization.provider = "openai".to_string();
+        assert_eq!(build_provider(&config).unwrap().name(), "openai");
+    }
+
+    #[test]
+    fn test_openai_request_shape() {
+        let hi = [ChatMessage::new("user", "Hi")];
+        let request = ChatRequest::new(Some("gpt-model".to_string()), "Be brief", &hi);
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["model"], "gpt-model");
+        assert_eq!(json["messages"][0]["role"], "system");
+        // Azure requests leave the model out.
+        let json = serde_json::to_value(ChatRequest::new(None, "Be brief", &hi)).unwrap();
+        assert!(json.get("model").is_none());
+    }
+
+    #[test]
+    fn test_anthropic_request_and_reply() {
+        let mut config = Config::default();
+        config.summarization.model = "claude-model".to_string();
+        config.summarization.api_key = "test-key".to_string();
+        let provider = AnthropicProvider::from_config(&config).unwrap();
+        let hi = [ChatMessage::new("user", "Hi")];
+        let json = serde_json::to_value(provider.request("Be brief", &hi)).unwrap();
+        assert_eq!(json["system"], "Be brief");
+        assert_eq!(json["messages"][0]["role"], "user");
+        assert_eq!(json["max_tokens"], MAX_COMPLETION_TOKENS);
+
+        let reply: AnthropicResponse = serde_json::from_str(
+            r#"{"content":[{"type":"text","text":"Busy "},{"type":"text","text":"day."}],
+                "stop_reason":"end_turn","usage":{"input_tokens":10,"output_tokens":3}}"#,
+        )
+        .unwrap();
+        assert_eq!(anthropic_text(&reply), "Busy day.");
+    }
+
+    /// Replies with the next of `replies`, cut off while any remain.
+    struct ScriptedProvider {
+        replies: std::cell::RefCell<Vec<&'static str>>,
+        requests: std::cell::RefCell<Vec<Vec<ChatMessage>>>,
+    }
+
+    impl ScriptedProvider {
+        fn new(replies: &[&'static str]) -> Self {
+            Self {
+                replies: std::cell::RefCell::new(replies.to_vec()),
+                requests: std::cell::RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl LlmProvider for ScriptedProvider {
+        fn name(&self) -> &str {
+            "scripted"
+        }
+
+        fn complete(&self, _system: &str, messages: &[ChatMessage]) -> Result<Completion> {
+            self.requests.borrow_mut().push(messages.to_vec());
+            let mut replies = self.replies.borrow_mut();
+            let text = replies.remove(0).to_string();
+            Ok(Completion {
+                text,
+                truncated: !replies.is_empty(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_chat_continues_truncated_replies() {
+        let provider = ScriptedProvider::new(&["A busy ", "day of ", "meetings."]);
+        let reply = provider.chat("Summarize", "transcripts").unwrap();
+        assert_eq!(reply, "A busy day of meetings.");
+
+        let requests = provider.requests.borrow();
+        assert_eq!(requests.len(), 3);
+        let last = &requests[2];
+        assert_eq!(last.len(), 3);
+        assert_eq!(last[1].role, "assistant");
+        assert_eq!(last[1].content, "A busy day of ");
+        assert_eq!(last[2].content, CONTINUE_PROMPT);
+    }
+
+    #[test]
+    fn test_chat_notes_truncation_after_max_continuations() {
+        let parts = ["a"; MAX_CONTINUATIONS + 2];
+        let provider = ScriptedProvider::new(&parts);
+        let reply = provider.chat("Summarize", "transcripts").unwrap();
+        assert_eq!(
+            reply,
+            format!("{}{}", "a".repeat(MAX_CONTINUATIONS + 1), TRUNCATION_NOTE)
+        );
+    }
 }
@@ -1,5 +1,10 @@
 pub mod email;
+pub mod email_smtp;
+pub mod export;
 pub mod html;
 pub mod llm;
+pub mod notes;
 pub mod prompt;
 pub mod runner;
+pub mod transport;
+pub mod webhook;
@@ -0,0 +1,132 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+
+/// Append a summary to the daily note `<vault_dir>/<date>.md`, creating the
+/// note if it doesn't exist.
+///
+/// The summary is wrapped in `<!-- deskmic:<key> -->` markers. If a block
+/// with the same key is already present (e.g. the summary was re-run), it is
+/// replaced in place rather than appended again.
+pub fn append_to_daily_note(
+    vault_dir: &Path,
+    date: NaiveDate,
+    heading: &str,
+    key: &str,
+    summary: &str,
+) -> Result<PathBuf> {
+    std::fs::create_dir_all(vault_dir)
+        .with_context(|| format!("Failed to create {}", vault_dir.display()))?;
+
+    let path = vault_dir.join(format!("{}.md", date.format("%Y-%m-%d")));
+    let existing = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+        }
+    };
+
+    let updated = upsert_block(&existing, key, heading, summary);
+    std::fs::write(&path, updated)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    Ok(path)
+}
+
+/// Insert or replace the marked summary block for `key` in `note`.
+fn upsert_block(note: &str, key: &str, heading: &str, summary: &str) -> String {
+    let start_marker = format!("<!-- deskmic:{} -->", key);
+    let end_marker = format!("<!-- /deskmic:{} -->", key);
+    let block = format!(
+        "{}\n{}\n\n{}\n{}\n",
+        start_marker,
+        heading,
+        summary.trim(),
+        end_marker
+    );
+
+    if let Some(start) = note.find(&start_marker) {
+        if let Some(end_offset) = note[start..].find(&end_marker) {
+            let mut end = start + end_offset + end_marker.len();
+            if note[end..].starts_with('\n') {
+                end += 1;
+            }
+            return format!("{}{}{}", &note[..start], block, &note[end..]);
+        }
+    }
+
+    let mut updated = note.to_string();
+    if !updated.is_empty() {
+        if !updated.ends_with('\n') {
+            updated.push('\n');
+        }
+        updated.push('\n');
+    }
+    updated.push_str(&block);
+    updated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 2, 10).unwrap()
+    }
+
+    #[test]
+    fn test_creates_daily_note() {
+        let dir = tempfile::tempdir().unwrap();
+        let path =
+            append_to_daily_note(dir.path(), date(), "## Recap", "2026-02-10-daily", "Busy day.")
+                .unwrap();
+
+        assert_eq!(path, dir.path().join("2026-02-10.md"));
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("## Recap\n\nBusy day.\n"));
+        assert!(content.starts_with("<!-- deskmic:2026-02-10-daily -->"));
+    }
+
+    #[test]
+    fn test_appends_after_existing_content() {
+        let note = "# Tuesday\n\n- [ ] groceries";
+        let updated = upsert_block(note, "2026-02-10-daily", "## Recap", "Busy day.");
+
+        assert!(updated.starts_with("# Tuesday\n\n- [ ] groceries\n\n<!-- deskmic:"));
+        assert!(updated.ends_with("<!-- /deskmic:2026-02-10-daily -->\n"));
+    }
+
+    #[test]
+    fn test_rerun_replaces_block() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("2026-02-10.md");
+        std::fs::write(&path, "# Tuesday\n").unwrap();
+
+        append_to_daily_note(dir.path(), date(), "## Recap", "2026-02-10-daily", "First.").unwrap();
+        std::fs::write(
+            &path,
+            std::fs::read_to_string(&path).unwrap() + "\nEvening notes\n",
+        )
+        .unwrap();
+        append_to_daily_note(dir.path(), date(), "## Recap", "2026-02-10-daily", "Second.")
+            .unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.matches("<!-- deskmic:2026-02-10-daily -->").count(), 1);
+        assert!(content.contains("Second."));
+        assert!(!content.contains("First."));
+        assert!(content.starts_with("# Tuesday\n"));
+        assert!(content.ends_with("\nEvening notes\n"));
+    }
+
+    #[test]
+    fn test_different_keys_coexist() {
+        let note = upsert_block("", "2026-02-10-daily", "## Recap", "Daily.");
+        let note = upsert_block(&note, "2026-02-10-weekly", "## Recap", "Weekly.");
+
+        assert!(note.contains("Daily."));
+        assert!(note.contains("Weekly."));
+    }
+}
@@ -1,5 +1,11 @@
 use std::collections::BTreeMap;
+use std::sync::OnceLock;
 
+use anyhow::Result;
+use regex::Regex;
+use tiktoken_rs::CoreBPE;
+
+use crate::config::{parse_recording_name, NoisePattern};
 use crate::transcribe::backend::Transcript;
 
 /// Noise patterns that should be filtered from transcripts before summarization.
@@ -16,44 +22,108 @@ const NOISE_PATTERNS: &[&str] = &[
     "[music]",
     "(static)",
     "(background noise)",
+    "(typing)",
 ];
 
 /// Returns true if the transcript text is considered noise (empty, whitespace-only,
-/// or matches known noise patterns).
+/// or matches known noise patterns). Spacing inside the brackets is ignored, so
+/// `[ Silence ]` matches `[silence]`.
 pub fn is_noise(text: &str) -> bool {
-    let trimmed = text.trim();
-    if trimmed.is_empty() {
+    let normalized = normalize_noise(text);
+    if normalized.is_empty() {
         return true;
     }
     NOISE_PATTERNS
         .iter()
-        .any(|p| trimmed.eq_ignore_ascii_case(p))
+        .any(|p| normalized.eq_ignore_ascii_case(p))
+}
+
+/// Trim, collapse runs of whitespace, and drop spaces just inside brackets.
+fn normalize_noise(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("[ ", "[")
+        .replace("( ", "(")
+        .replace(" ]", "]")
+        .replace(" )", ")")
+}
+
+/// The built-in noise patterns plus `[summarization] noise_patterns`.
+///
+/// A user pattern is matched against the whole transcript like the built-ins
+/// (case-insensitive, spacing inside brackets ignored), or, written as
+/// `/regex/`, searched for anywhere in it (case-insensitive; anchor with `^`
+/// and `$` to match the whole transcript).
+#[derive(Debug, Default)]
+pub struct NoiseFilter {
+    exact: Vec<String>,
+    regexes: Vec<Regex>,
+}
+
+impl NoiseFilter {
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        let mut filter = Self::default();
+        for pattern in patterns {
+            match NoisePattern::parse(pattern).map_err(anyhow::Error::msg)? {
+                NoisePattern::Regex(re) => filter.regexes.push(re),
+                NoisePattern::Exact(phrase) => {
+                    filter.exact.push(normalize_noise(&phrase).to_lowercase())
+                }
+            }
+        }
+        Ok(filter)
+    }
+
+    pub fn is_noise(&self, text: &str) -> bool {
+        if is_noise(text) {
+            return true;
+        }
+        let normalized = normalize_noise(text).to_lowercase();
+        self.exact.contains(&normalized) || self.regexes.iter().any(|re| re.is_match(text.trim()))
+    }
+}
+
+/// Extract the hour from a filename like "mic_14-30-00.wav" → 14, reading it
+/// with `output.filename_template`. Returns None if the filename doesn't match.
+pub fn extract_hour(template: &str, filename: &str) -> Option<u32> {
+    extract_time(template, filename).map(|(hour, _, _)| hour)
 }
 
-/// Extract the hour from a filename like "mic_14-30-00.wav" → 14.
-/// Returns None if the filename doesn't match the expected pattern.
-pub fn extract_hour(filename: &str) -> Option<u32> {
-    // Strip the source prefix (e.g. "mic_" or "teams_")
-    let time_part = filename.split('_').nth(1)?;
-    // Parse "HH-MM-SS.wav" → HH
-    let hour_str = time_part.split('-').next()?;
-    hour_str.parse().ok()
+/// Extract the time from a filename like "mic_14-30-05.wav" → (14, 30, 5),
+/// reading it with `output.filename_template`. Returns None if the filename
+/// doesn't match.
+pub fn extract_time(template: &str, filename: &str) -> Option<(u32, u32, u32)> {
+    parse_recording_name(template, filename).map(|name| name.time)
 }
 
 /// Group transcripts by hour based on filename timestamps.
 /// Returns a BTreeMap so hours are in sorted order.
-pub fn group_by_hour<'a>(transcripts: &[&'a Transcript]) -> BTreeMap<u32, Vec<&'a Transcript>> {
+pub fn group_by_hour<'a>(
+    template: &str,
+    transcripts: &[&'a Transcript],
+) -> BTreeMap<u32, Vec<&'a Transcript>> {
     let mut groups: BTreeMap<u32, Vec<&'a Transcript>> = BTreeMap::new();
     for t in transcripts {
-        if let Some(hour) = extract_hour(&t.file) {
+        if let Some(hour) = extract_hour(template, &t.file) {
             groups.entry(hour).or_default().push(t);
         }
     }
     groups
 }
 
+/// Tag shown for a transcript's source: the recorded source name with its
+/// first letter capitalized ("mic" -> "Mic", "zoom" -> "Zoom").
+fn source_tag(source: &str) -> String {
+    let mut chars = source.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => "Unknown".to_string(),
+    }
+}
+
 /// Format a single hour's transcripts into a readable block for the LLM prompt.
-fn format_hour_block(hour: u32, transcripts: &[&Transcript]) -> String {
+fn format_hour_block(template: &str, hour: u32, transcripts: &[&Transcript]) -> String {
     let mut lines = Vec::new();
     let hour_label = format!("{:02}:00–{:02}:59", hour, hour);
     lines.push(format!("### {}", hour_label));
@@ -61,15 +131,15 @@ fn format_hour_block(hour: u32, transcripts: &[&Transcript]) -> String {
 
     for t in transcripts {
         // Include the source and time from filename for context
-        let time_tag = t
-            .file
-            .split('_')
-            .nth(1)
-            .unwrap_or("")
-            .trim_end_matches(".wav")
-            .replace('-', ":");
-        let source_tag = if t.source == "mic" { "Mic" } else { "App" };
-        lines.push(format!("[{} {}] {}", time_tag, source_tag, t.text.trim()));
+        let time_tag = extract_time(template, &t.file)
+            .map(|(h, m, s)| format!("{:02}:{:02}:{:02}", h, m, s))
+            .unwrap_or_default();
+        lines.push(format!(
+            "[{} {}] {}",
+            time_tag,
+            source_tag(&t.source),
+            t.text.trim()
+        ));
     }
 
     lines.push(String::new());
@@ -116,16 +186,60 @@ fn default_system_prompt(date_label: &str) -> String {
     )
 }
 
+/// Returns the default combine-pass prompt with the given date label substituted in.
+fn default_combine_prompt(date_label: &str) -> String {
+    format!(
+        "You are a personal productivity assistant. Below are partial summaries of voice \
+         transcripts from {}. Combine them into a single coherent summary as flowing prose — \
+         no bullets, no headers. Write as if recapping the period in a personal journal. \
+         Weave in quotes with clear attribution. Use specific names, projects, and details. \
+         Deduplicate and merge overlapping content. Let themes connect naturally rather than \
+         following strict chronology. Match depth to density of content.",
+        date_label
+    )
+}
+
+/// System prompt for the combine pass that merges partial (per-chunk) summaries.
+/// `custom_combine_prompt` overrides the default when non-empty; use
+/// `{date_label}` as a placeholder for the date.
+pub fn combine_system_prompt(date_label: &str, custom_combine_prompt: &str) -> String {
+    if custom_combine_prompt.is_empty() {
+        default_combine_prompt(date_label)
+    } else {
+        custom_combine_prompt.replace("{date_label}", date_label)
+    }
+}
+
+/// Instruction appended to the system prompt when the previous day's summary
+/// is supplied as context.
+const PREVIOUS_CONTEXT_INSTRUCTION: &str = "\n\nCONTINUITY:\n\
+     - You are also given yesterday's summary as context. Do not re-summarize it.\n\
+     - Note follow-ups on anything it left open, and call out items carried over \
+     from yesterday that are still unresolved";
+
+/// Append the previous-day context to a system/user prompt pair.
+pub fn add_previous_context(system: &mut String, user: &mut String, previous_summary: &str) {
+    system.push_str(PREVIOUS_CONTEXT_INSTRUCTION);
+    user.insert_str(
+        0,
+        &format!("# Yesterday's context\n\n{}\n\n---\n\n", previous_summary.trim()),
+    );
+}
+
 /// Build the full prompt for a single summarization pass.
 /// `date_label` is something like "2026-02-17" or "2026-02-11 to 2026-02-17".
 /// `custom_system_prompt` overrides the default system prompt when non-empty;
 /// use `{date_label}` as a placeholder for the date.
+/// `previous_summary`, if given, is included as yesterday's context.
+/// Recording times are read from file names with `filename_template`.
 pub fn build_prompt(
     date_label: &str,
     transcripts: &[Transcript],
     custom_system_prompt: &str,
+    previous_summary: Option<&str>,
+    filename_template: &str,
 ) -> (String, String) {
-    let system = if custom_system_prompt.is_empty() {
+    let mut system = if custom_system_prompt.is_empty() {
         default_system_prompt(date_label)
     } else {
         custom_system_prompt.replace("{date_label}", date_label)
@@ -133,7 +247,7 @@ pub fn build_prompt(
 
     let filtered: Vec<&Transcript> = transcripts.iter().filter(|t| !is_noise(&t.text)).collect();
 
-    let grouped = group_by_hour(&filtered);
+    let grouped = group_by_hour(filename_template, &filtered);
 
     let mut user_parts = Vec::new();
     user_parts.push(format!(
@@ -142,16 +256,40 @@ pub fn build_prompt(
         filtered.len()
     ));
 
-    for (hour, hour_transcripts) in &grouped {
-        user_parts.push(format_hour_block(*hour, hour_transcripts));
+    for (hour, block) in &grouped {
+        user_parts.push(format_hour_block(filename_template, *hour, block));
+    }
+
+    let mut user = user_parts.join("\n");
+    if let Some(previous) = previous_summary {
+        add_previous_context(&mut system, &mut user, previous);
     }
 
-    (system, user_parts.join("\n"))
+    (system, user)
 }
 
-/// Estimate token count from text (rough: ~4 chars per token for English).
+/// Count the tokens in `text` with the o200k tokenizer used by current OpenAI
+/// models. If the tokenizer can't be loaded, falls back to ~4 bytes per token,
+/// which is only a rough guess outside English prose.
 pub fn estimate_tokens(text: &str) -> usize {
-    text.len() / 4
+    match tokenizer() {
+        Some(bpe) => bpe.encode_ordinary(text).len(),
+        None => text.len() / 4,
+    }
+}
+
+/// The shared tokenizer, built on first use; `None` if it failed to load.
+fn tokenizer() -> Option<&'static CoreBPE> {
+    static TOKENIZER: OnceLock<Option<CoreBPE>> = OnceLock::new();
+    TOKENIZER
+        .get_or_init(|| match tiktoken_rs::o200k_base() {
+            Ok(bpe) => Some(bpe),
+            Err(e) => {
+                tracing::warn!("Tokenizer unavailable, estimating from length: {}", e);
+                None
+            }
+        })
+        .as_ref()
 }
 
 /// If the transcript text is too large for a single pass, chunk it by hour groups
@@ -160,10 +298,11 @@ pub fn estimate_tokens(text: &str) -> usize {
 pub fn chunk_transcripts(
     transcripts: &[Transcript],
     max_tokens_per_chunk: usize,
+    filename_template: &str,
 ) -> Vec<Vec<Transcript>> {
     let filtered: Vec<&Transcript> = transcripts.iter().filter(|t| !is_noise(&t.text)).collect();
 
-    let grouped = group_by_hour(&filtered);
+    let grouped = group_by_hour(filename_template, &filtered);
     let mut chunks: Vec<Vec<Transcript>> = Vec::new();
     let mut current_chunk: Vec<Transcript> = Vec::new();
     let mut current_tokens: usize = 0;
@@ -218,6 +357,7 @@ pub fn chunk_transcripts(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::DEFAULT_FILENAME_TEMPLATE as TEMPLATE;
 
     fn make_transcript(file: &str, text: &str) -> Transcript {
         Transcript {
@@ -226,6 +366,8 @@ mod tests {
             duration_secs: 8.0,
             file: file.to_string(),
             text: text.to_string(),
+            engine: None,
+            segments: Vec::new(),
         }
     }
 
@@ -243,19 +385,68 @@ mod tests {
         assert!(is_noise("(coughing)"));
     }
 
+    #[test]
+    fn test_is_noise_bracket_spacing() {
+        assert!(is_noise("[ Silence ]"));
+        assert!(is_noise("(  typing )"));
+        assert!(!is_noise("[ Silence ] so anyway"));
+    }
+
+    #[test]
+    fn test_noise_filter_user_patterns() {
+        let patterns = vec![
+            "(door closes)".to_string(),
+            "/^\\(?(mouse )?click(ing)?\\)?$/".to_string(),
+        ];
+        let filter = NoiseFilter::new(&patterns).unwrap();
+        assert!(filter.is_noise("[BLANK_AUDIO]"));
+        assert!(filter.is_noise("( Door Closes )"));
+        assert!(filter.is_noise("(mouse clicking)"));
+        assert!(filter.is_noise("Click"));
+        assert!(!filter.is_noise("Click the second link"));
+        assert!(!filter.is_noise("The door closes at six"));
+
+        assert!(NoiseFilter::new(&["/(unclosed/".to_string()]).is_err());
+    }
+
     #[test]
     fn test_is_noise_real_speech() {
         assert!(!is_noise("Hello, how are you?"));
         assert!(!is_noise("Test, test, test."));
     }
 
+    #[test]
+    fn test_extract_time() {
+        assert_eq!(
+            extract_time(TEMPLATE, "mic_14-30-05.wav"),
+            Some((14, 30, 5))
+        );
+        assert_eq!(
+            extract_time(TEMPLATE, "teams_09-15-30_pc.flac"),
+            Some((9, 15, 30))
+        );
+        assert_eq!(extract_time(TEMPLATE, "mic_25-00-00.wav"), None);
+        assert_eq!(extract_time(TEMPLATE, "mic_14-30.wav"), None);
+        assert_eq!(extract_time(TEMPLATE, "invalid.wav"), None);
+
+        let custom = "{date}-{time}-{source}.{ext}";
+        assert_eq!(
+            extract_time(custom, "2026-02-17-14-30-05-mic.ogg"),
+            Some((14, 30, 5))
+        );
+        assert_eq!(
+            extract_hour(custom, "2026-02-17-09-00-00-zoom_pc.wav"),
+            Some(9)
+        );
+    }
+
     #[test]
     fn test_extract_hour() {
-        assert_eq!(extract_hour("mic_14-30-00.wav"), Some(14));
-        assert_eq!(extract_hour("teams_09-15-30.wav"), Some(9));
-        assert_eq!(extract_hour("mic_00-00-00.wav"), Some(0));
-        assert_eq!(extract_hour("mic_23-59-59.wav"), Some(23));
-        assert_eq!(extract_hour("invalid.wav"), None);
+        assert_eq!(extract_hour(TEMPLATE, "mic_14-30-00.wav"), Some(14));
+        assert_eq!(extract_hour(TEMPLATE, "teams_09-15-30.wav"), Some(9));
+        assert_eq!(extract_hour(TEMPLATE, "mic_00-00-00.wav"), Some(0));
+        assert_eq!(extract_hour(TEMPLATE, "mic_23-59-59.wav"), Some(23));
+        assert_eq!(extract_hour(TEMPLATE, "invalid.wav"), None);
     }
 
     #[test]
@@ -266,16 +457,38 @@ mod tests {
             make_transcript("mic_15-00-00.wav", "Goodbye"),
         ];
         let refs: Vec<&Transcript> = transcripts.iter().collect();
-        let groups = group_by_hour(&refs);
+        let groups = group_by_hour(TEMPLATE, &refs);
         assert_eq!(groups.len(), 2);
         assert_eq!(groups[&14].len(), 2);
         assert_eq!(groups[&15].len(), 1);
     }
 
+    #[test]
+    fn test_format_hour_block_source_tags() {
+        let mic = make_transcript("mic_14-30-00.wav", "Hello");
+        let zoom = Transcript {
+            source: "zoom".to_string(),
+            ..make_transcript("zoom_14-45-00.flac", "Standup")
+        };
+        let block = format_hour_block(TEMPLATE, 14, &[&mic, &zoom]);
+        assert!(block.contains("[14:30:00 Mic] Hello"));
+        assert!(block.contains("[14:45:00 Zoom] Standup"));
+
+        // Machine tags, other formats and custom templates don't leak into
+        // the time label.
+        let tagged = make_transcript("mic_14-50-10_desk.opus", "Bye");
+        let block = format_hour_block(TEMPLATE, 14, &[&tagged]);
+        assert!(block.contains("[14:50:10 Mic] Bye"), "{}", block);
+        let custom = make_transcript("2026-02-17-14-55-00-mic.wav", "Later");
+        let block = format_hour_block("{date}-{time}-{source}.{ext}", 14, &[&custom]);
+        assert!(block.contains("[14:55:00 Mic] Later"), "{}", block);
+        assert_eq!(source_tag(""), "Unknown");
+    }
+
     #[test]
     fn test_build_prompt_empty_uses_default() {
         let transcripts = vec![make_transcript("mic_14-30-00.wav", "Hello world")];
-        let (system, _user) = build_prompt("2026-02-17", &transcripts, "");
+        let (system, _user) = build_prompt("2026-02-17", &transcripts, "", None, TEMPLATE);
         assert!(system.contains("personal productivity assistant"));
         assert!(system.contains("2026-02-17"));
         assert!(system.contains("flowing prose"));
@@ -284,16 +497,19 @@ mod tests {
     #[test]
     fn test_estimate_tokens() {
         assert_eq!(estimate_tokens(""), 0);
-        assert_eq!(estimate_tokens("abcd"), 1);
-        assert_eq!(estimate_tokens("a]b"), 0); // 3 chars / 4 = 0
-        let long = "a".repeat(400);
-        assert_eq!(estimate_tokens(&long), 100);
+        assert_eq!(estimate_tokens("hello"), 1);
+        let english = "We agreed to ship the release on Friday. ".repeat(10);
+        let tokens = estimate_tokens(&english);
+        assert!((80..=110).contains(&tokens), "{}", tokens);
+        // Japanese is 3 bytes a character, so a byte count overestimates it.
+        let japanese = "金曜日にリリースすることで合意しました。".repeat(10);
+        assert!(estimate_tokens(&japanese) < japanese.len() / 4);
     }
 
     #[test]
     fn test_chunk_transcripts_single_chunk() {
         let transcripts = vec![make_transcript("mic_14-30-00.wav", "Hello world")];
-        let chunks = chunk_transcripts(&transcripts, 100_000);
+        let chunks = chunk_transcripts(&transcripts, 100_000, TEMPLATE);
         assert_eq!(chunks.len(), 1);
         assert_eq!(chunks[0].len(), 1);
     }
@@ -307,14 +523,14 @@ mod tests {
             make_transcript("mic_12-00-00.wav", &long_text),
         ];
         // Each chunk ~2500 tokens, limit to 3000 → should split into 3 chunks
-        let chunks = chunk_transcripts(&transcripts, 3000);
+        let chunks = chunk_transcripts(&transcripts, 3000, TEMPLATE);
         assert!(chunks.len() >= 2);
     }
 
     #[test]
     fn test_chunk_transcripts_empty() {
         let transcripts: Vec<Transcript> = vec![];
-        let chunks = chunk_transcripts(&transcripts, 100_000);
+        let chunks = chunk_transcripts(&transcripts, 100_000, TEMPLATE);
         assert_eq!(chunks.len(), 1);
         assert!(chunks[0].is_empty());
     }
@@ -322,8 +538,42 @@ mod tests {
     #[test]
     fn test_build_prompt_custom_system_prompt() {
         let transcripts = vec![make_transcript("mic_14-30-00.wav", "Hello world")];
-        let (system, _user) =
-            build_prompt("2026-02-17", &transcripts, "Summarize {date_label} please");
+        let (system, _user) = build_prompt(
+            "2026-02-17",
+            &transcripts,
+            "Summarize {date_label} please",
+            None,
+            TEMPLATE,
+        );
         assert_eq!(system, "Summarize 2026-02-17 please");
     }
+
+    #[test]
+    fn test_combine_system_prompt_default() {
+        let system = combine_system_prompt("2026-02-17", "");
+        assert!(system.contains("partial summaries"));
+        assert!(system.contains("2026-02-17"));
+    }
+
+    #[test]
+    fn test_combine_system_prompt_custom() {
+        let system = combine_system_prompt("2026-02-17", "Merge the notes for {date_label}.");
+        assert_eq!(system, "Merge the notes for 2026-02-17.");
+    }
+
+    #[test]
+    fn test_build_prompt_with_previous_summary() {
+        let transcripts = vec![make_transcript("mic_14-30-00.wav", "Hello world")];
+        let (system, user) = build_prompt(
+            "2026-02-17",
+            &transcripts,
+            "",
+            Some("Shipped the billing fix; still waiting on legal review."),
+            TEMPLATE,
+        );
+        assert!(system.contains("CONTINUITY"));
+        assert!(user.starts_with("# Yesterday's context"));
+        assert!(user.contains("waiting on legal review"));
+        assert!(user.contains("# Transcripts for 2026-02-17"));
+    }
 }
@@ -1,18 +1,39 @@
+use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
-use chrono::{Local, NaiveDate};
+use chrono::{Datelike, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
 
 use crate::config::Config;
-use crate::summarize::email::EmailClient;
 use crate::summarize::html;
-use crate::summarize::llm::LlmClient;
-use crate::summarize::prompt;
+use crate::summarize::llm::{build_provider, LlmProvider};
+use crate::summarize::notes;
+use crate::summarize::prompt::{self, NoiseFilter};
+use crate::summarize::transport::deliver_summary;
 use crate::transcribe::backend::Transcript;
+use crate::transcribe::store::{open_store, TranscriptStore};
 
 /// Main entry point for the summarize command.
-pub fn run_summarize(config: &Config, range: &str) -> Result<()> {
+///
+/// `output` overrides where the summary file is written (a file path, or an
+/// existing directory to write `<suffix>.<ext>` into). Email is sent as
+/// configured either way.
+///
+/// With `dry_run`, the summary is saved and printed to stdout, but nothing is
+/// emailed, posted, or added to the notes vault.
+pub fn run_summarize(
+    config: &Config,
+    range: &str,
+    output: Option<&Path>,
+    dry_run: bool,
+) -> Result<()> {
     let (dates, label, file_suffix) = resolve_date_range(range)?;
+    let format = OutputFormat::parse(&config.summarization.output_format)?;
+    if let Some(output) = output {
+        validate_output_path(output)?;
+    }
+    let subject = format!("deskmic {} — {}", file_suffix, label);
 
     tracing::info!(
         "Summarizing {} ({} date(s): {})",
@@ -23,7 +44,15 @@ pub fn run_summarize(config: &Config, range: &str) -> Result<()> {
 
     // 1. Load transcripts for the target dates
     let recordings_dir = &config.output.directory;
-    let transcripts = load_transcripts(recordings_dir, &dates)?;
+    let store = open_store(config)?;
+    let transcripts = load_transcripts(store.as_ref(), &dates)?;
+    let transcripts = filter_excluded(
+        transcripts,
+        &config.summarization.exclude_hours,
+        &config.summarization.exclude_sources,
+        &config.output.filename_template,
+    );
+    let transcripts = filter_noise(transcripts, &noise_filter(config)?);
 
     // 2. Check if there are any meaningful transcripts
     let meaningful_count = transcripts
@@ -34,20 +63,21 @@ pub fn run_summarize(config: &Config, range: &str) -> Result<()> {
     if meaningful_count == 0 {
         tracing::info!("No meaningful transcripts found for {}", label);
         let no_content_msg = format!("No transcripts recorded for {}.", label);
-        save_summary(recordings_dir, &file_suffix, &no_content_msg)?;
-
-        // Try to send a short notification email
-        match EmailClient::from_config(&config.summarization) {
-            Ok(email_client) => {
-                let subject = format!("deskmic {} — {}", file_suffix, label);
-                let html_body = html::markdown_to_html_email(&no_content_msg, &subject, &label);
-                match email_client.send_email(&subject, &no_content_msg, Some(&html_body)) {
-                    Ok(_) => tracing::info!("Notification email sent"),
-                    Err(e) => tracing::warn!("Failed to send notification email: {:#}", e),
-                }
-            }
-            Err(e) => tracing::info!("Email not configured, skipping: {:#}", e),
+        let saved = format.render(&no_content_msg, &subject, &label);
+        save_summary(recordings_dir, &file_suffix, &saved, format, output)?;
+        if dry_run {
+            println!("{}", no_content_msg);
+            return Ok(());
         }
+
+        // Try to send a short notification
+        let html_body = html::markdown_to_html_email(&no_content_msg, &subject, &label);
+        deliver_summary(
+            &config.summarization,
+            &subject,
+            &no_content_msg,
+            Some(&html_body),
+        );
         return Ok(());
     }
 
@@ -58,50 +88,127 @@ pub fn run_summarize(config: &Config, range: &str) -> Result<()> {
         label
     );
 
-    // 3. Build prompt and call LLM
-    let llm = LlmClient::from_config(config).context("Failed to initialize LLM client")?;
+    // 3. Build prompt and call LLM. A day with a rolling summary only needs
+    //    the transcripts recorded since its last update folded in.
+    let summary = if dates.len() == 1 && load_rolling_state(recordings_dir, dates[0]).is_some() {
+        tracing::info!("Finishing rolling summary for {}", label);
+        update_rolling_summary(config, store.as_ref(), dates[0])?.summary
+    } else {
+        let llm = build_provider(config).context("Failed to initialize LLM provider")?;
 
-    let custom_prompt = &config.summarization.system_prompt;
-    let summary = generate_summary(&llm, &label, &transcripts, custom_prompt)?;
+        let custom_prompt = &config.summarization.system_prompt;
+        let combine_prompt = &config.summarization.combine_prompt;
+        let previous_summary = if config.summarization.include_previous && dates.len() == 1 {
+            load_previous_summary(recordings_dir, dates[0])
+        } else {
+            None
+        };
+        generate_summary(
+            llm.as_ref(),
+            &label,
+            &transcripts,
+            custom_prompt,
+            combine_prompt,
+            previous_summary.as_deref(),
+            &config.output.filename_template,
+        )?
+    };
 
     // 4. Save summary locally (always, even if email fails)
-    save_summary(recordings_dir, &file_suffix, &summary)?;
-
-    // 5. Send email
-    match EmailClient::from_config(&config.summarization) {
-        Ok(email_client) => {
-            let subject = format!("deskmic {} — {}", file_suffix, label);
-            let html_body = html::markdown_to_html_email(&summary, &subject, &label);
-            match email_client.send_email(&subject, &summary, Some(&html_body)) {
-                Ok(op_id) => {
-                    tracing::info!("Summary email sent (operation: {})", op_id);
-                }
-                Err(e) => {
-                    tracing::error!("Failed to send summary email: {:#}", e);
-                    tracing::info!("Summary saved locally — check recordings/summaries/");
-                }
-            }
-        }
-        Err(e) => {
-            tracing::warn!("Email not configured, skipping: {:#}", e);
-            tracing::info!("Summary saved locally — check recordings/summaries/");
+    let saved = format.render(&summary, &subject, &label);
+    let summary_path = save_summary(recordings_dir, &file_suffix, &saved, format, output)?;
+
+    if dry_run {
+        println!("{}", summary);
+        tracing::info!("Dry run: summary not delivered");
+        return Ok(());
+    }
+
+    // 4b. Append to the daily note in the notes vault, if configured
+    if let Some(vault_dir) = &config.summarization.notes_vault_dir {
+        let note_date = *dates.last().unwrap();
+        match notes::append_to_daily_note(
+            vault_dir,
+            note_date,
+            &config.summarization.notes_heading,
+            &file_suffix,
+            &summary,
+        ) {
+            Ok(path) => tracing::info!("Summary added to daily note {}", path.display()),
+            Err(e) => tracing::warn!("Failed to update daily note: {:#}", e),
         }
     }
 
+    // 5. Deliver via every configured channel (email and/or webhook)
+    let html_body = html::markdown_to_html_email(&summary, &subject, &label);
+    if deliver_summary(&config.summarization, &subject, &summary, Some(&html_body)) == 0 {
+        tracing::info!("Summary saved locally — check {}", summary_path.display());
+    }
+
     println!("Summary generated for {}", label);
     Ok(())
 }
 
+/// Format of the locally saved summary, from `[summarization] output_format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// The LLM's Markdown as-is (`.md`).
+    Markdown,
+    /// A standalone page in the email layout (`.html`).
+    Html,
+    /// Markdown stripped to plain text (`.txt`).
+    Text,
+}
+
+impl OutputFormat {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "markdown" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            "text" => Ok(Self::Text),
+            other => anyhow::bail!(
+                "Unknown summary output format: {} (expected markdown, html, or text)",
+                other
+            ),
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+            Self::Text => "txt",
+        }
+    }
+
+    /// The file contents for a Markdown `summary`.
+    fn render(self, summary: &str, subject: &str, label: &str) -> String {
+        match self {
+            Self::Markdown => summary.to_string(),
+            Self::Html => html::markdown_to_html_email(summary, subject, label),
+            Self::Text => html::markdown_to_text(summary),
+        }
+    }
+}
+
 /// Parse a date range argument into target dates, a human-readable label, and a file suffix.
 ///
 /// Accepted formats:
 /// - `"daily"` → yesterday
 /// - `"weekly"` → last 7 days
+/// - `"monthly"` → the previous calendar month
+/// - `"last:N"` → the last N days, ending yesterday (max 90 days)
 /// - `"YYYY-MM-DD"` → that specific date
 /// - `"YYYY-MM-DD..YYYY-MM-DD"` → inclusive date range (max 90 days)
 pub fn resolve_date_range(arg: &str) -> Result<(Vec<NaiveDate>, String, String)> {
-    let today = Local::now().date_naive();
+    resolve_date_range_from(arg, Local::now().date_naive())
+}
 
+/// `resolve_date_range` relative to `today`.
+fn resolve_date_range_from(
+    arg: &str,
+    today: NaiveDate,
+) -> Result<(Vec<NaiveDate>, String, String)> {
     match arg {
         "daily" => {
             let yesterday = today - chrono::Duration::days(1);
@@ -121,6 +228,32 @@ pub fn resolve_date_range(arg: &str) -> Result<(Vec<NaiveDate>, String, String)>
             let suffix = format!("{}-weekly", last);
             Ok((dates, label, suffix))
         }
+        "monthly" => {
+            let first_of_this_month = today.with_day(1).unwrap();
+            let end = first_of_this_month - chrono::Duration::days(1);
+            let start = end.with_day(1).unwrap();
+            let label = start.format("%Y-%m").to_string();
+            let suffix = format!("{}-monthly", label);
+            Ok((dates_between(start, end), label, suffix))
+        }
+        _ if arg.starts_with("last:") => {
+            let days: i64 = arg["last:".len()..]
+                .parse()
+                .ok()
+                .filter(|&n| n > 0)
+                .with_context(|| format!("Invalid range '{}'. Expected last:N", arg))?;
+            if days > 90 {
+                anyhow::bail!(
+                    "last:{} spans more than 90 days. Use a shorter range.",
+                    days
+                );
+            }
+            let end = today - chrono::Duration::days(1);
+            let start = today - chrono::Duration::days(days);
+            let label = format!("{} to {}", start.format("%Y-%m-%d"), end.format("%Y-%m-%d"));
+            let suffix = format!("{}-last-{}", end.format("%Y-%m-%d"), days);
+            Ok((dates_between(start, end), label, suffix))
+        }
         _ if arg.contains("..") => {
             let parts: Vec<&str> = arg.splitn(2, "..").collect();
             if parts.len() != 2 {
@@ -146,22 +279,15 @@ pub fn resolve_date_range(arg: &str) -> Result<(Vec<NaiveDate>, String, String)>
                 );
             }
 
-            let mut dates = Vec::new();
-            let mut d = start;
-            while d <= end {
-                dates.push(d);
-                d += chrono::Duration::days(1);
-            }
-
             let label = format!("{} to {}", start.format("%Y-%m-%d"), end.format("%Y-%m-%d"));
             let suffix = format!("{}-to-{}", start.format("%Y-%m-%d"), end.format("%Y-%m-%d"));
-            Ok((dates, label, suffix))
+            Ok((dates_between(start, end), label, suffix))
         }
         _ => {
             // Single date
             let date = NaiveDate::parse_from_str(arg, "%Y-%m-%d").with_context(|| {
                 format!(
-                    "Invalid date range '{}'. Expected: daily, weekly, YYYY-MM-DD, or YYYY-MM-DD..YYYY-MM-DD",
+                    "Invalid date range '{}'. Expected: daily, weekly, monthly, last:N, YYYY-MM-DD, or YYYY-MM-DD..YYYY-MM-DD",
                     arg
                 )
             })?;
@@ -172,51 +298,109 @@ pub fn resolve_date_range(arg: &str) -> Result<(Vec<NaiveDate>, String, String)>
     }
 }
 
-/// Load JSONL transcripts for the given dates.
-fn load_transcripts(recordings_dir: &Path, dates: &[NaiveDate]) -> Result<Vec<Transcript>> {
-    let transcript_dir = recordings_dir.join("transcripts");
-    let mut all_transcripts = Vec::new();
+/// Every date from `start` to `end`, inclusive.
+fn dates_between(start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+    let mut dates = Vec::new();
+    let mut d = start;
+    while d <= end {
+        dates.push(d);
+        d += chrono::Duration::days(1);
+    }
+    dates
+}
 
+/// Load transcripts for the given dates from the transcript store.
+pub fn load_transcripts(store: &dyn TranscriptStore, dates: &[NaiveDate]) -> Result<Vec<Transcript>> {
+    let mut all_transcripts = Vec::new();
     for date in dates {
-        let filename = format!("{}.jsonl", date.format("%Y-%m-%d"));
-        let path = transcript_dir.join(&filename);
+        let date = date.format("%Y-%m-%d").to_string();
+        all_transcripts.extend(store.load_date(&date)?);
+    }
+    Ok(all_transcripts)
+}
 
-        if !path.exists() {
-            tracing::debug!("No transcript file for {}", date);
-            continue;
-        }
+/// Drop transcripts recorded during `exclude_hours` (by file-name time, read
+/// with `filename_template`) or from `exclude_sources` (case-insensitive), so
+/// they never reach the LLM.
+fn filter_excluded(
+    transcripts: Vec<Transcript>,
+    exclude_hours: &[u32],
+    exclude_sources: &[String],
+    filename_template: &str,
+) -> Vec<Transcript> {
+    if exclude_hours.is_empty() && exclude_sources.is_empty() {
+        return transcripts;
+    }
 
-        let content = std::fs::read_to_string(&path)
-            .with_context(|| format!("Failed to read {}", path.display()))?;
+    let before = transcripts.len();
+    let kept: Vec<Transcript> = transcripts
+        .into_iter()
+        .filter(|t| {
+            let hour_excluded = prompt::extract_hour(filename_template, &t.file)
+                .is_some_and(|hour| exclude_hours.contains(&hour));
+            let source_excluded = exclude_sources
+                .iter()
+                .any(|s| s.eq_ignore_ascii_case(&t.source));
+            !hour_excluded && !source_excluded
+        })
+        .collect();
 
-        for (line_num, line) in content.lines().enumerate() {
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                continue;
-            }
-            match serde_json::from_str::<Transcript>(trimmed) {
-                Ok(t) => all_transcripts.push(t),
-                Err(e) => {
-                    tracing::warn!(
-                        "Failed to parse line {} of {}: {}",
-                        line_num + 1,
-                        filename,
-                        e
-                    );
-                }
-            }
-        }
+    if kept.len() < before {
+        tracing::info!("Excluded {} transcript(s) by hour/source", before - kept.len());
     }
+    kept
+}
 
-    Ok(all_transcripts)
+fn noise_filter(config: &Config) -> Result<NoiseFilter> {
+    NoiseFilter::new(&config.summarization.noise_patterns)
+        .context("Invalid [summarization] noise_patterns")
+}
+
+/// Drop transcripts that are only noise, including the user's
+/// `noise_patterns`, so they don't count as meaningful or reach the LLM.
+fn filter_noise(transcripts: Vec<Transcript>, noise: &NoiseFilter) -> Vec<Transcript> {
+    let before = transcripts.len();
+    let kept: Vec<Transcript> = transcripts
+        .into_iter()
+        .filter(|t| !noise.is_noise(&t.text))
+        .collect();
+    if kept.len() < before {
+        tracing::info!("Dropped {} noise transcript(s)", before - kept.len());
+    }
+    kept
+}
+
+/// Load the saved daily summary for the day before `date`, if there is one
+/// with real content (the "no transcripts" placeholder is skipped). HTML
+/// summaries aren't used as context.
+fn load_previous_summary(recordings_dir: &Path, date: NaiveDate) -> Option<String> {
+    let previous = date - chrono::Duration::days(1);
+    let summaries = recordings_dir.join("summaries");
+    let (path, content) = ["md", "txt"].iter().find_map(|ext| {
+        let path = summaries.join(format!("{}-daily.{}", previous.format("%Y-%m-%d"), ext));
+        let content = std::fs::read_to_string(&path).ok()?;
+        Some((path, content))
+    })?;
+    if content.trim().is_empty() || content.starts_with("No transcripts recorded") {
+        return None;
+    }
+    tracing::info!("Including previous summary as context: {}", path.display());
+    Some(content)
 }
 
 /// Generate a summary using the LLM, handling chunking if needed.
+///
+/// `custom_combine_prompt` replaces the built-in combine-pass prompt when
+/// non-empty. `previous_summary` is passed to the single pass, or to the
+/// combine pass when chunking, so it is only sent to the model once.
 fn generate_summary(
-    llm: &LlmClient,
+    llm: &dyn LlmProvider,
     date_label: &str,
     transcripts: &[Transcript],
     custom_system_prompt: &str,
+    custom_combine_prompt: &str,
+    previous_summary: Option<&str>,
+    filename_template: &str,
 ) -> Result<String> {
     // Estimate total tokens in transcript content
     let total_text: String = transcripts
@@ -238,7 +422,13 @@ fn generate_summary(
 
     if estimated_tokens <= MAX_SINGLE_PASS_TOKENS {
         // Single pass
-        let (system, user) = prompt::build_prompt(date_label, transcripts, custom_system_prompt);
+        let (system, user) = prompt::build_prompt(
+            date_label,
+            transcripts,
+            custom_system_prompt,
+            previous_summary,
+            filename_template,
+        );
         let summary = llm
             .chat(&system, &user)
             .context("LLM summarization failed")?;
@@ -251,37 +441,55 @@ fn generate_summary(
         estimated_tokens
     );
 
-    let chunks = prompt::chunk_transcripts(transcripts, CHUNK_TOKENS);
+    let chunks = prompt::chunk_transcripts(transcripts, CHUNK_TOKENS, filename_template);
     let mut partial_summaries = Vec::new();
 
     for (i, chunk) in chunks.iter().enumerate() {
         tracing::info!("Summarizing chunk {}/{}", i + 1, chunks.len());
         let chunk_label = format!("{} (part {}/{})", date_label, i + 1, chunks.len());
-        let (system, user) = prompt::build_prompt(&chunk_label, chunk, custom_system_prompt);
+        let (system, user) = prompt::build_prompt(
+            &chunk_label,
+            chunk,
+            custom_system_prompt,
+            None,
+            filename_template,
+        );
         let partial = llm
             .chat(&system, &user)
             .with_context(|| format!("LLM summarization failed for chunk {}", i + 1))?;
         partial_summaries.push(partial);
     }
 
-    // Combine partial summaries
-    let combine_system = format!(
-        "You are a personal productivity assistant. Below are partial summaries of voice \
-         transcripts from {}. Combine them into a single coherent summary as flowing prose — \
-         no bullets, no headers. Write as if recapping the period in a personal journal. \
-         Weave in quotes with clear attribution. Use specific names, projects, and details. \
-         Deduplicate and merge overlapping content. Let themes connect naturally rather than \
-         following strict chronology. Match depth to density of content.",
-        date_label
-    );
+    combine_summaries(
+        llm,
+        date_label,
+        &partial_summaries,
+        custom_combine_prompt,
+        previous_summary,
+    )
+}
+
+/// Merge partial summaries into one with the combine-pass prompt.
+fn combine_summaries(
+    llm: &dyn LlmProvider,
+    date_label: &str,
+    partial_summaries: &[String],
+    custom_combine_prompt: &str,
+    previous_summary: Option<&str>,
+) -> Result<String> {
+    let mut combine_system = prompt::combine_system_prompt(date_label, custom_combine_prompt);
 
-    let combine_user = partial_summaries
+    let mut combine_user = partial_summaries
         .iter()
         .enumerate()
         .map(|(i, s)| format!("## Partial Summary {}\n\n{}", i + 1, s))
         .collect::<Vec<_>>()
         .join("\n\n---\n\n");
 
+    if let Some(previous) = previous_summary {
+        prompt::add_previous_context(&mut combine_system, &mut combine_user, previous);
+    }
+
     let final_summary = llm
         .chat(&combine_system, &combine_user)
         .context("LLM combination pass failed")?;
@@ -289,13 +497,158 @@ fn generate_summary(
     Ok(final_summary)
 }
 
-/// Save the summary to a local markdown file.
-fn save_summary(recordings_dir: &Path, file_suffix: &str, content: &str) -> Result<PathBuf> {
-    let summary_dir = recordings_dir.join("summaries");
-    std::fs::create_dir_all(&summary_dir)
-        .with_context(|| format!("Failed to create {}", summary_dir.display()))?;
+/// Progress of a day's rolling summary, stored in
+/// `summaries/<date>-rolling.json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RollingState {
+    /// Recordings (`Transcript::file`) whose transcripts are already folded
+    /// in. Tracked by name, not count: transcripts synced from another
+    /// machine can load ahead of ones already processed.
+    processed: BTreeSet<String>,
+    /// Summary of those transcripts so far.
+    summary: String,
+}
+
+fn rolling_state_path(recordings_dir: &Path, date: NaiveDate) -> PathBuf {
+    recordings_dir
+        .join("summaries")
+        .join(format!("{}-rolling.json", date.format("%Y-%m-%d")))
+}
+
+fn load_rolling_state(recordings_dir: &Path, date: NaiveDate) -> Option<RollingState> {
+    let content = std::fs::read_to_string(rolling_state_path(recordings_dir, date)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_rolling_state(recordings_dir: &Path, date: NaiveDate, state: &RollingState) -> Result<()> {
+    let path = rolling_state_path(recordings_dir, date);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(state)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// The transcripts recorded since the rolling summary was last updated.
+///
+/// If a transcript already processed is gone (the store was rewritten), the
+/// rolling summary starts over.
+fn new_since(state: &mut RollingState, all: Vec<Transcript>) -> Vec<Transcript> {
+    let files: BTreeSet<&str> = all.iter().map(|t| t.file.as_str()).collect();
+    if state.processed.iter().any(|f| !files.contains(f.as_str())) {
+        tracing::warn!("Transcripts for the day shrank, rebuilding rolling summary");
+        *state = RollingState::default();
+    }
+    all.into_iter()
+        .filter(|t| !state.processed.contains(&t.file))
+        .collect()
+}
+
+/// Fold transcripts recorded since the last update into the day's rolling
+/// summary, merging with the existing summary via the combine pass.
+fn update_rolling_summary(
+    config: &Config,
+    store: &dyn TranscriptStore,
+    date: NaiveDate,
+) -> Result<RollingState> {
+    let recordings_dir = &config.output.directory;
+    let label = date.format("%Y-%m-%d").to_string();
+    let mut state = load_rolling_state(recordings_dir, date).unwrap_or_default();
+
+    let all = store.load_date(&label)?;
+    let files: BTreeSet<String> = all.iter().map(|t| t.file.clone()).collect();
+    let new = filter_excluded(
+        new_since(&mut state, all),
+        &config.summarization.exclude_hours,
+        &config.summarization.exclude_sources,
+        &config.output.filename_template,
+    );
+    let new = filter_noise(new, &noise_filter(config)?);
+    let meaningful = new.iter().filter(|t| !prompt::is_noise(&t.text)).count();
+
+    if meaningful > 0 {
+        tracing::info!("Adding {} new transcript(s) to rolling summary", meaningful);
+        let llm = build_provider(config).context("Failed to initialize LLM provider")?;
+        let summarization = &config.summarization;
+        let partial = generate_summary(
+            llm.as_ref(),
+            &label,
+            &new,
+            &summarization.system_prompt,
+            &summarization.combine_prompt,
+            None,
+            &config.output.filename_template,
+        )?;
+        state.summary = if state.summary.is_empty() {
+            partial
+        } else {
+            combine_summaries(
+                llm.as_ref(),
+                &label,
+                &[std::mem::take(&mut state.summary), partial],
+                &summarization.combine_prompt,
+                None,
+            )?
+        };
+    }
+
+    state.processed = files;
+    save_rolling_state(recordings_dir, date, &state)?;
+    Ok(state)
+}
+
+/// Entry point for `deskmic summarize --rolling`: bring today's rolling
+/// summary up to date and save it as `summaries/<date>-rolling.<ext>`, with
+/// the extension following `summarization.output_format`.
+///
+/// Nothing is emailed; the regular daily summary the next morning finishes the
+/// rolling summary instead of summarizing the whole day from scratch.
+pub fn run_rolling_summary(config: &Config, output: Option<&Path>) -> Result<()> {
+    let format = OutputFormat::parse(&config.summarization.output_format)?;
+    if let Some(output) = output {
+        validate_output_path(output)?;
+    }
+
+    let today = Local::now().date_naive();
+    let store = open_store(config)?;
+    let state = update_rolling_summary(config, store.as_ref(), today)?;
+
+    if state.summary.is_empty() {
+        println!("No transcripts to summarize yet for {}", today.format("%Y-%m-%d"));
+        return Ok(());
+    }
+
+    let label = today.format("%Y-%m-%d").to_string();
+    let suffix = format!("{}-rolling", label);
+    let subject = format!("deskmic {} — {}", suffix, label);
+    let saved = format.render(&state.summary, &subject, &label);
+    let path = save_summary(&config.output.directory, &suffix, &saved, format, output)?;
+    println!("Rolling summary updated: {}", path.display());
+    Ok(())
+}
+
+/// Save the rendered summary to a local file.
+///
+/// Defaults to `recordings/summaries/<suffix>.<ext>`, with the extension
+/// matching `format`; `output` overrides this (see `resolve_output_path`).
+fn save_summary(
+    recordings_dir: &Path,
+    file_suffix: &str,
+    content: &str,
+    format: OutputFormat,
+    output: Option<&Path>,
+) -> Result<PathBuf> {
+    let file_name = format!("{}.{}", file_suffix, format.extension());
+    let path = match output {
+        Some(output) => resolve_output_path(output, &file_name),
+        None => {
+            let summary_dir = recordings_dir.join("summaries");
+            std::fs::create_dir_all(&summary_dir)
+                .with_context(|| format!("Failed to create {}", summary_dir.display()))?;
+            summary_dir.join(file_name)
+        }
+    };
 
-    let path = summary_dir.join(format!("{}.md", file_suffix));
     std::fs::write(&path, content)
         .with_context(|| format!("Failed to write {}", path.display()))?;
 
@@ -303,9 +656,44 @@ fn save_summary(recordings_dir: &Path, file_suffix: &str, content: &str) -> Resu
     Ok(path)
 }
 
+/// Resolve an `--output` argument: an existing directory gets `file_name`
+/// inside it, anything else is used as the file path.
+pub(crate) fn resolve_output_path(output: &Path, file_name: &str) -> PathBuf {
+    if output.is_dir() {
+        output.join(file_name)
+    } else {
+        output.to_path_buf()
+    }
+}
+
+/// Check that an `--output` target can be written before doing any LLM work.
+pub(crate) fn validate_output_path(output: &Path) -> Result<()> {
+    let dir = if output.is_dir() {
+        output.to_path_buf()
+    } else {
+        match output.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+            _ => PathBuf::from("."),
+        }
+    };
+
+    if !dir.is_dir() {
+        anyhow::bail!("Output directory does not exist: {}", dir.display());
+    }
+
+    // Probe with a throwaway file; permission bits alone aren't reliable on Windows.
+    let probe = dir.join(".deskmic-write-check");
+    std::fs::write(&probe, b"")
+        .with_context(|| format!("Output directory is not writable: {}", dir.display()))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::DEFAULT_FILENAME_TEMPLATE as TEMPLATE;
+    use crate::transcribe::store::JsonlStore;
 
     #[test]
     fn test_resolve_date_range_daily() {
@@ -363,12 +751,55 @@ mod tests {
         assert!(resolve_date_range("2025-01-01..2025-12-31").is_err()); // 365 days > 90
     }
 
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_date_range_monthly() {
+        let (dates, label, suffix) = resolve_date_range_from("monthly", ymd(2026, 3, 15)).unwrap();
+        assert_eq!(dates.len(), 28);
+        assert_eq!(dates[0], ymd(2026, 2, 1));
+        assert_eq!(dates[27], ymd(2026, 2, 28));
+        assert_eq!(label, "2026-02");
+        assert_eq!(suffix, "2026-02-monthly");
+    }
+
+    #[test]
+    fn test_resolve_date_range_monthly_previous_year() {
+        let (dates, label, suffix) = resolve_date_range_from("monthly", ymd(2026, 1, 1)).unwrap();
+        assert_eq!(dates.len(), 31);
+        assert_eq!(dates[0], ymd(2025, 12, 1));
+        assert_eq!(dates[30], ymd(2025, 12, 31));
+        assert_eq!(label, "2025-12");
+        assert_eq!(suffix, "2025-12-monthly");
+    }
+
+    #[test]
+    fn test_resolve_date_range_last_n() {
+        let (dates, label, suffix) = resolve_date_range_from("last:3", ymd(2026, 3, 2)).unwrap();
+        assert_eq!(dates.len(), 3);
+        assert_eq!(dates[0], ymd(2026, 2, 27));
+        assert_eq!(dates[2], ymd(2026, 3, 1));
+        assert_eq!(label, "2026-02-27 to 2026-03-01");
+        assert_eq!(suffix, "2026-03-01-last-3");
+    }
+
+    #[test]
+    fn test_resolve_date_range_last_n_invalid() {
+        let today = ymd(2026, 3, 2);
+        let (dates, _label, _suffix) = resolve_date_range_from("last:90", today).unwrap();
+        assert_eq!(dates.len(), 90);
+        assert!(resolve_date_range_from("last:91", today).is_err()); // > 90 days
+        assert!(resolve_date_range_from("last:0", today).is_err());
+        assert!(resolve_date_range_from("last:", today).is_err());
+        assert!(resolve_date_range_from("last:x", today).is_err());
+    }
+
     #[test]
     fn test_load_transcripts_missing_dir() {
-        let result = load_transcripts(
-            Path::new("/nonexistent"),
-            &[NaiveDate::from_ymd_opt(2026, 2, 17).unwrap()],
-        );
+        let store = JsonlStore::new(Path::new("/nonexistent"));
+        let result = load_transcripts(&store, &[NaiveDate::from_ymd_opt(2026, 2, 17).unwrap()]);
         assert!(result.is_ok());
         assert!(result.unwrap().is_empty());
     }
@@ -384,18 +815,263 @@ mod tests {
         std::fs::write(transcript_dir.join("2026-02-17.jsonl"), jsonl).unwrap();
 
         let dates = vec![NaiveDate::from_ymd_opt(2026, 2, 17).unwrap()];
-        let transcripts = load_transcripts(tmp.path(), &dates).unwrap();
+        let transcripts = load_transcripts(&JsonlStore::new(tmp.path()), &dates).unwrap();
         assert_eq!(transcripts.len(), 2);
         assert_eq!(transcripts[0].text, "Hello");
         assert_eq!(transcripts[1].text, "World");
     }
 
+    fn transcript(source: &str, file: &str) -> Transcript {
+        Transcript {
+            timestamp: "2026-02-17".to_string(),
+            source: source.to_string(),
+            duration_secs: 5.0,
+            file: file.to_string(),
+            text: "Hello".to_string(),
+            engine: None,
+            segments: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_filter_excluded_hours_and_sources() {
+        let transcripts = vec![
+            transcript("mic", "mic_11-59-00.wav"),
+            transcript("mic", "mic_12-15-00.wav"),
+            transcript("teams", "teams_13-00-00.wav"),
+            transcript("system", "system_15-00-00.wav"),
+            transcript("mic", "no-time.wav"),
+        ];
+        let kept = filter_excluded(transcripts, &[12, 13], &["System".to_string()], TEMPLATE);
+        let files: Vec<&str> = kept.iter().map(|t| t.file.as_str()).collect();
+        assert_eq!(files, vec!["mic_11-59-00.wav", "no-time.wav"]);
+
+        let custom = "{date}-{time}-{source}.{ext}";
+        let transcripts = vec![
+            transcript("mic", "2026-02-17-11-59-00-mic.wav"),
+            transcript("mic", "2026-02-17-12-15-00-mic.wav"),
+        ];
+        let kept = filter_excluded(transcripts, &[12], &[], custom);
+        assert_eq!(kept[0].file, "2026-02-17-11-59-00-mic.wav");
+        assert_eq!(kept.len(), 1);
+    }
+
+    #[test]
+    fn test_rolling_state_roundtrip() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 2, 17).unwrap();
+        assert!(load_rolling_state(tmp.path(), date).is_none());
+
+        let state = RollingState {
+            processed: ["mic_09-00-00.wav".to_string()].into(),
+            summary: "Morning standup.".to_string(),
+        };
+        save_rolling_state(tmp.path(), date, &state).unwrap();
+        assert!(tmp.path().join("summaries/2026-02-17-rolling.json").exists());
+
+        let loaded = load_rolling_state(tmp.path(), date).unwrap();
+        assert_eq!(loaded.processed, state.processed);
+        assert_eq!(loaded.summary, "Morning standup.");
+    }
+
+    #[test]
+    fn test_new_since_skips_processed() {
+        let mut state = RollingState {
+            processed: [
+                "mic_09-00-00.wav".to_string(),
+                "mic_10-00-00.wav".to_string(),
+            ]
+            .into(),
+            summary: "So far".to_string(),
+        };
+        let all = vec![
+            transcript("mic", "mic_09-00-00.wav"),
+            transcript("mic", "mic_10-00-00.wav"),
+            transcript("mic", "mic_11-00-00.wav"),
+        ];
+        let new = new_since(&mut state, all);
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].file, "mic_11-00-00.wav");
+        assert_eq!(state.summary, "So far");
+    }
+
+    #[test]
+    fn test_new_since_resets_when_store_shrinks() {
+        let mut state = RollingState {
+            processed: ["mic_08-00-00.wav".to_string()].into(),
+            summary: "Stale".to_string(),
+        };
+        let new = new_since(&mut state, vec![transcript("mic", "mic_09-00-00.wav")]);
+        assert_eq!(new.len(), 1);
+        assert!(state.processed.is_empty());
+        assert!(state.summary.is_empty());
+    }
+
+    #[test]
+    fn test_new_since_with_transcripts_from_two_machines() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let laptop = JsonlStore::for_machine(tmp.path(), Some("laptop".to_string()));
+        let desktop = JsonlStore::for_machine(tmp.path(), Some("desktop".to_string()));
+        let date = "2026-02-17";
+
+        laptop
+            .append(date, &transcript("mic", "mic_09-00-00_laptop.wav"))
+            .unwrap();
+        let mut state = RollingState::default();
+        let new = new_since(&mut state, laptop.load_date(date).unwrap());
+        assert_eq!(new.len(), 1);
+        state.processed = new.into_iter().map(|t| t.file).collect();
+
+        // The desktop's file syncs in later, and sorts ahead of the laptop's.
+        desktop
+            .append(date, &transcript("mic", "mic_10-00-00_desktop.wav"))
+            .unwrap();
+        let all = laptop.load_date(date).unwrap();
+        assert_eq!(all[0].file, "mic_10-00-00_desktop.wav");
+        let new = new_since(&mut state, all);
+        let files: Vec<&str> = new.iter().map(|t| t.file.as_str()).collect();
+        assert_eq!(files, vec!["mic_10-00-00_desktop.wav"]);
+    }
+
+    #[test]
+    fn test_filter_noise_user_patterns() {
+        let mut typing = transcript("mic", "mic_09-00-00.wav");
+        typing.text = "( Typing )".to_string();
+        let mut chair = transcript("mic", "mic_09-05-00.wav");
+        chair.text = "(chair squeaks)".to_string();
+        let speech = transcript("mic", "mic_09-10-00.wav");
+
+        let noise = NoiseFilter::new(&["/squeak/".to_string()]).unwrap();
+        let kept = filter_noise(vec![typing, chair, speech], &noise);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].file, "mic_09-10-00.wav");
+    }
+
+    #[test]
+    fn test_filter_excluded_noop_when_empty() {
+        let transcripts = vec![transcript("mic", "mic_12-15-00.wav")];
+        assert_eq!(filter_excluded(transcripts, &[], &[], TEMPLATE).len(), 1);
+    }
+
     #[test]
     fn test_save_summary() {
         let tmp = tempfile::TempDir::new().unwrap();
-        let path = save_summary(tmp.path(), "2026-02-17-daily", "# Test Summary").unwrap();
+        let path = save_summary(
+            tmp.path(),
+            "2026-02-17-daily",
+            "# Test Summary",
+            OutputFormat::Markdown,
+            None,
+        )
+        .unwrap();
         assert!(path.exists());
         let content = std::fs::read_to_string(&path).unwrap();
         assert_eq!(content, "# Test Summary");
     }
+
+    #[test]
+    fn test_save_summary_output_file() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let target = tmp.path().join("notes").join("today.md");
+        std::fs::create_dir_all(target.parent().unwrap()).unwrap();
+
+        let path = save_summary(
+            tmp.path(),
+            "2026-02-17-daily",
+            "# Test",
+            OutputFormat::Markdown,
+            Some(&target),
+        )
+        .unwrap();
+        assert_eq!(path, target);
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "# Test");
+        assert!(!tmp.path().join("summaries").exists());
+    }
+
+    #[test]
+    fn test_save_summary_output_directory() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = save_summary(
+            tmp.path(),
+            "2026-02-17-daily",
+            "# Test",
+            OutputFormat::Markdown,
+            Some(tmp.path()),
+        )
+        .unwrap();
+        assert_eq!(path, tmp.path().join("2026-02-17-daily.md"));
+    }
+
+    #[test]
+    fn test_save_summary_extension_follows_format() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let format = OutputFormat::parse("html").unwrap();
+        let html = format.render("## Morning", "deskmic", "2026-02-17");
+        let path = save_summary(tmp.path(), "2026-02-17-daily", &html, format, None).unwrap();
+        assert_eq!(path, tmp.path().join("summaries/2026-02-17-daily.html"));
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(saved.contains("<h2>Morning</h2>"));
+
+        let format = OutputFormat::parse("text").unwrap();
+        let text = format.render("## **Morning**", "deskmic", "2026-02-17");
+        assert_eq!(text, "Morning");
+        assert_eq!(format.extension(), "txt");
+        assert!(OutputFormat::parse("pdf").is_err());
+    }
+
+    #[test]
+    fn test_validate_output_path_missing_parent() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let target = tmp.path().join("missing").join("summary.md");
+        assert!(validate_output_path(&target).is_err());
+    }
+
+    #[test]
+    fn test_validate_output_path_ok() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        assert!(validate_output_path(&tmp.path().join("summary.md")).is_ok());
+        assert!(validate_output_path(tmp.path()).is_ok());
+        assert!(!tmp.path().join(".deskmic-write-check").exists());
+    }
+
+    #[test]
+    fn test_load_previous_summary() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let summaries = tmp.path().join("summaries");
+        std::fs::create_dir_all(&summaries).unwrap();
+        std::fs::write(summaries.join("2026-02-16-daily.md"), "Open: legal review").unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2026, 2, 17).unwrap();
+        assert_eq!(
+            load_previous_summary(tmp.path(), date).as_deref(),
+            Some("Open: legal review")
+        );
+
+        // Plain-text summaries are used too.
+        std::fs::write(summaries.join("2026-02-15-daily.txt"), "Open: budget").unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 2, 16).unwrap();
+        assert_eq!(
+            load_previous_summary(tmp.path(), date).as_deref(),
+            Some("Open: budget")
+        );
+
+        // No summary for the day before 2026-02-15.
+        let earlier = NaiveDate::from_ymd_opt(2026, 2, 15).unwrap();
+        assert!(load_previous_summary(tmp.path(), earlier).is_none());
+    }
+
+    #[test]
+    fn test_load_previous_summary_skips_placeholder() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let summaries = tmp.path().join("summaries");
+        std::fs::create_dir_all(&summaries).unwrap();
+        std::fs::write(
+            summaries.join("2026-02-16-daily.md"),
+            "No transcripts recorded for 2026-02-16.",
+        )
+        .unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2026, 2, 17).unwrap();
+        assert!(load_previous_summary(tmp.path(), date).is_none());
+    }
 }
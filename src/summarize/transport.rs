@@ -0,0 +1,186 @@
+use anyhow::Result;
+
+use crate::config::SummarizationConfig;
+use crate::summarize::email::EmailClient;
+use crate::summarize::email_smtp::SmtpEmailClient;
+use crate::summarize::webhook::WebhookClient;
+
+/// A delivery channel for finished summaries.
+///
+/// Selected by `[summarization] transport`:
+/// - `"acs"` — Azure Communication Services email (`EmailClient`)
+/// - `"smtp"` — SMTP email (`SmtpEmailClient`)
+/// - `"webhook"` — JSON or Slack POST to `webhook_url` (`WebhookClient`)
+///
+/// A set `webhook_url` is also delivered to alongside an email transport.
+pub trait SummaryTransport {
+    /// Human-readable transport name for logs.
+    fn name(&self) -> &str;
+
+    /// Deliver a summary. Returns a transport-specific id or status for logging.
+    fn send(&self, subject: &str, text: &str, html: Option<&str>) -> Result<String>;
+}
+
+/// Build the transport configured in `[summarization] transport`.
+pub fn build_transport(config: &SummarizationConfig) -> Result<Box<dyn SummaryTransport>> {
+    match config.transport.as_str() {
+        "acs" => Ok(Box::new(EmailClient::from_config(config)?)),
+        "smtp" => Ok(Box::new(SmtpEmailClient::from_config(config)?)),
+        "webhook" => Ok(Box::new(WebhookClient::from_config(config)?)),
+        other => anyhow::bail!(
+            "Unknown summary transport: {} (expected acs, smtp, or webhook)",
+            other
+        ),
+    }
+}
+
+impl SummaryTransport for EmailClient {
+    fn name(&self) -> &str {
+        "acs"
+    }
+
+    fn send(&self, subject: &str, text: &str, html: Option<&str>) -> Result<String> {
+        self.send_email(subject, text, html)
+    }
+}
+
+/// Every configured delivery channel: the `transport`, plus the webhook when
+/// `webhook_url` is set alongside an email transport. Channels that can't be
+/// built (e.g. ACS without credentials) are logged and left out.
+pub fn build_transports(config: &SummarizationConfig) -> Vec<Box<dyn SummaryTransport>> {
+    let mut transports = Vec::new();
+    match build_transport(config) {
+        Ok(transport) => transports.push(transport),
+        Err(e) => tracing::warn!(
+            "Summary delivery via {} not configured: {:#}",
+            config.transport,
+            e
+        ),
+    }
+    if config.transport != "webhook" && !config.webhook_url.is_empty() {
+        match WebhookClient::from_config(config) {
+            Ok(webhook) => transports.push(Box::new(webhook) as Box<dyn SummaryTransport>),
+            Err(e) => tracing::warn!("Summary webhook not usable: {:#}", e),
+        }
+    }
+    transports
+}
+
+/// Send a summary through every configured channel. A failing channel is
+/// logged and doesn't stop the others. Returns how many channels succeeded.
+pub fn deliver_summary(
+    config: &SummarizationConfig,
+    subject: &str,
+    text: &str,
+    html: Option<&str>,
+) -> usize {
+    let mut delivered = 0;
+    for transport in build_transports(config) {
+        match transport.send(subject, text, html) {
+            Ok(id) => {
+                tracing::info!("Summary sent via {} ({})", transport.name(), id);
+                delivered += 1;
+            }
+            Err(e) => tracing::error!("Failed to send summary via {}: {:#}", transport.name(), e),
+        }
+    }
+    delivered
+}
+
+impl SummaryTransport for WebhookClient {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    fn send(&self, subject: &str, text: &str, html: Option<&str>) -> Result<String> {
+        self.send_summary(subject, text, html)
+    }
+}
+
+/// Entry point for `deskmic test-email`: send a short message through the
+/// configured transport so delivery settings can be checked without running
+/// a full summary.
+pub fn run_test_email(config: &SummarizationConfig) -> Result<()> {
+    let transports = build_transports(config);
+    if transports.is_empty() {
+        // Surface why the configured transport couldn't be built.
+        build_transport(config)?;
+    }
+    let subject = "deskmic test message";
+    let text = "This is a test message from deskmic. Summary delivery is configured correctly.";
+    let mut failed = 0;
+    for transport in &transports {
+        match transport.send(subject, text, None) {
+            Ok(id) => println!("Test message sent via {} ({})", transport.name(), id),
+            Err(e) => {
+                println!("Test message via {} failed: {:#}", transport.name(), e);
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!(
+            "{} of {} delivery channels failed",
+            failed,
+            transports.len()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_transport_unknown() {
+        let config = SummarizationConfig {
+            transport: "pigeon".to_string(),
+            ..Default::default()
+        };
+        let err = build_transport(&config).err().unwrap();
+        assert!(format!("{}", err).contains("Unknown summary transport"));
+    }
+
+    #[test]
+    fn test_build_transport_webhook_requires_url() {
+        let config = SummarizationConfig {
+            transport: "webhook".to_string(),
+            ..Default::default()
+        };
+        assert!(build_transport(&config).is_err());
+    }
+
+    #[test]
+    fn test_build_transport_webhook() {
+        let config = SummarizationConfig {
+            transport: "webhook".to_string(),
+            webhook_url: "https://example.com/hook".to_string(),
+            ..Default::default()
+        };
+        let transport = build_transport(&config).unwrap();
+        assert_eq!(transport.name(), "webhook");
+    }
+
+    #[test]
+    fn test_build_transports_adds_webhook() {
+        let config = SummarizationConfig {
+            transport: "smtp".to_string(),
+            webhook_url: "https://hooks.slack.com/services/T0/B0/x".to_string(),
+            webhook_format: "slack".to_string(),
+            ..Default::default()
+        };
+        // SMTP has no host configured, so only the webhook remains.
+        let names: Vec<String> = build_transports(&config)
+            .iter()
+            .map(|t| t.name().to_string())
+            .collect();
+        assert_eq!(names, vec!["webhook"]);
+
+        let config = SummarizationConfig {
+            transport: "webhook".to_string(),
+            ..config
+        };
+        assert_eq!(build_transports(&config).len(), 1);
+    }
+}
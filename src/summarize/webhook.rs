@@ -0,0 +1,201 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::config::SummarizationConfig;
+
+/// Slack rejects section blocks with more than 3000 characters of text.
+const SLACK_SECTION_LIMIT: usize = 3000;
+
+/// Payload shape posted to `webhook_url`, from `[summarization] webhook_format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookFormat {
+    /// `{"subject", "text", "html"}`.
+    Json,
+    /// Slack incoming-webhook message: a header block plus mrkdwn sections.
+    Slack,
+}
+
+impl WebhookFormat {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "json" => Ok(Self::Json),
+            "slack" => Ok(Self::Slack),
+            other => anyhow::bail!("Unknown webhook format: {} (expected json or slack)", other),
+        }
+    }
+}
+
+/// Posts summaries to a webhook (Slack, Teams workflows, or anything that
+/// accepts JSON).
+pub struct WebhookClient {
+    url: String,
+    format: WebhookFormat,
+    client: reqwest::blocking::Client,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonPayload<'a> {
+    subject: &'a str,
+    text: &'a str,
+    html: Option<&'a str>,
+}
+
+impl WebhookClient {
+    pub fn from_config(config: &SummarizationConfig) -> Result<Self> {
+        if config.webhook_url.is_empty() {
+            anyhow::bail!(
+                "Webhook URL not configured. Set [summarization] webhook_url in deskmic.toml"
+            );
+        }
+        let format = WebhookFormat::parse(&config.webhook_format)?;
+
+        let client = reqwest::blocking::Client::builder()
+            .timeout(std::time::Duration::from_secs(60))
+            .build()?;
+
+        Ok(Self {
+            url: config.webhook_url.clone(),
+            format,
+            client,
+        })
+    }
+
+    /// POST a summary. Returns the HTTP status for logging.
+    pub fn send_summary(&self, subject: &str, text: &str, html: Option<&str>) -> Result<String> {
+        let payload = match self.format {
+            WebhookFormat::Json => serde_json::to_value(JsonPayload {
+                subject,
+                text,
+                html,
+            })?,
+            WebhookFormat::Slack => slack_payload(subject, text),
+        };
+
+        tracing::info!("Posting summary to webhook");
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&payload)
+            .send()
+            .context("Failed to post summary to webhook")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().unwrap_or_default();
+            anyhow::bail!("Webhook returned HTTP {}: {}", status.as_u16(), body);
+        }
+        Ok(format!("HTTP {}", status.as_u16()))
+    }
+}
+
+/// A Slack message: the subject as a header, then the summary as mrkdwn
+/// sections split at paragraph boundaries to stay under Slack's limit.
+fn slack_payload(subject: &str, text: &str) -> serde_json::Value {
+    let mut blocks = vec![json!({
+        "type": "header",
+        "text": { "type": "plain_text", "text": subject },
+    })];
+    for section in split_sections(&to_slack_mrkdwn(text), SLACK_SECTION_LIMIT) {
+        blocks.push(json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": section },
+        }));
+    }
+    // `text` is the notification fallback.
+    json!({ "text": subject, "blocks": blocks })
+}
+
+/// Rewrite the Markdown the LLM produces into Slack's mrkdwn: `**bold**`
+/// becomes `*bold*` and headings become bold lines.
+fn to_slack_mrkdwn(markdown: &str) -> String {
+    markdown
+        .lines()
+        .map(|line| {
+            let line = line.replace("**", "*");
+            let heading = line.trim_start_matches('#');
+            if heading.len() < line.len() && heading.starts_with(' ') {
+                format!("*{}*", heading.trim())
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Split `text` into pieces of at most `limit` characters, preferring blank
+/// lines, then newlines, as break points.
+fn split_sections(text: &str, limit: usize) -> Vec<String> {
+    let mut sections = Vec::new();
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        if rest.chars().count() <= limit {
+            sections.push(rest.to_string());
+            break;
+        }
+        let window_end = rest
+            .char_indices()
+            .nth(limit)
+            .map(|(i, _)| i)
+            .unwrap_or(rest.len());
+        let window = &rest[..window_end];
+        let cut = window
+            .rfind("\n\n")
+            .or_else(|| window.rfind('\n'))
+            .filter(|&i| i > 0)
+            .unwrap_or(window_end);
+        sections.push(rest[..cut].trim_end().to_string());
+        rest = rest[cut..].trim_start();
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_payload_shape() {
+        let payload = JsonPayload {
+            subject: "deskmic daily",
+            text: "Summary",
+            html: None,
+        };
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["subject"], "deskmic daily");
+        assert_eq!(json["text"], "Summary");
+        assert!(json["html"].is_null());
+    }
+
+    #[test]
+    fn test_slack_payload_shape() {
+        let payload = slack_payload("deskmic daily", "## Morning\n\nShipped **v2**.");
+        assert_eq!(payload["text"], "deskmic daily");
+        assert_eq!(payload["blocks"][0]["type"], "header");
+        assert_eq!(payload["blocks"][0]["text"]["text"], "deskmic daily");
+        assert_eq!(payload["blocks"][1]["text"]["type"], "mrkdwn");
+        assert_eq!(
+            payload["blocks"][1]["text"]["text"],
+            "*Morning*\n\nShipped *v2*."
+        );
+    }
+
+    #[test]
+    fn test_split_sections() {
+        let text = format!("{}\n\n{}", "a".repeat(20), "b".repeat(20));
+        assert_eq!(split_sections(&text, 100), vec![text.clone()]);
+        assert_eq!(
+            split_sections(&text, 30),
+            vec!["a".repeat(20), "b".repeat(20)]
+        );
+        // No break points: hard split.
+        assert_eq!(split_sections(&"c".repeat(25), 10).len(), 3);
+    }
+
+    #[test]
+    fn test_webhook_format_parse() {
+        assert_eq!(WebhookFormat::parse("slack").unwrap(), WebhookFormat::Slack);
+        assert!(WebhookFormat::parse("xml").is_err());
+    }
+}
@@ -4,16 +4,27 @@ use anyhow::Result;
 use reqwest::blocking::multipart;
 
 use crate::config::AzureConfig;
-use crate::transcribe::backend::{Transcript, TranscriptionBackend};
+use crate::transcribe::backend::{
+    transcript_metadata, TranscribeError, Transcript, TranscriptionBackend,
+};
+use crate::transcribe::rate_limit::RateLimiter;
+use crate::transcribe::wav_split::split_wav;
+
+/// Azure OpenAI rejects audio uploads larger than 25 MB.
+const MAX_UPLOAD_BYTES: u64 = 25 * 1024 * 1024;
 
 pub struct AzureOpenAIBackend {
     endpoint: String,
     api_key: String,
     deployment: String,
+    api_version: String,
+    limiter: RateLimiter,
+    /// `output.filename_template`, to read each recording's source.
+    filename_template: String,
 }
 
 impl AzureOpenAIBackend {
-    pub fn new(config: &AzureConfig) -> Result<Self> {
+    pub fn new(config: &AzureConfig, filename_template: &str) -> Result<Self> {
         let api_key = if config.api_key.is_empty() {
             std::env::var("DESKMIC_AZURE_KEY")
                 .map_err(|_| anyhow::anyhow!("Azure API key not configured"))?
@@ -25,8 +36,59 @@ impl AzureOpenAIBackend {
             endpoint: config.endpoint.clone(),
             api_key,
             deployment: config.deployment.clone(),
+            api_version: config.api_version.clone(),
+            limiter: RateLimiter::new(config.requests_per_minute, config.max_concurrent_requests),
+            filename_template: filename_template.to_string(),
         })
     }
+
+    /// POST one audio part (`mime` is its content type) to the transcription
+    /// endpoint and return its text. Rate limiting, server errors and network
+    /// failures come back as `TranscribeError::Transient`.
+    fn upload(
+        &self,
+        client: &reqwest::blocking::Client,
+        part: multipart::Part,
+        filename: &str,
+        mime: &str,
+    ) -> Result<String> {
+        let url = format!(
+            "{}/openai/deployments/{}/audio/transcriptions?api-version={}",
+            self.endpoint, self.deployment, self.api_version
+        );
+
+        let form = multipart::Form::new()
+            .part("file", part.file_name(filename.to_string()).mime_str(mime)?)
+            .text("response_format", "json");
+
+        let _permit = self.limiter.acquire();
+        let response = client
+            .post(&url)
+            .header("api-key", &self.api_key)
+            .multipart(form)
+            .send()
+            .map_err(|e| {
+                if e.is_timeout() || e.is_connect() || e.is_request() {
+                    TranscribeError::Transient(e.to_string())
+                } else {
+                    TranscribeError::Permanent(e.to_string())
+                }
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response
+                .text()
+                .unwrap_or_else(|_| "unable to read response body".to_string());
+            let message = format!("Azure OpenAI returned HTTP {}: {}", status.as_u16(), body);
+            if status.as_u16() == 429 || status.is_server_error() {
+                return Err(TranscribeError::Transient(message).into());
+            }
+            return Err(TranscribeError::Permanent(message).into());
+        }
+        let body: serde_json::Value = response.json()?;
+        Ok(body["text"].as_str().unwrap_or("").to_string())
+    }
 }
 
 impl TranscriptionBackend for AzureOpenAIBackend {
@@ -35,62 +97,63 @@ impl TranscriptionBackend for AzureOpenAIBackend {
     }
 
     fn transcribe(&self, audio_path: &Path) -> Result<Transcript> {
-        let url = format!(
-            "{}/openai/deployments/{}/audio/transcriptions?api-version=2024-06-01",
-            self.endpoint, self.deployment
-        );
-
-        let file_bytes = std::fs::read(audio_path)?;
         let filename = audio_path
             .file_name()
             .ok_or_else(|| anyhow::anyhow!("audio path has no filename: {}", audio_path.display()))?
             .to_string_lossy()
             .to_string();
 
-        let form = multipart::Form::new()
-            .part(
-                "file",
-                multipart::Part::bytes(file_bytes)
-                    .file_name(filename.clone())
-                    .mime_str("audio/wav")?,
-            )
-            .text("response_format", "json");
-
         let client = reqwest::blocking::Client::builder()
             .timeout(std::time::Duration::from_secs(300))
             .build()?;
-        let response = client
-            .post(&url)
-            .header("api-key", &self.api_key)
-            .multipart(form)
-            .send()?;
 
-        let response = response.error_for_status()?;
-        let body: serde_json::Value = response.json()?;
-        let text = body["text"].as_str().unwrap_or("").to_string();
-
-        // Get duration from WAV header
-        let reader = hound::WavReader::open(audio_path)?;
-        let spec = reader.spec();
-        let duration_secs = reader.duration() as f64 / spec.sample_rate as f64;
-
-        let source = if filename.starts_with("mic") {
-            "mic"
+        // Stream the file straight from disk; only oversized files are split,
+        // one in-memory piece at a time.
+        let file_size = std::fs::metadata(audio_path)?.len();
+        let text = if file_size <= MAX_UPLOAD_BYTES {
+            let file = std::fs::File::open(audio_path)?;
+            let part = multipart::Part::reader_with_length(file, file_size);
+            let mime = match audio_path.extension().and_then(|e| e.to_str()) {
+                Some("flac") => "audio/flac",
+                Some("ogg") => "audio/ogg",
+                _ => "audio/wav",
+            };
+            self.upload(&client, part, &filename, mime)?
         } else {
-            "teams"
+            tracing::info!(
+                "{} is {:.1} MB (over the {} MB upload limit), splitting at silence",
+                filename,
+                file_size as f64 / (1024.0 * 1024.0),
+                MAX_UPLOAD_BYTES / (1024 * 1024)
+            );
+            // Pieces are always WAV, whatever the recording's format.
+            let piece_name = Path::new(&filename).with_extension("wav");
+            let piece_name = piece_name.to_string_lossy();
+            let mut texts = Vec::new();
+            split_wav(audio_path, MAX_UPLOAD_BYTES, |bytes| {
+                let part = multipart::Part::bytes(bytes);
+                texts.push(self.upload(&client, part, &piece_name, "audio/wav")?);
+                Ok(())
+            })?;
+            texts
+                .iter()
+                .map(|t| t.trim())
+                .filter(|t| !t.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
         };
-        let timestamp = audio_path
-            .parent()
-            .and_then(|p| p.file_name())
-            .map(|d| d.to_string_lossy().to_string())
-            .unwrap_or_default();
+
+        let (source, timestamp, duration_secs) =
+            transcript_metadata(audio_path, &self.filename_template)?;
 
         Ok(Transcript {
             timestamp,
-            source: source.to_string(),
+            source,
             duration_secs,
             file: filename,
             text,
+            engine: Some(format!("{}/{}", self.name(), self.deployment)),
+            segments: Vec::new(),
         })
     }
 }
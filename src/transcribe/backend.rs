@@ -2,6 +2,31 @@ use std::path::Path;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::audio::decode;
+use crate::config::parse_recording_name;
+
+/// Transcription failures a backend can classify. Errors of any other type
+/// are treated as permanent.
+///
+/// `Transient` failures (rate limiting, server errors, dropped connections)
+/// are retried with backoff; permanent ones count towards skipping the file.
+#[derive(Error, Debug)]
+pub enum TranscribeError {
+    #[error("Transient transcription failure: {0}")]
+    Transient(String),
+    #[error("Transcription failed: {0}")]
+    Permanent(String),
+}
+
+/// Whether `err` is a `TranscribeError::Transient`, i.e. worth retrying.
+pub fn is_transient(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<TranscribeError>(),
+        Some(TranscribeError::Transient(_))
+    )
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transcript {
@@ -10,9 +35,123 @@ pub struct Transcript {
     pub duration_secs: f64,
     pub file: String,
     pub text: String,
+    /// Backend and model that produced the transcript (e.g.
+    /// "azure-openai/whisper-1"). Absent for transcripts written before this
+    /// was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine: Option<String>,
+    /// Timed pieces of `text`, when the backend reports them (local Whisper
+    /// does, Azure doesn't). Empty for older transcripts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub segments: Vec<TranscriptSegment>,
 }
 
-pub trait TranscriptionBackend: Send {
+/// A stretch of a transcript with its start and end offset into the recording.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    pub start_secs: f64,
+    pub end_secs: f64,
+    pub text: String,
+}
+
+pub trait TranscriptionBackend: Send + Sync {
     fn name(&self) -> &str;
     fn transcribe(&self, audio_path: &Path) -> Result<Transcript>;
 }
+
+/// Capture source of a recording, read from its file name with
+/// `output.filename_template` (`zoom_09-00-00.wav` -> "zoom",
+/// `mic-desk_09-00-00_laptop.flac` -> "mic-desk"). A name that doesn't fit
+/// the template gives everything before the first `_`.
+pub fn source_from_filename(template: &str, filename: &str) -> String {
+    if let Some(name) = parse_recording_name(template, filename) {
+        return name.source;
+    }
+    let stem = Path::new(filename)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| filename.to_string());
+    match stem.split_once('_') {
+        Some((source, _)) => source.to_string(),
+        None => stem,
+    }
+}
+
+/// The metadata every backend records for `audio_path`: its source (see
+/// `source_from_filename`, with `filename_template`), its date folder name
+/// (the transcript `timestamp`), and its length in seconds from the file header.
+pub fn transcript_metadata(
+    audio_path: &Path,
+    filename_template: &str,
+) -> Result<(String, String, f64)> {
+    let filename = audio_path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("audio path has no filename: {}", audio_path.display()))?
+        .to_string_lossy()
+        .to_string();
+    let source = source_from_filename(filename_template, &filename);
+    let timestamp = audio_path
+        .parent()
+        .and_then(|p| p.file_name())
+        .map(|d| d.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let duration_secs = decode::duration_secs(audio_path)?;
+    Ok((source, timestamp, duration_secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DEFAULT_FILENAME_TEMPLATE as TEMPLATE;
+
+    #[test]
+    fn test_source_from_filename() {
+        let source = |filename| source_from_filename(TEMPLATE, filename);
+        assert_eq!(source("mic_09-00-00.wav"), "mic");
+        assert_eq!(source("teams_13-00-00.flac"), "teams");
+        assert_eq!(source("zoom_13-00-00.ogg"), "zoom");
+        assert_eq!(source("system_13-00-00_laptop.wav"), "system");
+        assert_eq!(source("mic-headset_09-00-00.wav"), "mic-headset");
+        assert_eq!(source("recording.wav"), "recording");
+
+        let custom = "{date}-{time}-{source}.{ext}";
+        let source = |filename| source_from_filename(custom, filename);
+        assert_eq!(source("2026-02-17-09-00-00-mic-desk.wav"), "mic-desk");
+        assert_eq!(source("2026-02-17-09-00-00-zoom_laptop.ogg"), "zoom");
+    }
+
+    #[test]
+    fn test_is_transient() {
+        assert!(is_transient(
+            &TranscribeError::Transient("HTTP 429".to_string()).into()
+        ));
+        assert!(!is_transient(
+            &TranscribeError::Permanent("HTTP 400".to_string()).into()
+        ));
+        assert!(!is_transient(&anyhow::anyhow!("corrupt WAV")));
+    }
+
+    #[test]
+    fn test_transcript_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let day = dir.path().join("2026-03-16");
+        std::fs::create_dir_all(&day).unwrap();
+        let path = day.join("zoom_14-30-00.wav");
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 16000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for _ in 0..8000 {
+            writer.write_sample(0i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let (source, timestamp, duration_secs) = transcript_metadata(&path, TEMPLATE).unwrap();
+        assert_eq!(source, "zoom");
+        assert_eq!(timestamp, "2026-03-16");
+        assert!((duration_secs - 0.5).abs() < 1e-9);
+    }
+}
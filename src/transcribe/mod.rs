@@ -1,7 +1,12 @@
 pub mod azure_openai;
 pub mod backend;
+pub mod models;
+pub mod rate_limit;
 pub mod runner;
 pub mod state;
 pub mod status;
+pub mod store;
+pub mod subtitles;
+pub mod wav_split;
 #[cfg(target_os = "windows")]
 pub mod whisper_local;
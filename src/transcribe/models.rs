@@ -0,0 +1,350 @@
+// Whisper model downloads: shared by the setup wizard and the local backend,
+// which fetches a known model the first time it's configured.
+
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
+
+const HF_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/";
+
+/// First bytes of every ggml model file (`0x67676d6c` little-endian).
+const GGML_MAGIC: &[u8; 4] = b"lmgg";
+
+/// Models published as `ggml-{name}.bin` that may be downloaded by name.
+pub const KNOWN_MODELS: &[&str] = &[
+    "tiny",
+    "tiny.en",
+    "base",
+    "base.en",
+    "small",
+    "small.en",
+    "medium",
+    "medium.en",
+    "large-v3",
+];
+
+/// SHA-256 of the models offered by `deskmic setup`, pinned so a download is
+/// verified even when the published checksum can't be fetched.
+const PINNED_SHA256: &[(&str, &str)] = &[
+    (
+        "tiny.en",
+        "921e4cf8686fdd993dcd081a5da5b6c365bfde1162e72b08d75ac75289920b1f",
+    ),
+    (
+        "base.en",
+        "a03779c86df3323075f5e796cb2ce5029f00ec8869eee3fdfb897afe36c6d002",
+    ),
+    (
+        "small.en",
+        "c6138d6d58ecc8322097e0f987c32f1be8bb0a18532a3f88f734d1bbf9c41e5d",
+    ),
+];
+
+/// The pinned SHA-256 of model `name`, if it has one.
+pub fn pinned_sha256(name: &str) -> Option<&'static str> {
+    PINNED_SHA256
+        .iter()
+        .find(|(model, _)| *model == name)
+        .map(|(_, sha256)| *sha256)
+}
+
+/// Whether `name` is a model we know how to download.
+pub fn is_known_model(name: &str) -> bool {
+    KNOWN_MODELS.contains(&name)
+}
+
+/// Where a downloaded model lives: `ggml-{name}.bin` beside the exe.
+pub fn model_path(name: &str) -> Result<PathBuf> {
+    let exe = std::env::current_exe()?;
+    let exe_dir = exe
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine executable directory"))?;
+    Ok(exe_dir.join(format!("ggml-{name}.bin")))
+}
+
+/// Path to model `name`, downloading it first if it isn't there yet.
+/// Refuses names not in `KNOWN_MODELS`.
+pub fn ensure_model(name: &str) -> Result<PathBuf> {
+    if !is_known_model(name) {
+        anyhow::bail!(
+            "Unknown Whisper model '{}'; known models: {}",
+            name,
+            KNOWN_MODELS.join(", ")
+        );
+    }
+    let dest = model_path(name)?;
+    if dest.exists() {
+        return Ok(dest);
+    }
+
+    tracing::info!(
+        "Whisper model {} not found, downloading to {}",
+        name,
+        dest.display()
+    );
+    download_model(name, &dest, &mut |_, _| {})?;
+    tracing::info!("Downloaded Whisper model {}", name);
+    Ok(dest)
+}
+
+/// Download model `name` from Hugging Face to `dest`, and check it against its
+/// pinned SHA-256 or, for models without a pin, the one Hugging Face publishes.
+/// `progress` is called with the bytes downloaded so far and the total, if
+/// known, after each chunk.
+pub fn download_model(name: &str, dest: &Path, progress: &mut Progress) -> Result<()> {
+    let url = format!("{HF_BASE_URL}ggml-{name}.bin");
+    let sha256 = match pinned_sha256(name) {
+        Some(pinned) => Some(pinned.to_string()),
+        None => published_sha256(&url),
+    };
+    if sha256.is_none() {
+        tracing::warn!(
+            "No published checksum for {}; the download won't be verified",
+            url
+        );
+    }
+    download_file(&url, dest, sha256.as_deref(), progress)?;
+    check_model_file(dest)
+}
+
+/// The SHA-256 Hugging Face publishes for a large (LFS) file: the
+/// `X-Linked-Etag` of the redirect to its CDN copy, so the redirect isn't
+/// followed.
+fn published_sha256(url: &str) -> Option<String> {
+    let client = reqwest::blocking::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .ok()?;
+    let response = client.head(url).send().ok()?;
+    let etag = response.headers().get("x-linked-etag")?.to_str().ok()?;
+    let etag = etag.trim_start_matches("W/").trim_matches('"');
+    (etag.len() == 64 && etag.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| etag.to_ascii_lowercase())
+}
+
+/// Lower-case hex SHA-256 of the file at `path`.
+fn file_sha256(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Check that `path` looks like a ggml Whisper model, so a bad download
+/// (e.g. an HTML error page) gets a clear error instead of a failed load.
+pub fn check_model_file(path: &Path) -> Result<()> {
+    let mut magic = [0u8; 4];
+    let header = std::fs::File::open(path).and_then(|mut f| f.read_exact(&mut magic));
+    if header.is_err() || &magic != GGML_MAGIC {
+        anyhow::bail!(
+            "{} is not a Whisper model file (corrupt or incomplete download?). \
+             Delete it and run `deskmic setup` to download it again",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Download progress callback: `(bytes downloaded, total bytes if known)`.
+pub type Progress = dyn FnMut(u64, Option<u64>);
+
+/// Download `url` to `dest`, reporting to `progress`. The file is written as
+/// `<dest>.part` and renamed once it has the full `Content-Length` and, if
+/// given, the expected SHA-256. A `.part` file left by an interrupted
+/// download is resumed with a range request, or downloaded again if the
+/// server doesn't support ranges.
+fn download_file(
+    url: &str,
+    dest: &Path,
+    sha256: Option<&str>,
+    progress: &mut Progress,
+) -> Result<()> {
+    let part_path = dest.with_extension("bin.part");
+    let resume_from = std::fs::metadata(&part_path).map_or(0, |m| m.len());
+
+    let mut request = reqwest::blocking::Client::new().get(url);
+    if resume_from > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={resume_from}-"));
+    }
+    let response = request.send()?;
+    let status = response.status();
+    if status == StatusCode::RANGE_NOT_SATISFIABLE {
+        // The partial file is no prefix of the current model; start over.
+        std::fs::remove_file(&part_path)?;
+        return download_file(url, dest, sha256, progress);
+    }
+    if !status.is_success() {
+        anyhow::bail!("HTTP {status}");
+    }
+
+    let content_length = response
+        .headers()
+        .get(reqwest::header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+
+    let (mut file, mut downloaded) = if status == StatusCode::PARTIAL_CONTENT {
+        let mb_done = resume_from as f64 / 1_048_576.0;
+        tracing::info!("Resuming download of {} at {:.1} MB", url, mb_done);
+        let file = std::fs::OpenOptions::new().append(true).open(&part_path)?;
+        (file, resume_from)
+    } else {
+        (std::fs::File::create(&part_path)?, 0)
+    };
+    // With a range response, Content-Length counts only the remaining bytes.
+    let total_bytes = content_length.map(|len| downloaded + len);
+    let mut reader = response;
+    let mut buf = [0u8; 8192];
+
+    loop {
+        let n = reader
+            .read(&mut buf)
+            .context("Download interrupted; run again to resume")?;
+        if n == 0 {
+            break;
+        }
+        file.write_all(&buf[..n])?;
+        downloaded += n as u64;
+        progress(downloaded, total_bytes);
+    }
+
+    drop(file);
+    if let Some(total) = total_bytes {
+        if downloaded != total {
+            anyhow::bail!(
+                "Download incomplete ({downloaded} of {total} bytes); run again to resume"
+            );
+        }
+    }
+    if let Some(expected) = sha256 {
+        let actual = file_sha256(&part_path)?;
+        if actual != expected {
+            // Resuming a corrupt file can't fix it.
+            std::fs::remove_file(&part_path)?;
+            anyhow::bail!(
+                "Downloaded file is corrupt (SHA-256 {actual}, expected {expected}); \
+                 it was deleted, run again to download it afresh"
+            );
+        }
+    }
+    std::fs::rename(&part_path, dest)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_known_model() {
+        assert!(is_known_model("base.en"));
+        assert!(is_known_model("large-v3"));
+        assert!(!is_known_model("base.en.bin"));
+        assert!(!is_known_model("../../evil"));
+    }
+
+    #[test]
+    fn test_pinned_models_are_known() {
+        for (name, sha256) in PINNED_SHA256 {
+            assert!(is_known_model(name), "{name}");
+            assert_eq!(sha256.len(), 64, "{name}");
+            assert!(sha256.chars().all(|c| c.is_ascii_hexdigit()), "{name}");
+        }
+        assert_eq!(pinned_sha256("large-v3"), None);
+    }
+
+    /// Serve `body` on a local port, answering `Range: bytes=N-` with the
+    /// rest of it when `ranges` is set. Returns the URL.
+    fn serve(body: &'static [u8], ranges: bool) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = [0u8; 4096];
+                let n = stream.read(&mut request).unwrap();
+                let request = String::from_utf8_lossy(&request[..n]).to_lowercase();
+                let start = request
+                    .lines()
+                    .find_map(|l| l.strip_prefix("range: bytes="))
+                    .and_then(|r| r.trim().trim_end_matches('-').parse::<usize>().ok())
+                    .filter(|_| ranges);
+                let (status, part) = match start {
+                    Some(start) => ("206 Partial Content", &body[start..]),
+                    None => ("200 OK", body),
+                };
+                let header = format!(
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    part.len()
+                );
+                stream.write_all(header.as_bytes()).unwrap();
+                stream.write_all(part).unwrap();
+            }
+        });
+        format!("http://{addr}/ggml-test.bin")
+    }
+
+    #[test]
+    fn test_download_file_resumes_part_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("ggml-test.bin");
+        let body: &[u8] = b"0123456789abcdef";
+
+        std::fs::write(dest.with_extension("bin.part"), &body[..6]).unwrap();
+        download_file(&serve(body, true), &dest, None, &mut |_, _| {}).unwrap();
+        assert_eq!(std::fs::read(&dest).unwrap(), body);
+        assert!(!dest.with_extension("bin.part").exists());
+    }
+
+    #[test]
+    fn test_download_file_restarts_without_range_support() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("ggml-test.bin");
+        let body: &[u8] = b"0123456789abcdef";
+
+        std::fs::write(dest.with_extension("bin.part"), b"stale").unwrap();
+        download_file(&serve(body, false), &dest, None, &mut |_, _| {}).unwrap();
+        assert_eq!(std::fs::read(&dest).unwrap(), body);
+    }
+
+    #[test]
+    fn test_download_file_checks_sha256() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("ggml-test.bin");
+        let body: &[u8] = b"0123456789abcdef";
+        let url = serve(body, true);
+
+        let wrong = "0".repeat(64);
+        let err = download_file(&url, &dest, Some(&wrong), &mut |_, _| {}).unwrap_err();
+        assert!(err.to_string().contains("corrupt"), "{}", err);
+        assert!(!dest.exists());
+        assert!(!dest.with_extension("bin.part").exists());
+
+        let right = format!("{:x}", Sha256::digest(body));
+        download_file(&url, &dest, Some(&right), &mut |_, _| {}).unwrap();
+        assert_eq!(file_sha256(&dest).unwrap(), right);
+    }
+
+    #[test]
+    fn test_check_model_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let model = dir.path().join("ggml-good.bin");
+        std::fs::write(&model, b"lmgg\x01\x00\x00\x00").unwrap();
+        check_model_file(&model).unwrap();
+
+        let html = dir.path().join("ggml-html.bin");
+        std::fs::write(&html, b"<!DOCTYPE html>").unwrap();
+        assert!(check_model_file(&html).is_err());
+        assert!(check_model_file(&dir.path().join("ggml-empty.bin")).is_err());
+    }
+
+    #[test]
+    fn test_ensure_model_refuses_unknown_names() {
+        let err = ensure_model("https://example.com/model").unwrap_err();
+        assert!(err.to_string().contains("Unknown Whisper model"), "{}", err);
+        assert!(model_path("base.en").unwrap().ends_with("ggml-base.en.bin"));
+    }
+}
@@ -0,0 +1,180 @@
+// Client-side rate limiting for cloud transcription backends.
+//
+// Combines a token bucket (requests per minute) with a concurrency cap so a
+// large catch-up backlog stays within the deployment's quota instead of
+// repeatedly tripping 429s.
+
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Token bucket refilled continuously at `per_minute / 60` tokens per second,
+/// holding at most `per_minute` tokens (so a full minute's quota can burst).
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(per_minute: u32, now: Instant) -> Self {
+        let capacity = per_minute as f64;
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: capacity / 60.0,
+            last_refill: now,
+        }
+    }
+
+    /// Try to take one token at `now`. Returns `None` on success, or how long
+    /// to wait before a token becomes available.
+    pub fn try_acquire(&mut self, now: Instant) -> Option<Duration> {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            let missing = 1.0 - self.tokens;
+            Some(Duration::from_secs_f64(missing / self.refill_per_sec))
+        }
+    }
+}
+
+/// Blocking rate limiter shared by all requests to one backend.
+///
+/// A limit of `0` disables that dimension (unlimited).
+pub struct RateLimiter {
+    bucket: Option<Mutex<TokenBucket>>,
+    max_concurrent: usize,
+    in_flight: Mutex<usize>,
+    slot_freed: Condvar,
+}
+
+/// Held for the duration of a request; releases its concurrency slot on drop.
+pub struct RatePermit<'a> {
+    limiter: &'a RateLimiter,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_minute: u32, max_concurrent: u32) -> Self {
+        Self {
+            bucket: (requests_per_minute > 0)
+                .then(|| Mutex::new(TokenBucket::new(requests_per_minute, Instant::now()))),
+            max_concurrent: max_concurrent as usize,
+            in_flight: Mutex::new(0),
+            slot_freed: Condvar::new(),
+        }
+    }
+
+    /// Block until both a concurrency slot and a rate token are available.
+    pub fn acquire(&self) -> RatePermit<'_> {
+        {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            while self.max_concurrent > 0 && *in_flight >= self.max_concurrent {
+                in_flight = self.slot_freed.wait(in_flight).unwrap();
+            }
+            *in_flight += 1;
+        }
+
+        if let Some(bucket) = &self.bucket {
+            loop {
+                let wait = bucket.lock().unwrap().try_acquire(Instant::now());
+                match wait {
+                    None => break,
+                    Some(d) => {
+                        tracing::debug!("Rate limit reached, waiting {:.1}s", d.as_secs_f64());
+                        std::thread::sleep(d);
+                    }
+                }
+            }
+        }
+
+        RatePermit { limiter: self }
+    }
+}
+
+impl Drop for RatePermit<'_> {
+    fn drop(&mut self) {
+        let mut in_flight = self.limiter.in_flight.lock().unwrap();
+        *in_flight -= 1;
+        self.limiter.slot_freed.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_bucket_allows_burst_up_to_capacity() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(3, start);
+        assert!(bucket.try_acquire(start).is_none());
+        assert!(bucket.try_acquire(start).is_none());
+        assert!(bucket.try_acquire(start).is_none());
+        assert!(bucket.try_acquire(start).is_some());
+    }
+
+    #[test]
+    fn test_bucket_reports_wait_and_refills() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(60, start); // 1 token per second
+        for _ in 0..60 {
+            assert!(bucket.try_acquire(start).is_none());
+        }
+        let wait = bucket.try_acquire(start).unwrap();
+        assert!((wait.as_secs_f64() - 1.0).abs() < 0.01);
+
+        assert!(bucket.try_acquire(start + Duration::from_secs(1)).is_none());
+    }
+
+    #[test]
+    fn test_bucket_does_not_exceed_capacity() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2, start);
+        let later = start + Duration::from_secs(600);
+        assert!(bucket.try_acquire(later).is_none());
+        assert!(bucket.try_acquire(later).is_none());
+        assert!(bucket.try_acquire(later).is_some());
+    }
+
+    #[test]
+    fn test_limiter_caps_concurrency() {
+        let limiter = Arc::new(RateLimiter::new(0, 2));
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..6)
+            .map(|_| {
+                let (limiter, active, peak) = (limiter.clone(), active.clone(), peak.clone());
+                std::thread::spawn(move || {
+                    let _permit = limiter.acquire();
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(20));
+                    active.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[test]
+    fn test_limiter_unlimited_does_not_block() {
+        let limiter = RateLimiter::new(0, 0);
+        let _a = limiter.acquire();
+        let _b = limiter.acquire();
+        let _c = limiter.acquire();
+    }
+}
@@ -1,15 +1,38 @@
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::time::Duration;
 
 use anyhow::Result;
 
-use crate::config::Config;
+use crate::config::{parse_recording_name, Config};
 use crate::search;
-use crate::transcribe::backend::{Transcript, TranscriptionBackend};
-use crate::transcribe::state::TranscriptionState;
+use crate::transcribe::backend::{is_transient, Transcript, TranscriptionBackend};
+use crate::transcribe::state::{TranscribeLock, TranscriptionState, MAX_FAILURES};
 use crate::transcribe::status::{TranscriberState, TranscriptionStatus};
+use crate::transcribe::store::{open_store, TranscriptStore};
+use crate::transcribe::subtitles;
+
+/// Waits between attempts at a file that failed transiently (rate limited,
+/// server error); one retry per entry.
+const RETRY_DELAYS: [Duration; 3] = [
+    Duration::from_secs(2),
+    Duration::from_secs(4),
+    Duration::from_secs(8),
+];
 
 /// Find all unprocessed WAV files in the recordings directory.
-fn find_pending_files(recordings_dir: &Path, state: &TranscriptionState) -> Result<Vec<PathBuf>> {
+///
+/// With a `machine_id`, recordings tagged with a different machine's id are
+/// left for that machine to transcribe (names are read with
+/// `filename_template`). Poisoned files (see `TranscriptionState::poisoned`)
+/// are skipped.
+fn find_pending_files(
+    recordings_dir: &Path,
+    state: &TranscriptionState,
+    machine_id: Option<&str>,
+    filename_template: &str,
+) -> Result<Vec<PathBuf>> {
     let mut pending = Vec::new();
 
     if !recordings_dir.exists() {
@@ -25,14 +48,26 @@ fn find_pending_files(recordings_dir: &Path, state: &TranscriptionState) -> Resu
         for file in std::fs::read_dir(entry.path())? {
             let file = file?;
             let path = file.path();
-            if path.extension().map(|e| e == "wav").unwrap_or(false) {
+            if crate::audio::decode::is_recording(&path) {
                 let relative = path
                     .strip_prefix(recordings_dir)?
                     .to_string_lossy()
                     .replace('\\', "/");
-                if !state.is_transcribed(&relative) {
-                    pending.push(path);
+                if state.is_transcribed(&relative)
+                    || belongs_to_other_machine(&path, machine_id, filename_template)
+                {
+                    continue;
                 }
+                if let Some(failed) = state.poisoned(&relative) {
+                    tracing::debug!(
+                        "Skipping {}: failed {} times ({})",
+                        relative,
+                        failed.failures,
+                        failed.last_error
+                    );
+                    continue;
+                }
+                pending.push(path);
             }
         }
     }
@@ -41,25 +76,24 @@ fn find_pending_files(recordings_dir: &Path, state: &TranscriptionState) -> Resu
     Ok(pending)
 }
 
-/// Resolve the model path from config. If the value is already an absolute path
-/// or ends in ".bin", use it as-is. Otherwise, treat it as a model name and
-/// construct "ggml-{name}.bin" in the exe directory.
-fn resolve_model_path(model: &str) -> String {
-    let path = Path::new(model);
-    // If it's already an absolute path or has a .bin extension, use as-is
-    if path.is_absolute() || path.extension().map(|e| e == "bin").unwrap_or(false) {
-        return model.to_string();
-    }
-    // Otherwise construct ggml-{model}.bin next to the exe
-    if let Ok(exe_path) = std::env::current_exe() {
-        if let Some(exe_dir) = exe_path.parent() {
-            return exe_dir
-                .join(format!("ggml-{}.bin", model))
-                .to_string_lossy()
-                .to_string();
-        }
-    }
-    format!("ggml-{}.bin", model)
+/// Whether a recording's name, read with `filename_template`, carries another
+/// machine's id (`{source}_{HH-MM-SS}_{machine}.wav`). Untagged recordings
+/// belong to everyone.
+fn belongs_to_other_machine(
+    path: &Path,
+    machine_id: Option<&str>,
+    filename_template: &str,
+) -> bool {
+    let Some(machine_id) = machine_id else {
+        return false;
+    };
+    let filename = path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    parse_recording_name(filename_template, &filename)
+        .and_then(|name| name.machine)
+        .is_some_and(|owner| owner != machine_id)
 }
 
 /// Build the appropriate backend from config.
@@ -73,9 +107,20 @@ fn build_backend(
         "local" => {
             #[cfg(target_os = "windows")]
             {
-                use crate::transcribe::whisper_local::WhisperLocal;
-                let model_path = resolve_model_path(&config.transcription.model);
-                Ok(Box::new(WhisperLocal::new(&model_path)?))
+                use crate::transcribe::models;
+                use crate::transcribe::whisper_local::{resolve_model_path, WhisperLocal};
+                let model = &config.transcription.model;
+                let model_path = match resolve_model_path(model) {
+                    Ok(path) => path,
+                    // A known model that hasn't been downloaded yet.
+                    Err(_) if models::is_known_model(model) => models::ensure_model(model)?,
+                    Err(e) => return Err(e),
+                };
+                Ok(Box::new(WhisperLocal::new(
+                    &model_path.to_string_lossy(),
+                    &config.transcription,
+                    &config.output.filename_template,
+                )?))
             }
             #[cfg(not(target_os = "windows"))]
             {
@@ -86,21 +131,102 @@ fn build_backend(
             use crate::transcribe::azure_openai::AzureOpenAIBackend;
             Ok(Box::new(AzureOpenAIBackend::new(
                 &config.transcription.azure,
+                &config.output.filename_template,
             )?))
         }
         other => anyhow::bail!("Unknown transcription backend: {}", other),
     }
 }
 
-/// Append a transcript to the daily JSONL file and update state.
+/// Transcribe `path`, retrying transient failures after each of `delays`.
+fn transcribe_with_retry(
+    backend: &dyn TranscriptionBackend,
+    path: &Path,
+    delays: &[Duration],
+) -> Result<Transcript> {
+    let mut attempt = 0;
+    loop {
+        match backend.transcribe(path) {
+            Err(e) if is_transient(&e) && attempt < delays.len() => {
+                tracing::warn!(
+                    "Transient failure transcribing {}, retrying in {}s (attempt {}/{}): {:#}",
+                    path.display(),
+                    delays[attempt].as_secs(),
+                    attempt + 1,
+                    delays.len() + 1,
+                    e
+                );
+                std::thread::sleep(delays[attempt]);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Progress of a transcription worker, by index into the file list.
+enum WorkerEvent {
+    Started(usize),
+    Finished(usize, Result<Transcript>),
+}
+
+/// Transcribe `files` on up to `concurrency` worker threads, handing each
+/// event to `on_event` on the calling thread. If `on_event` fails, workers
+/// finish the file they're on and take no more; the error is returned.
+fn transcribe_files(
+    backend: &dyn TranscriptionBackend,
+    files: &[PathBuf],
+    concurrency: usize,
+    mut on_event: impl FnMut(WorkerEvent) -> Result<()>,
+) -> Result<()> {
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+    std::thread::scope(|scope| {
+        for _ in 0..concurrency.clamp(1, files.len().max(1)) {
+            let tx = tx.clone();
+            let next = &next;
+            scope.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = files.get(i) else { break };
+                if tx.send(WorkerEvent::Started(i)).is_err() {
+                    break;
+                }
+                let result = transcribe_with_retry(backend, path, &RETRY_DELAYS);
+                if tx.send(WorkerEvent::Finished(i, result)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+        // Returning drops the receiver, which stops the workers.
+        for event in rx {
+            on_event(event)?;
+        }
+        Ok(())
+    })
+}
+
+/// Append a transcript to the transcript store and update state, including
+/// the lifetime stats. Returns `false`, writing nothing, if the file is
+/// already marked transcribed, so a file is never appended twice.
 fn save_transcript(
     transcript: &Transcript,
     audio_path: &Path,
     recordings_dir: &Path,
+    store: &dyn TranscriptStore,
     state: &mut TranscriptionState,
-) -> Result<()> {
-    let transcript_dir = recordings_dir.join("transcripts");
-    std::fs::create_dir_all(&transcript_dir)?;
+    machine_id: Option<&str>,
+    emit_subtitles: bool,
+) -> Result<bool> {
+    // Normalize to forward slashes for cross-platform consistency.
+    let relative = audio_path
+        .strip_prefix(recordings_dir)?
+        .to_string_lossy()
+        .replace('\\', "/");
+    if state.is_transcribed(&relative) {
+        tracing::warn!("{} is already transcribed, not saving it again", relative);
+        return Ok(false);
+    }
 
     let date_dir = audio_path
         .parent()
@@ -109,27 +235,38 @@ fn save_transcript(
         .ok_or_else(|| {
             anyhow::anyhow!("cannot determine date dir from: {}", audio_path.display())
         })?;
-    let jsonl_path = transcript_dir.join(format!("{}.jsonl", date_dir));
-    let mut file = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&jsonl_path)?;
-    use std::io::Write;
-    writeln!(file, "{}", serde_json::to_string(transcript)?)?;
-
-    // Mark as transcribed (normalize to forward slashes for cross-platform consistency)
-    let relative = audio_path
-        .strip_prefix(recordings_dir)?
-        .to_string_lossy()
-        .replace('\\', "/");
+    store.append(&date_dir, transcript)?;
+
+    if emit_subtitles {
+        let dir = recordings_dir.join("transcripts").join(&date_dir);
+        match subtitles::write_subtitles(transcript, &dir) {
+            Ok(files) if files.is_empty() => {
+                tracing::debug!("No speech in {}, skipping subtitles", transcript.file)
+            }
+            Ok(_) => tracing::debug!("Wrote subtitles for {}", transcript.file),
+            Err(e) => tracing::warn!("Failed to write subtitles for {}: {:?}", transcript.file, e),
+        }
+    }
+
+    let words = transcript.text.split_whitespace().count() as u64;
+    state.record_transcription(transcript.duration_secs, words);
     state.mark_transcribed(relative);
-    state.save(recordings_dir)?;
+    state.save(recordings_dir, machine_id)?;
 
-    Ok(())
+    Ok(true)
 }
 
 /// Run one-shot transcription of all pending files.
 pub fn run_transcribe_oneshot(config: &Config, backend_override: Option<&str>) -> Result<()> {
+    let machine_id = config.output.machine_id();
+    let Some(_lock) = TranscribeLock::try_acquire(&config.output.directory, machine_id.as_deref())?
+    else {
+        println!(
+            "Another transcriber (usually the background `transcribe --watch`) is already \
+             running; it will pick up the pending files."
+        );
+        return Ok(());
+    };
     let mut status = TranscriptionStatus::new();
     run_transcribe_oneshot_with_status(config, backend_override, &mut status)?;
     Ok(())
@@ -143,8 +280,12 @@ fn run_transcribe_oneshot_with_status(
     status: &mut TranscriptionStatus,
 ) -> Result<usize> {
     let recordings_dir = &config.output.directory;
-    let mut state = TranscriptionState::load(recordings_dir)?;
-    let pending = find_pending_files(recordings_dir, &state)?;
+    let machine_id = config.output.machine_id();
+    let machine_id = machine_id.as_deref();
+    let mut state = TranscriptionState::load(recordings_dir, machine_id)?;
+    status.lifetime = state.lifetime.clone();
+    let template = &config.output.filename_template;
+    let pending = find_pending_files(recordings_dir, &state, machine_id, template)?;
 
     if pending.is_empty() {
         tracing::info!("No pending files to transcribe");
@@ -152,56 +293,106 @@ fn run_transcribe_oneshot_with_status(
         status.queue_length = 0;
         status.current_file = None;
         status.touch();
-        let _ = status.write(recordings_dir);
+        let _ = status.write(recordings_dir, machine_id);
         return Ok(0);
     }
 
     tracing::info!("Found {} pending files", pending.len());
     let backend = build_backend(config, backend_override)?;
+    let store = open_store(config)?;
 
     status.queue_length = pending.len();
     status.state = TranscriberState::Transcribing;
     status.touch();
-    let _ = status.write(recordings_dir);
-
-    for (i, path) in pending.iter().enumerate() {
-        let relative = path
-            .strip_prefix(recordings_dir)
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|_| path.display().to_string());
-
-        status.current_file = Some(relative);
-        status.queue_length = pending.len() - i;
-        status.touch();
-        let _ = status.write(recordings_dir);
-
-        tracing::info!("Transcribing: {}", path.display());
-        match backend.transcribe(path) {
-            Ok(transcript) => {
-                tracing::info!(
-                    "Transcribed: {} ({:.1}s)",
-                    transcript.file,
-                    transcript.duration_secs
-                );
-                // Update session stats
-                status.session.files_done += 1;
-                status.session.audio_secs += transcript.duration_secs;
-                status.session.words += transcript.text.split_whitespace().count() as u64;
+    let _ = status.write(recordings_dir, machine_id);
+
+    // Transcription runs on worker threads; results are saved here, one at a
+    // time, so the state and store need no locking.
+    let mut finished = 0;
+    transcribe_files(
+        backend.as_ref(),
+        &pending,
+        config.transcription.concurrency as usize,
+        |event| {
+            let (i, result) = match event {
+                WorkerEvent::Started(i) => {
+                    let path = &pending[i];
+                    let relative = path
+                        .strip_prefix(recordings_dir)
+                        .map(|p| p.to_string_lossy().to_string())
+                        .unwrap_or_else(|_| path.display().to_string());
+
+                    status.current_file = Some(relative);
+                    status.queue_length = pending.len() - finished;
+                    status.touch();
+                    let _ = status.write(recordings_dir, machine_id);
 
-                save_transcript(&transcript, path, recordings_dir, &mut state)?;
-            }
-            Err(e) => {
-                tracing::error!("Failed to transcribe {}: {:?}", path.display(), e);
-                // Continue with next file
+                    tracing::info!("Transcribing: {}", path.display());
+                    return Ok(());
+                }
+                WorkerEvent::Finished(i, result) => (i, result),
+            };
+            finished += 1;
+            let path = &pending[i];
+            match result {
+                Ok(transcript) => {
+                    tracing::info!(
+                        "Transcribed: {} ({:.1}s)",
+                        transcript.file,
+                        transcript.duration_secs
+                    );
+                    let saved = save_transcript(
+                        &transcript,
+                        path,
+                        recordings_dir,
+                        store.as_ref(),
+                        &mut state,
+                        machine_id,
+                        config.transcription.emit_subtitles,
+                    )?;
+                    if saved {
+                        let words = transcript.text.split_whitespace().count() as u64;
+                        status.session.record(transcript.duration_secs, words);
+                        status.lifetime = state.lifetime.clone();
+                    }
+                }
+                Err(e) if is_transient(&e) => {
+                    // Left pending for the next pass.
+                    tracing::error!(
+                        "Failed to transcribe {} after {} attempts: {:#}",
+                        path.display(),
+                        RETRY_DELAYS.len() + 1,
+                        e
+                    );
+                }
+                Err(e) => {
+                    let key = path
+                        .strip_prefix(recordings_dir)?
+                        .to_string_lossy()
+                        .replace('\\', "/");
+                    let failures = state.record_failure(&key, &format!("{:#}", e));
+                    if failures >= MAX_FAILURES {
+                        tracing::warn!(
+                            "Giving up on {} after {} failures, last error: {:#}",
+                            path.display(),
+                            failures,
+                            e
+                        );
+                    } else {
+                        tracing::error!("Failed to transcribe {}: {:?}", path.display(), e);
+                    }
+                    state.save(recordings_dir, machine_id)?;
+                }
             }
-        }
-    }
+            Ok(())
+        },
+    )?;
 
     status.state = TranscriberState::UpToDate;
     status.queue_length = 0;
     status.current_file = None;
     status.touch();
-    let _ = status.write(recordings_dir);
+    let _ = status.write(recordings_dir, machine_id);
 
     Ok(status.session.files_done as usize)
 }
@@ -210,10 +401,12 @@ fn run_transcribe_oneshot_with_status(
 pub fn run_transcribe_watch(config: &Config, backend_override: Option<&str>) -> Result<()> {
     let idle_config = &config.transcription.idle_watch;
     let recordings_dir = &config.output.directory;
+    let machine_id = config.output.machine_id();
+    let machine_id = machine_id.as_deref();
     let mut status = TranscriptionStatus::new();
 
     // Write initial status so the tray can see us immediately.
-    let _ = status.write(recordings_dir);
+    let _ = status.write(recordings_dir, machine_id);
 
     loop {
         // Check CPU usage
@@ -228,8 +421,21 @@ pub fn run_transcribe_watch(config: &Config, backend_override: Option<&str>) ->
 
         if cpu_usage < idle_config.cpu_threshold_percent {
             tracing::info!("System idle (CPU: {:.1}%), processing...", cpu_usage);
-            match run_transcribe_oneshot_with_status(config, backend_override, &mut status) {
-                Ok(files_done) => {
+            // The lock is held for the whole pass.
+            let pass = match TranscribeLock::try_acquire(recordings_dir, machine_id) {
+                Ok(Some(_lock)) => {
+                    run_transcribe_oneshot_with_status(config, backend_override, &mut status)
+                        .map(Some)
+                }
+                Ok(None) => Ok(None),
+                Err(e) => Err(e),
+            };
+            match pass {
+                Ok(None) => {
+                    // A manual run is working through the queue and writing status.
+                    tracing::info!("Another transcriber is running, skipping this pass");
+                }
+                Ok(Some(files_done)) => {
                     status.error_message = None;
                     if files_done > 0 {
                         tracing::info!("Transcribed {} files, running index...", files_done);
@@ -243,7 +449,7 @@ pub fn run_transcribe_watch(config: &Config, backend_override: Option<&str>) ->
                     status.state = TranscriberState::Error;
                     status.error_message = Some(format!("{:#}", e));
                     status.touch();
-                    let _ = status.write(recordings_dir);
+                    let _ = status.write(recordings_dir, machine_id);
                 }
             }
         } else {
@@ -251,7 +457,7 @@ pub fn run_transcribe_watch(config: &Config, backend_override: Option<&str>) ->
             status.state = TranscriberState::Idle;
             status.current_file = None;
             status.touch();
-            let _ = status.write(recordings_dir);
+            let _ = status.write(recordings_dir, machine_id);
         }
 
         std::thread::sleep(std::time::Duration::from_secs(
@@ -263,6 +469,7 @@ pub fn run_transcribe_watch(config: &Config, backend_override: Option<&str>) ->
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::DEFAULT_FILENAME_TEMPLATE as TEMPLATE;
     use tempfile::TempDir;
 
     /// Helper to create a minimal valid WAV file.
@@ -285,14 +492,15 @@ mod tests {
     fn test_find_pending_files_empty_dir() {
         let tmp = TempDir::new().unwrap();
         let state = TranscriptionState::default();
-        let pending = find_pending_files(tmp.path(), &state).unwrap();
+        let pending = find_pending_files(tmp.path(), &state, None, TEMPLATE).unwrap();
         assert!(pending.is_empty());
     }
 
     #[test]
     fn test_find_pending_files_nonexistent_dir() {
         let state = TranscriptionState::default();
-        let pending = find_pending_files(Path::new("/nonexistent/path"), &state).unwrap();
+        let pending =
+            find_pending_files(Path::new("/nonexistent/path"), &state, None, TEMPLATE).unwrap();
         assert!(pending.is_empty());
     }
 
@@ -305,7 +513,7 @@ mod tests {
         create_wav_file(&date_dir.join("teams_14-30-00.wav"));
 
         let state = TranscriptionState::default();
-        let pending = find_pending_files(tmp.path(), &state).unwrap();
+        let pending = find_pending_files(tmp.path(), &state, None, TEMPLATE).unwrap();
         assert_eq!(pending.len(), 2);
     }
 
@@ -320,11 +528,174 @@ mod tests {
         let mut state = TranscriptionState::default();
         state.mark_transcribed("2026-02-16/mic_14-30-00.wav".to_string());
 
-        let pending = find_pending_files(tmp.path(), &state).unwrap();
+        let pending = find_pending_files(tmp.path(), &state, None, TEMPLATE).unwrap();
         assert_eq!(pending.len(), 1);
         assert!(pending[0].to_string_lossy().contains("teams_14-30-00.wav"));
     }
 
+    #[test]
+    fn test_find_pending_files_skips_poisoned() {
+        let tmp = TempDir::new().unwrap();
+        let date_dir = tmp.path().join("2026-02-16");
+        std::fs::create_dir_all(&date_dir).unwrap();
+        create_wav_file(&date_dir.join("mic_14-30-00.wav"));
+
+        let mut state = TranscriptionState::default();
+        for _ in 0..MAX_FAILURES {
+            assert_eq!(
+                find_pending_files(tmp.path(), &state, None, TEMPLATE)
+                    .unwrap()
+                    .len(),
+                1
+            );
+            state.record_failure("2026-02-16/mic_14-30-00.wav", "corrupt WAV");
+        }
+        assert!(find_pending_files(tmp.path(), &state, None, TEMPLATE)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_save_transcript_is_idempotent() {
+        let tmp = TempDir::new().unwrap();
+        let audio = tmp.path().join("2026-02-16").join("mic_14-30-00.wav");
+        let store = crate::transcribe::store::JsonlStore::new(tmp.path());
+        let mut state = TranscriptionState::default();
+        let transcript = Transcript {
+            timestamp: "2026-02-16T14:30:00".to_string(),
+            source: "mic".to_string(),
+            duration_secs: 5.0,
+            file: "mic_14-30-00.wav".to_string(),
+            text: "hello there".to_string(),
+            engine: None,
+            segments: Vec::new(),
+        };
+
+        for expected in [true, false] {
+            let saved = save_transcript(
+                &transcript,
+                &audio,
+                tmp.path(),
+                &store,
+                &mut state,
+                None,
+                false,
+            )
+            .unwrap();
+            assert_eq!(saved, expected);
+        }
+        assert_eq!(store.load_date("2026-02-16").unwrap().len(), 1);
+        assert_eq!(state.lifetime.files_done, 1);
+        assert!(TranscriptionState::load(tmp.path(), None)
+            .unwrap()
+            .is_transcribed("2026-02-16/mic_14-30-00.wav"));
+    }
+
+    /// Fails transiently `transient_failures` times, then with `then`.
+    struct FlakyBackend {
+        transient_failures: std::sync::atomic::AtomicU32,
+        then: Option<&'static str>,
+    }
+
+    impl TranscriptionBackend for FlakyBackend {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        fn transcribe(&self, audio_path: &Path) -> Result<Transcript> {
+            use crate::transcribe::backend::TranscribeError;
+            let left = self.transient_failures.load(Ordering::Relaxed);
+            if left > 0 {
+                self.transient_failures.store(left - 1, Ordering::Relaxed);
+                return Err(TranscribeError::Transient("HTTP 429".to_string()).into());
+            }
+            if let Some(error) = self.then {
+                anyhow::bail!(error);
+            }
+            Ok(Transcript {
+                timestamp: "2026-02-16".to_string(),
+                source: "mic".to_string(),
+                duration_secs: 1.0,
+                file: audio_path.display().to_string(),
+                text: "Hello".to_string(),
+                engine: None,
+                segments: Vec::new(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_transcribe_with_retry() {
+        let delays = [Duration::ZERO; 2];
+        let path = Path::new("mic_14-30-00.wav");
+        let flaky = |transient_failures, then| FlakyBackend {
+            transient_failures: std::sync::atomic::AtomicU32::new(transient_failures),
+            then,
+        };
+
+        // Recovers within the retry budget.
+        assert!(transcribe_with_retry(&flaky(2, None), path, &delays).is_ok());
+        // Still rate limited after every retry.
+        let err = transcribe_with_retry(&flaky(3, None), path, &delays).unwrap_err();
+        assert!(is_transient(&err));
+        // Permanent errors aren't retried.
+        let backend = flaky(0, Some("corrupt WAV"));
+        let err = transcribe_with_retry(&backend, path, &delays).unwrap_err();
+        assert!(!is_transient(&err));
+    }
+
+    #[test]
+    fn test_transcribe_files_concurrently() {
+        let backend = FlakyBackend {
+            transient_failures: 0.into(),
+            then: None,
+        };
+        let files: Vec<PathBuf> = (0..7)
+            .map(|i| PathBuf::from(format!("mic_14-30-0{}.wav", i)))
+            .collect();
+
+        let mut started = Vec::new();
+        let mut finished = Vec::new();
+        transcribe_files(&backend, &files, 3, |event| {
+            match event {
+                WorkerEvent::Started(i) => started.push(i),
+                WorkerEvent::Finished(i, result) => {
+                    assert!(started.contains(&i));
+                    assert_eq!(result.unwrap().file, files[i].display().to_string());
+                    finished.push(i);
+                }
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        finished.sort();
+        assert_eq!(finished, (0..7).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_transcribe_files_stops_on_error() {
+        let backend = FlakyBackend {
+            transient_failures: 0.into(),
+            then: None,
+        };
+        let files: Vec<PathBuf> = (0..50)
+            .map(|i| PathBuf::from(format!("mic_{}.wav", i)))
+            .collect();
+
+        let mut finished = 0;
+        let err = transcribe_files(&backend, &files, 2, |event| {
+            if let WorkerEvent::Finished(..) = event {
+                finished += 1;
+                anyhow::bail!("disk full");
+            }
+            Ok(())
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "disk full");
+        assert_eq!(finished, 1);
+    }
+
     #[test]
     fn test_find_pending_files_ignores_non_wav() {
         let tmp = TempDir::new().unwrap();
@@ -334,7 +705,7 @@ mod tests {
         create_wav_file(&date_dir.join("mic_14-30-00.wav"));
 
         let state = TranscriptionState::default();
-        let pending = find_pending_files(tmp.path(), &state).unwrap();
+        let pending = find_pending_files(tmp.path(), &state, None, TEMPLATE).unwrap();
         assert_eq!(pending.len(), 1);
     }
 
@@ -345,28 +716,39 @@ mod tests {
         create_wav_file(&tmp.path().join("stray.wav"));
 
         let state = TranscriptionState::default();
-        let pending = find_pending_files(tmp.path(), &state).unwrap();
+        let pending = find_pending_files(tmp.path(), &state, None, TEMPLATE).unwrap();
         assert!(pending.is_empty());
     }
 
     #[test]
-    fn test_resolve_model_path_short_name() {
-        let result = resolve_model_path("base.en");
-        assert!(result.contains("ggml-base.en.bin"));
-    }
+    fn test_find_pending_files_skips_other_machines() {
+        let tmp = TempDir::new().unwrap();
+        let date_dir = tmp.path().join("2026-02-16");
+        std::fs::create_dir_all(&date_dir).unwrap();
+        create_wav_file(&date_dir.join("mic_14-30-00.wav"));
+        create_wav_file(&date_dir.join("mic_14-31-00_desk.wav"));
+        create_wav_file(&date_dir.join("mic_14-32-00_laptop.wav"));
 
-    #[test]
-    fn test_resolve_model_path_absolute_path() {
-        let abs = if cfg!(windows) {
-            "C:\\models\\ggml-base.en.bin"
-        } else {
-            "/tmp/models/ggml-base.en.bin"
-        };
-        assert_eq!(resolve_model_path(abs), abs);
+        let state = TranscriptionState::default();
+        let pending = find_pending_files(tmp.path(), &state, Some("desk"), TEMPLATE).unwrap();
+        let names: Vec<String> = pending
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["mic_14-30-00.wav", "mic_14-31-00_desk.wav"]);
+
+        let pending = find_pending_files(tmp.path(), &state, None, TEMPLATE).unwrap();
+        assert_eq!(pending.len(), 3);
     }
 
     #[test]
-    fn test_resolve_model_path_bin_extension() {
-        assert_eq!(resolve_model_path("my-model.bin"), "my-model.bin");
+    fn test_belongs_to_other_machine_with_custom_template() {
+        let template = "{date}-{time}-{source}.{ext}";
+        let other = Path::new("2026-02-16-14-30-00-mic_laptop.wav");
+        assert!(belongs_to_other_machine(other, Some("desk"), template));
+        let ours = Path::new("2026-02-16-14-30-00-mic_desk.wav");
+        assert!(!belongs_to_other_machine(ours, Some("desk"), template));
+        let untagged = Path::new("2026-02-16-14-30-00-mic-headset.wav");
+        assert!(!belongs_to_other_machine(untagged, Some("desk"), template));
     }
 }
@@ -1,39 +1,189 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::path::Path;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
+use crate::config::machine_scoped;
+use crate::transcribe::status::TranscriptionStats;
+
+const STATE_FILE_NAME: &str = ".deskmic-state.json";
+const LOCK_FILE_NAME: &str = ".deskmic-transcribe.lock";
+
+/// Permanent failures after which a file is skipped for good.
+pub const MAX_FAILURES: u32 = 3;
+
+/// A file that failed to transcribe, and why.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FailedFile {
+    pub failures: u32,
+    pub last_error: String,
+}
+
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct TranscriptionState {
     pub transcribed_files: HashSet<String>,
+    /// Files that failed permanently (e.g. a corrupt recording). Once a file
+    /// reaches `MAX_FAILURES` it is poisoned and no longer retried.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub failed_files: BTreeMap<String, FailedFile>,
+    /// Everything this machine has transcribed, across transcriber restarts.
+    #[serde(default)]
+    pub lifetime: TranscriptionStats,
+    /// Files transcribed by other machines sharing the recordings directory.
+    /// Read from their state files and never saved into this one.
+    #[serde(skip)]
+    pub other_machines: HashSet<String>,
+    /// Files other machines have given up on (`MAX_FAILURES` failures). Not
+    /// saved either.
+    #[serde(skip)]
+    pub other_machines_poisoned: HashSet<String>,
 }
 
 impl TranscriptionState {
-    pub fn load(recordings_dir: &Path) -> Result<Self> {
-        let path = recordings_dir.join(".deskmic-state.json");
-        if path.exists() {
-            let content = std::fs::read_to_string(&path)?;
-            Ok(serde_json::from_str(&content)?)
+    /// Load this machine's state, plus (read-only) the state files of any
+    /// other machines sharing the directory, so their work isn't repeated.
+    ///
+    /// A corrupt state file is moved aside to `<name>.corrupt` and loading
+    /// starts fresh, rather than failing every transcription run.
+    pub fn load(recordings_dir: &Path, machine_id: Option<&str>) -> Result<Self> {
+        let own_name = machine_scoped(STATE_FILE_NAME, machine_id);
+        let path = recordings_dir.join(&own_name);
+        let mut state: Self = if path.exists() {
+            // Read bytes: a file cut off mid-character isn't valid UTF-8.
+            let content = std::fs::read(&path)?;
+            match serde_json::from_slice(&content) {
+                Ok(state) => state,
+                Err(e) => {
+                    let mut backup = path.clone().into_os_string();
+                    backup.push(".corrupt");
+                    tracing::warn!(
+                        "State file {} is corrupt ({}); starting fresh, old file kept as {}",
+                        path.display(),
+                        e,
+                        Path::new(&backup).display()
+                    );
+                    if let Err(e) = std::fs::rename(&path, &backup) {
+                        tracing::warn!("Failed to move corrupt state file aside: {}", e);
+                    }
+                    Self::default()
+                }
+            }
         } else {
-            Ok(Self::default())
+            Self::default()
+        };
+
+        if recordings_dir.exists() {
+            for entry in std::fs::read_dir(recordings_dir)? {
+                let name = entry?.file_name().to_string_lossy().to_string();
+                if name == own_name || !is_state_file_name(&name) {
+                    continue;
+                }
+                let other = std::fs::read_to_string(recordings_dir.join(&name))
+                    .ok()
+                    .and_then(|c| serde_json::from_str::<Self>(&c).ok());
+                match other {
+                    Some(other) => {
+                        state.other_machines.extend(other.transcribed_files);
+                        let given_up = other.failed_files.into_iter();
+                        state.other_machines_poisoned.extend(
+                            given_up
+                                .filter(|(_, failed)| failed.failures >= MAX_FAILURES)
+                                .map(|(file, _)| file),
+                        );
+                    }
+                    None => tracing::warn!("Ignoring unreadable state file {}", name),
+                }
+            }
         }
+
+        Ok(state)
     }
 
-    pub fn save(&self, recordings_dir: &Path) -> Result<()> {
-        let path = recordings_dir.join(".deskmic-state.json");
+    pub fn save(&self, recordings_dir: &Path, machine_id: Option<&str>) -> Result<()> {
+        let path = recordings_dir.join(machine_scoped(STATE_FILE_NAME, machine_id));
         let content = serde_json::to_string_pretty(self)?;
-        std::fs::write(path, content)?;
+        // Write atomically, like `TranscriptionStatus::write`: the transcriber
+        // is often killed by the watchdog, and a truncated state file would
+        // lose track of everything already transcribed.
+        let mut tmp_path = path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        std::fs::write(&tmp_path, &content)?;
+        std::fs::rename(&tmp_path, &path)?;
         Ok(())
     }
 
     pub fn is_transcribed(&self, file_path: &str) -> bool {
-        self.transcribed_files.contains(file_path)
+        self.transcribed_files.contains(file_path) || self.other_machines.contains(file_path)
     }
 
     pub fn mark_transcribed(&mut self, file_path: String) {
+        self.failed_files.remove(&file_path);
         self.transcribed_files.insert(file_path);
     }
+
+    /// Add a transcribed file to the lifetime stats. Saved with the state.
+    pub fn record_transcription(&mut self, duration_secs: f64, word_count: u64) {
+        self.lifetime.record(duration_secs, word_count);
+    }
+
+    /// Record a permanent failure for `file_path` and return how many times
+    /// it has failed.
+    pub fn record_failure(&mut self, file_path: &str, error: &str) -> u32 {
+        let entry = self.failed_files.entry(file_path.to_string()).or_default();
+        entry.failures += 1;
+        entry.last_error = error.to_string();
+        entry.failures
+    }
+
+    /// The failure record of a file that has failed `MAX_FAILURES` times.
+    pub fn poisoned(&self, file_path: &str) -> Option<&FailedFile> {
+        self.failed_files
+            .get(file_path)
+            .filter(|f| f.failures >= MAX_FAILURES)
+    }
+
+    /// Whether any machine has transcribed `file_path` or given up on it, so
+    /// keeping the recording around won't lead to a transcript.
+    pub fn is_settled(&self, file_path: &str) -> bool {
+        self.is_transcribed(file_path)
+            || self.poisoned(file_path).is_some()
+            || self.other_machines_poisoned.contains(file_path)
+    }
+}
+
+/// Exclusive lock held for a transcription pass, so the `transcribe --watch`
+/// daemon and a manual `deskmic transcribe` never work on the same files at
+/// once. The OS releases it when the holder exits, even if it is killed.
+pub struct TranscribeLock {
+    _file: std::fs::File,
+}
+
+impl TranscribeLock {
+    /// Take this machine's lock, or `None` if another process holds it.
+    pub fn try_acquire(recordings_dir: &Path, machine_id: Option<&str>) -> Result<Option<Self>> {
+        std::fs::create_dir_all(recordings_dir)?;
+        let path = recordings_dir.join(machine_scoped(LOCK_FILE_NAME, machine_id));
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Self { _file: file })),
+            Err(std::fs::TryLockError::WouldBlock) => Ok(None),
+            Err(std::fs::TryLockError::Error(e)) => Err(e.into()),
+        }
+    }
+}
+
+/// `.deskmic-state.json` or a machine-scoped `.deskmic-state.<id>.json`.
+fn is_state_file_name(name: &str) -> bool {
+    name == STATE_FILE_NAME
+        || name
+            .strip_prefix(".deskmic-state.")
+            .and_then(|rest| rest.strip_suffix(".json"))
+            .is_some_and(|id| !id.is_empty() && !id.contains('.'))
 }
 
 #[cfg(test)]
@@ -46,9 +196,9 @@ mod tests {
         let tmp = TempDir::new().unwrap();
         let mut state = TranscriptionState::default();
         state.mark_transcribed("2026-02-16/mic_14-30-00.wav".to_string());
-        state.save(tmp.path()).unwrap();
+        state.save(tmp.path(), None).unwrap();
 
-        let loaded = TranscriptionState::load(tmp.path()).unwrap();
+        let loaded = TranscriptionState::load(tmp.path(), None).unwrap();
         assert!(loaded.is_transcribed("2026-02-16/mic_14-30-00.wav"));
         assert!(!loaded.is_transcribed("2026-02-16/teams_14-30-00.wav"));
     }
@@ -56,7 +206,145 @@ mod tests {
     #[test]
     fn test_empty_state_from_nonexistent() {
         let tmp = TempDir::new().unwrap();
-        let state = TranscriptionState::load(tmp.path()).unwrap();
+        let state = TranscriptionState::load(tmp.path(), None).unwrap();
+        assert!(state.transcribed_files.is_empty());
+    }
+
+    #[test]
+    fn test_save_leaves_no_temp_file() {
+        let tmp = TempDir::new().unwrap();
+        let mut state = TranscriptionState::default();
+        state.mark_transcribed("2026-02-16/mic_14-30-00.wav".to_string());
+        state.save(tmp.path(), None).unwrap();
+        state.save(tmp.path(), None).unwrap();
+
+        assert!(tmp.path().join(".deskmic-state.json").exists());
+        assert!(!tmp.path().join(".deskmic-state.json.tmp").exists());
+    }
+
+    #[test]
+    fn test_corrupt_state_starts_fresh() {
+        let tmp = TempDir::new().unwrap();
+        // Truncated mid-write.
+        std::fs::write(
+            tmp.path().join(".deskmic-state.json"),
+            r#"{"transcribed_files":["2026-02-16/mic_14-3"#,
+        )
+        .unwrap();
+
+        let state = TranscriptionState::load(tmp.path(), None).unwrap();
         assert!(state.transcribed_files.is_empty());
+        assert!(!tmp.path().join(".deskmic-state.json").exists());
+        assert!(tmp.path().join(".deskmic-state.json.corrupt").exists());
+    }
+
+    #[test]
+    fn test_state_unions_other_machines() {
+        let tmp = TempDir::new().unwrap();
+        let mut laptop = TranscriptionState::default();
+        laptop.mark_transcribed("2026-02-16/mic_09-00-00_laptop.wav".to_string());
+        laptop.save(tmp.path(), Some("laptop")).unwrap();
+
+        let mut desk = TranscriptionState::load(tmp.path(), Some("desk")).unwrap();
+        assert!(desk.is_transcribed("2026-02-16/mic_09-00-00_laptop.wav"));
+        desk.mark_transcribed("2026-02-16/mic_10-00-00_desk.wav".to_string());
+        desk.save(tmp.path(), Some("desk")).unwrap();
+
+        // Each machine only writes its own file.
+        let saved: TranscriptionState = serde_json::from_str(
+            &std::fs::read_to_string(tmp.path().join(".deskmic-state.desk.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(saved.transcribed_files.len(), 1);
+
+        let laptop = TranscriptionState::load(tmp.path(), Some("laptop")).unwrap();
+        assert!(laptop.is_transcribed("2026-02-16/mic_10-00-00_desk.wav"));
+    }
+
+    #[test]
+    fn test_lifetime_stats_survive_reload() {
+        let tmp = TempDir::new().unwrap();
+        let mut state = TranscriptionState::default();
+        state.record_transcription(30.0, 50);
+        state.save(tmp.path(), None).unwrap();
+
+        let mut loaded = TranscriptionState::load(tmp.path(), None).unwrap();
+        loaded.record_transcription(15.0, 20);
+        assert_eq!(loaded.lifetime.files_done, 2);
+        assert_eq!(loaded.lifetime.audio_secs, 45.0);
+        assert_eq!(loaded.lifetime.words, 70);
+
+        // State files written before lifetime stats existed still load.
+        std::fs::write(
+            tmp.path().join(".deskmic-state.json"),
+            r#"{"transcribed_files":["2026-02-16/mic_14-30-00.wav"]}"#,
+        )
+        .unwrap();
+        let old = TranscriptionState::load(tmp.path(), None).unwrap();
+        assert_eq!(old.lifetime, TranscriptionStats::default());
+    }
+
+    #[test]
+    fn test_failures_poison_file() {
+        let tmp = TempDir::new().unwrap();
+        let file = "2026-02-16/mic_14-30-00.wav";
+        let mut state = TranscriptionState::default();
+        for attempt in 1..MAX_FAILURES {
+            assert_eq!(state.record_failure(file, "corrupt WAV"), attempt);
+            assert!(state.poisoned(file).is_none());
+        }
+        state.record_failure(file, "corrupt WAV");
+        state.save(tmp.path(), None).unwrap();
+
+        let mut loaded = TranscriptionState::load(tmp.path(), None).unwrap();
+        assert_eq!(loaded.poisoned(file).unwrap().last_error, "corrupt WAV");
+
+        loaded.mark_transcribed(file.to_string());
+        assert!(loaded.poisoned(file).is_none());
+    }
+
+    #[test]
+    fn test_other_machines_failures_are_settled() {
+        let tmp = TempDir::new().unwrap();
+        let (given_up, retrying) = ("2026-02-16/mic_09-00-00.wav", "2026-02-16/mic_10-00-00.wav");
+        let mut laptop = TranscriptionState::default();
+        for _ in 0..MAX_FAILURES {
+            laptop.record_failure(given_up, "corrupt WAV");
+        }
+        laptop.record_failure(retrying, "timeout");
+        laptop.save(tmp.path(), Some("laptop")).unwrap();
+
+        let all = TranscriptionState::load(tmp.path(), None).unwrap();
+        assert!(all.is_settled(given_up));
+        assert!(!all.is_settled(retrying));
+        // Another machine may still manage it, so it isn't skipped here.
+        assert!(all.poisoned(given_up).is_none());
+    }
+
+    #[test]
+    fn test_transcribe_lock_is_exclusive() {
+        let tmp = TempDir::new().unwrap();
+        let lock = TranscribeLock::try_acquire(tmp.path(), None).unwrap();
+        assert!(lock.is_some());
+        assert!(TranscribeLock::try_acquire(tmp.path(), None)
+            .unwrap()
+            .is_none());
+        // Other machines sharing the directory have their own lock.
+        assert!(TranscribeLock::try_acquire(tmp.path(), Some("desk"))
+            .unwrap()
+            .is_some());
+
+        drop(lock);
+        assert!(TranscribeLock::try_acquire(tmp.path(), None)
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_is_state_file_name() {
+        assert!(is_state_file_name(".deskmic-state.json"));
+        assert!(is_state_file_name(".deskmic-state.desk.json"));
+        assert!(!is_state_file_name(".deskmic-state.desk.json.tmp"));
+        assert!(!is_state_file_name(".transcription-status.json"));
     }
 }
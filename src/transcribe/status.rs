@@ -3,6 +3,8 @@ use std::path::Path;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
+use crate::config::machine_scoped;
+
 /// Current state of the transcription process.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -29,13 +31,22 @@ impl std::fmt::Display for TranscriberState {
 }
 
 /// Cumulative statistics for transcription.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct TranscriptionStats {
     pub files_done: u64,
     pub audio_secs: f64,
     pub words: u64,
 }
 
+impl TranscriptionStats {
+    /// Count one transcribed file.
+    pub fn record(&mut self, duration_secs: f64, word_count: u64) {
+        self.files_done += 1;
+        self.audio_secs += duration_secs;
+        self.words += word_count;
+    }
+}
+
 /// Status snapshot written to disk by the transcription process and read by
 /// the tray UI.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +59,9 @@ pub struct TranscriptionStatus {
     pub queue_length: usize,
     /// Stats accumulated since the transcriber process started.
     pub session: TranscriptionStats,
+    /// All-time stats for this machine, persisted in `TranscriptionState`.
+    #[serde(default)]
+    pub lifetime: TranscriptionStats,
     /// Last observed CPU usage percentage.
     pub last_cpu_percent: f32,
     /// ISO-8601 timestamp of the last status update.
@@ -67,6 +81,7 @@ impl TranscriptionStatus {
             current_file: None,
             queue_length: 0,
             session: TranscriptionStats::default(),
+            lifetime: TranscriptionStats::default(),
             last_cpu_percent: 0.0,
             updated_at: chrono::Local::now().to_rfc3339(),
             error_message: None,
@@ -74,12 +89,14 @@ impl TranscriptionStatus {
     }
 
     /// Write the status to the status file in the recordings directory.
-    pub fn write(&self, recordings_dir: &Path) -> Result<()> {
-        let path = recordings_dir.join(STATUS_FILE_NAME);
+    /// With a `machine_id`, each machine has its own status file.
+    pub fn write(&self, recordings_dir: &Path, machine_id: Option<&str>) -> Result<()> {
+        let path = recordings_dir.join(machine_scoped(STATUS_FILE_NAME, machine_id));
         let content = serde_json::to_string_pretty(self)?;
         // Write atomically: write to temp then rename, to avoid the reader
         // seeing a half-written file.
-        let tmp_path = recordings_dir.join(".transcription-status.json.tmp");
+        let mut tmp_path = path.clone().into_os_string();
+        tmp_path.push(".tmp");
         std::fs::write(&tmp_path, &content)?;
         std::fs::rename(&tmp_path, &path)?;
         Ok(())
@@ -87,8 +104,8 @@ impl TranscriptionStatus {
 
     /// Read the status file from the recordings directory. Returns `None` if
     /// the file doesn't exist or can't be parsed (e.g. partially written).
-    pub fn read(recordings_dir: &Path) -> Option<Self> {
-        let path = recordings_dir.join(STATUS_FILE_NAME);
+    pub fn read(recordings_dir: &Path, machine_id: Option<&str>) -> Option<Self> {
+        let path = recordings_dir.join(machine_scoped(STATUS_FILE_NAME, machine_id));
         let content = std::fs::read_to_string(&path).ok()?;
         serde_json::from_str(&content).ok()
     }
@@ -149,8 +166,8 @@ mod tests {
         status.session.words = 250;
         status.last_cpu_percent = 15.2;
 
-        status.write(tmp.path()).unwrap();
-        let loaded = TranscriptionStatus::read(tmp.path()).unwrap();
+        status.write(tmp.path(), None).unwrap();
+        let loaded = TranscriptionStatus::read(tmp.path(), None).unwrap();
 
         assert_eq!(loaded.state, TranscriberState::Transcribing);
         assert_eq!(loaded.current_file.as_deref(), Some("2026-02-16/mic_14-30-00.wav"));
@@ -163,7 +180,20 @@ mod tests {
     #[test]
     fn test_status_read_nonexistent() {
         let tmp = TempDir::new().unwrap();
-        assert!(TranscriptionStatus::read(tmp.path()).is_none());
+        assert!(TranscriptionStatus::read(tmp.path(), None).is_none());
+    }
+
+    #[test]
+    fn test_status_per_machine() {
+        let tmp = TempDir::new().unwrap();
+        let mut status = TranscriptionStatus::new();
+        status.queue_length = 7;
+        status.write(tmp.path(), Some("desk")).unwrap();
+
+        assert!(tmp.path().join(".transcription-status.desk.json").exists());
+        assert!(TranscriptionStatus::read(tmp.path(), None).is_none());
+        let loaded = TranscriptionStatus::read(tmp.path(), Some("desk")).unwrap();
+        assert_eq!(loaded.queue_length, 7);
     }
 
     #[test]
@@ -0,0 +1,669 @@
+// Transcript storage: per-day JSONL files (default) or a single SQLite database.
+//
+// Everything that writes or reads transcripts (the transcriber, summarize,
+// the search indexer) goes through `TranscriptStore`, selected by
+// `transcription.store`. Dates are "YYYY-MM-DD" strings, matching the
+// recording date folders.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+
+use crate::config::{machine_scoped, Config};
+use crate::transcribe::backend::Transcript;
+
+/// Name of the SQLite database inside `recordings/transcripts/`.
+pub const SQLITE_FILE_NAME: &str = "transcripts.db";
+
+pub trait TranscriptStore {
+    /// Short store name ("jsonl" or "sqlite").
+    fn name(&self) -> &str;
+
+    /// Append a transcript for `date`. Does nothing if a transcript of the
+    /// same `file` is already stored for `date`, so a recording transcribed
+    /// again after a crash isn't saved twice.
+    fn append(&self, date: &str, transcript: &Transcript) -> Result<()>;
+
+    /// Load all transcripts for `date` in the order they were saved, one per
+    /// `file`. A JSONL store shared by several machines keeps each machine's
+    /// order but lists the machines one after another, so callers tracking
+    /// what they've seen should go by `file`, not position. Returns an empty
+    /// list if there are none.
+    fn load_date(&self, date: &str) -> Result<Vec<Transcript>>;
+
+    /// All dates that have transcripts, sorted ascending.
+    fn dates(&self) -> Result<Vec<String>>;
+
+    /// A value that changes whenever transcripts for `date` change. Used by
+    /// the search indexer to skip dates that haven't changed.
+    fn revision(&self, date: &str) -> Result<i64>;
+}
+
+/// Open the store configured in `transcription.store`.
+pub fn open_store(config: &Config) -> Result<Box<dyn TranscriptStore>> {
+    let recordings_dir = &config.output.directory;
+    match config.transcription.store.as_str() {
+        "jsonl" => Ok(Box::new(JsonlStore::for_machine(
+            recordings_dir,
+            config.output.machine_id(),
+        ))),
+        "sqlite" => Ok(Box::new(SqliteStore::open(recordings_dir)?)),
+        other => anyhow::bail!("Unknown transcript store: {} (expected jsonl or sqlite)", other),
+    }
+}
+
+// --- JSONL ---
+
+/// One `transcripts/YYYY-MM-DD.jsonl` file per day, one transcript per line.
+///
+/// With a machine id, this machine appends to `YYYY-MM-DD.<machine>.jsonl`
+/// instead, and reads union every machine's file for the day, so several
+/// machines can share a synced recordings directory.
+pub struct JsonlStore {
+    dir: PathBuf,
+    machine_id: Option<String>,
+}
+
+impl JsonlStore {
+    pub fn new(recordings_dir: &Path) -> Self {
+        Self::for_machine(recordings_dir, None)
+    }
+
+    pub fn for_machine(recordings_dir: &Path, machine_id: Option<String>) -> Self {
+        Self {
+            dir: recordings_dir.join("transcripts"),
+            machine_id,
+        }
+    }
+
+    fn path_for(&self, date: &str) -> PathBuf {
+        self.dir.join(machine_scoped(
+            &format!("{}.jsonl", date),
+            self.machine_id.as_deref(),
+        ))
+    }
+
+    /// All files holding transcripts for `date` (any machine), sorted by name.
+    fn day_files(&self, date: &str) -> Result<Vec<PathBuf>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut files: Vec<PathBuf> = std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let name = path.file_name()?.to_string_lossy().to_string();
+                (jsonl_date(&name)? == date).then_some(path)
+            })
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+}
+
+/// The date of a `YYYY-MM-DD.jsonl` or `YYYY-MM-DD.<machine>.jsonl` file name.
+fn jsonl_date(file_name: &str) -> Option<&str> {
+    let stem = file_name.strip_suffix(".jsonl")?;
+    Some(stem.split_once('.').map_or(stem, |(date, _)| date))
+}
+
+impl TranscriptStore for JsonlStore {
+    fn name(&self) -> &str {
+        "jsonl"
+    }
+
+    fn append(&self, date: &str, transcript: &Transcript) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.path_for(date);
+        if path.exists() && load_jsonl(&path)?.iter().any(|t| t.file == transcript.file) {
+            tracing::warn!(
+                "{} is already in {}, skipping",
+                transcript.file,
+                path.display()
+            );
+            return Ok(());
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        use std::io::Write;
+        writeln!(file, "{}", serde_json::to_string(transcript)?)?;
+        Ok(())
+    }
+
+    fn load_date(&self, date: &str) -> Result<Vec<Transcript>> {
+        let files = self.day_files(date)?;
+        if files.is_empty() {
+            tracing::debug!("No transcript file for {}", date);
+        }
+        let mut transcripts = Vec::new();
+        for path in files {
+            transcripts.extend(load_jsonl(&path)?);
+        }
+        Ok(dedup_by_file(transcripts))
+    }
+
+    fn dates(&self) -> Result<Vec<String>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut dates: Vec<String> = std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name().to_string_lossy().to_string();
+                jsonl_date(&name).map(|d| d.to_string())
+            })
+            .collect();
+        dates.sort();
+        dates.dedup();
+        Ok(dates)
+    }
+
+    fn revision(&self, date: &str) -> Result<i64> {
+        let files = self.day_files(date)?;
+        if files.is_empty() {
+            anyhow::bail!("No transcript file for {}", date);
+        }
+        let mut latest = 0;
+        for path in files {
+            let metadata = std::fs::metadata(&path)
+                .with_context(|| format!("Failed to stat {}", path.display()))?;
+            let modified = metadata
+                .modified()?
+                .duration_since(std::time::UNIX_EPOCH)?
+                .as_secs() as i64;
+            latest = latest.max(modified);
+        }
+        Ok(latest)
+    }
+}
+
+/// Keep the first transcript of each `file`. Duplicates were written by
+/// versions that could save a recording twice after a crash.
+fn dedup_by_file(transcripts: Vec<Transcript>) -> Vec<Transcript> {
+    let mut seen = std::collections::HashSet::new();
+    transcripts
+        .into_iter()
+        .filter(|t| seen.insert(t.file.clone()))
+        .collect()
+}
+
+/// Load transcripts from a JSONL file, skipping lines that don't parse.
+pub fn load_jsonl(path: &Path) -> Result<Vec<Transcript>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+
+    let mut transcripts = Vec::new();
+    for (line_num, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Transcript>(trimmed) {
+            Ok(t) => transcripts.push(t),
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to parse line {} of {}: {}",
+                    line_num + 1,
+                    path.display(),
+                    e
+                );
+            }
+        }
+    }
+
+    Ok(transcripts)
+}
+
+// --- SQLite ---
+
+/// All transcripts in `transcripts/transcripts.db`, indexed by date.
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    pub fn open(recordings_dir: &Path) -> Result<Self> {
+        let dir = recordings_dir.join("transcripts");
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(SQLITE_FILE_NAME);
+        let conn = Connection::open(&path)
+            .with_context(|| format!("failed to open transcript db at {}", path.display()))?;
+        conn.execute_batch("PRAGMA journal_mode = WAL;")
+            .context("failed to set WAL mode")?;
+        Self::init_schema(&conn)?;
+        Ok(Self { conn })
+    }
+
+    /// Open an in-memory database for tests.
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory().context("failed to open in-memory db")?;
+        Self::init_schema(&conn)?;
+        Ok(Self { conn })
+    }
+
+    fn init_schema(conn: &Connection) -> Result<()> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS transcripts (
+                id             INTEGER PRIMARY KEY AUTOINCREMENT,
+                date           TEXT NOT NULL,
+                timestamp      TEXT NOT NULL,
+                source         TEXT NOT NULL,
+                file           TEXT NOT NULL,
+                duration_secs  REAL NOT NULL,
+                text           TEXT NOT NULL,
+                segments       TEXT,
+                engine         TEXT
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_transcripts_date ON transcripts(date);",
+        )
+        .context("failed to create transcripts table")?;
+
+        // Databases created before `engine` was recorded lack the column.
+        let has_engine: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('transcripts') WHERE name = 'engine'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_engine {
+            conn.execute_batch("ALTER TABLE transcripts ADD COLUMN engine TEXT;")
+                .context("failed to add engine column")?;
+        }
+        Ok(())
+    }
+}
+
+impl TranscriptStore for SqliteStore {
+    fn name(&self) -> &str {
+        "sqlite"
+    }
+
+    fn append(&self, date: &str, transcript: &Transcript) -> Result<()> {
+        // Segments are stored as a JSON array (NULL when there are none).
+        let segments = if transcript.segments.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(&transcript.segments)?)
+        };
+        let inserted = self.conn.execute(
+            "INSERT INTO transcripts
+                 (date, timestamp, source, file, duration_secs, text, engine, segments)
+             SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8
+             WHERE NOT EXISTS (SELECT 1 FROM transcripts WHERE date = ?1 AND file = ?4)",
+            params![
+                date,
+                transcript.timestamp,
+                transcript.source,
+                transcript.file,
+                transcript.duration_secs,
+                transcript.text,
+                transcript.engine,
+                segments,
+            ],
+        )?;
+        if inserted == 0 {
+            tracing::warn!(
+                "{} is already stored for {}, skipping",
+                transcript.file,
+                date
+            );
+        }
+        Ok(())
+    }
+
+    fn load_date(&self, date: &str) -> Result<Vec<Transcript>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT timestamp, source, duration_secs, file, text, engine, segments
+             FROM transcripts WHERE date = ?1 ORDER BY id",
+        )?;
+        let rows = stmt.query_map(params![date], |row| {
+            let segments: Option<String> = row.get(6)?;
+            Ok(Transcript {
+                timestamp: row.get(0)?,
+                source: row.get(1)?,
+                duration_secs: row.get(2)?,
+                file: row.get(3)?,
+                text: row.get(4)?,
+                engine: row.get(5)?,
+                segments: segments
+                    .and_then(|json| serde_json::from_str(&json).ok())
+                    .unwrap_or_default(),
+            })
+        })?;
+        Ok(dedup_by_file(
+            rows.collect::<std::result::Result<Vec<_>, _>>()?,
+        ))
+    }
+
+    fn dates(&self) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT date FROM transcripts ORDER BY date")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        Ok(rows.collect::<std::result::Result<Vec<String>, _>>()?)
+    }
+
+    fn revision(&self, date: &str) -> Result<i64> {
+        // Rows are append-only, so the highest id changes on every insert.
+        let rev: Option<i64> = self.conn.query_row(
+            "SELECT MAX(id) FROM transcripts WHERE date = ?1",
+            params![date],
+            |row| row.get(0),
+        )?;
+        Ok(rev.unwrap_or(0))
+    }
+}
+
+/// Copy all JSONL transcripts into the SQLite store.
+///
+/// Dates that already have rows in SQLite are skipped, so this is safe to run
+/// more than once. The JSONL files are left in place. Returns the number of
+/// transcripts copied.
+pub fn migrate_jsonl_to_sqlite(jsonl: &JsonlStore, sqlite: &SqliteStore) -> Result<usize> {
+    let existing = sqlite.dates()?;
+    let mut copied = 0;
+
+    for date in jsonl.dates()? {
+        if existing.contains(&date) {
+            tracing::info!("Skipping {} (already in SQLite)", date);
+            continue;
+        }
+        let transcripts = jsonl.load_date(&date)?;
+        let tx = sqlite.conn.unchecked_transaction()?;
+        for t in &transcripts {
+            sqlite.append(&date, t)?;
+        }
+        tx.commit()?;
+        copied += transcripts.len();
+        tracing::info!("Migrated {} transcripts for {}", transcripts.len(), date);
+    }
+
+    Ok(copied)
+}
+
+/// Entry point for `deskmic migrate-transcripts`.
+pub fn run_migrate(config: &Config) -> Result<()> {
+    let recordings_dir = &config.output.directory;
+    let jsonl = JsonlStore::new(recordings_dir);
+    let sqlite = SqliteStore::open(recordings_dir)?;
+    let copied = migrate_jsonl_to_sqlite(&jsonl, &sqlite)?;
+    println!(
+        "Migrated {} transcripts into {}",
+        copied,
+        recordings_dir
+            .join("transcripts")
+            .join(SQLITE_FILE_NAME)
+            .display()
+    );
+    if config.transcription.store != "sqlite" {
+        println!("Set `store = \"sqlite\"` under [transcription] to start using it.");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_transcript(file: &str, text: &str) -> Transcript {
+        Transcript {
+            timestamp: "2026-03-16".to_string(),
+            source: "mic".to_string(),
+            duration_secs: 5.0,
+            file: file.to_string(),
+            text: text.to_string(),
+            engine: None,
+            segments: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_load_jsonl_valid() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("2026-03-16.jsonl");
+        std::fs::write(
+            &path,
+            r#"{"timestamp":"2026-03-16","source":"mic","duration_secs":5.0,"file":"mic_09-37-31.wav","text":"Hello world"}
+{"timestamp":"2026-03-16","source":"mic","duration_secs":3.0,"file":"mic_09-37-40.wav","text":"Testing"}
+"#,
+        )
+        .unwrap();
+        let transcripts = load_jsonl(&path).unwrap();
+        assert_eq!(transcripts.len(), 2);
+        assert_eq!(transcripts[0].text, "Hello world");
+        assert_eq!(transcripts[1].text, "Testing");
+    }
+
+    #[test]
+    fn test_load_jsonl_skips_invalid_lines() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("bad.jsonl");
+        std::fs::write(
+            &path,
+            r#"{"timestamp":"2026-03-16","source":"mic","duration_secs":5.0,"file":"mic_09-37-31.wav","text":"Good"}
+not valid json
+{"timestamp":"2026-03-16","source":"mic","duration_secs":3.0,"file":"mic_09-37-40.wav","text":"Also good"}
+"#,
+        )
+        .unwrap();
+        let transcripts = load_jsonl(&path).unwrap();
+        assert_eq!(transcripts.len(), 2);
+    }
+
+    #[test]
+    fn test_load_jsonl_empty_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("empty.jsonl");
+        std::fs::write(&path, "").unwrap();
+        let transcripts = load_jsonl(&path).unwrap();
+        assert!(transcripts.is_empty());
+    }
+
+    #[test]
+    fn test_jsonl_store_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = JsonlStore::new(dir.path());
+        assert!(store.dates().unwrap().is_empty());
+        assert!(store.load_date("2026-03-16").unwrap().is_empty());
+
+        store
+            .append("2026-03-16", &make_transcript("mic_09-00-00.wav", "a"))
+            .unwrap();
+        store
+            .append("2026-03-16", &make_transcript("mic_09-05-00.wav", "b"))
+            .unwrap();
+        store
+            .append("2026-03-15", &make_transcript("mic_10-00-00.wav", "c"))
+            .unwrap();
+
+        assert_eq!(store.dates().unwrap(), vec!["2026-03-15", "2026-03-16"]);
+        let loaded = store.load_date("2026-03-16").unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[1].text, "b");
+    }
+
+    #[test]
+    fn test_append_skips_duplicate_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let jsonl = JsonlStore::new(dir.path());
+        let sqlite = SqliteStore::open_in_memory().unwrap();
+        for store in [&jsonl as &dyn TranscriptStore, &sqlite] {
+            store
+                .append("2026-03-16", &make_transcript("mic_09-00-00.wav", "first"))
+                .unwrap();
+            store
+                .append("2026-03-16", &make_transcript("mic_09-00-00.wav", "again"))
+                .unwrap();
+            // Same file name on another day is a different recording.
+            store
+                .append("2026-03-17", &make_transcript("mic_09-00-00.wav", "b"))
+                .unwrap();
+
+            let loaded = store.load_date("2026-03-16").unwrap();
+            assert_eq!(loaded.len(), 1, "{}", store.name());
+            assert_eq!(loaded[0].text, "first");
+            assert_eq!(store.load_date("2026-03-17").unwrap().len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_load_date_drops_existing_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("transcripts")).unwrap();
+        let line = serde_json::to_string(&make_transcript("mic_09-00-00.wav", "a")).unwrap();
+        std::fs::write(
+            dir.path().join("transcripts/2026-03-16.jsonl"),
+            format!("{}\n{}\n", line, line),
+        )
+        .unwrap();
+
+        let store = JsonlStore::new(dir.path());
+        assert_eq!(store.load_date("2026-03-16").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_jsonl_store_unions_machines() {
+        let dir = tempfile::tempdir().unwrap();
+        let laptop = JsonlStore::for_machine(dir.path(), Some("laptop".to_string()));
+        let desk = JsonlStore::for_machine(dir.path(), Some("desk".to_string()));
+        let legacy = JsonlStore::new(dir.path());
+
+        legacy
+            .append("2026-03-16", &make_transcript("mic_08-00-00.wav", "old"))
+            .unwrap();
+        laptop
+            .append("2026-03-16", &make_transcript("mic_09-00-00_laptop.wav", "a"))
+            .unwrap();
+        desk.append("2026-03-16", &make_transcript("mic_10-00-00_desk.wav", "b"))
+            .unwrap();
+        desk.append("2026-03-17", &make_transcript("mic_10-00-00_desk.wav", "c"))
+            .unwrap();
+
+        assert!(dir.path().join("transcripts/2026-03-16.desk.jsonl").exists());
+        assert_eq!(laptop.load_date("2026-03-16").unwrap().len(), 3);
+        assert_eq!(legacy.load_date("2026-03-16").unwrap().len(), 3);
+        assert_eq!(desk.dates().unwrap(), vec!["2026-03-16", "2026-03-17"]);
+        assert!(desk.revision("2026-03-16").unwrap() > 0);
+    }
+
+    #[test]
+    fn test_sqlite_store_roundtrip() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        assert!(store.dates().unwrap().is_empty());
+        assert_eq!(store.revision("2026-03-16").unwrap(), 0);
+
+        store
+            .append("2026-03-16", &make_transcript("mic_09-00-00.wav", "a"))
+            .unwrap();
+        let rev1 = store.revision("2026-03-16").unwrap();
+        store
+            .append("2026-03-16", &make_transcript("mic_09-05-00.wav", "b"))
+            .unwrap();
+        assert!(store.revision("2026-03-16").unwrap() > rev1);
+
+        let loaded = store.load_date("2026-03-16").unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].file, "mic_09-00-00.wav");
+        assert_eq!(loaded[1].text, "b");
+        assert_eq!(store.dates().unwrap(), vec!["2026-03-16"]);
+    }
+
+    #[test]
+    fn test_engine_roundtrip() {
+        let mut t = make_transcript("mic_09-00-00.wav", "a");
+        t.engine = Some("azure-openai/whisper-1".to_string());
+
+        let sqlite = SqliteStore::open_in_memory().unwrap();
+        sqlite.append("2026-03-16", &t).unwrap();
+        sqlite
+            .append("2026-03-16", &make_transcript("mic_09-05-00.wav", "b"))
+            .unwrap();
+        let loaded = sqlite.load_date("2026-03-16").unwrap();
+        assert_eq!(loaded[0].engine.as_deref(), Some("azure-openai/whisper-1"));
+        assert!(loaded[1].engine.is_none());
+
+        let dir = tempfile::tempdir().unwrap();
+        let jsonl = JsonlStore::new(dir.path());
+        jsonl.append("2026-03-16", &t).unwrap();
+        let loaded = jsonl.load_date("2026-03-16").unwrap();
+        assert_eq!(loaded[0].engine.as_deref(), Some("azure-openai/whisper-1"));
+    }
+
+    #[test]
+    fn test_segments_roundtrip() {
+        use crate::transcribe::backend::TranscriptSegment;
+
+        let mut t = make_transcript("mic_09-00-00.wav", "Hello there. Bye.");
+        t.segments = vec![
+            TranscriptSegment {
+                start_secs: 0.0,
+                end_secs: 1.5,
+                text: "Hello there.".to_string(),
+            },
+            TranscriptSegment {
+                start_secs: 1.5,
+                end_secs: 2.2,
+                text: "Bye.".to_string(),
+            },
+        ];
+
+        let sqlite = SqliteStore::open_in_memory().unwrap();
+        sqlite.append("2026-03-16", &t).unwrap();
+        sqlite
+            .append("2026-03-16", &make_transcript("mic_09-05-00.wav", "b"))
+            .unwrap();
+        let loaded = sqlite.load_date("2026-03-16").unwrap();
+        assert_eq!(loaded[0].segments, t.segments);
+        assert!(loaded[1].segments.is_empty());
+
+        let dir = tempfile::tempdir().unwrap();
+        let jsonl = JsonlStore::new(dir.path());
+        jsonl.append("2026-03-16", &t).unwrap();
+        let loaded = jsonl.load_date("2026-03-16").unwrap();
+        assert_eq!(loaded[0].segments, t.segments);
+    }
+
+    #[test]
+    fn test_sqlite_adds_engine_column_to_old_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_dir = dir.path().join("transcripts");
+        std::fs::create_dir_all(&db_dir).unwrap();
+        let conn = Connection::open(db_dir.join(SQLITE_FILE_NAME)).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE transcripts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, date TEXT NOT NULL,
+                timestamp TEXT NOT NULL, source TEXT NOT NULL, file TEXT NOT NULL,
+                duration_secs REAL NOT NULL, text TEXT NOT NULL, segments TEXT
+            );",
+        )
+        .unwrap();
+        drop(conn);
+
+        let store = SqliteStore::open(dir.path()).unwrap();
+        store
+            .append("2026-03-16", &make_transcript("mic_09-00-00.wav", "a"))
+            .unwrap();
+        assert_eq!(store.load_date("2026-03-16").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_migrate_jsonl_to_sqlite_is_idempotent() {
+        let dir = tempfile::tempdir().unwrap();
+        let jsonl = JsonlStore::new(dir.path());
+        jsonl
+            .append("2026-03-15", &make_transcript("mic_10-00-00.wav", "c"))
+            .unwrap();
+        jsonl
+            .append("2026-03-16", &make_transcript("mic_09-00-00.wav", "a"))
+            .unwrap();
+
+        let sqlite = SqliteStore::open(dir.path()).unwrap();
+        assert_eq!(migrate_jsonl_to_sqlite(&jsonl, &sqlite).unwrap(), 2);
+        assert_eq!(migrate_jsonl_to_sqlite(&jsonl, &sqlite).unwrap(), 0);
+
+        assert_eq!(sqlite.dates().unwrap(), vec!["2026-03-15", "2026-03-16"]);
+        assert_eq!(sqlite.load_date("2026-03-15").unwrap()[0].text, "c");
+    }
+}
@@ -0,0 +1,191 @@
+// Subtitle export: SRT and WebVTT files built from transcript segments.
+//
+// Written by the transcriber next to the day's transcripts when
+// `transcription.emit_subtitles` is set, e.g.
+// `transcripts/2026-03-16/mic_09-00-00.srt`, for overlaying on a screen
+// recording of the same session.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::transcribe::backend::Transcript;
+
+/// A subtitle cue: start and end offsets in seconds, and its text.
+type Cue<'a> = (f64, f64, &'a str);
+
+/// Whisper's markers for non-speech audio, e.g. "[BLANK_AUDIO]" or "(music)".
+fn is_noise(text: &str) -> bool {
+    let text = text.trim();
+    text.is_empty()
+        || (text.starts_with('[') && text.ends_with(']'))
+        || (text.starts_with('(') && text.ends_with(')'))
+}
+
+/// Cues for `transcript`: one per segment with speech, or the whole text over
+/// the whole recording when the backend didn't report segments.
+fn cues(transcript: &Transcript) -> Vec<Cue<'_>> {
+    if transcript.segments.is_empty() {
+        if is_noise(&transcript.text) {
+            return Vec::new();
+        }
+        return vec![(0.0, transcript.duration_secs, transcript.text.trim())];
+    }
+    transcript
+        .segments
+        .iter()
+        .filter(|s| !is_noise(&s.text))
+        .map(|s| (s.start_secs, s.end_secs.max(s.start_secs), s.text.trim()))
+        .collect()
+}
+
+/// `HH:MM:SS<sep>mmm`.
+fn format_time(secs: f64, separator: char) -> String {
+    let millis = (secs.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        separator,
+        millis % 1000
+    )
+}
+
+/// The transcript as SubRip (`.srt`). Empty when there's nothing to show.
+pub fn to_srt(transcript: &Transcript) -> String {
+    let mut out = String::new();
+    for (i, (start, end, text)) in cues(transcript).into_iter().enumerate() {
+        out.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            format_time(start, ','),
+            format_time(end, ','),
+            text
+        ));
+    }
+    out
+}
+
+/// The transcript as WebVTT (`.vtt`). Empty when there's nothing to show.
+pub fn to_vtt(transcript: &Transcript) -> String {
+    let cues = cues(transcript);
+    if cues.is_empty() {
+        return String::new();
+    }
+    let mut out = String::from("WEBVTT\n\n");
+    for (start, end, text) in cues {
+        out.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            format_time(start, '.'),
+            format_time(end, '.'),
+            text
+        ));
+    }
+    out
+}
+
+/// Write `<dir>/<recording stem>.srt` and `.vtt` for `transcript`. Writes
+/// nothing for a transcript with no speech. Returns the files written.
+pub fn write_subtitles(transcript: &Transcript, dir: &Path) -> Result<Vec<PathBuf>> {
+    let srt = to_srt(transcript);
+    if srt.is_empty() {
+        return Ok(Vec::new());
+    }
+    let stem = Path::new(&transcript.file)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| transcript.file.clone());
+
+    std::fs::create_dir_all(dir)?;
+    let srt_path = dir.join(format!("{}.srt", stem));
+    let vtt_path = dir.join(format!("{}.vtt", stem));
+    std::fs::write(&srt_path, srt)?;
+    std::fs::write(&vtt_path, to_vtt(transcript))?;
+    Ok(vec![srt_path, vtt_path])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcribe::backend::TranscriptSegment;
+
+    fn segment(start_secs: f64, end_secs: f64, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            start_secs,
+            end_secs,
+            text: text.to_string(),
+        }
+    }
+
+    fn make_transcript(text: &str, segments: Vec<TranscriptSegment>) -> Transcript {
+        Transcript {
+            timestamp: "2026-03-16".to_string(),
+            source: "mic".to_string(),
+            duration_secs: 3725.5,
+            file: "mic_09-00-00.flac".to_string(),
+            text: text.to_string(),
+            engine: None,
+            segments,
+        }
+    }
+
+    fn lecture() -> Transcript {
+        make_transcript(
+            "Welcome back. Today: entropy.",
+            vec![
+                segment(0.0, 2.5, " Welcome back."),
+                segment(2.5, 3.0, "[BLANK_AUDIO]"),
+                segment(3661.25, 3664.0, "Today: entropy."),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_to_srt() {
+        assert_eq!(
+            to_srt(&lecture()),
+            "1\n00:00:00,000 --> 00:00:02,500\nWelcome back.\n\n\
+             2\n01:01:01,250 --> 01:01:04,000\nToday: entropy.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_to_vtt() {
+        assert_eq!(
+            to_vtt(&lecture()),
+            "WEBVTT\n\n\
+             00:00:00.000 --> 00:00:02.500\nWelcome back.\n\n\
+             01:01:01.250 --> 01:01:04.000\nToday: entropy.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_no_segments_uses_whole_recording() {
+        let t = make_transcript("Hello", Vec::new());
+        assert_eq!(to_srt(&t), "1\n00:00:00,000 --> 01:02:05,500\nHello\n\n");
+    }
+
+    #[test]
+    fn test_noise_only_writes_no_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let noise = make_transcript("[BLANK_AUDIO]", vec![segment(0.0, 5.0, "[BLANK_AUDIO]")]);
+        assert!(to_vtt(&noise).is_empty());
+        assert!(write_subtitles(&noise, dir.path()).unwrap().is_empty());
+        assert!(
+            write_subtitles(&make_transcript("", Vec::new()), dir.path())
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+        let written = write_subtitles(&lecture(), dir.path()).unwrap();
+        assert_eq!(
+            written,
+            vec![
+                dir.path().join("mic_09-00-00.srt"),
+                dir.path().join("mic_09-00-00.vtt")
+            ]
+        );
+    }
+}
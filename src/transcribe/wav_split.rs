@@ -0,0 +1,271 @@
+use std::io::Cursor;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+/// Size of the WAV header written for each piece (RIFF + fmt + data headers).
+const WAV_HEADER_BYTES: u64 = 44;
+
+/// Width of the window used to measure loudness when looking for a split point.
+const QUIET_WINDOW_SECS: f64 = 0.1;
+
+/// How far back from a piece's end to look for a quiet split point.
+const SEARCH_WINDOW_SECS: f64 = 30.0;
+
+/// Split a recording (WAV, FLAC or Ogg Opus) into in-memory WAV pieces of at most
+/// `max_bytes` each, cutting at the quietest point near the end of each piece
+/// so words aren't split mid-way. `on_piece` is called with each encoded piece
+/// in order.
+///
+/// Only one piece's worth of samples is held in memory at a time. Returns the
+/// number of pieces produced.
+pub fn split_wav(
+    path: &Path,
+    max_bytes: u64,
+    on_piece: impl FnMut(Vec<u8>) -> Result<()>,
+) -> Result<usize> {
+    if crate::audio::decode::is_opus(path) {
+        // Opus is small enough that decoding it whole is fine; the pieces
+        // come out as (much larger) WAV either way.
+        let (samples, sample_rate) = crate::audio::decode::read_samples(path)?;
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let samples = samples.into_iter().map(|s| Ok(s as i32));
+        return split_samples(spec, samples, max_bytes, on_piece);
+    }
+
+    let is_flac = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("flac"));
+    if is_flac {
+        let mut reader = claxon::FlacReader::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let info = reader.streaminfo();
+        let spec = hound::WavSpec {
+            channels: info.channels as u16,
+            sample_rate: info.sample_rate,
+            bits_per_sample: info.bits_per_sample as u16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let samples = reader.samples().map(|s| s.map_err(anyhow::Error::from));
+        split_samples(spec, samples, max_bytes, on_piece)
+    } else {
+        let mut reader = hound::WavReader::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let spec = reader.spec();
+        let samples = reader
+            .samples::<i32>()
+            .map(|s| s.map_err(anyhow::Error::from));
+        split_samples(spec, samples, max_bytes, on_piece)
+    }
+}
+
+/// `split_wav` over an already-opened sample stream described by `spec`.
+fn split_samples(
+    spec: hound::WavSpec,
+    mut samples: impl Iterator<Item = Result<i32>>,
+    max_bytes: u64,
+    mut on_piece: impl FnMut(Vec<u8>) -> Result<()>,
+) -> Result<usize> {
+    let channels = spec.channels.max(1) as usize;
+    let bytes_per_sample = (spec.bits_per_sample as u64).div_ceil(8);
+
+    let max_samples = max_bytes.saturating_sub(WAV_HEADER_BYTES) / bytes_per_sample;
+    let max_samples = (max_samples as usize / channels) * channels;
+    if max_samples == 0 {
+        anyhow::bail!("Upload size limit {} bytes is too small to split into", max_bytes);
+    }
+
+    let window = ((spec.sample_rate as f64 * QUIET_WINDOW_SECS) as usize).max(1) * channels;
+    let search = (spec.sample_rate as f64 * SEARCH_WINDOW_SECS) as usize * channels;
+
+    let mut buffer: Vec<i32> = Vec::with_capacity(max_samples);
+    let mut pieces = 0;
+
+    loop {
+        while buffer.len() < max_samples {
+            match samples.next() {
+                Some(sample) => buffer.push(sample?),
+                None => break,
+            }
+        }
+        if buffer.is_empty() {
+            break;
+        }
+
+        let full = buffer.len() == max_samples;
+        let split = if full {
+            let search_from = buffer.len().saturating_sub(search.min(buffer.len() / 2));
+            find_split_point(&buffer, search_from, window, channels)
+        } else {
+            buffer.len()
+        };
+
+        on_piece(encode_wav(spec, &buffer[..split])?)?;
+        pieces += 1;
+        buffer.drain(..split);
+
+        if !full && buffer.is_empty() {
+            break;
+        }
+    }
+
+    Ok(pieces)
+}
+
+/// Index (frame-aligned) of the quietest `window` of samples at or after
+/// `search_from`. Falls back to the end of `samples` if there is no room to
+/// search.
+pub fn find_split_point(
+    samples: &[i32],
+    search_from: usize,
+    window: usize,
+    channels: usize,
+) -> usize {
+    let align = |i: usize| (i / channels) * channels;
+    let start = align(search_from);
+    if window == 0 || start + window > samples.len() {
+        return samples.len();
+    }
+
+    let mut best = samples.len();
+    let mut best_energy = u64::MAX;
+    let mut pos = start;
+    while pos + window <= samples.len() {
+        let energy: u64 = samples[pos..pos + window]
+            .iter()
+            .map(|s| s.unsigned_abs() as u64)
+            .sum();
+        if energy < best_energy {
+            best_energy = energy;
+            best = align(pos + window / 2);
+        }
+        pos += window;
+    }
+
+    // Never produce an empty piece.
+    if best == 0 {
+        samples.len()
+    } else {
+        best
+    }
+}
+
+/// Encode samples as a complete WAV file in memory.
+fn encode_wav(spec: hound::WavSpec, samples: &[i32]) -> Result<Vec<u8>> {
+    let mut cursor = Cursor::new(Vec::new());
+    {
+        let mut writer = hound::WavWriter::new(&mut cursor, spec)?;
+        for &sample in samples {
+            writer.write_sample(sample)?;
+        }
+        writer.finalize()?;
+    }
+    Ok(cursor.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> hound::WavSpec {
+        hound::WavSpec {
+            channels: 1,
+            sample_rate: 100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        }
+    }
+
+    #[test]
+    fn test_find_split_point_picks_quiet_window() {
+        let mut samples = vec![1000; 100];
+        for s in &mut samples[60..70] {
+            *s = 0;
+        }
+        assert_eq!(find_split_point(&samples, 40, 10, 1), 65);
+    }
+
+    #[test]
+    fn test_find_split_point_is_frame_aligned() {
+        let mut samples = vec![1000; 100];
+        for s in &mut samples[50..60] {
+            *s = 0;
+        }
+        assert_eq!(find_split_point(&samples, 41, 10, 2) % 2, 0);
+    }
+
+    #[test]
+    fn test_find_split_point_without_room_returns_end() {
+        let samples = vec![1000; 20];
+        assert_eq!(find_split_point(&samples, 15, 10, 1), 20);
+    }
+
+    #[test]
+    fn test_split_wav_respects_limit_and_keeps_all_samples() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mic_09-00-00.wav");
+        let mut writer = hound::WavWriter::create(&path, spec()).unwrap();
+        for i in 0..1000 {
+            // Loud speech with a short pause every 3 seconds.
+            let sample: i16 = if i % 300 < 280 { 8000 } else { 0 };
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let max_bytes = WAV_HEADER_BYTES + 2 * 400;
+        let mut sizes = Vec::new();
+        let mut total_samples = 0;
+        let count = split_wav(&path, max_bytes, |bytes| {
+            sizes.push(bytes.len() as u64);
+            let reader = hound::WavReader::new(Cursor::new(bytes)).unwrap();
+            total_samples += reader.duration();
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(count, sizes.len());
+        assert!(count >= 3);
+        assert!(sizes.iter().all(|&s| s <= max_bytes));
+        assert_eq!(total_samples, 1000);
+    }
+
+    #[test]
+    fn test_split_wav_small_file_is_one_piece() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mic_09-00-00.wav");
+        let mut writer = hound::WavWriter::create(&path, spec()).unwrap();
+        for _ in 0..100 {
+            writer.write_sample(100i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let count = split_wav(&path, 1024 * 1024, |_| Ok(())).unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_split_wav_reads_flac() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mic_09-00-00.flac");
+        let mut writer = crate::audio::flac::FlacWriter::create(&path, 100, &[]).unwrap();
+        writer.write_samples(&[500i16; 1000]).unwrap();
+        writer.finalize().unwrap();
+
+        let mut total_samples = 0;
+        let count = split_wav(&path, WAV_HEADER_BYTES + 2 * 400, |bytes| {
+            let reader = hound::WavReader::new(Cursor::new(bytes)).unwrap();
+            assert_eq!(reader.spec(), spec());
+            total_samples += reader.duration();
+            Ok(())
+        })
+        .unwrap();
+
+        assert!(count >= 3);
+        assert_eq!(total_samples, 1000);
+    }
+}
@@ -1,19 +1,138 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
 
-use crate::transcribe::backend::{Transcript, TranscriptionBackend};
+use crate::audio::decode;
+use crate::config::TranscriptionConfig;
+use crate::transcribe::backend::{
+    transcript_metadata, Transcript, TranscriptSegment, TranscriptionBackend,
+};
+use crate::transcribe::models;
+
+/// Find the model file for `transcription.model`: an absolute path to an
+/// existing file as-is, otherwise `ggml-{model}.bin` beside the exe, then in
+/// `%APPDATA%\deskmic\models\`. A name ending in `.bin` is tried as a
+/// relative path first, then as the file name in those directories.
+pub fn resolve_model_path(model: &str) -> Result<PathBuf> {
+    let path = Path::new(model);
+    if path.is_absolute() {
+        if path.is_file() {
+            return Ok(path.to_path_buf());
+        }
+        anyhow::bail!("Whisper model not found: {}", path.display());
+    }
+
+    let candidates = model_candidates(model);
+    if let Some(found) = candidates.iter().find(|p| p.is_file()) {
+        return Ok(found.clone());
+    }
+    let searched: Vec<String> = candidates
+        .iter()
+        .map(|p| format!("  {}", p.display()))
+        .collect();
+    anyhow::bail!(
+        "Whisper model '{}' not found. Searched:\n{}",
+        model,
+        searched.join("\n")
+    )
+}
+
+/// Where to look for a model given by name, in search order.
+fn model_candidates(model: &str) -> Vec<PathBuf> {
+    let file_name = if model.ends_with(".bin") {
+        model.to_string()
+    } else {
+        format!("ggml-{}.bin", model)
+    };
+    let mut candidates = Vec::new();
+    if model.ends_with(".bin") {
+        // A relative path to a model file.
+        candidates.push(PathBuf::from(model));
+    }
+    if let Some(exe_dir) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+    {
+        candidates.push(exe_dir.join(&file_name));
+    }
+    if let Some(config_dir) = dirs::config_dir() {
+        candidates.push(config_dir.join("deskmic").join("models").join(&file_name));
+    }
+    candidates
+}
+
+/// Language to pass to Whisper for `transcription.language`: `None` lets
+/// Whisper detect it ("auto"). English-only models (`*.en`) always get "en".
+fn whisper_language(model: &str, language: &str) -> Option<String> {
+    let language = language.trim().to_lowercase();
+    if model.ends_with(".en") {
+        if language != "en" {
+            tracing::warn!(
+                "Model {} is English-only; ignoring transcription.language = \"{}\"",
+                model,
+                language
+            );
+        }
+        return Some("en".to_string());
+    }
+    if language == "auto" {
+        None
+    } else {
+        Some(language)
+    }
+}
+
+/// Threads per transcription for `transcription.whisper_threads`. 0 shares
+/// the physical cores between the `concurrency` transcriptions running at
+/// once, so running several doesn't load the machine more than one.
+fn whisper_threads(configured: u32, concurrency: u32) -> i32 {
+    if configured > 0 {
+        return configured as i32;
+    }
+    let cores = sysinfo::System::physical_core_count().unwrap_or(4) as u32;
+    (cores / concurrency.max(1)).max(1) as i32
+}
 
 pub struct WhisperLocal {
     ctx: WhisperContext,
+    /// Model file stem (e.g. "ggml-base.en"), recorded as the transcript engine.
+    model: String,
+    n_threads: i32,
+    /// `None` to auto-detect.
+    language: Option<String>,
+    /// `output.filename_template`, to read each recording's source.
+    filename_template: String,
 }
 
 impl WhisperLocal {
-    pub fn new(model_path: &str) -> Result<Self> {
+    pub fn new(
+        model_path: &str,
+        config: &TranscriptionConfig,
+        filename_template: &str,
+    ) -> Result<Self> {
+        models::check_model_file(Path::new(model_path))?;
         let ctx = WhisperContext::new_with_params(model_path, WhisperContextParameters::default())
             .map_err(|e| anyhow::anyhow!("Failed to load Whisper model: {:?}", e))?;
-        Ok(Self { ctx })
+        let model = Path::new(model_path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| model_path.to_string());
+        let n_threads = whisper_threads(config.whisper_threads, config.concurrency);
+        let language = whisper_language(&model, &config.language);
+        tracing::info!(
+            "Whisper model {}: {} threads, language {}",
+            model,
+            n_threads,
+            language.as_deref().unwrap_or("auto")
+        );
+        Ok(Self {
+            ctx,
+            model,
+            n_threads,
+            language,
+            filename_template: filename_template.to_string(),
+        })
     }
 }
 
@@ -23,18 +142,12 @@ impl TranscriptionBackend for WhisperLocal {
     }
 
     fn transcribe(&self, audio_path: &Path) -> Result<Transcript> {
-        // Read WAV file
-        let mut reader = hound::WavReader::open(audio_path)?;
-        let spec = reader.spec();
-        let samples_i16: Vec<i16> = reader
-            .samples::<i16>()
-            .collect::<std::result::Result<Vec<_>, _>>()?;
+        // Read the recording (WAV or FLAC)
+        let (samples_i16, sample_rate) = decode::read_samples(audio_path)?;
 
         // Convert i16 to f32 normalized [-1.0, 1.0]
         let samples_f32: Vec<f32> = samples_i16.iter().map(|&s| s as f32 / 32768.0).collect();
 
-        let duration_secs = samples_f32.len() as f64 / spec.sample_rate as f64;
-
         // Run whisper
         let mut state = self
             .ctx
@@ -42,47 +155,104 @@ impl TranscriptionBackend for WhisperLocal {
             .map_err(|e| anyhow::anyhow!("Failed to create state: {:?}", e))?;
 
         let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
-        params.set_n_threads(4);
-        params.set_language(Some("en"));
+        params.set_n_threads(self.n_threads);
+        if let Some(language) = &self.language {
+            params.set_language(Some(language.as_str()));
+        }
 
         state
             .full(params, &samples_f32)
             .map_err(|e| anyhow::anyhow!("Transcription failed: {:?}", e))?;
 
         let mut text = String::new();
+        let mut segments = Vec::new();
         let n_segments = state.full_n_segments();
         for i in 0..n_segments {
             if let Some(segment) = state.get_segment(i) {
                 if let Ok(segment_text) = segment.to_str_lossy() {
                     text.push_str(&segment_text);
                     text.push(' ');
+                    // Whisper timestamps are in centiseconds.
+                    segments.push(TranscriptSegment {
+                        start_secs: segment.start_timestamp() as f64 / 100.0,
+                        end_secs: segment.end_timestamp() as f64 / 100.0,
+                        text: segment_text.trim().to_string(),
+                    });
                 }
             }
         }
 
-        // Extract source and timestamp from filename
+        let (source, timestamp, duration_secs) =
+            transcript_metadata(audio_path, &self.filename_template)?;
         let filename = audio_path
             .file_name()
-            .ok_or_else(|| anyhow::anyhow!("audio path has no filename: {}", audio_path.display()))?
-            .to_string_lossy()
-            .to_string();
-        let source = if filename.starts_with("mic") {
-            "mic"
-        } else {
-            "teams"
-        };
-        let timestamp = audio_path
-            .parent()
-            .and_then(|p| p.file_name())
-            .map(|d| d.to_string_lossy().to_string())
+            .map(|f| f.to_string_lossy().to_string())
             .unwrap_or_default();
 
         Ok(Transcript {
             timestamp,
-            source: source.to_string(),
+            source,
             duration_secs,
             file: filename,
             text: text.trim().to_string(),
+            engine: Some(format!("{}/{}", self.name(), self.model)),
+            segments,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_candidates() {
+        let candidates = model_candidates("base.en");
+        assert!(candidates.iter().all(|p| p.ends_with("ggml-base.en.bin")));
+        assert!(candidates
+            .last()
+            .unwrap()
+            .ends_with("deskmic/models/ggml-base.en.bin"));
+        assert!(model_candidates("my-model.bin")[0].ends_with("my-model.bin"));
+    }
+
+    #[test]
+    fn test_whisper_language() {
+        assert_eq!(
+            whisper_language("ggml-large-v3", "de").as_deref(),
+            Some("de")
+        );
+        assert_eq!(whisper_language("ggml-large-v3", "auto"), None);
+        // English-only models ignore other languages.
+        assert_eq!(
+            whisper_language("ggml-base.en", "de").as_deref(),
+            Some("en")
+        );
+        assert_eq!(
+            whisper_language("ggml-base.en", "auto").as_deref(),
+            Some("en")
+        );
+    }
+
+    #[test]
+    fn test_whisper_threads() {
+        assert_eq!(whisper_threads(12, 4), 12);
+        assert!(whisper_threads(0, 1) >= 1);
+        assert_eq!(whisper_threads(0, 1000), 1);
+        assert!(whisper_threads(0, 2) <= whisper_threads(0, 1));
+    }
+
+    #[test]
+    fn test_resolve_model_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let model = dir.path().join("ggml-base.en.bin");
+        std::fs::write(&model, b"ggml").unwrap();
+        assert_eq!(resolve_model_path(&model.to_string_lossy()).unwrap(), model);
+
+        let missing = dir.path().join("ggml-tiny.en.bin");
+        assert!(resolve_model_path(&missing.to_string_lossy()).is_err());
+
+        let err = resolve_model_path("no-such-model").unwrap_err().to_string();
+        assert!(err.contains("ggml-no-such-model.bin"), "{}", err);
+    }
+}
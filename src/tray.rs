@@ -1,75 +1,161 @@
 // System tray UI for deskmic (Windows only).
 //
-// Provides pause/resume, open recordings folder, open settings, and quit actions.
+// Provides pause/resume, open recordings folder, open latest recording (WAV,
+// FLAC or Ogg Opus), open settings, and quit actions, plus on-demand
+// "Transcribe Now" / "Summarize Today" runs in child processes.
+// Pause/resume can also be toggled with a global hotkey (`hotkeys.pause_toggle`),
+// and "Save Last 30s" has one too (`hotkeys.save_clip`).
 // Also displays transcription status from the status file written by the
-// transcriber child process.
+// transcriber child process, and today's recording volume.
+// The icon is tinted by state (recording, paused, transcriber error).
 // Requires a Win32 message pump to process tray icon events.
 
 use tray_icon::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
 use tray_icon::{Icon, TrayIconBuilder};
 
+use std::process::Child;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 
 use anyhow::Result;
 
-use crate::transcribe::status::TranscriptionStatus;
+use crate::audio::pipeline::ClipRequests;
+use crate::config::{HotkeysConfig, DEFAULT_FILENAME_TEMPLATE};
+use crate::hotkey;
+use crate::transcribe::status::{TranscriberState, TranscriptionStatus};
 
 /// How often to poll the transcription status file (seconds).
 const STATUS_POLL_INTERVAL_SECS: u64 = 5;
 
+/// How often to re-measure recordings on disk (seconds). This walks the whole
+/// tree, so it runs far less often than the status poll.
+const STORAGE_POLL_INTERVAL_SECS: u64 = 60;
+
+/// Id of the pause/resume hotkey registered on the tray thread.
+const PAUSE_HOTKEY_ID: i32 = 1;
+
+/// Id of the save-clip hotkey registered on the tray thread.
+const SAVE_CLIP_HOTKEY_ID: i32 = 2;
+
+/// Microphone glyph (white on transparent), tinted per state at runtime.
+const ICON_PNG: &[u8] = include_bytes!("../assets/tray-icon.png");
+
+/// What the tray icon shows at a glance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IconState {
+    Recording,
+    Paused,
+    /// The transcriber reported an error (shown even while paused).
+    Error,
+}
+
+impl IconState {
+    fn color(self) -> [u8; 3] {
+        match self {
+            Self::Recording => [220, 40, 40],
+            Self::Paused => [140, 140, 140],
+            Self::Error => [240, 160, 0],
+        }
+    }
+}
+
 /// Run the system tray UI on the current thread.
 ///
 /// This function blocks until `shutdown` is set to `true`. It pumps Win32
-/// messages so that `tray-icon` menu events are delivered.
+/// messages so that `tray-icon` menu events and `WM_HOTKEY` are delivered.
+/// "Save Last Ns" (menu or `hotkeys.save_clip`) is passed on through `clips`.
 pub fn run_tray(
     recordings_dir: std::path::PathBuf,
+    machine_id: Option<String>,
     config_path: Option<std::path::PathBuf>,
+    hotkeys: HotkeysConfig,
+    clips: Arc<ClipRequests>,
     shutdown: Arc<AtomicBool>,
     paused: Arc<AtomicBool>,
 ) -> Result<()> {
     // Build menu
     let menu = Menu::new();
     let status_item = MenuItem::new("Status: Recording", false, None);
+    let storage_item = MenuItem::new("Recordings: -", false, None);
     let pause_item = MenuItem::new("Pause", true, None);
     let resume_item = MenuItem::new("Resume", true, None);
+    let clip_item = MenuItem::new("Record Note (60s)", true, None);
+    let save_clip_item = MenuItem::new(
+        format!("Save Last {}s", clips.secs()),
+        clips.secs() > 0,
+        None,
+    );
+    let transcribe_now_item = MenuItem::new("Transcribe Now", true, None);
+    let summarize_today_item = MenuItem::new("Summarize Today", true, None);
     let open_folder_item = MenuItem::new("Open Recordings", true, None);
+    let open_latest_item = MenuItem::new("Open Latest Recording", true, None);
     let settings_item = MenuItem::new("Settings", true, None);
 
-    // Transcription status items (grayed-out, informational only)
-    let tx_status_item = MenuItem::new("Transcriber: starting...", false, None);
-    let tx_queue_item = MenuItem::new("Queue: -", false, None);
-    let tx_session_item = MenuItem::new("Session: -", false, None);
-    let tx_cpu_item = MenuItem::new("CPU: -", false, None);
+    let tx_items = TranscriptionItems::new();
 
     let quit_item = MenuItem::new("Quit", true, None);
 
     menu.append(&status_item)?;
+    menu.append(&storage_item)?;
     menu.append(&PredefinedMenuItem::separator())?;
     menu.append(&pause_item)?;
     menu.append(&resume_item)?;
+    menu.append(&clip_item)?;
+    menu.append(&save_clip_item)?;
+    menu.append(&transcribe_now_item)?;
+    menu.append(&summarize_today_item)?;
     menu.append(&PredefinedMenuItem::separator())?;
-    menu.append(&tx_status_item)?;
-    menu.append(&tx_queue_item)?;
-    menu.append(&tx_session_item)?;
-    menu.append(&tx_cpu_item)?;
+    menu.append(&tx_items.status)?;
+    menu.append(&tx_items.queue)?;
+    menu.append(&tx_items.session)?;
+    menu.append(&tx_items.cpu)?;
     menu.append(&PredefinedMenuItem::separator())?;
     menu.append(&open_folder_item)?;
+    menu.append(&open_latest_item)?;
     menu.append(&settings_item)?;
     menu.append(&PredefinedMenuItem::separator())?;
     menu.append(&quit_item)?;
 
-    // Create a simple 16×16 red icon (RGBA).
-    let icon = Icon::from_rgba(vec![255, 0, 0, 255].repeat(16 * 16), 16, 16)?;
+    let glyph = match decode_png(ICON_PNG) {
+        Ok(glyph) => Some(glyph),
+        Err(e) => {
+            tracing::warn!("Failed to decode tray icon, using a plain one: {:#}", e);
+            None
+        }
+    };
+    let mut icon_state = IconState::Recording;
 
     let tray_icon = TrayIconBuilder::new()
         .with_menu(Box::new(menu))
         .with_tooltip("deskmic - Recording")
-        .with_icon(icon)
+        .with_icon(state_icon(glyph.as_ref(), icon_state)?)
         .build()?;
 
+    // Hidden window for WM_QUERYENDSESSION / WM_ENDSESSION; its messages are
+    // dispatched by the pump below.
+    let end_session_hwnd = match crate::shutdown::create_end_session_window() {
+        Ok(hwnd) => Some(hwnd),
+        Err(e) => {
+            tracing::warn!("Failed to create end-session window: {:?}", e);
+            None
+        }
+    };
+
+    let pause_registered =
+        register_tray_hotkey(PAUSE_HOTKEY_ID, &hotkeys.pause_toggle, "Pause/resume");
+    let save_clip_registered = clips.secs() > 0
+        && register_tray_hotkey(SAVE_CLIP_HOTKEY_ID, &hotkeys.save_clip, "Save clip");
+
     let mut last_status_poll = Instant::now();
+    let mut recording_summary = update_storage_display(&recordings_dir, &storage_item);
+    let mut last_storage_poll = Instant::now();
+    open_latest_item.set_enabled(crate::monitoring::latest_recording(&recordings_dir).is_some());
+    // On-demand runs started from the menu; their items stay disabled until
+    // the child exits.
+    let mut transcribe_child: Option<Child> = None;
+    let mut summarize_child: Option<Child> = None;
+    let mut transcriber_error = false;
 
     // Event loop — process menu events + pump Win32 messages.
     loop {
@@ -83,11 +169,30 @@ pub fn run_tray(
                 shutdown.store(true, Ordering::Relaxed);
                 break;
             } else if event.id == pause_item.id() {
-                paused.store(true, Ordering::Relaxed);
-                status_item.set_text("Status: Paused");
+                set_paused(&paused, &status_item, true);
             } else if event.id == resume_item.id() {
-                paused.store(false, Ordering::Relaxed);
-                status_item.set_text("Status: Recording");
+                set_paused(&paused, &status_item, false);
+            } else if event.id == clip_item.id() {
+                // Run the clip in a child process so the tray stays responsive.
+                let _ = spawn_subcommand(config_path.as_deref(), &["clip", "--duration", "60"]);
+            } else if event.id == save_clip_item.id() {
+                save_clip(&clips);
+            } else if event.id == transcribe_now_item.id() && transcribe_child.is_none() {
+                transcribe_child = spawn_subcommand(config_path.as_deref(), &["transcribe"]);
+                transcribe_now_item.set_enabled(transcribe_child.is_none());
+            } else if event.id == summarize_today_item.id() && summarize_child.is_none() {
+                let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+                summarize_child =
+                    spawn_subcommand(config_path.as_deref(), &["summarize", today.as_str()]);
+                summarize_today_item.set_enabled(summarize_child.is_none());
+            } else if event.id == open_latest_item.id() {
+                // Opening the file itself plays it in the default player.
+                match crate::monitoring::latest_recording(&recordings_dir) {
+                    Some(path) => {
+                        let _ = std::process::Command::new("explorer").arg(&path).spawn();
+                    }
+                    None => open_latest_item.set_enabled(false),
+                }
             } else if event.id == open_folder_item.id() {
                 let _ = std::process::Command::new("explorer")
                     .arg(&recordings_dir)
@@ -112,15 +217,29 @@ pub fn run_tray(
         // Periodically poll the transcription status file.
         if last_status_poll.elapsed().as_secs() >= STATUS_POLL_INTERVAL_SECS {
             last_status_poll = Instant::now();
-            update_transcription_display(
+            if last_storage_poll.elapsed().as_secs() >= STORAGE_POLL_INTERVAL_SECS {
+                last_storage_poll = Instant::now();
+                recording_summary = update_storage_display(&recordings_dir, &storage_item);
+                let has_recordings = crate::monitoring::latest_recording(&recordings_dir).is_some();
+                open_latest_item.set_enabled(has_recordings);
+            }
+            let tx_state = update_transcription_display(
                 &recordings_dir,
+                machine_id.as_deref(),
                 &tray_icon,
-                &tx_status_item,
-                &tx_queue_item,
-                &tx_session_item,
-                &tx_cpu_item,
+                &tx_items,
                 &paused,
+                recording_summary.as_deref(),
             );
+
+            transcriber_error = tx_state == Some(TranscriberState::Error);
+
+            // A transcription already under way (ours or the watcher's) makes
+            // another one pointless.
+            let transcribing = child_running(&mut transcribe_child)
+                || tx_state == Some(TranscriberState::Transcribing);
+            transcribe_now_item.set_enabled(!transcribing);
+            summarize_today_item.set_enabled(!child_running(&mut summarize_child));
         }
 
         // Pump Win32 messages so tray-icon receives window messages.
@@ -128,63 +247,293 @@ pub fn run_tray(
             use windows::Win32::UI::WindowsAndMessaging::*;
             let mut msg = MSG::default();
             while PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).into() {
+                if msg.message == WM_HOTKEY && msg.wParam.0 == PAUSE_HOTKEY_ID as usize {
+                    let now_paused = !paused.load(Ordering::Relaxed);
+                    set_paused(&paused, &status_item, now_paused);
+                    continue;
+                }
+                if msg.message == WM_HOTKEY && msg.wParam.0 == SAVE_CLIP_HOTKEY_ID as usize {
+                    save_clip(&clips);
+                    continue;
+                }
                 let _ = TranslateMessage(&msg);
                 DispatchMessageW(&msg);
             }
         }
 
+        // `paused` can also change from other threads (session lock), so the
+        // icon is compared against the desired state every iteration.
+        let wanted = if transcriber_error {
+            IconState::Error
+        } else if paused.load(Ordering::Relaxed) {
+            IconState::Paused
+        } else {
+            IconState::Recording
+        };
+        if wanted != icon_state {
+            icon_state = wanted;
+            match state_icon(glyph.as_ref(), icon_state) {
+                Ok(icon) => {
+                    let _ = tray_icon.set_icon(Some(icon));
+                }
+                Err(e) => tracing::warn!("Failed to build tray icon: {:?}", e),
+            }
+        }
+
         std::thread::sleep(std::time::Duration::from_millis(50));
     }
 
+    if pause_registered {
+        hotkey::unregister_hotkey(PAUSE_HOTKEY_ID);
+    }
+    if save_clip_registered {
+        hotkey::unregister_hotkey(SAVE_CLIP_HOTKEY_ID);
+    }
+    if let Some(hwnd) = end_session_hwnd {
+        unsafe {
+            let _ = windows::Win32::UI::WindowsAndMessaging::DestroyWindow(hwnd);
+        }
+    }
+
     Ok(())
 }
 
+/// An RGBA image: pixels, width, height.
+type Rgba = (Vec<u8>, u32, u32);
+
+/// Decode an 8-bit RGBA PNG.
+fn decode_png(bytes: &[u8]) -> Result<Rgba> {
+    let mut reader = png::Decoder::new(bytes).read_info()?;
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels)?;
+    if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+        anyhow::bail!(
+            "expected 8-bit RGBA, got {:?} {:?}",
+            info.color_type,
+            info.bit_depth
+        );
+    }
+    pixels.truncate(info.buffer_size());
+    Ok((pixels, info.width, info.height))
+}
+
+/// The icon for `state`: the glyph tinted with the state's color, or a plain
+/// 16×16 square of that color if the glyph couldn't be decoded.
+fn state_icon(glyph: Option<&Rgba>, state: IconState) -> Result<Icon> {
+    let [r, g, b] = state.color();
+    let icon = match glyph {
+        Some((pixels, width, height)) => {
+            let tint = |value: u8, channel: u8| (value as u16 * channel as u16 / 255) as u8;
+            let tinted = pixels
+                .chunks_exact(4)
+                .flat_map(|p| [tint(p[0], r), tint(p[1], g), tint(p[2], b), p[3]])
+                .collect();
+            Icon::from_rgba(tinted, *width, *height)?
+        }
+        None => Icon::from_rgba([r, g, b, 255].repeat(16 * 16), 16, 16)?,
+    };
+    Ok(icon)
+}
+
+/// Start this exe with `args` (after `--config`, if one was given) without
+/// waiting for it. Returns `None` and logs if it couldn't be started.
+fn spawn_subcommand(config_path: Option<&std::path::Path>, args: &[&str]) -> Option<Child> {
+    let exe = std::env::current_exe().ok()?;
+    let mut cmd = std::process::Command::new(exe);
+    if let Some(path) = config_path {
+        cmd.arg("--config").arg(path);
+    }
+    cmd.args(args).stdin(std::process::Stdio::null());
+    match cmd.spawn() {
+        Ok(child) => {
+            tracing::info!("Started `deskmic {}` from the tray", args.join(" "));
+            Some(child)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to start `deskmic {}`: {}", args.join(" "), e);
+            None
+        }
+    }
+}
+
+/// Whether a menu-started child is still running; clears it once it exits.
+fn child_running(child: &mut Option<Child>) -> bool {
+    if let Some(c) = child {
+        match c.try_wait() {
+            Ok(None) => return true,
+            Ok(Some(status)) if !status.success() => {
+                tracing::warn!("Tray-started run exited with {}", status);
+            }
+            _ => {}
+        }
+    }
+    *child = None;
+    false
+}
+
+/// Pause or resume recording and show the new state in the menu.
+fn set_paused(paused: &AtomicBool, status_item: &MenuItem, value: bool) {
+    paused.store(value, Ordering::Relaxed);
+    status_item.set_text(if value {
+        "Status: Paused"
+    } else {
+        "Status: Recording"
+    });
+}
+
+/// Ask every capture pipeline to save its recent audio as a clip.
+fn save_clip(clips: &ClipRequests) {
+    tracing::info!("Saving the last {}s of audio", clips.secs());
+    clips.request();
+}
+
+/// Register the `action` hotkey on this thread under `id`. A combination that
+/// is invalid or already taken by another application is logged and skipped
+/// rather than stopping the tray. Returns whether it was registered.
+fn register_tray_hotkey(id: i32, spec: &str, action: &str) -> bool {
+    if spec.is_empty() {
+        return false;
+    }
+    let registered = hotkey::parse_hotkey(spec)
+        .and_then(|key| hotkey::register_hotkey(id, key).map_err(Into::into));
+    match registered {
+        Ok(()) => {
+            tracing::info!("{} hotkey: {}", action, spec);
+            true
+        }
+        Err(e) => {
+            tracing::warn!("{} hotkey {} unavailable: {}", action, spec, e);
+            false
+        }
+    }
+}
+
+/// Measure today's recordings and the whole tree, and show both in the menu.
+/// Returns the "N files, X MB today" text for the tooltip.
+fn update_storage_display(
+    recordings_dir: &std::path::Path,
+    storage_item: &MenuItem,
+) -> Option<String> {
+    let today = chrono::Local::now().date_naive();
+    match (
+        crate::storage::get_day_stats(recordings_dir, today),
+        // Only the totals are shown, so the source breakdown's template
+        // doesn't matter.
+        crate::storage::get_storage_stats(recordings_dir, DEFAULT_FILENAME_TEMPLATE),
+    ) {
+        (Ok((files, bytes)), Ok(total)) => {
+            let today_text = format!(
+                "{} files, {:.1} MB today",
+                files,
+                bytes as f64 / 1_048_576.0
+            );
+            storage_item.set_text(format!(
+                "Recordings: {} | {:.2} GB total",
+                today_text,
+                total.bytes as f64 / 1_073_741_824.0
+            ));
+            Some(today_text)
+        }
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::warn!("Failed to measure recordings: {}", e);
+            storage_item.set_text("Recordings: -");
+            None
+        }
+    }
+}
+
+/// Transcription status items (grayed-out, informational only).
+struct TranscriptionItems {
+    status: MenuItem,
+    queue: MenuItem,
+    session: MenuItem,
+    cpu: MenuItem,
+}
+
+impl TranscriptionItems {
+    fn new() -> Self {
+        Self {
+            status: MenuItem::new("Transcriber: starting...", false, None),
+            queue: MenuItem::new("Queue: -", false, None),
+            session: MenuItem::new("Session: -", false, None),
+            cpu: MenuItem::new("CPU: -", false, None),
+        }
+    }
+}
+
+/// Shorten `tooltip` to the 127 UTF-16 units Windows allows, ending in "..."
+/// and cutting only between characters.
+fn fit_tooltip(tooltip: String) -> String {
+    if tooltip.encode_utf16().count() <= 127 {
+        return tooltip;
+    }
+    let mut units = 0;
+    let end = tooltip
+        .char_indices()
+        .find(|(_, c)| {
+            units += c.len_utf16();
+            units > 124
+        })
+        .map_or(tooltip.len(), |(i, _)| i);
+    format!("{}...", &tooltip[..end])
+}
+
 /// Read the transcription status file and update tray tooltip + menu items.
+/// `recording_summary` (today's volume) is added to the tooltip if known.
+/// Returns the transcriber's state, if a status file was found.
 fn update_transcription_display(
     recordings_dir: &std::path::Path,
+    machine_id: Option<&str>,
     tray_icon: &tray_icon::TrayIcon,
-    tx_status_item: &MenuItem,
-    tx_queue_item: &MenuItem,
-    tx_session_item: &MenuItem,
-    tx_cpu_item: &MenuItem,
+    items: &TranscriptionItems,
     paused: &Arc<AtomicBool>,
-) {
+    recording_summary: Option<&str>,
+) -> Option<TranscriberState> {
     let recording_state = if paused.load(Ordering::Relaxed) {
         "Paused"
     } else {
         "Recording"
     };
+    let recording_state = match recording_summary {
+        Some(summary) => format!("{} ({})", recording_state, summary),
+        None => recording_state.to_string(),
+    };
 
-    match TranscriptionStatus::read(recordings_dir) {
+    match TranscriptionStatus::read(recordings_dir, machine_id) {
         Some(status) => {
             // Update tooltip with combined recording + transcription info
             let tx_summary = status.tooltip_summary();
             let tooltip = format!("deskmic - {} | {}", recording_state, tx_summary);
-            // Windows tooltips are limited to 127 chars
-            let tooltip = if tooltip.len() > 127 {
-                format!("{}...", &tooltip[..124])
-            } else {
-                tooltip
-            };
-            let _ = tray_icon.set_tooltip(Some(&tooltip));
+            let _ = tray_icon.set_tooltip(Some(&fit_tooltip(tooltip)));
 
             // Update menu items
-            tx_status_item.set_text(format!("Transcriber: {}", status.state));
+            items
+                .status
+                .set_text(format!("Transcriber: {}", status.state));
 
             if status.queue_length > 0 {
-                tx_queue_item.set_text(format!("Queue: {} files pending", status.queue_length));
+                items
+                    .queue
+                    .set_text(format!("Queue: {} files pending", status.queue_length));
             } else {
-                tx_queue_item.set_text("Queue: empty");
+                items.queue.set_text("Queue: empty");
             }
 
             let words = status.session.words;
             let mins = status.session.audio_secs / 60.0;
-            tx_session_item.set_text(format!(
-                "Session: {} files, {:.1} min, {} words",
-                status.session.files_done, mins, words
+            items.session.set_text(format!(
+                "Session: {} files, {:.1} min, {} words | All time: {} files, {:.1} h",
+                status.session.files_done,
+                mins,
+                words,
+                status.lifetime.files_done,
+                status.lifetime.audio_secs / 3600.0
             ));
 
-            tx_cpu_item.set_text(format!("CPU: {:.0}%", status.last_cpu_percent));
+            items
+                .cpu
+                .set_text(format!("CPU: {:.0}%", status.last_cpu_percent));
+            Some(status.state)
         }
         None => {
             // No status file yet — transcriber may not have started
@@ -192,10 +541,11 @@ fn update_transcription_display(
                 "deskmic - {} | Transcriber: not running",
                 recording_state
             )));
-            tx_status_item.set_text("Transcriber: not running");
-            tx_queue_item.set_text("Queue: -");
-            tx_session_item.set_text("Session: -");
-            tx_cpu_item.set_text("CPU: -");
+            items.status.set_text("Transcriber: not running");
+            items.queue.set_text("Queue: -");
+            items.session.set_text("Session: -");
+            items.cpu.set_text("CPU: -");
+            None
         }
     }
 }
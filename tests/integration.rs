@@ -14,13 +14,27 @@ fn test_file_writer_creates_wav_on_speech() {
         directory: tmp.path().to_path_buf(),
         max_file_duration_mins: 60,
         organize_by_date: true,
+        embed_metadata: false,
+        machine_id: String::new(),
+        format: "wav".to_string(),
+        filename_template: deskmic::config::DEFAULT_FILENAME_TEMPLATE.to_string(),
     };
 
     let (sender, receiver) = mpsc::channel();
 
     let config_clone = output_config.clone();
+    let paused = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
     let writer = std::thread::spawn(move || {
-        run_file_writer(receiver, &config_clone, 16000).unwrap();
+        run_file_writer(
+            receiver,
+            &config_clone,
+            16000,
+            std::time::Duration::ZERO,
+            paused,
+            None,
+            None,
+        )
+        .unwrap();
     });
 
     // Simulate speech